@@ -0,0 +1,545 @@
+//! Accessibility support for GPUI.
+//!
+//! While a window prepaints, elements describe themselves to assistive technologies by
+//! contributing nodes to an accessibility tree. [`Div`](crate::Div) exposes this through the
+//! `.role()` and `.aria_*()` builders on [`InteractiveElement`](crate::InteractiveElement),
+//! text elements contribute [`Role::StaticText`] leaves, and custom elements can call
+//! [`Window::with_accessibility_node`] from their `prepaint` implementation.
+//!
+//! The tree is only built while the platform reports that an assistive technology is
+//! listening (see [`Window::is_accessibility_active`]). Once a frame has been drawn, the
+//! finished [`AccessibilityTree`] is handed to the platform window, which forwards it to the
+//! native accessibility API. Requests coming back from assistive technologies are routed to
+//! the node's listeners registered with [`Window::on_accessibility_action`].
+
+use crate::{App, Bounds, FocusHandle, Pixels, SharedString, Window};
+use collections::FxHashMap;
+use smallvec::SmallVec;
+use std::rc::Rc;
+
+/// The semantic role of an accessibility node, describing what kind of control it is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[expect(missing_docs)]
+pub enum Role {
+    #[default]
+    Group,
+    Window,
+    Unknown,
+    StaticText,
+    Heading,
+    Paragraph,
+    Label,
+    Image,
+    Button,
+    Link,
+    CheckBox,
+    RadioButton,
+    Switch,
+    TextInput,
+    MultilineTextInput,
+    ComboBox,
+    Slider,
+    SpinButton,
+    ProgressIndicator,
+    ScrollView,
+    List,
+    ListItem,
+    Tree,
+    TreeItem,
+    Table,
+    Row,
+    Cell,
+    TabList,
+    Tab,
+    TabPanel,
+    Menu,
+    MenuBar,
+    MenuItem,
+    Toolbar,
+    Dialog,
+    AlertDialog,
+    Alert,
+    Tooltip,
+    Separator,
+    Document,
+    Pane,
+}
+
+/// The checked state of a checkbox, switch or toggle button.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Toggled {
+    /// The control is not checked.
+    False,
+    /// The control is checked.
+    True,
+    /// The control is partially checked, e.g. a checkbox for a partially selected group.
+    Mixed,
+}
+
+impl From<bool> for Toggled {
+    fn from(value: bool) -> Self {
+        if value { Self::True } else { Self::False }
+    }
+}
+
+/// An action an assistive technology can ask an accessibility node to perform.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AccessibilityAction {
+    /// Do the equivalent of a single click or tap.
+    Click,
+    /// Move keyboard focus to the node.
+    Focus,
+    /// Expand the node, e.g. a tree item or disclosure.
+    Expand,
+    /// Collapse the node, e.g. a tree item or disclosure.
+    Collapse,
+    /// Increment the node's value, e.g. a slider.
+    Increment,
+    /// Decrement the node's value, e.g. a slider.
+    Decrement,
+    /// Scroll until the node is visible.
+    ScrollIntoView,
+    /// Replace the node's value with [`AccessibilityActionRequest::value`].
+    SetValue,
+    /// Show the node's context menu.
+    ShowContextMenu,
+}
+
+/// Identifies a node in an [`AccessibilityTree`].
+///
+/// Ids are derived from the element ids enclosing the node, so nodes keep their id across
+/// frames as long as the element tree doesn't change shape around them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AccessibilityNodeId(pub u64);
+
+impl AccessibilityNodeId {
+    /// The id of the root node, which represents the window itself.
+    pub const ROOT: Self = Self(0);
+}
+
+/// A request from an assistive technology to perform an action on a node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessibilityActionRequest {
+    /// The node the action should be performed on.
+    pub target: AccessibilityNodeId,
+    /// The action to perform.
+    pub action: AccessibilityAction,
+    /// The new value, for [`AccessibilityAction::SetValue`].
+    pub value: Option<SharedString>,
+}
+
+pub(crate) type AccessibilityActionListener =
+    Rc<dyn Fn(&AccessibilityActionRequest, &mut Window, &mut App)>;
+
+/// The properties an element reports about itself to assistive technologies.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccessibilityProperties {
+    /// The semantic role of the node.
+    pub role: Role,
+    /// The name read out for the node, e.g. a button's caption.
+    pub label: Option<SharedString>,
+    /// Additional text describing the node.
+    pub description: Option<SharedString>,
+    /// The node's current value, e.g. the contents of a text input.
+    pub value: Option<SharedString>,
+    /// The checked state, for checkboxes, switches and toggle buttons.
+    pub toggled: Option<Toggled>,
+    /// Whether the node is selected, for items in a list, tree or tab list.
+    pub selected: Option<bool>,
+    /// Whether the node is expanded, for disclosures, tree items and menus.
+    pub expanded: Option<bool>,
+    /// Whether the node is disabled.
+    pub disabled: bool,
+    /// The actions the node supports.
+    pub actions: SmallVec<[AccessibilityAction; 2]>,
+}
+
+impl AccessibilityProperties {
+    /// Create properties for a node with the given role.
+    pub fn new(role: Role) -> Self {
+        Self {
+            role,
+            ..Default::default()
+        }
+    }
+
+    /// Set the name read out for the node.
+    pub fn label(mut self, label: impl Into<SharedString>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Set the additional text describing the node.
+    pub fn description(mut self, description: impl Into<SharedString>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Set the node's current value.
+    pub fn value(mut self, value: impl Into<SharedString>) -> Self {
+        self.value = Some(value.into());
+        self
+    }
+
+    /// Set the node's checked state.
+    pub fn toggled(mut self, toggled: impl Into<Toggled>) -> Self {
+        self.toggled = Some(toggled.into());
+        self
+    }
+
+    /// Set whether the node is selected.
+    pub fn selected(mut self, selected: bool) -> Self {
+        self.selected = Some(selected);
+        self
+    }
+
+    /// Set whether the node is expanded.
+    pub fn expanded(mut self, expanded: bool) -> Self {
+        self.expanded = Some(expanded);
+        self
+    }
+
+    /// Set whether the node is disabled.
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    /// Declare that the node supports the given action.
+    pub fn action(mut self, action: AccessibilityAction) -> Self {
+        self.add_action(action);
+        self
+    }
+
+    pub(crate) fn add_action(&mut self, action: AccessibilityAction) {
+        if !self.actions.contains(&action) {
+            self.actions.push(action);
+        }
+    }
+}
+
+/// A node in an [`AccessibilityTree`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessibilityNode {
+    /// The node's id.
+    pub id: AccessibilityNodeId,
+    /// The node's parent, or `None` for the root.
+    pub parent: Option<AccessibilityNodeId>,
+    /// The node's children, in paint order.
+    pub children: Vec<AccessibilityNodeId>,
+    /// The node's bounds in window coordinates.
+    pub bounds: Bounds<Pixels>,
+    /// Whether the node is linked to a [`FocusHandle`].
+    pub focusable: bool,
+    /// Whether the node's [`FocusHandle`] is focused.
+    pub focused: bool,
+    /// The properties reported by the element.
+    pub properties: AccessibilityProperties,
+}
+
+impl AccessibilityNode {
+    /// The node's role.
+    pub fn role(&self) -> Role {
+        self.properties.role
+    }
+
+    /// The node's label, if any.
+    pub fn label(&self) -> Option<&SharedString> {
+        self.properties.label.as_ref()
+    }
+
+    /// Whether the node supports the given action.
+    pub fn supports(&self, action: AccessibilityAction) -> bool {
+        self.properties.actions.contains(&action)
+    }
+}
+
+/// A snapshot of a window's accessibility tree, as of the last drawn frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessibilityTree {
+    nodes: Vec<AccessibilityNode>,
+    ids: FxHashMap<AccessibilityNodeId, usize>,
+    focus: AccessibilityNodeId,
+}
+
+impl AccessibilityTree {
+    /// The root node, which represents the window itself.
+    pub fn root(&self) -> &AccessibilityNode {
+        &self.nodes[0]
+    }
+
+    /// Look up the node with the given id.
+    pub fn node(&self, id: AccessibilityNodeId) -> Option<&AccessibilityNode> {
+        self.ids.get(&id).map(|ix| &self.nodes[*ix])
+    }
+
+    /// All nodes in the tree, in depth-first order starting at the root.
+    pub fn nodes(&self) -> impl Iterator<Item = &AccessibilityNode> {
+        self.nodes.iter()
+    }
+
+    /// The children of the given node.
+    pub fn children(&self, id: AccessibilityNodeId) -> impl Iterator<Item = &AccessibilityNode> {
+        self.node(id)
+            .into_iter()
+            .flat_map(|node| node.children.iter())
+            .filter_map(|child| self.node(*child))
+    }
+
+    /// The node that currently has keyboard focus, or the root if no node does.
+    pub fn focus(&self) -> &AccessibilityNode {
+        self.node(self.focus).unwrap_or_else(|| self.root())
+    }
+
+    /// Find the first node matching the given predicate, in depth-first order.
+    pub fn find(
+        &self,
+        predicate: impl Fn(&AccessibilityNode) -> bool,
+    ) -> Option<&AccessibilityNode> {
+        self.nodes.iter().find(|node| predicate(node))
+    }
+
+    /// Find all nodes with the given role, in depth-first order.
+    pub fn find_by_role(&self, role: Role) -> impl Iterator<Item = &AccessibilityNode> {
+        self.nodes.iter().filter(move |node| node.role() == role)
+    }
+
+    /// Find the first node with the given label, in depth-first order.
+    pub fn find_by_label(&self, label: &str) -> Option<&AccessibilityNode> {
+        self.find(|node| node.label().is_some_and(|node_label| node_label == label))
+    }
+
+    /// Build a tree from the nodes recorded while prepainting a frame. `nodes` must be in
+    /// depth-first order, with every parent preceding its children.
+    pub(crate) fn new(
+        window_bounds: Bounds<Pixels>,
+        nodes: &[AccessibilityNodeEntry],
+        focused: impl Fn(&FocusHandle) -> bool,
+    ) -> Self {
+        let mut tree = Self {
+            nodes: Vec::with_capacity(nodes.len() + 1),
+            ids: FxHashMap::default(),
+            focus: AccessibilityNodeId::ROOT,
+        };
+        tree.push(AccessibilityNode {
+            id: AccessibilityNodeId::ROOT,
+            parent: None,
+            children: Vec::new(),
+            bounds: window_bounds,
+            focusable: false,
+            focused: false,
+            properties: AccessibilityProperties::new(Role::Window),
+        });
+
+        for entry in nodes {
+            let parent = entry
+                .parent
+                .filter(|parent| tree.ids.contains_key(parent))
+                .unwrap_or(AccessibilityNodeId::ROOT);
+            let is_focused = entry.focus_handle.as_ref().is_some_and(&focused);
+            if is_focused {
+                tree.focus = entry.id;
+            }
+            tree.push(AccessibilityNode {
+                id: entry.id,
+                parent: Some(parent),
+                children: Vec::new(),
+                bounds: entry.bounds,
+                focusable: entry.focus_handle.is_some(),
+                focused: is_focused,
+                properties: entry.properties.clone(),
+            });
+            let parent_ix = tree.ids[&parent];
+            tree.nodes[parent_ix].children.push(entry.id);
+        }
+
+        tree
+    }
+
+    fn push(&mut self, node: AccessibilityNode) {
+        self.ids.insert(node.id, self.nodes.len());
+        self.nodes.push(node);
+    }
+}
+
+/// A node recorded while prepainting a frame, along with the state needed to perform actions
+/// on it.
+#[derive(Clone)]
+pub(crate) struct AccessibilityNodeEntry {
+    pub(crate) id: AccessibilityNodeId,
+    /// The hash the node's id was derived from, used to keep ids unique within a frame.
+    pub(crate) id_base: u64,
+    pub(crate) parent: Option<AccessibilityNodeId>,
+    pub(crate) bounds: Bounds<Pixels>,
+    pub(crate) focus_handle: Option<FocusHandle>,
+    pub(crate) properties: AccessibilityProperties,
+    pub(crate) action_listeners: SmallVec<[(AccessibilityAction, AccessibilityActionListener); 1]>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Context, FocusHandle, InteractiveElement, IntoElement, ParentElement, Render,
+        StatefulInteractiveElement, Styled, TestAppContext, VisualContext as _, Window, div, px,
+    };
+
+    struct Toolbar {
+        focus_handle: FocusHandle,
+        saves: usize,
+        expanded: bool,
+    }
+
+    impl Render for Toolbar {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .role(Role::Toolbar)
+                .aria_label("Editor toolbar")
+                .size_full()
+                .child(
+                    div()
+                        .id("save")
+                        .role(Role::Button)
+                        .track_focus(&self.focus_handle)
+                        .w(px(80.))
+                        .h(px(20.))
+                        .on_click(cx.listener(|this, _, _, _| this.saves += 1))
+                        .child("Save"),
+                )
+                .child(
+                    div()
+                        .id("more")
+                        .role(Role::Button)
+                        .aria_label("More")
+                        .aria_expanded(self.expanded)
+                        .on_accessibility_action(
+                            AccessibilityAction::Expand,
+                            cx.listener(|this, _, _, cx| {
+                                this.expanded = true;
+                                cx.notify();
+                            }),
+                        ),
+                )
+        }
+    }
+
+    #[crate::test]
+    fn test_accessibility_tree(cx: &mut TestAppContext) {
+        let (toolbar, cx) = cx.add_window_view(|_, cx| Toolbar {
+            focus_handle: cx.focus_handle(),
+            saves: 0,
+            expanded: false,
+        });
+        cx.simulate_accessibility_activation();
+
+        let tree = cx.accessibility_tree();
+        let root = tree.root();
+        assert_eq!(root.role(), Role::Window);
+        let toolbar_node = tree.children(root.id).next().unwrap();
+        assert_eq!(toolbar_node.role(), Role::Toolbar);
+        assert_eq!(toolbar_node.label().unwrap(), "Editor toolbar");
+
+        let buttons = tree.find_by_role(Role::Button).collect::<Vec<_>>();
+        assert_eq!(buttons.len(), 2);
+        assert_eq!(buttons[0].parent, Some(toolbar_node.id));
+        assert!(buttons[0].focusable);
+        assert!(buttons[0].supports(AccessibilityAction::Click));
+        assert!(buttons[0].supports(AccessibilityAction::Focus));
+        let text = tree.children(buttons[0].id).next().unwrap();
+        assert_eq!(text.role(), Role::StaticText);
+        assert_eq!(text.label().unwrap(), "Save");
+        assert_eq!(buttons[1].properties.expanded, Some(false));
+        assert!(buttons[1].supports(AccessibilityAction::Expand));
+        assert_eq!(tree.focus().id, root.id);
+
+        let save = buttons[0].id;
+        let more = buttons[1].id;
+        cx.simulate_accessibility_action(save, AccessibilityAction::Focus);
+        let tree = cx.accessibility_tree();
+        assert_eq!(tree.focus().id, save);
+        assert!(tree.node(save).unwrap().focused);
+
+        cx.simulate_accessibility_action(save, AccessibilityAction::Click);
+        toolbar.read_with(cx, |toolbar, _| assert_eq!(toolbar.saves, 1));
+
+        cx.simulate_accessibility_action(more, AccessibilityAction::Expand);
+        let tree = cx.accessibility_tree();
+        assert_eq!(tree.node(more).unwrap().properties.expanded, Some(true));
+    }
+
+    struct SearchField {
+        query: SharedString,
+    }
+
+    impl Render for SearchField {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            div().size_full().child(
+                div().id("search").role(Role::Group).child(
+                    div()
+                        .id("query")
+                        .role(Role::TextInput)
+                        .aria_label("Search")
+                        .aria_value(self.query.clone())
+                        .on_accessibility_action(
+                            AccessibilityAction::SetValue,
+                            cx.listener(|this, request: &AccessibilityActionRequest, _, cx| {
+                                this.query = request.value.clone().unwrap_or_default();
+                                cx.notify();
+                            }),
+                        ),
+                ),
+            )
+        }
+    }
+
+    #[crate::test]
+    fn test_accessibility_tree_requires_activation(cx: &mut TestAppContext) {
+        let (_, cx) = cx.add_window_view(|_, _| SearchField { query: "".into() });
+        cx.run_until_parked();
+        let window = cx.window_handle();
+        assert!(cx.cx.accessibility_tree(window).is_none());
+        cx.update(|window, _| assert!(!window.is_accessibility_active()));
+
+        cx.simulate_accessibility_activation();
+        cx.update(|window, _| assert!(window.is_accessibility_active()));
+        let tree = cx.accessibility_tree();
+        let group = tree.find_by_role(Role::Group).next().unwrap();
+        assert_eq!(group.parent, Some(AccessibilityNodeId::ROOT));
+        let input = tree.find_by_label("Search").unwrap();
+        assert_eq!(input.role(), Role::TextInput);
+        assert_eq!(input.parent, Some(group.id));
+    }
+
+    #[crate::test]
+    fn test_accessibility_set_value(cx: &mut TestAppContext) {
+        let (search, cx) = cx.add_window_view(|_, _| SearchField { query: "".into() });
+        cx.simulate_accessibility_activation();
+        let input = cx.accessibility_tree().find_by_label("Search").unwrap().id;
+        assert!(
+            cx.accessibility_tree()
+                .node(input)
+                .unwrap()
+                .supports(AccessibilityAction::SetValue)
+        );
+
+        let window = cx.window_handle();
+        cx.cx.simulate_accessibility_action(
+            window,
+            AccessibilityActionRequest {
+                target: input,
+                action: AccessibilityAction::SetValue,
+                value: Some("needle".into()),
+            },
+        );
+        search.read_with(cx, |search, _| assert_eq!(search.query, "needle"));
+        let tree = cx.accessibility_tree();
+        assert_eq!(
+            tree.node(input).unwrap().properties.value,
+            Some("needle".into())
+        );
+
+        // Actions the node doesn't handle are ignored.
+        cx.simulate_accessibility_action(input, AccessibilityAction::Expand);
+        search.read_with(cx, |search, _| assert_eq!(search.query, "needle"));
+    }
+}
@@ -75,8 +75,8 @@ macro_rules! actions {
 ///
 /// - `name = "ActionName"` overrides the action's name. This must not contain `::`.
 ///
-/// - `no_json` causes the `build` method to always error and `action_json_schema` to return `None`,
-///   and allows actions not implement `serde::Serialize` and `schemars::JsonSchema`.
+/// - `no_json` causes the `build` method to always error and `action_json_schema` and `to_json` to
+///   return `None`, and allows actions not implement `serde::Serialize` and `schemars::JsonSchema`.
 ///
 /// - `no_register` skips registering the action. This is useful for implementing the `Action` trait
 ///   while not supporting invocation by name or JSON deserialization.
@@ -89,6 +89,9 @@ macro_rules! actions {
 /// - `deprecated = "Message about why this action is deprecation"` specifies a deprecation message.
 ///   In Zed, the keymap JSON schema will cause this to be displayed as a warning.
 ///
+/// Actions with data are serialized by `to_json` if they implement `serde::Serialize`, and can
+/// otherwise not be recorded by [`App::start_macro_recording`](crate::App::start_macro_recording).
+///
 /// # Manual Implementation
 ///
 /// If you want to control the behavior of the action trait manually, you can use the lower-level
@@ -135,6 +138,12 @@ pub trait Action: Any + Send {
     where
         Self: Sized;
 
+    /// Serialize this action's data to JSON, such that [`Action::build`] reconstructs it. This is
+    /// used to record actions for replay. Returns `None` if the action can't be serialized.
+    fn to_json(&self) -> Option<serde_json::Value> {
+        None
+    }
+
     /// Optional JSON schema for the action's input data.
     fn action_json_schema(_: &mut schemars::SchemaGenerator) -> Option<schemars::Schema>
     where
@@ -281,6 +290,34 @@ pub struct MacroActionData {
 
 inventory::collect!(MacroActionBuilder);
 
+/// This type must be public so that our macros can serialize actions in other crates, calling
+/// `(&&ActionJson(action)).to_json()` with [`SerializeActionJson`] and [`NoActionJson`] in scope.
+/// The first applies when the action implements `serde::Serialize`, and takes precedence because
+/// its receiver needs one less dereference. But this is an implementation detail and should not be
+/// used directly.
+#[doc(hidden)]
+pub struct ActionJson<'a, T>(pub &'a T);
+
+#[doc(hidden)]
+pub trait SerializeActionJson {
+    fn to_json(&self) -> Option<serde_json::Value>;
+}
+
+impl<T: serde::Serialize> SerializeActionJson for &ActionJson<'_, T> {
+    fn to_json(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(self.0).ok()
+    }
+}
+
+#[doc(hidden)]
+pub trait NoActionJson {
+    fn to_json(&self) -> Option<serde_json::Value> {
+        None
+    }
+}
+
+impl<T> NoActionJson for ActionJson<'_, T> {}
+
 impl ActionRegistry {
     /// Load all registered actions into the registry.
     pub(crate) fn load_actions(&mut self) {
@@ -26,6 +26,8 @@ pub use async_context::*;
 use collections::{FxHashMap, FxHashSet, HashMap, VecDeque};
 pub use context::*;
 pub use entity_map::*;
+pub use macro_recorder::*;
+pub use session::*;
 #[cfg(any(test, feature = "test-support"))]
 pub use headless_app_context::*;
 use http_client::{HttpClient, Url};
@@ -44,19 +46,24 @@ use crate::{
     Action, ActionBuildError, ActionRegistry, Any, AnyView, AnyWindowHandle, AppContext, Arena,
     ArenaBox, Asset, AssetSource, BackgroundExecutor, Bounds, ClipboardItem, CursorStyle,
     DispatchPhase, DisplayId, EventEmitter, FocusHandle, FocusMap, ForegroundExecutor, Global,
-    KeyBinding, KeyContext, Keymap, Keystroke, LayoutId, Menu, MenuItem, OwnedMenu,
-    PathPromptOptions, Pixels, Platform, PlatformDisplay, PlatformKeyboardLayout,
-    PlatformKeyboardMapper, Point, Priority, PromptBuilder, PromptButton, PromptHandle,
-    PromptLevel, Render, RenderImage, RenderablePromptHandle, Reservation, ScreenCaptureSource,
-    SharedString, SubscriberSet, Subscription, SvgRenderer, Task, TextRenderingMode, TextSystem,
+    GlobalWindowStateStore, KeyBinding, KeyBindingContextPredicate, KeyContext, Keymap, Keystroke,
+    LayoutId, Menu, MenuItem, Notification, NotificationId, NotificationResponse, OwnedMenu,
+    PathPromptOptions, PendingInputTimeout, PendingInputTimeouts, Pixels, Platform,
+    PlatformDisplay, PlatformKeyboardLayout, PlatformKeyboardMapper, Point, Priority,
+    PromptBuilder, PromptButton, PromptHandle, PromptLevel, Render, RenderImage,
+    RenderablePromptHandle, Reservation, ScreenCaptureSource, SharedString, StatusItem,
+    StatusItemClick, SubscriberSet, Subscription, SvgRenderer, Task, TextRenderingMode, TextSystem,
     ThermalState, Window, WindowAppearance, WindowHandle, WindowId, WindowInvalidator,
+    WindowStateStore, apply_window_state,
     colors::{Colors, GlobalColors},
-    hash, init_app_menus,
+    hash, init_app_menus, window_state_store,
 };
 
 mod async_context;
 mod context;
 mod entity_map;
+mod macro_recorder;
+mod session;
 #[cfg(any(test, feature = "test-support"))]
 mod headless_app_context;
 #[cfg(any(test, feature = "test-support"))]
@@ -249,6 +256,8 @@ pub(crate) type KeystrokeObserver =
     Box<dyn FnMut(&KeystrokeEvent, &mut Window, &mut App) -> bool + 'static>;
 type QuitHandler = Box<dyn FnOnce(&mut App) -> LocalBoxFuture<'static, ()> + 'static>;
 type WindowClosedHandler = Box<dyn FnMut(&mut App)>;
+type NotificationHandler = Box<dyn FnMut(NotificationResponse, &mut App)>;
+type StatusItemClickHandler = Box<dyn FnMut(StatusItemClick, &mut App)>;
 type ReleaseListener = Box<dyn FnOnce(&mut dyn Any, &mut App) + 'static>;
 type NewEntityListener = Box<dyn FnMut(AnyEntity, &mut Option<&mut Window>, &mut App) + 'static>;
 
@@ -607,6 +616,8 @@ pub struct App {
     pub(crate) window_handles: FxHashMap<WindowId, AnyWindowHandle>,
     pub(crate) focus_handles: Arc<FocusMap>,
     pub(crate) keymap: Rc<RefCell<Keymap>>,
+    pub(crate) pending_input_timeouts: PendingInputTimeouts,
+    pub(crate) macro_recorder: MacroRecorder,
     pub(crate) keyboard_layout: Box<dyn PlatformKeyboardLayout>,
     pub(crate) keyboard_mapper: Rc<dyn PlatformKeyboardMapper>,
     pub(crate) global_action_listeners:
@@ -621,12 +632,18 @@ pub struct App {
     pub(crate) keystroke_interceptors: SubscriberSet<(), KeystrokeObserver>,
     pub(crate) keyboard_layout_observers: SubscriberSet<(), Handler>,
     pub(crate) thermal_state_observers: SubscriberSet<(), Handler>,
+    /// Whether the platform has been asked to report thermal state changes, which only happens
+    /// once they're observed.
+    watching_thermal_state: Cell<bool>,
     pub(crate) release_listeners: SubscriberSet<EntityId, ReleaseListener>,
     pub(crate) global_observers: SubscriberSet<TypeId, Handler>,
     pub(crate) quit_observers: SubscriberSet<(), QuitHandler>,
     pub(crate) restart_observers: SubscriberSet<(), Handler>,
     pub(crate) restart_path: Option<PathBuf>,
     pub(crate) window_closed_observers: SubscriberSet<(), WindowClosedHandler>,
+    notification_handlers: FxHashMap<NotificationId, NotificationHandler>,
+    next_notification_id: u64,
+    status_item_click_handler: Option<StatusItemClickHandler>,
     pub(crate) layout_id_buffer: Vec<LayoutId>, // We recycle this memory across layout requests.
     pub(crate) propagate_event: bool,
     pub(crate) prompt_builder: Option<PromptBuilder>,
@@ -693,6 +710,8 @@ impl App {
                 window_handles: FxHashMap::default(),
                 focus_handles: Arc::new(RwLock::new(SlotMap::with_key())),
                 keymap: Rc::new(RefCell::new(Keymap::default())),
+                pending_input_timeouts: PendingInputTimeouts::default(),
+                macro_recorder: MacroRecorder::default(),
                 keyboard_layout,
                 keyboard_mapper,
                 global_action_listeners: FxHashMap::default(),
@@ -708,11 +727,15 @@ impl App {
                 keystroke_interceptors: SubscriberSet::new(),
                 keyboard_layout_observers: SubscriberSet::new(),
                 thermal_state_observers: SubscriberSet::new(),
+                watching_thermal_state: Cell::new(false),
                 global_observers: SubscriberSet::new(),
                 quit_observers: SubscriberSet::new(),
                 restart_observers: SubscriberSet::new(),
                 restart_path: None,
                 window_closed_observers: SubscriberSet::new(),
+                notification_handlers: FxHashMap::default(),
+                next_notification_id: 0,
+                status_item_click_handler: None,
                 layout_id_buffer: Default::default(),
                 propagate_event: true,
                 prompt_builder: Some(PromptBuilder::Default),
@@ -747,14 +770,33 @@ impl App {
             }
         }));
 
-        platform.on_thermal_state_change(Box::new({
+        platform.on_notification_response(Box::new({
             let app = Rc::downgrade(&app);
-            move || {
+            move |id, response| {
                 if let Some(app) = app.upgrade() {
-                    let cx = &mut app.borrow_mut();
-                    cx.thermal_state_observers
-                        .clone()
-                        .retain(&(), move |callback| (callback)(cx));
+                    app.borrow_mut().update(|cx| {
+                        let closed = response == NotificationResponse::Closed;
+                        if let Some(mut handler) = cx.notification_handlers.remove(&id) {
+                            handler(response, cx);
+                            if !closed {
+                                cx.notification_handlers.entry(id).or_insert(handler);
+                            }
+                        }
+                    });
+                }
+            }
+        }));
+
+        platform.on_status_item_click(Box::new({
+            let app = Rc::downgrade(&app);
+            move |click| {
+                if let Some(app) = app.upgrade() {
+                    app.borrow_mut().update(|cx| {
+                        if let Some(mut handler) = cx.status_item_click_handler.take() {
+                            handler(click, cx);
+                            cx.status_item_click_handler.get_or_insert(handler);
+                        }
+                    });
                 }
             }
         }));
@@ -1080,6 +1122,43 @@ impl App {
         })
     }
 
+    /// Set where the states of windows opened with [`App::open_restorable_window`] are kept.
+    pub fn set_window_state_store(&mut self, store: impl WindowStateStore) {
+        self.set_global(GlobalWindowStateStore(Rc::new(store)));
+    }
+
+    /// Opens a new window with the state it was in when it was last open, saved under the given
+    /// identifier, and keeps saving its state as it changes. Windows are restored on the display
+    /// they were on if it's still connected, and moved within the visible bounds of the display
+    /// they're restored on.
+    pub fn open_restorable_window<V: 'static + Render>(
+        &mut self,
+        id: impl Into<SharedString>,
+        options: crate::WindowOptions,
+        build_root_view: impl FnOnce(&mut Window, &mut App) -> Entity<V>,
+    ) -> anyhow::Result<WindowHandle<V>> {
+        let id = id.into();
+        let options = self.restore_window_options(&id, options);
+        let handle = self.open_window(options, build_root_view)?;
+        handle.update(self, |_, window, _| window.persist_state(id))?;
+        Ok(handle)
+    }
+
+    /// Returns the given options with the bounds and display of the window state saved under the
+    /// given identifier, if there's one, see [`App::open_restorable_window`].
+    pub fn restore_window_options(
+        &self,
+        id: &str,
+        mut options: crate::WindowOptions,
+    ) -> crate::WindowOptions {
+        if let Some(state) =
+            window_state_store(self).and_then(|store| store.load(id).log_err().flatten())
+        {
+            apply_window_state(&state, &mut options, self);
+        }
+        options
+    }
+
     /// Instructs the platform to activate the application by bringing it to the foreground.
     pub fn activate(&self, ignoring_other_apps: bool) {
         self.platform.activate(ignoring_other_apps);
@@ -1130,7 +1209,9 @@ impl App {
             .cloned()
     }
 
-    /// Returns the current thermal state of the system.
+    /// Returns the current thermal state of the system. Some platforms only keep track of it
+    /// once it's observed with [`App::on_thermal_state_change`], and report it as nominal until
+    /// then.
     pub fn thermal_state(&self) -> ThermalState {
         self.platform.thermal_state()
     }
@@ -1140,6 +1221,19 @@ impl App {
     where
         F: 'static + FnMut(&mut App),
     {
+        if !self.watching_thermal_state.replace(true) {
+            self.platform.on_thermal_state_change(Box::new({
+                let app = self.this.clone();
+                move || {
+                    if let Some(app) = app.upgrade() {
+                        let cx = &mut app.borrow_mut();
+                        cx.thermal_state_observers
+                            .clone()
+                            .retain(&(), move |callback| (callback)(cx));
+                    }
+                }
+            }));
+        }
         let (subscription, activate) = self.thermal_state_observers.insert(
             (),
             Box::new(move |cx| {
@@ -1151,6 +1245,28 @@ impl App {
         subscription
     }
 
+    /// Shows a system notification, invoking `on_response` whenever the user responds to it.
+    /// Showing a notification that [`Notification::replace`]s another keeps the other's id and
+    /// replaces its handler.
+    pub fn show_notification(
+        &mut self,
+        notification: Notification,
+        on_response: impl FnMut(NotificationResponse, &mut App) + 'static,
+    ) -> NotificationId {
+        let id = notification.replaces.unwrap_or_else(|| {
+            self.next_notification_id += 1;
+            NotificationId(self.next_notification_id)
+        });
+        self.notification_handlers.insert(id, Box::new(on_response));
+        self.platform.show_notification(id, notification);
+        id
+    }
+
+    /// Closes a notification shown with [`Self::show_notification`].
+    pub fn close_notification(&mut self, id: NotificationId) {
+        self.platform.close_notification(id);
+    }
+
     /// Returns the appearance of the application's windows.
     pub fn window_appearance(&self) -> WindowAppearance {
         self.platform.window_appearance()
@@ -1161,6 +1277,13 @@ impl App {
         self.platform.read_from_clipboard()
     }
 
+    /// Reads data in the given MIME type from the platform clipboard. Some platforms, like
+    /// Linux, only read custom types when asked, so they're missing from
+    /// [`Self::read_from_clipboard`].
+    pub fn read_custom_from_clipboard(&self, mime: &str) -> Option<Vec<u8>> {
+        self.platform.read_custom_from_clipboard(mime)
+    }
+
     /// Sets the text rendering mode for the application.
     pub fn set_text_rendering_mode(&mut self, mode: TextRenderingMode) {
         self.text_rendering_mode.set(mode);
@@ -1880,11 +2003,85 @@ impl App {
         self.pending_effects.push_back(Effect::RefreshWindows);
     }
 
+    /// Set how long windows wait for the rest of a multi-stroke binding before dispatching the
+    /// keystrokes typed so far on their own. Without a context, this sets the default, which is
+    /// one second. With one, it applies while the focused element's context stack matches, and
+    /// the deepest matching context wins. Fails if the context can't be parsed.
+    pub fn set_pending_input_timeout(
+        &mut self,
+        timeout: PendingInputTimeout,
+        context: Option<&str>,
+    ) -> Result<()> {
+        let predicate = context.map(KeyBindingContextPredicate::parse).transpose()?;
+        self.pending_input_timeouts.set(timeout, predicate);
+        Ok(())
+    }
+
     /// Get all key bindings in the app.
     pub fn key_bindings(&self) -> Rc<RefCell<Keymap>> {
         self.keymap.clone()
     }
 
+    /// Start recording the actions handled by the focused element in any window, and the text
+    /// typed into it, discarding any recording already in progress. Actions and text that are
+    /// dispatched by another action's handlers, or by [`Window::replay_macro`], aren't recorded.
+    pub fn start_macro_recording(&mut self) {
+        self.macro_recorder.start();
+    }
+
+    /// Stop recording, returning what was recorded since [`App::start_macro_recording`], or `None`
+    /// if no recording was in progress.
+    pub fn stop_macro_recording(&mut self) -> Option<MacroRecording> {
+        self.macro_recorder.stop()
+    }
+
+    /// Returns whether a macro is being recorded.
+    pub fn is_recording_macro(&self) -> bool {
+        self.macro_recorder.is_recording()
+    }
+
+    /// Register a view that windows saved by [`App::save_session`] can have as their root view.
+    pub fn register_persistent_view<V: PersistentView>(&mut self) {
+        self.default_global::<SessionRegistry>().register::<V>();
+    }
+
+    /// Set the version session files are saved at, which is 0 unless set. Files saved at older
+    /// versions are migrated with the migrations added by [`App::add_session_migration`] when
+    /// they're restored.
+    pub fn set_session_version(&mut self, version: u32) {
+        self.default_global::<SessionRegistry>()
+            .set_version(version);
+    }
+
+    /// Add a migration of session files saved at the given version to the next version, which
+    /// updates their JSON in place.
+    pub fn add_session_migration(
+        &mut self,
+        from_version: u32,
+        migrate: impl Fn(&mut serde_json::Value) -> Result<()> + 'static,
+    ) {
+        self.default_global::<SessionRegistry>()
+            .add_migration(from_version, migrate);
+    }
+
+    /// Save the open windows whose root views are registered with
+    /// [`App::register_persistent_view`] to a session file, with their bounds and the states of
+    /// their views. Windows that can't be saved are skipped and logged. Call this when the
+    /// application quits, see [`Context::on_app_quit`].
+    pub fn save_session(&mut self, path: &Path) -> Result<()> {
+        let json = save_session(self)?;
+        crate::util::write_atomically(path, json)
+            .with_context(|| format!("failed to write session {path:?}"))
+    }
+
+    /// Reopen the windows saved to a session file by [`App::save_session`], restoring their views,
+    /// and return them. Windows that can't be restored are skipped and logged.
+    pub fn restore_session(&mut self, path: &Path) -> Result<Vec<AnyWindowHandle>> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read session {path:?}"))?;
+        restore_session(&json, self)
+    }
+
     /// Register a global handler for actions invoked via the keyboard. These handlers are run at
     /// the end of the bubble phase for actions, and so will only be invoked if there are no other
     /// handlers or if they called `cx.propagate()`.
@@ -2065,6 +2262,23 @@ impl App {
         self.platform.set_dock_menu(menus, &self.keymap.borrow())
     }
 
+    /// Shows an icon in the system tray or status area, replacing the one shown before, if any.
+    /// `on_click` is invoked when the icon is clicked, while right-clicking it shows its menu.
+    pub fn set_status_item(
+        &mut self,
+        item: StatusItem,
+        on_click: impl FnMut(StatusItemClick, &mut App) + 'static,
+    ) {
+        self.status_item_click_handler = Some(Box::new(on_click));
+        self.platform.set_status_item(Some(item), &self.keymap.borrow());
+    }
+
+    /// Removes the icon shown with [`Self::set_status_item`].
+    pub fn remove_status_item(&mut self) {
+        self.status_item_click_handler = None;
+        self.platform.set_status_item(None, &self.keymap.borrow());
+    }
+
     /// Performs the action associated with the given dock menu item, only used on Windows for now.
     pub fn perform_dock_menu_action(&self, action: usize) {
         self.platform.perform_dock_menu_action(action);
@@ -2078,6 +2292,18 @@ impl App {
         self.platform.add_recent_document(path);
     }
 
+    /// Returns the recent documents the platform remembers for the application, most recently
+    /// used first. The list is shared with the system, so it can include documents opened by
+    /// other instances of the application.
+    pub fn recent_documents(&self) -> Task<Result<Vec<PathBuf>>> {
+        self.platform.recent_documents()
+    }
+
+    /// Removes all documents from the list of recent documents for the application.
+    pub fn clear_recent_documents(&self) {
+        self.platform.clear_recent_documents();
+    }
+
     /// Updates the jump list with the updated list of recent paths for the application, only used on Windows for now.
     /// Note that this also sets the dock menu on Windows.
     pub fn update_jump_list(
@@ -2628,7 +2854,11 @@ impl<'a, T> Drop for GpuiBorrow<'a, T> {
 
 #[cfg(test)]
 mod test {
-    use std::{cell::RefCell, rc::Rc};
+    use std::{
+        cell::RefCell,
+        path::{Path, PathBuf},
+        rc::Rc,
+    };
 
     use crate::{AppContext, TestAppContext};
 
@@ -2662,4 +2892,28 @@ mod test {
 
         assert_eq!(*observation_count.borrow(), 2);
     }
+
+    #[test]
+    fn test_recent_documents() {
+        let cx = TestAppContext::single();
+        cx.update(|cx| {
+            cx.add_recent_document(Path::new("/a.txt"));
+            cx.add_recent_document(Path::new("/b.txt"));
+            cx.add_recent_document(Path::new("/a.txt"));
+        });
+        let documents = cx.update(|cx| cx.recent_documents());
+        assert_eq!(
+            cx.foreground_executor().block_test(documents).unwrap(),
+            [PathBuf::from("/a.txt"), PathBuf::from("/b.txt")]
+        );
+
+        cx.update(|cx| cx.clear_recent_documents());
+        let documents = cx.update(|cx| cx.recent_documents());
+        assert!(
+            cx.foreground_executor()
+                .block_test(documents)
+                .unwrap()
+                .is_empty()
+        );
+    }
 }
@@ -1,7 +1,8 @@
 use crate::{
     AnyView, AnyWindowHandle, AppContext, AsyncApp, DispatchPhase, Effect, EntityId, EventEmitter,
-    FocusHandle, FocusOutEvent, Focusable, Global, KeystrokeObserver, Priority, Reservation,
-    SubscriberSet, Subscription, Task, WeakEntity, WeakFocusHandle, Window, WindowHandle,
+    FocusHandle, FocusOutEvent, Focusable, Global, KeystrokeObserver, PendingKeystrokes, Priority,
+    Reservation, SubscriberSet, Subscription, Task, WeakEntity, WeakFocusHandle, Window,
+    WindowHandle,
 };
 use anyhow::Result;
 use futures::FutureExt;
@@ -527,6 +528,26 @@ impl<'a, T: 'static> Context<'a, T> {
         subscription
     }
 
+    /// Register a callback to be invoked with the window's pending keystrokes and the bindings
+    /// that could complete them whenever they change, e.g. to show which keys can come next.
+    /// The keystrokes are empty once the pending input is dispatched, times out or is
+    /// abandoned.
+    pub fn observe_pending_keystrokes(
+        &self,
+        window: &mut Window,
+        mut callback: impl FnMut(&mut T, &PendingKeystrokes, &mut Window, &mut Context<T>)
+        + 'static,
+    ) -> Subscription {
+        let mut last_keystrokes = Vec::new();
+        self.observe_pending_input(window, move |view, window, cx| {
+            let pending = window.pending_keystrokes();
+            if pending.keystrokes != last_keystrokes {
+                last_keystrokes = pending.keystrokes.clone();
+                callback(view, &pending, window, cx);
+            }
+        })
+    }
+
     /// Register a listener to be called when the given focus handle receives focus.
     /// Returns a subscription and persists until the subscription is dropped.
     pub fn on_focus(
@@ -0,0 +1,441 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{Action, App, FocusId, KeyContext};
+
+/// A recorded sequence of actions and typed text, started with [`App::start_macro_recording`].
+/// Recordings can be saved as JSON, to be kept as macros or attached to bug reports, and replayed
+/// with [`Window::replay_macro`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MacroRecording {
+    /// The recorded steps, in the order they happened.
+    pub steps: Vec<MacroStep>,
+}
+
+/// A step of a [`MacroRecording`]. Each step records the key contexts of the element it
+/// happened in, outermost first, in the format of [`KeyContext::parse`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MacroStep {
+    /// An action that was dispatched on the focused element and handled.
+    Action {
+        /// The action's name, see [`Action::name`].
+        name: String,
+        /// The action's data, see [`Action::to_json`]. Omitted for actions without data.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        arguments: Option<serde_json::Value>,
+        /// The key contexts of the element the action was dispatched on.
+        context: Vec<String>,
+    },
+    /// Text that was inserted into the focused input handler.
+    Text {
+        /// The inserted text.
+        text: String,
+        /// The key contexts of the focused element.
+        context: Vec<String>,
+    },
+}
+
+impl MacroRecording {
+    /// Parse a recording from JSON, as produced by [`MacroRecording::to_json`].
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("invalid macro recording")
+    }
+
+    /// Serialize the recording as JSON.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Load a recording from a JSON file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("failed to read macro recording {path:?}"))?;
+        Self::from_json(&json)
+    }
+
+    /// Save the recording to a JSON file.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_json()?)
+            .with_context(|| format!("failed to write macro recording {path:?}"))
+    }
+}
+
+/// A step of a recording, with its action built and its key contexts parsed, ready to be
+/// replayed.
+pub(crate) struct MacroReplayStep {
+    pub(crate) kind: MacroReplayStepKind,
+    context: Vec<KeyContext>,
+}
+
+pub(crate) enum MacroReplayStepKind {
+    Action(Box<dyn Action>),
+    Text(String),
+}
+
+impl MacroReplayStep {
+    pub(crate) fn build(step: &MacroStep, cx: &App) -> Result<Self> {
+        let (kind, context) = match step {
+            MacroStep::Action {
+                name,
+                arguments,
+                context,
+            } => (
+                MacroReplayStepKind::Action(cx.build_action(name, arguments.clone())?),
+                context,
+            ),
+            MacroStep::Text { text, context } => (MacroReplayStepKind::Text(text.clone()), context),
+        };
+        let context = context
+            .iter()
+            .map(|context| KeyContext::parse(context))
+            .collect::<Result<_>>()
+            .context("invalid key context in macro recording")?;
+        Ok(Self { kind, context })
+    }
+
+    /// Checks that the step is replayed in the same kind of element it was recorded in, going by
+    /// the primary identifier of the innermost key context, so that the step doesn't act on
+    /// whatever else has focus.
+    pub(crate) fn check_context(&self, context_stack: &[KeyContext]) -> Result<()> {
+        let innermost = |stack: &[KeyContext]| Some(stack.last()?.primary()?.key.to_string());
+        let Some(recorded) = innermost(&self.context) else {
+            return Ok(());
+        };
+        let focused = innermost(context_stack);
+        anyhow::ensure!(
+            focused.as_ref() == Some(&recorded),
+            "macro step recorded in `{recorded}` can't be replayed with focus in `{}`",
+            focused.as_deref().unwrap_or("no key context")
+        );
+        Ok(())
+    }
+}
+
+/// Records the actions and text that reach the focused element while a recording is in progress.
+#[derive(Default)]
+pub(crate) struct MacroRecorder {
+    steps: Option<Vec<MacroStep>>,
+    /// The element the last recorded text was inserted into.
+    text_focus: Option<FocusId>,
+    /// How many actions, or replayed steps, are being dispatched. Whatever they dispatch or insert
+    /// in turn is reproduced by replaying them, so it isn't recorded.
+    dispatch_depth: usize,
+}
+
+impl MacroRecorder {
+    pub(crate) fn is_recording(&self) -> bool {
+        self.steps.is_some()
+    }
+
+    pub(crate) fn start(&mut self) {
+        self.steps = Some(Vec::new());
+    }
+
+    pub(crate) fn stop(&mut self) -> Option<MacroRecording> {
+        Some(MacroRecording {
+            steps: self.steps.take()?,
+        })
+    }
+
+    pub(crate) fn is_dispatching(&self) -> bool {
+        self.dispatch_depth > 0
+    }
+
+    /// Called before dispatching an action or replaying a step. Returns whether it's outermost,
+    /// and should be recorded.
+    pub(crate) fn begin_dispatch(&mut self) -> bool {
+        self.dispatch_depth += 1;
+        self.dispatch_depth == 1
+    }
+
+    pub(crate) fn end_dispatch(&mut self) {
+        self.dispatch_depth -= 1;
+    }
+
+    pub(crate) fn record_action(&mut self, action: &dyn Action, context_stack: &[KeyContext]) {
+        let Some(steps) = self.steps.as_mut() else {
+            return;
+        };
+        let Some(arguments) = action.to_json() else {
+            log::warn!(
+                "not recording action {}, which can't be serialized",
+                action.name()
+            );
+            return;
+        };
+        let arguments = arguments
+            .as_object()
+            .is_none_or(|fields| !fields.is_empty())
+            .then_some(arguments);
+        steps.push(MacroStep::Action {
+            name: action.name().to_string(),
+            arguments,
+            context: context(context_stack),
+        });
+    }
+
+    pub(crate) fn record_text(
+        &mut self,
+        text: &str,
+        focus: Option<FocusId>,
+        context_stack: &[KeyContext],
+    ) {
+        if self.dispatch_depth > 0 {
+            return;
+        }
+        let Some(steps) = self.steps.as_mut() else {
+            return;
+        };
+        // Text is inserted a character at a time as it's typed, so join it back up.
+        let same_focus = std::mem::replace(&mut self.text_focus, focus) == focus;
+        if let Some(MacroStep::Text {
+            text: last_text, ..
+        }) = steps.last_mut()
+            && same_focus
+        {
+            last_text.push_str(text);
+        } else {
+            steps.push(MacroStep::Text {
+                text: text.to_string(),
+                context: context(context_stack),
+            });
+        }
+    }
+}
+
+fn context(context_stack: &[KeyContext]) -> Vec<String> {
+    context_stack
+        .iter()
+        .map(|context| format!("{context:?}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    use super::{MacroRecording, MacroStep};
+    use crate::{
+        self as gpui, Action, Bounds, Context, ElementInputHandler, EntityInputHandler,
+        FocusHandle, InteractiveElement, IntoElement, KeyBinding, ParentElement, Pixels, Point,
+        Render, Styled, TestAppContext, UTF16Selection, Window, canvas, div,
+    };
+
+    actions!(macro_test, [Increment]);
+
+    #[derive(Clone, PartialEq, Serialize, Deserialize, JsonSchema, Action)]
+    #[action(namespace = macro_test)]
+    struct Append {
+        suffix: String,
+    }
+
+    struct Editor {
+        focus_handle: FocusHandle,
+        count: usize,
+        text: String,
+    }
+
+    impl Render for Editor {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            let editor = cx.entity();
+            div()
+                .key_context("Editor")
+                .track_focus(&self.focus_handle)
+                .size_full()
+                .on_action(cx.listener(|this, _: &Increment, _, _| this.count += 1))
+                .on_action(
+                    cx.listener(|this, action: &Append, _, _| this.text.push_str(&action.suffix)),
+                )
+                .child(
+                    canvas(
+                        |_, _, _| {},
+                        move |bounds, _, window, cx| {
+                            let focus_handle = editor.read(cx).focus_handle.clone();
+                            window.handle_input(
+                                &focus_handle,
+                                ElementInputHandler::new(bounds, editor),
+                                cx,
+                            );
+                        },
+                    )
+                    .size_full(),
+                )
+        }
+    }
+
+    impl EntityInputHandler for Editor {
+        fn text_for_range(
+            &mut self,
+            _: Range<usize>,
+            _: &mut Option<Range<usize>>,
+            _: &mut Window,
+            _: &mut Context<Self>,
+        ) -> Option<String> {
+            None
+        }
+
+        fn selected_text_range(
+            &mut self,
+            _: bool,
+            _: &mut Window,
+            _: &mut Context<Self>,
+        ) -> Option<UTF16Selection> {
+            None
+        }
+
+        fn marked_text_range(&self, _: &mut Window, _: &mut Context<Self>) -> Option<Range<usize>> {
+            None
+        }
+
+        fn unmark_text(&mut self, _: &mut Window, _: &mut Context<Self>) {}
+
+        fn replace_text_in_range(
+            &mut self,
+            _: Option<Range<usize>>,
+            text: &str,
+            _: &mut Window,
+            _: &mut Context<Self>,
+        ) {
+            self.text.push_str(text);
+        }
+
+        fn replace_and_mark_text_in_range(
+            &mut self,
+            _: Option<Range<usize>>,
+            _: &str,
+            _: Option<Range<usize>>,
+            _: &mut Window,
+            _: &mut Context<Self>,
+        ) {
+        }
+
+        fn bounds_for_range(
+            &mut self,
+            _: Range<usize>,
+            _: Bounds<Pixels>,
+            _: &mut Window,
+            _: &mut Context<Self>,
+        ) -> Option<Bounds<Pixels>> {
+            None
+        }
+
+        fn character_index_for_point(
+            &mut self,
+            _: Point<Pixels>,
+            _: &mut Window,
+            _: &mut Context<Self>,
+        ) -> Option<usize> {
+            None
+        }
+    }
+
+    #[gpui::test]
+    fn test_record_and_replay_macro(cx: &mut TestAppContext) {
+        cx.update(|cx| cx.bind_keys([KeyBinding::new("ctrl-i", Increment, None)]));
+        let (editor, cx) = cx.add_window_view(|_, cx| Editor {
+            focus_handle: cx.focus_handle(),
+            count: 0,
+            text: String::new(),
+        });
+        cx.update(|window, cx| {
+            let focus_handle = editor.read(cx).focus_handle.clone();
+            window.focus(&focus_handle, cx);
+            cx.start_macro_recording();
+        });
+
+        cx.simulate_keystrokes("ctrl-i");
+        cx.simulate_input("ab");
+        cx.dispatch_action(Append { suffix: "c".into() });
+        // Unhandled keystrokes aren't recorded.
+        cx.simulate_keystrokes("ctrl-x");
+
+        let recording = cx.update(|_, cx| cx.stop_macro_recording()).unwrap();
+        let context = vec!["Editor".to_string()];
+        assert_eq!(
+            recording.steps,
+            [
+                MacroStep::Action {
+                    name: "macro_test::Increment".into(),
+                    arguments: None,
+                    context: context.clone(),
+                },
+                MacroStep::Text {
+                    text: "ab".into(),
+                    context: context.clone(),
+                },
+                MacroStep::Action {
+                    name: "macro_test::Append".into(),
+                    arguments: Some(serde_json::json!({ "suffix": "c" })),
+                    context,
+                },
+            ]
+        );
+        let recording = MacroRecording::from_json(&recording.to_json().unwrap()).unwrap();
+        editor.update(cx, |editor, _| {
+            assert_eq!((editor.count, editor.text.as_str()), (1, "abc"))
+        });
+
+        // Replaying isn't itself recorded.
+        cx.update(|_, cx| cx.start_macro_recording());
+        cx.update(|window, cx| window.replay_macro(&recording, 2, cx))
+            .detach();
+        cx.run_until_parked();
+        editor.update(cx, |editor, _| {
+            assert_eq!((editor.count, editor.text.as_str()), (3, "abcabcabc"))
+        });
+        let replayed = cx.update(|_, cx| cx.stop_macro_recording()).unwrap();
+        assert_eq!(replayed.steps, []);
+
+        let unknown = MacroRecording {
+            steps: vec![MacroStep::Action {
+                name: "macro_test::Unknown".into(),
+                arguments: None,
+                context: Vec::new(),
+            }],
+        };
+        let replay = cx.update(|window, cx| window.replay_macro(&unknown, 1, cx));
+        assert!(cx.foreground_executor().block_test(replay).is_err());
+
+        // Steps recorded in another kind of element aren't replayed in the editor.
+        let elsewhere = MacroRecording {
+            steps: vec![
+                MacroStep::Action {
+                    name: "macro_test::Increment".into(),
+                    arguments: None,
+                    context: vec!["Workspace".into(), "Editor mode=full".into()],
+                },
+                MacroStep::Text {
+                    text: "d".into(),
+                    context: vec!["Terminal".into()],
+                },
+            ],
+        };
+        let replay = cx.update(|window, cx| window.replay_macro(&elsewhere, 1, cx));
+        assert!(cx.foreground_executor().block_test(replay).is_err());
+        editor.update(cx, |editor, _| {
+            assert_eq!((editor.count, editor.text.as_str()), (4, "abcabcabc"))
+        });
+    }
+
+    #[test]
+    fn test_action_to_json() {
+        assert_eq!(Increment.to_json(), Some(serde_json::json!({})));
+        assert_eq!(
+            Append { suffix: "x".into() }.to_json(),
+            Some(serde_json::json!({ "suffix": "x" }))
+        );
+
+        #[derive(Clone, PartialEq, Deserialize, JsonSchema, Action)]
+        #[action(namespace = macro_test)]
+        struct NotSerializable {
+            _value: usize,
+        }
+        assert_eq!(NotSerializable { _value: 1 }.to_json(), None);
+    }
+}
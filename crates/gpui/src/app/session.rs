@@ -0,0 +1,433 @@
+use std::{any::TypeId, collections::BTreeMap};
+
+use anyhow::{Context as _, Result, anyhow};
+use collections::HashMap;
+use gpui_util::ResultExt as _;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{
+    AnyView, AnyWindowHandle, App, AppContext as _, Context, Entity, Global, Render, Window,
+    WindowOptions, WindowState, apply_window_state,
+};
+
+/// A view that can be saved with the rest of the session by [`App::save_session`], and restored
+/// when the application is launched again by [`App::restore_session`].
+///
+/// Views save the states of the persistent views they contain as part of their own, with
+/// [`PersistentView::save_entity`], and restore them with [`PersistentView::restore_entity`], so
+/// whole view hierarchies are saved from the root views of windows. Root views have to be
+/// registered with [`App::register_persistent_view`].
+pub trait PersistentView: Render + Sized {
+    /// Identifies the view in session files, so it has to stay the same across versions of the
+    /// application.
+    const KIND: &'static str;
+
+    /// The saved state of the view.
+    type State: Serialize + DeserializeOwned;
+
+    /// Save the state of the view.
+    fn save(&self, cx: &App) -> Self::State;
+
+    /// Create the view from a saved state.
+    fn restore(state: Self::State, window: &mut Window, cx: &mut Context<Self>) -> Self;
+
+    /// The options to open windows with when the view is restored as their root view. Their
+    /// bounds and display are replaced with the saved ones.
+    fn window_options(_cx: &App) -> WindowOptions {
+        WindowOptions::default()
+    }
+
+    /// Save the state of the given view.
+    fn save_entity(entity: &Entity<Self>, cx: &App) -> Self::State {
+        entity.read(cx).save(cx)
+    }
+
+    /// Create a view from a saved state, in the given window.
+    fn restore_entity(state: Self::State, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::restore(state, window, cx))
+    }
+}
+
+/// The contents of a session file.
+#[derive(Serialize, Deserialize)]
+struct SessionFile {
+    version: u32,
+    windows: Vec<SavedWindow>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedWindow {
+    /// The [`PersistentView::KIND`] of the window's root view.
+    kind: String,
+    window: WindowState,
+    view: serde_json::Value,
+}
+
+type SaveView = fn(AnyView, &App) -> Result<serde_json::Value>;
+type OpenWindow = fn(serde_json::Value, &WindowState, &mut App) -> Result<AnyWindowHandle>;
+
+/// The persistent views and migrations registered with the app.
+#[derive(Default)]
+pub(crate) struct SessionRegistry {
+    version: u32,
+    /// Migrations of session files from each version to the next.
+    migrations: BTreeMap<u32, Box<dyn Fn(&mut serde_json::Value) -> Result<()>>>,
+    save: HashMap<TypeId, (&'static str, SaveView)>,
+    open: HashMap<&'static str, OpenWindow>,
+}
+
+impl Global for SessionRegistry {}
+
+impl SessionRegistry {
+    pub(crate) fn register<V: PersistentView>(&mut self) {
+        let save: SaveView = |view, cx| {
+            let view = view
+                .downcast::<V>()
+                .map_err(|_| anyhow!("not a {} view", V::KIND))?;
+            Ok(serde_json::to_value(V::save_entity(&view, cx))?)
+        };
+        let open: OpenWindow = |state, window_state, cx| {
+            let state = serde_json::from_value::<V::State>(state)
+                .with_context(|| format!("invalid {} state", V::KIND))?;
+            let mut options = V::window_options(cx);
+            apply_window_state(window_state, &mut options, cx);
+            let handle =
+                cx.open_window(options, |window, cx| V::restore_entity(state, window, cx))?;
+            Ok(handle.into())
+        };
+        self.save.insert(TypeId::of::<V>(), (V::KIND, save));
+        self.open.insert(V::KIND, open);
+    }
+
+    pub(crate) fn set_version(&mut self, version: u32) {
+        self.version = version;
+    }
+
+    pub(crate) fn add_migration(
+        &mut self,
+        from_version: u32,
+        migrate: impl Fn(&mut serde_json::Value) -> Result<()> + 'static,
+    ) {
+        self.migrations.insert(from_version, Box::new(migrate));
+    }
+
+    /// Bring a session file saved at an older version up to the current one.
+    fn migrate(&self, session: &mut serde_json::Value) -> Result<()> {
+        let version = session
+            .get("version")
+            .and_then(|version| version.as_u64())
+            .context("session file has no version")?;
+        let version = u32::try_from(version)
+            .with_context(|| format!("session file version {version} is out of range"))?;
+        if version > self.version {
+            anyhow::bail!(
+                "session file version {version} is newer than version {}",
+                self.version
+            );
+        }
+        for version in version..self.version {
+            let migrate = self
+                .migrations
+                .get(&version)
+                .with_context(|| format!("no migration from session version {version}"))?;
+            migrate(session)
+                .with_context(|| format!("failed to migrate session version {version}"))?;
+        }
+        session["version"] = self.version.into();
+        Ok(())
+    }
+}
+
+/// Save the windows whose root views are registered persistent views, as the JSON of a session
+/// file. Windows that can't be saved are skipped.
+pub(crate) fn save_session(cx: &mut App) -> Result<String> {
+    let (version, save) = match cx.try_global::<SessionRegistry>() {
+        Some(registry) => (registry.version, registry.save.clone()),
+        None => (0, HashMap::default()),
+    };
+    let mut windows = Vec::new();
+    for handle in cx.windows() {
+        let saved = handle.update(cx, |root, window, cx| {
+            let (kind, save) = save.get(&root.entity_type())?;
+            let view = save(root, cx).with_context(|| format!("failed to save {kind} window"));
+            Some(view.map(|view| SavedWindow {
+                kind: kind.to_string(),
+                window: window.window_state(cx),
+                view,
+            }))
+        });
+        if let Ok(Some(saved)) = saved
+            && let Some(saved) = saved.log_err()
+        {
+            windows.push(saved);
+        }
+    }
+    Ok(serde_json::to_string_pretty(&SessionFile {
+        version,
+        windows,
+    })?)
+}
+
+/// Open the windows saved in the JSON of a session file, migrating it first if it was saved at an
+/// older version. Windows that can't be restored are skipped.
+pub(crate) fn restore_session(json: &str, cx: &mut App) -> Result<Vec<AnyWindowHandle>> {
+    let mut session = serde_json::from_str::<serde_json::Value>(json).context("invalid session")?;
+    let open = {
+        let registry = cx.default_global::<SessionRegistry>();
+        registry.migrate(&mut session)?;
+        registry.open.clone()
+    };
+    let session = serde_json::from_value::<SessionFile>(session).context("invalid session")?;
+
+    let mut handles = Vec::new();
+    for saved in session.windows {
+        let Some(open) = open.get(saved.kind.as_str()) else {
+            log::error!("no persistent view registered for {:?}", saved.kind);
+            continue;
+        };
+        if let Some(handle) = open(saved.view, &saved.window, cx).log_err() {
+            handles.push(handle);
+        }
+    }
+    Ok(handles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Bounds, IntoElement, ParentElement as _, TestAppContext, WindowBounds, div, point, px, size,
+    };
+
+    struct Workspace {
+        panes: Vec<Entity<Pane>>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct WorkspaceState {
+        panes: Vec<PaneState>,
+    }
+
+    impl Render for Workspace {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div().children(self.panes.clone())
+        }
+    }
+
+    impl PersistentView for Workspace {
+        const KIND: &'static str = "workspace";
+        type State = WorkspaceState;
+
+        fn save(&self, cx: &App) -> Self::State {
+            WorkspaceState {
+                panes: self
+                    .panes
+                    .iter()
+                    .map(|pane| Pane::save_entity(pane, cx))
+                    .collect(),
+            }
+        }
+
+        fn restore(state: Self::State, window: &mut Window, cx: &mut Context<Self>) -> Self {
+            Self {
+                panes: state
+                    .panes
+                    .into_iter()
+                    .map(|pane| Pane::restore_entity(pane, window, cx))
+                    .collect(),
+            }
+        }
+    }
+
+    struct Pane {
+        path: String,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct PaneState {
+        path: String,
+    }
+
+    impl Render for Pane {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div()
+        }
+    }
+
+    impl PersistentView for Pane {
+        const KIND: &'static str = "pane";
+        type State = PaneState;
+
+        fn save(&self, _: &App) -> Self::State {
+            PaneState {
+                path: self.path.clone(),
+            }
+        }
+
+        fn restore(state: Self::State, _: &mut Window, _: &mut Context<Self>) -> Self {
+            Self { path: state.path }
+        }
+    }
+
+    fn restored_paths(handles: &[AnyWindowHandle], cx: &mut TestAppContext) -> Vec<Vec<String>> {
+        handles
+            .iter()
+            .map(|handle| {
+                handle
+                    .read(cx, |workspace: Entity<Workspace>, cx| {
+                        let panes = &workspace.read(cx).panes;
+                        panes
+                            .iter()
+                            .map(|pane| pane.read(cx).path.clone())
+                            .collect()
+                    })
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[crate::test]
+    fn test_save_and_restore_session(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            cx.register_persistent_view::<Workspace>();
+            cx.set_session_version(1);
+        });
+        let bounds = Bounds::new(point(px(100.), px(50.)), size(px(800.), px(600.)));
+        let workspace = cx.update(|cx| {
+            let options = WindowOptions {
+                window_bounds: Some(WindowBounds::Windowed(bounds)),
+                ..Default::default()
+            };
+            cx.open_window(options, |_, cx| {
+                let panes = ["a.rs", "b.rs"]
+                    .map(|path| cx.new(|_| Pane { path: path.into() }))
+                    .to_vec();
+                cx.new(|_| Workspace { panes })
+            })
+            .unwrap()
+        });
+        // Windows whose root views aren't persistent aren't saved.
+        cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| {
+                cx.new(|_| Pane {
+                    path: "c.rs".into(),
+                })
+            })
+        })
+        .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        cx.update(|cx| cx.save_session(&path)).unwrap();
+        for handle in cx.update(|cx| cx.windows()) {
+            handle
+                .update(cx, |_, window, _| window.remove_window())
+                .unwrap();
+        }
+        assert!(workspace.update(cx, |_, _, _| ()).is_err());
+
+        let handles = cx.update(|cx| cx.restore_session(&path)).unwrap();
+        assert_eq!(restored_paths(&handles, cx), [["a.rs", "b.rs"]]);
+        let restored_bounds = handles[0]
+            .update(cx, |_, window, _| window.window_bounds())
+            .unwrap();
+        assert_eq!(restored_bounds, WindowBounds::Windowed(bounds));
+    }
+
+    /// A view whose state can't be saved, as JSON object keys have to be strings.
+    struct Unsaveable;
+
+    impl Render for Unsaveable {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div()
+        }
+    }
+
+    impl PersistentView for Unsaveable {
+        const KIND: &'static str = "unsaveable";
+        type State = BTreeMap<(u32, u32), bool>;
+
+        fn save(&self, _: &App) -> Self::State {
+            BTreeMap::from_iter([((0, 0), true)])
+        }
+
+        fn restore(_: Self::State, _: &mut Window, _: &mut Context<Self>) -> Self {
+            Self
+        }
+    }
+
+    #[crate::test]
+    fn test_skip_windows_that_fail_to_save(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            cx.register_persistent_view::<Workspace>();
+            cx.register_persistent_view::<Unsaveable>();
+            cx.open_window(Default::default(), |_, cx| cx.new(|_| Unsaveable))
+                .unwrap();
+            cx.open_window(Default::default(), |_, cx| {
+                let panes = vec![cx.new(|_| Pane {
+                    path: "a.rs".into(),
+                })];
+                cx.new(|_| Workspace { panes })
+            })
+            .unwrap();
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        cx.update(|cx| cx.save_session(&path)).unwrap();
+        let handles = cx.update(|cx| cx.restore_session(&path)).unwrap();
+        assert_eq!(restored_paths(&handles, cx), [["a.rs"]]);
+    }
+
+    #[crate::test]
+    fn test_migrate_session(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            cx.register_persistent_view::<Workspace>();
+            cx.set_session_version(2);
+            cx.add_session_migration(0, |session| {
+                for window in session["windows"].as_array_mut().into_iter().flatten() {
+                    window["view"]["panes"] = window["view"]["files"].take();
+                }
+                Ok(())
+            });
+        });
+        let window = serde_json::json!({
+            "bounds": {
+                "windowed": {
+                    "origin": { "x": 0., "y": 0. },
+                    "size": { "width": 640., "height": 480. },
+                },
+            },
+            "display": null,
+            "scale_factor": 1.,
+        });
+        let session = serde_json::json!({
+            "version": 0,
+            "windows": [{
+                "kind": "workspace",
+                "window": window,
+                "view": { "files": [{ "path": "a.rs" }] },
+            }],
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        let mut out_of_range = session.clone();
+        out_of_range["version"] = (u64::from(u32::MAX) + 1).into();
+        std::fs::write(&path, out_of_range.to_string()).unwrap();
+        let error = cx.update(|cx| cx.restore_session(&path)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "session file version 4294967296 is out of range"
+        );
+
+        std::fs::write(&path, session.to_string()).unwrap();
+
+        // There's no migration from version 1 to 2 yet.
+        let error = cx.update(|cx| cx.restore_session(&path)).unwrap_err();
+        assert_eq!(error.to_string(), "no migration from session version 1");
+
+        cx.update(|cx| cx.add_session_migration(1, |_| Ok(())));
+        let handles = cx.update(|cx| cx.restore_session(&path)).unwrap();
+        assert_eq!(restored_paths(&handles, cx), [["a.rs"]]);
+    }
+}
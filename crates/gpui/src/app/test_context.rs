@@ -1,11 +1,14 @@
 use crate::{
+    AccessibilityAction, AccessibilityActionRequest, AccessibilityNodeId, AccessibilityTree,
     Action, AnyView, AnyWindowHandle, App, AppCell, AppContext, AsyncApp, AvailableSpace,
     BackgroundExecutor, BorrowAppContext, Bounds, Capslock, ClipboardItem, DrawPhase, Drawable,
-    Element, Empty, EventEmitter, ForegroundExecutor, Global, InputEvent, Keystroke, Modifiers,
-    ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels,
-    Platform, Point, Render, Result, Size, Task, TestDispatcher, TestPlatform,
-    TestScreenCaptureSource, TestWindow, TextSystem, VisualContext, Window, WindowBounds,
-    WindowHandle, WindowOptions, app::GpuiMode,
+    Element, Empty, EventEmitter, ExternalDrag, ExternalDragOutcome, ForegroundExecutor, Global,
+    InputEvent, Keystroke, Modifiers, ModifiersChangedEvent, MouseButton, MouseDownEvent,
+    MouseMoveEvent, MouseUpEvent, Notification, NotificationId, NotificationResponse, Pixels,
+    Platform, Point, Render, Result, Size, StatusItem, StatusItemClick, Task, TestDispatcher,
+    TestNativeControl, TestNativeControlEvent, TestNativeControlId, TestNativeControlKind,
+    TestNativePresentation, TestPlatform, TestScreenCaptureSource, TestWindow, TextSystem,
+    VisualContext, Window, WindowBounds, WindowHandle, WindowOptions, app::GpuiMode,
 };
 use anyhow::{anyhow, bail};
 use futures::{Stream, StreamExt, channel::oneshot};
@@ -349,6 +352,30 @@ impl TestAppContext {
         self.test_platform.pending_prompt()
     }
 
+    /// Reads the icon currently shown with `cx.set_status_item()`.
+    pub fn read_status_item<R>(&self, read: impl FnOnce(Option<&StatusItem>) -> R) -> R {
+        read(self.test_platform.status_item.borrow().as_ref())
+    }
+
+    /// Simulates the user clicking the icon shown with `cx.set_status_item()`.
+    pub fn simulate_status_item_click(&self, click: StatusItemClick) {
+        self.test_platform.simulate_status_item_click(click);
+    }
+
+    /// The notifications currently shown with `cx.show_notification()`.
+    pub fn shown_notifications(&self) -> Vec<(NotificationId, Notification)> {
+        self.test_platform.shown_notifications()
+    }
+
+    /// Simulates the user responding to a notification.
+    pub fn simulate_notification_response(
+        &self,
+        id: NotificationId,
+        response: NotificationResponse,
+    ) {
+        self.test_platform.simulate_notification_response(id, response);
+    }
+
     /// All the urls that have been opened with cx.open_url() during this test.
     pub fn opened_url(&self) -> Option<String> {
         self.test_platform.opened_url.borrow().clone()
@@ -359,6 +386,60 @@ impl TestAppContext {
         self.test_window(window_handle).simulate_resize(size);
     }
 
+    /// Returns the accessibility tree the given window last handed to the platform.
+    pub fn accessibility_tree(&self, window: AnyWindowHandle) -> Option<AccessibilityTree> {
+        self.test_window(window).0.lock().accessibility_tree.clone()
+    }
+
+    /// Simulates an assistive technology starting to listen to the given window, which then
+    /// redraws to build its accessibility tree.
+    pub fn simulate_accessibility_activation(&mut self, window: AnyWindowHandle) {
+        self.test_window(window).simulate_accessibility_activation();
+        self.update_window(window, |_, window, _| window.refresh())
+            .unwrap();
+        self.background_executor.run_until_parked();
+    }
+
+    /// Simulates an assistive technology asking the given window to perform an action.
+    pub fn simulate_accessibility_action(
+        &self,
+        window: AnyWindowHandle,
+        request: AccessibilityActionRequest,
+    ) {
+        self.test_window(window)
+            .simulate_accessibility_action(request);
+        self.background_executor.run_until_parked();
+    }
+
+    /// Returns the data being dragged out of the given window, if a drag is in progress.
+    pub fn external_drag(&self, window: AnyWindowHandle) -> Option<ExternalDrag> {
+        self.test_window(window).external_drag()
+    }
+
+    /// Simulates the drag out of the given window ending, with the drop target doing the given
+    /// thing with the data. Returns the data, or `None` if no drag was in progress.
+    pub fn simulate_external_drag_end(
+        &mut self,
+        window: AnyWindowHandle,
+        outcome: ExternalDragOutcome,
+    ) -> Option<ExternalDrag> {
+        let drag = self.test_window(window).simulate_external_drag_end(outcome);
+        self.background_executor.run_until_parked();
+        drag
+    }
+
+    /// Returns the native controls the given window's elements configured, in the order they
+    /// were first created. Controls whose elements are no longer rendered are omitted.
+    pub fn native_controls(&self, window: AnyWindowHandle) -> Vec<TestNativeControl> {
+        self.test_window(window).1.controls()
+    }
+
+    /// Returns the panels, popovers, alerts and context menus the given window's native
+    /// controls have shown, oldest first.
+    pub fn native_presentations(&self, window: AnyWindowHandle) -> Vec<TestNativePresentation> {
+        self.test_window(window).1.presentations()
+    }
+
     /// Returns true if there's an alert dialog open.
     pub fn expect_restart(&self) -> oneshot::Receiver<Option<PathBuf>> {
         let (tx, rx) = futures::channel::oneshot::channel();
@@ -830,6 +911,123 @@ impl VisualTestContext {
         self.simulate_window_resize(self.window, size)
     }
 
+    /// Returns the window's accessibility tree as of the last drawn frame.
+    pub fn accessibility_tree(&mut self) -> AccessibilityTree {
+        self.run_until_parked();
+        let window = self.window;
+        self.cx
+            .accessibility_tree(window)
+            .expect("window has not been drawn since an assistive technology started listening")
+    }
+
+    /// Simulates an assistive technology starting to listen to the window, which then redraws
+    /// to build its accessibility tree.
+    pub fn simulate_accessibility_activation(&mut self) {
+        let window = self.window;
+        self.cx.simulate_accessibility_activation(window)
+    }
+
+    /// Simulates an assistive technology asking the given node to perform an action.
+    pub fn simulate_accessibility_action(
+        &mut self,
+        target: AccessibilityNodeId,
+        action: AccessibilityAction,
+    ) {
+        let window = self.window;
+        self.cx.simulate_accessibility_action(
+            window,
+            AccessibilityActionRequest {
+                target,
+                action,
+                value: None,
+            },
+        );
+    }
+
+    /// Returns the data being dragged out of the window, if a drag is in progress.
+    pub fn external_drag(&self) -> Option<ExternalDrag> {
+        self.cx.external_drag(self.window)
+    }
+
+    /// Simulates the drag out of the window ending, with the drop target doing the given thing
+    /// with the data. Returns the data, or `None` if no drag was in progress.
+    pub fn simulate_external_drag_end(
+        &mut self,
+        outcome: ExternalDragOutcome,
+    ) -> Option<ExternalDrag> {
+        let window = self.window;
+        self.cx.simulate_external_drag_end(window, outcome)
+    }
+
+    /// Returns the window's native controls as of the last drawn frame.
+    pub fn native_controls(&mut self) -> Vec<TestNativeControl> {
+        self.run_until_parked();
+        self.cx.native_controls(self.window)
+    }
+
+    /// Returns the first native control of the given kind with the given title.
+    pub fn native_control(
+        &mut self,
+        kind: TestNativeControlKind,
+        title: &str,
+    ) -> TestNativeControl {
+        self.native_controls()
+            .into_iter()
+            .find(|control| control.kind == kind && control.title.as_deref() == Some(title))
+            .unwrap_or_else(|| panic!("no {kind:?} titled {title:?}"))
+    }
+
+    /// Returns the panels, popovers, alerts and context menus the window's native controls
+    /// have shown, oldest first.
+    pub fn native_presentations(&mut self) -> Vec<TestNativePresentation> {
+        self.run_until_parked();
+        self.cx.native_presentations(self.window)
+    }
+
+    /// Simulates the user interacting with a native control, then runs the element's
+    /// handler for it.
+    pub fn simulate_native_control_event(
+        &mut self,
+        control: TestNativeControlId,
+        event: TestNativeControlEvent,
+    ) {
+        let window = self.window;
+        self.cx.test_window(window).1.simulate_event(control, event);
+        self.run_native_control_callbacks();
+    }
+
+    /// Simulates the user clicking one of the buttons of the oldest open alert sheet.
+    pub fn simulate_native_alert_response(&mut self, button_index: usize) {
+        let window = self.window;
+        self.cx
+            .test_window(window)
+            .1
+            .simulate_alert_response(button_index);
+        self.run_native_control_callbacks();
+    }
+
+    /// Simulates the user choosing an action from the oldest open context menu, or
+    /// dismissing it with `None`.
+    pub fn simulate_native_context_menu_selection(&mut self, index: Option<usize>) {
+        let window = self.window;
+        self.cx
+            .test_window(window)
+            .1
+            .simulate_context_menu_selection(index);
+        self.run_native_control_callbacks();
+    }
+
+    /// Native controls defer their handlers to the next frame, which the test platform
+    /// never requests on its own.
+    fn run_native_control_callbacks(&mut self) {
+        self.update(|window, cx| {
+            for callback in window.next_frame_callbacks.take() {
+                callback(window, cx);
+            }
+        });
+        self.run_until_parked();
+    }
+
     /// debug_bounds returns the bounds of the element with the given selector.
     pub fn debug_bounds(&mut self, selector: &'static str) -> Option<Bounds<Pixels>> {
         self.update(|window, _| window.rendered_frame.debug_bounds.get(selector).copied())
@@ -0,0 +1,541 @@
+use std::ops::Range;
+
+use crate::{
+    Action, App, Context, DismissEvent, Div, EventEmitter, FocusHandle, Focusable, FontWeight,
+    HighlightStyle, InteractiveElement, IntoElement, KeyDownEvent, NoAction, ParentElement, Render,
+    ScrollStrategy, SharedString, Stateful, StatefulInteractiveElement, Styled, StyledText,
+    UniformListScrollHandle, Window, div, opaque_grey, prelude::FluentBuilder as _, uniform_list,
+    white,
+};
+
+/// A command that can be run from a command palette, describing one registered action.
+pub struct Command {
+    /// The action's name, like `editor::MoveUp`.
+    pub name: &'static str,
+    /// The action's name as it's shown to users, like `editor: move up`. See
+    /// [`humanize_action_name`].
+    pub label: SharedString,
+    /// The action's documentation, from its doc comment.
+    pub documentation: Option<&'static str>,
+    /// Why the action is deprecated, if it is.
+    pub deprecation_message: Option<&'static str>,
+    /// The keystrokes bound to the action in the focused context, if any, separated by spaces.
+    pub keybinding: Option<SharedString>,
+    /// The action to dispatch, if it's handled in the focused context.
+    pub action: Option<Box<dyn Action>>,
+}
+
+impl Command {
+    /// Returns whether the command is handled in the focused context.
+    pub fn is_available(&self) -> bool {
+        self.action.is_some()
+    }
+}
+
+/// A command matching a [`CommandRegistry::search`] query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandMatch {
+    /// The index of the command in [`CommandRegistry::commands`].
+    pub command_ix: usize,
+    /// How well the command matches, higher is better.
+    pub score: usize,
+    /// The byte offsets of the matched characters in the command's label, for highlighting.
+    pub positions: Vec<usize>,
+}
+
+/// The commands that can be run in a window, merging every registered action's documentation
+/// with whether it's handled, and how it's bound, in the focused context. This is the data source
+/// for a command palette, and [`CommandPalette`] is a default one.
+pub struct CommandRegistry {
+    commands: Vec<Command>,
+}
+
+impl CommandRegistry {
+    /// Collect the commands for the window's focused element. Deprecated aliases of actions and
+    /// [`crate::NoAction`] are left out.
+    pub fn new(window: &Window, cx: &App) -> Self {
+        let mut available_actions = window.available_actions(cx);
+        let deprecated_aliases = cx.deprecated_actions_to_preferred_actions();
+        let mut commands = cx
+            .all_action_names()
+            .iter()
+            .filter(|&&name| {
+                !deprecated_aliases.contains_key(name) && name != NoAction::name_for_type()
+            })
+            .map(|&name| {
+                let action = available_actions
+                    .iter()
+                    .position(|action| action.name() == name)
+                    .map(|ix| available_actions.swap_remove(ix));
+                let keybinding = action
+                    .as_ref()
+                    .and_then(|action| window.highest_precedence_binding_for_action(&**action))
+                    .map(|binding| {
+                        binding
+                            .keystrokes()
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                            .into()
+                    });
+                Command {
+                    name,
+                    label: humanize_action_name(name).into(),
+                    documentation: cx.action_documentation().get(name).copied(),
+                    deprecation_message: cx.action_deprecation_messages().get(name).copied(),
+                    keybinding,
+                    action,
+                }
+            })
+            .collect::<Vec<_>>();
+        commands.sort_by(|a, b| a.label.cmp(&b.label));
+        Self { commands }
+    }
+
+    /// All of the commands, sorted by label.
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    /// Fuzzy-match the query against the commands' labels, ignoring case and whitespace. Matches
+    /// are sorted with available commands first, then by score, then by label. An empty query
+    /// matches every command.
+    pub fn search(&self, query: &str) -> Vec<CommandMatch> {
+        let mut matches = self
+            .commands
+            .iter()
+            .enumerate()
+            .filter_map(|(command_ix, command)| {
+                let (score, positions) = fuzzy_match(query, &command.label)?;
+                Some(CommandMatch {
+                    command_ix,
+                    score,
+                    positions,
+                })
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by(|a, b| {
+            let (a_command, b_command) =
+                (&self.commands[a.command_ix], &self.commands[b.command_ix]);
+            b_command
+                .is_available()
+                .cmp(&a_command.is_available())
+                .then(b.score.cmp(&a.score))
+                .then(a.command_ix.cmp(&b.command_ix))
+        });
+        matches
+    }
+}
+
+/// Convert an action name to the form shown to users, like `editor::GoToDefinition` to
+/// `editor: go to definition`.
+pub fn humanize_action_name(name: &str) -> String {
+    let mut result = String::with_capacity(name.len() + 8);
+    let mut previous = None;
+    let mut chars = name.chars().peekable();
+    while let Some(char) = chars.next() {
+        if char == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            result.push_str(": ");
+            previous = Some(' ');
+            continue;
+        }
+        if char == '_' {
+            result.push(' ');
+        } else if char.is_uppercase() {
+            if previous
+                .is_some_and(|previous: char| previous.is_lowercase() || previous.is_numeric())
+            {
+                result.push(' ');
+            }
+            result.extend(char.to_lowercase());
+        } else {
+            result.push(char);
+        }
+        previous = Some(char);
+    }
+    result
+}
+
+/// Match the query's characters in order in the candidate, ignoring case and whitespace. Matches
+/// at the start of words and runs of consecutive characters score higher. Returns the score and
+/// the byte offsets of the matched characters.
+fn fuzzy_match(query: &str, candidate: &str) -> Option<(usize, Vec<usize>)> {
+    let mut score = 0;
+    let mut positions = Vec::new();
+    let mut candidate_chars = candidate.char_indices();
+    let mut previous_char = None;
+    let mut previous_matched = false;
+    for query_char in query.chars().filter(|char| !char.is_whitespace()) {
+        loop {
+            let (offset, char) = candidate_chars.next()?;
+            let at_word_start =
+                previous_char.is_none_or(|previous: char| !previous.is_alphanumeric());
+            previous_char = Some(char);
+            if !char.to_lowercase().eq(query_char.to_lowercase()) {
+                previous_matched = false;
+                continue;
+            }
+
+            score += 1;
+            if at_word_start {
+                score += 2;
+            }
+            if previous_matched {
+                score += 3;
+            }
+            previous_matched = true;
+            positions.push(offset);
+            break;
+        }
+    }
+    Some((score, positions))
+}
+
+/// A default command palette, listing the commands available where focus was when it was
+/// created. Type to filter, use the arrow keys to select a command, and enter to run it, which
+/// dispatches its action on the previously focused element. Escape dismisses the palette, which
+/// emits [`DismissEvent`] either way.
+pub struct CommandPalette {
+    focus_handle: FocusHandle,
+    previous_focus: Option<FocusHandle>,
+    registry: CommandRegistry,
+    query: String,
+    matches: Vec<CommandMatch>,
+    selected_ix: usize,
+    scroll_handle: UniformListScrollHandle,
+}
+
+impl CommandPalette {
+    /// Create a palette for the commands available where focus is now, and focus it.
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let registry = CommandRegistry::new(window, cx);
+        let focus_handle = cx.focus_handle();
+        let previous_focus = window.focused(cx);
+        window.focus(&focus_handle, cx);
+        let mut this = Self {
+            focus_handle,
+            previous_focus,
+            registry,
+            query: String::new(),
+            matches: Vec::new(),
+            selected_ix: 0,
+            scroll_handle: UniformListScrollHandle::new(),
+        };
+        this.update_matches();
+        this
+    }
+
+    /// The commands the palette was created with.
+    pub fn registry(&self) -> &CommandRegistry {
+        &self.registry
+    }
+
+    /// The query typed so far.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Replace the query, and select the best match.
+    pub fn set_query(&mut self, query: impl Into<String>, cx: &mut Context<Self>) {
+        self.query = query.into();
+        self.update_matches();
+        cx.notify();
+    }
+
+    /// The available commands matching the query, best first.
+    pub fn matches(&self) -> &[CommandMatch] {
+        &self.matches
+    }
+
+    /// The index of the selected match.
+    pub fn selected_index(&self) -> usize {
+        self.selected_ix
+    }
+
+    /// Select the match at the given index.
+    pub fn set_selected_index(&mut self, ix: usize, cx: &mut Context<Self>) {
+        self.selected_ix = ix.min(self.matches.len().saturating_sub(1));
+        self.scroll_handle
+            .scroll_to_item(self.selected_ix, ScrollStrategy::Nearest);
+        cx.notify();
+    }
+
+    /// Run the selected command on the previously focused element, and dismiss the palette.
+    pub fn confirm(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let action = self
+            .matches
+            .get(self.selected_ix)
+            .and_then(|mat| self.registry.commands[mat.command_ix].action.as_ref())
+            .map(|action| action.boxed_clone());
+        self.dismiss(window, cx);
+        if let Some(action) = action {
+            window.dispatch_action(action, cx);
+        }
+    }
+
+    /// Restore focus to where it was before the palette was created, and emit [`DismissEvent`].
+    pub fn dismiss(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(previous_focus) = &self.previous_focus {
+            window.focus(previous_focus, cx);
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn update_matches(&mut self) {
+        self.matches = self.registry.search(&self.query);
+        self.matches
+            .retain(|mat| self.registry.commands[mat.command_ix].is_available());
+        self.selected_ix = 0;
+        self.scroll_handle.scroll_to_item(0, ScrollStrategy::Top);
+    }
+
+    fn handle_key_down(
+        &mut self,
+        event: &KeyDownEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let keystroke = &event.keystroke;
+        match keystroke.key.as_str() {
+            "enter" => self.confirm(window, cx),
+            "escape" => self.dismiss(window, cx),
+            "up" => self.set_selected_index(self.selected_ix.saturating_sub(1), cx),
+            "down" => self.set_selected_index(self.selected_ix + 1, cx),
+            "backspace" => {
+                let mut query = self.query.clone();
+                query.pop();
+                self.set_query(query, cx);
+            }
+            _ => {
+                let Some(key_char) = &keystroke.key_char else {
+                    return;
+                };
+                if keystroke.modifiers.control || keystroke.modifiers.platform {
+                    return;
+                }
+                let query = format!("{}{key_char}", self.query);
+                self.set_query(query, cx);
+            }
+        }
+        cx.stop_propagation();
+    }
+}
+
+impl EventEmitter<DismissEvent> for CommandPalette {}
+
+impl Focusable for CommandPalette {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for CommandPalette {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let query = if self.query.is_empty() {
+            div()
+                .text_color(opaque_grey(0.5, 1.))
+                .child("Type a command…")
+        } else {
+            div().child(self.query.clone())
+        };
+
+        div()
+            .key_context("CommandPalette")
+            .track_focus(&self.focus_handle)
+            .on_key_down(cx.listener(Self::handle_key_down))
+            .w_96()
+            .flex()
+            .flex_col()
+            .bg(white())
+            .rounded_lg()
+            .overflow_hidden()
+            .child(
+                div()
+                    .p_2()
+                    .border_b_1()
+                    .border_color(opaque_grey(0.2, 0.5))
+                    .child(query),
+            )
+            .child(
+                uniform_list(
+                    "command-palette-matches",
+                    self.matches.len(),
+                    cx.processor(|this, range: Range<usize>, _, cx| {
+                        this.matches[range.clone()]
+                            .iter()
+                            .zip(range)
+                            .map(|(mat, ix)| this.render_match(mat, ix, cx))
+                            .collect()
+                    }),
+                )
+                .track_scroll(&self.scroll_handle)
+                .h_64(),
+            )
+    }
+}
+
+impl CommandPalette {
+    fn render_match(&self, mat: &CommandMatch, ix: usize, cx: &mut Context<Self>) -> Stateful<Div> {
+        let command = &self.registry.commands[mat.command_ix];
+        let highlights = mat.positions.iter().map(|&offset| {
+            let len = command.label[offset..]
+                .chars()
+                .next()
+                .map_or(0, char::len_utf8);
+            (
+                offset..offset + len,
+                HighlightStyle {
+                    font_weight: Some(FontWeight::BOLD),
+                    ..Default::default()
+                },
+            )
+        });
+
+        div()
+            .id(ix)
+            .flex()
+            .flex_row()
+            .justify_between()
+            .px_2()
+            .py_1()
+            .text_sm()
+            .cursor_pointer()
+            .when(ix == self.selected_ix, |this| {
+                this.bg(opaque_grey(0.5, 0.2))
+            })
+            .child(StyledText::new(command.label.clone()).with_highlights(highlights))
+            .children(
+                command
+                    .keybinding
+                    .clone()
+                    .map(|keybinding| div().text_color(opaque_grey(0.4, 1.)).child(keybinding)),
+            )
+            .on_click(cx.listener(move |this, _, window, cx| {
+                this.selected_ix = ix;
+                this.confirm(window, cx);
+            }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CommandPalette, CommandRegistry, fuzzy_match, humanize_action_name};
+    use crate::{
+        AppContext as _, Context, DismissEvent, Entity, FocusHandle, InteractiveElement,
+        IntoElement, KeyBinding, Keystroke, ParentElement, Render, Subscription, TestAppContext,
+        Window, div,
+    };
+
+    actions!(palette_test, [Undo, Redo, Unhandled]);
+
+    struct Editor {
+        focus_handle: FocusHandle,
+        redo_count: usize,
+        palette: Option<(Entity<CommandPalette>, Subscription)>,
+    }
+
+    impl Render for Editor {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .key_context("Editor")
+                .track_focus(&self.focus_handle)
+                .on_action(cx.listener(|_, _: &Undo, _, _| {}))
+                .on_action(cx.listener(|this, _: &Redo, _, _| this.redo_count += 1))
+                .children(self.palette.as_ref().map(|(palette, _)| palette.clone()))
+        }
+    }
+
+    #[test]
+    fn test_humanize_action_name() {
+        assert_eq!(
+            humanize_action_name("editor::GoToDefinition"),
+            "editor: go to definition"
+        );
+        assert_eq!(
+            humanize_action_name("git_panel::ToggleFocus"),
+            "git panel: toggle focus"
+        );
+        assert_eq!(humanize_action_name("Quit"), "quit");
+    }
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert_eq!(fuzzy_match("mu", "editor: move up"), Some((6, vec![8, 13])));
+        assert_eq!(
+            fuzzy_match("MO  ve", "editor: move up").unwrap().1,
+            [8, 9, 10, 11]
+        );
+        assert_eq!(fuzzy_match("um", "editor: move up"), None);
+        assert_eq!(fuzzy_match("", "editor: move up"), Some((0, vec![])));
+    }
+
+    #[crate::test]
+    fn test_command_palette(cx: &mut TestAppContext) {
+        cx.update(|cx| cx.bind_keys([KeyBinding::new("ctrl-z", Undo, Some("Editor"))]));
+        let (editor, cx) = cx.add_window_view(|_, cx| Editor {
+            focus_handle: cx.focus_handle(),
+            redo_count: 0,
+            palette: None,
+        });
+        cx.update(|window, cx| {
+            let focus_handle = editor.read(cx).focus_handle.clone();
+            window.focus(&focus_handle, cx);
+        });
+        cx.run_until_parked();
+
+        cx.update(|window, cx| {
+            let registry = CommandRegistry::new(window, cx);
+            let command = |name| {
+                registry
+                    .commands()
+                    .iter()
+                    .find(|command| command.name == name)
+                    .unwrap()
+            };
+            let undo = command("palette_test::Undo");
+            assert_eq!(undo.label, "palette test: undo");
+            assert!(undo.is_available());
+            assert_eq!(
+                undo.keybinding,
+                Some(Keystroke::parse("ctrl-z").unwrap().to_string().into())
+            );
+            let unhandled = command("palette_test::Unhandled");
+            assert!(!unhandled.is_available());
+            assert_eq!(unhandled.keybinding, None);
+
+            let matches = registry.search("palette redo");
+            let best = &registry.commands()[matches[0].command_ix];
+            assert_eq!(best.name, "palette_test::Redo");
+        });
+
+        editor.update_in(cx, |editor, window, cx| {
+            let palette = cx.new(|cx| CommandPalette::new(window, cx));
+            let subscription = cx.subscribe(&palette, |editor, _, _: &DismissEvent, cx| {
+                editor.palette = None;
+                cx.notify();
+            });
+            editor.palette = Some((palette, subscription));
+            cx.notify();
+        });
+        cx.simulate_input("palette");
+        let palette = editor.read_with(cx, |editor, _| editor.palette.as_ref().unwrap().0.clone());
+        palette.read_with(cx, |palette, _| {
+            assert_eq!(palette.query(), "palette");
+            let names = palette
+                .matches()
+                .iter()
+                .map(|mat| palette.registry().commands()[mat.command_ix].name)
+                .collect::<Vec<_>>();
+            // Unhandled commands aren't listed.
+            assert_eq!(names, ["palette_test::Redo", "palette_test::Undo"]);
+        });
+
+        cx.simulate_keystrokes("down down up enter");
+        editor.read_with(cx, |editor, _| {
+            assert_eq!(editor.redo_count, 1);
+            assert!(editor.palette.is_none());
+        });
+    }
+}
@@ -489,6 +489,7 @@ impl<E: Element> Drawable<E> {
                     window,
                     cx,
                 );
+                window.paint_queued_native_controls(cx);
 
                 if global_id.is_some() {
                     window.element_id_stack.pop();
@@ -16,18 +16,19 @@
 //! constructed by combining these two systems into an all-in-one element.
 
 use crate::{
-    AbsoluteLength, Action, AnyDrag, AnyElement, AnyTooltip, AnyView, App, Bounds, ClickEvent,
-    DispatchPhase, Display, Element, ElementId, Entity, FocusHandle, Global, GlobalElementId,
-    Hitbox, HitboxBehavior, HitboxId, InspectorElementId, IntoElement, IsZero, KeyContext,
-    KeyDownEvent, KeyUpEvent, KeyboardButton, KeyboardClickEvent, LayoutId, ModifiersChangedEvent,
-    MouseButton, MouseClickEvent, MouseDownEvent, MouseMoveEvent, MousePressureEvent, MouseUpEvent,
-    Overflow, ParentElement, Pixels, PinchEvent, Point, Render, RotationEvent, ScrollWheelEvent,
-    SharedString, Size, Style,
-    StyleRefinement, Styled, Task, TooltipId, Visibility, Window, WindowControlArea, point, px,
-    size,
+    AbsoluteLength, AccessibilityAction, AccessibilityActionRequest, AccessibilityProperties,
+    Action, AnyDrag, AnyElement, AnyTooltip, AnyView, App, Bounds, ClickEvent, DispatchPhase,
+    Display, Element, ElementId, Entity, FocusGroup, FocusHandle, Global, GlobalElementId, Hitbox,
+    HitboxBehavior, HitboxId, InspectorElementId, IntoElement, IsZero, KeyContext, KeyDownEvent,
+    KeyUpEvent, KeyboardButton, KeyboardClickEvent, LayoutId, ModifiersChangedEvent, MouseButton,
+    MouseClickEvent, MouseDownEvent, MouseMoveEvent, MousePressureEvent, MouseUpEvent, Overflow,
+    ParentElement, PinchEvent, Pixels, Point, Redo, Render, Role, RotationEvent, ScrollWheelEvent,
+    SharedString, Size, Style, StyleRefinement, Styled, Task, Toggled, TooltipId, Undo,
+    UndoManager, Visibility, Window, WindowControlArea, point, px, size,
 };
 use collections::HashMap;
 use refineable::Refineable;
+use scheduler::Instant;
 use smallvec::SmallVec;
 use stacksafe::{StackSafe, stacksafe};
 use std::{
@@ -44,10 +45,50 @@ use std::{
 use gpui_util::ResultExt;
 
 use super::ImageCacheProvider;
+use crate::accessibility::AccessibilityActionListener;
 
 const DRAG_THRESHOLD: f64 = 2.;
 const TOOLTIP_SHOW_DELAY: Duration = Duration::from_millis(500);
 const HOVERABLE_TOOLTIP_HIDE_DELAY: Duration = Duration::from_millis(500);
+/// The distance from a scrollable element's edge within which a drag starts scrolling it.
+const DRAG_AUTOSCROLL_EDGE: Pixels = px(40.);
+/// The scroll speed, in pixels per second, when a drag is at (or past) a scrollable element's edge.
+const DRAG_AUTOSCROLL_MAX_SPEED: f32 = 1200.;
+
+/// The velocity, in pixels per second, at which a scrollable element with the given bounds
+/// should scroll while something is dragged at `position`. Positive values reveal content
+/// below or to the right.
+///
+/// The speed ramps up quadratically as the position gets closer to one of the edges, and is at its
+/// maximum when the position is past the edge.
+pub(crate) fn drag_autoscroll_velocity(
+    position: Point<Pixels>,
+    bounds: Bounds<Pixels>,
+) -> Point<f32> {
+    fn axis_velocity(position: Pixels, start: Pixels, end: Pixels) -> f32 {
+        let edge = DRAG_AUTOSCROLL_EDGE.min((end - start) / 2.);
+        if edge <= px(0.) {
+            return 0.;
+        }
+
+        let speed = |distance: Pixels| {
+            let proximity = ((edge - distance) / edge).clamp(0., 1.);
+            DRAG_AUTOSCROLL_MAX_SPEED * proximity * proximity
+        };
+        if position < start + edge {
+            -speed(position - start)
+        } else if position > end - edge {
+            speed(end - position)
+        } else {
+            0.
+        }
+    }
+
+    point(
+        axis_velocity(position.x, bounds.left(), bounds.right()),
+        axis_velocity(position.y, bounds.top(), bounds.bottom()),
+    )
+}
 
 /// The styling information for a given group.
 pub struct GroupStyle {
@@ -726,6 +767,71 @@ pub trait InteractiveElement: Sized {
         self
     }
 
+    /// Set the role this element reports to assistive technologies, adding it to the window's
+    /// accessibility tree.
+    fn role(mut self, role: Role) -> Self {
+        self.interactivity().accessibility_properties().role = role;
+        self
+    }
+
+    /// Set the name assistive technologies read out for this element.
+    fn aria_label(mut self, label: impl Into<SharedString>) -> Self {
+        self.interactivity().accessibility_properties().label = Some(label.into());
+        self
+    }
+
+    /// Set additional text assistive technologies use to describe this element.
+    fn aria_description(mut self, description: impl Into<SharedString>) -> Self {
+        self.interactivity().accessibility_properties().description = Some(description.into());
+        self
+    }
+
+    /// Set the value this element reports to assistive technologies, e.g. a slider's position.
+    fn aria_value(mut self, value: impl Into<SharedString>) -> Self {
+        self.interactivity().accessibility_properties().value = Some(value.into());
+        self
+    }
+
+    /// Set the checked state this element reports to assistive technologies.
+    fn aria_checked(mut self, toggled: impl Into<Toggled>) -> Self {
+        self.interactivity().accessibility_properties().toggled = Some(toggled.into());
+        self
+    }
+
+    /// Set whether this element reports itself as selected to assistive technologies.
+    fn aria_selected(mut self, selected: bool) -> Self {
+        self.interactivity().accessibility_properties().selected = Some(selected);
+        self
+    }
+
+    /// Set whether this element reports itself as expanded to assistive technologies.
+    fn aria_expanded(mut self, expanded: bool) -> Self {
+        self.interactivity().accessibility_properties().expanded = Some(expanded);
+        self
+    }
+
+    /// Set whether this element reports itself as disabled to assistive technologies.
+    fn aria_disabled(mut self, disabled: bool) -> Self {
+        self.interactivity().accessibility_properties().disabled = disabled;
+        self
+    }
+
+    /// Handle requests from assistive technologies to perform the given action on this element.
+    fn on_accessibility_action(
+        mut self,
+        action: AccessibilityAction,
+        listener: impl Fn(&AccessibilityActionRequest, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.interactivity().accessibility_properties();
+        self.interactivity()
+            .accessibility
+            .as_mut()
+            .unwrap()
+            .action_listeners
+            .push((action, Rc::new(listener)));
+        self
+    }
+
     /// Set the keymap context for this element. This will be used to determine
     /// which action to dispatch from the keymap.
     fn key_context<C, E>(mut self, key_context: C) -> Self
@@ -974,6 +1080,27 @@ pub trait InteractiveElement: Sized {
         self
     }
 
+    /// Handle [`Undo`] and [`Redo`] with the given history. Each is only handled, and available,
+    /// while the history had something to undo or redo when the element was rendered, so render
+    /// it from a view that's notified when its history changes, see [`UndoManager::on_change`].
+    fn undo_manager(mut self, history: &UndoManager) -> Self {
+        if history.can_undo() {
+            let history = history.clone();
+            self.interactivity().on_action(move |_: &Undo, window, cx| {
+                history.undo(cx);
+                window.refresh();
+            });
+        }
+        if history.can_redo() {
+            let history = history.clone();
+            self.interactivity().on_action(move |_: &Redo, window, cx| {
+                history.redo(cx);
+                window.refresh();
+            });
+        }
+        self
+    }
+
     /// Bind the given callback to an action dispatch, based on a dynamic action parameter
     /// instead of a type parameter. Useful for component libraries that want to expose
     /// action bindings to their users.
@@ -1172,6 +1299,22 @@ pub trait StatefulInteractiveElement: InteractiveElement {
         self
     }
 
+    /// Make the focusable elements inside this one a focus group: a single tab stop, within
+    /// which the arrow keys move focus. See [`FocusGroup`] for details.
+    fn focus_group(mut self, focus_group: FocusGroup) -> Self {
+        self.interactivity().focus_group = Some(focus_group);
+        self
+    }
+
+    /// Trap focus within this element, for modals and popovers: while it's painted,
+    /// [`Window::focus_next`] and [`Window::focus_prev`] cycle through the tab stops inside it.
+    /// When it's first painted, its first tab stop is focused, and when it's gone, focus returns to
+    /// what was focused before it appeared.
+    fn focus_trap(mut self) -> Self {
+        self.interactivity().focus_trap = true;
+        self
+    }
+
     /// Set the overflow x and y to scroll.
     fn overflow_scroll(mut self) -> Self {
         self.interactivity().base_style.overflow.x = Some(Overflow::Scroll);
@@ -1200,6 +1343,13 @@ pub trait StatefulInteractiveElement: InteractiveElement {
         self
     }
 
+    /// Set whether this element scrolls when something is dragged near its edges, so that
+    /// off-screen drop targets can be reached. Enabled by default for scrollable elements.
+    fn drag_autoscroll(mut self, enabled: bool) -> Self {
+        self.interactivity().base_style.drag_autoscroll = Some(enabled);
+        self
+    }
+
     /// Track the scroll state of this element with the given handle.
     fn track_scroll(mut self, scroll_handle: &ScrollHandle) -> Self {
         self.interactivity().tracked_scroll_handle = Some(scroll_handle.clone());
@@ -1713,6 +1863,9 @@ pub struct Interactivity {
     pub(crate) tab_index: Option<isize>,
     pub(crate) tab_group: bool,
     pub(crate) tab_stop: bool,
+    pub(crate) focus_group: Option<FocusGroup>,
+    pub(crate) focus_trap: bool,
+    pub(crate) accessibility: Option<Box<InteractiveAccessibility>>,
 
     #[cfg(any(feature = "inspector", debug_assertions))]
     pub(crate) source_location: Option<&'static core::panic::Location<'static>>,
@@ -1872,9 +2025,18 @@ impl Interactivity {
                                 None
                             };
 
+                            self.autoscroll_for_drag(
+                                bounds,
+                                &style,
+                                element_state.as_mut(),
+                                window,
+                                cx,
+                            );
                             let scroll_offset =
                                 self.clamp_scroll_position(bounds, &style, window, cx);
-                            let result = f(&style, scroll_offset, hitbox, window, cx);
+                            let result = self.with_accessibility_node(bounds, window, |window| {
+                                f(&style, scroll_offset, hitbox, window, cx)
+                            });
                             (result, element_state)
                         },
                     )
@@ -1883,6 +2045,50 @@ impl Interactivity {
         )
     }
 
+    pub(crate) fn accessibility_properties(&mut self) -> &mut AccessibilityProperties {
+        &mut self.accessibility.get_or_insert_default().properties
+    }
+
+    /// Describe this element to assistive technologies while `f` prepaints its children. Only
+    /// elements with accessibility properties or a tracked focus handle get a node.
+    fn with_accessibility_node<R>(
+        &self,
+        bounds: Bounds<Pixels>,
+        window: &mut Window,
+        f: impl FnOnce(&mut Window) -> R,
+    ) -> R {
+        if !window.is_accessibility_active()
+            || (self.accessibility.is_none() && self.tracked_focus_handle.is_none())
+        {
+            return f(window);
+        }
+
+        let mut properties = self
+            .accessibility
+            .as_ref()
+            .map(|accessibility| accessibility.properties.clone())
+            .unwrap_or_default();
+        if !self.click_listeners.is_empty() {
+            properties.add_action(AccessibilityAction::Click);
+        }
+        window.with_accessibility_node(
+            properties,
+            bounds,
+            self.tracked_focus_handle.as_ref(),
+            |window| {
+                if let Some(accessibility) = self.accessibility.as_ref() {
+                    for (action, listener) in &accessibility.action_listeners {
+                        let listener = listener.clone();
+                        window.on_accessibility_action(*action, move |request, window, cx| {
+                            listener(request, window, cx)
+                        });
+                    }
+                }
+                f(window)
+            },
+        )
+    }
+
     fn should_insert_hitbox(&self, style: &Style, window: &Window, cx: &App) -> bool {
         self.hitbox_behavior != HitboxBehavior::Normal
             || self.window_control.is_some()
@@ -1908,18 +2114,89 @@ impl Interactivity {
             || window.is_inspector_picking(cx)
     }
 
-    fn clamp_scroll_position(
+    /// Scroll this element while something is being dragged near its edges.
+    fn autoscroll_for_drag(
         &self,
         bounds: Bounds<Pixels>,
         style: &Style,
+        element_state: Option<&mut InteractiveElementState>,
         window: &mut Window,
-        _cx: &mut App,
-    ) -> Point<Pixels> {
+        cx: &mut App,
+    ) {
+        let (Some(scroll_offset), Some(element_state)) =
+            (self.scroll_offset.as_ref(), element_state)
+        else {
+            return;
+        };
+
+        let position = window.mouse_position();
+        let visible_bounds = window.content_mask().bounds.intersect(&bounds);
+        let mut velocity = if style.drag_autoscroll
+            && cx.has_active_drag()
+            && visible_bounds.contains(&position)
+        {
+            drag_autoscroll_velocity(position, visible_bounds)
+        } else {
+            Point::default()
+        };
+        if style.overflow.x != Overflow::Scroll {
+            velocity.x = 0.;
+        }
+        if style.overflow.y != Overflow::Scroll {
+            velocity.y = 0.;
+        }
+        if velocity == Point::default() {
+            element_state.drag_autoscroll_at = None;
+            return;
+        }
+
+        let now = cx.background_executor().now();
+        if let Some(last_autoscroll_at) = element_state.drag_autoscroll_at.replace(now) {
+            let elapsed = now
+                .saturating_duration_since(last_autoscroll_at)
+                .as_secs_f32();
+            let scroll_max = self.scroll_max(bounds, style, window);
+            let mut scroll_offset = scroll_offset.borrow_mut();
+            let old_scroll_offset = *scroll_offset;
+            scroll_offset.x =
+                (scroll_offset.x - px(velocity.x * elapsed)).clamp(-scroll_max.x, px(0.));
+            scroll_offset.y =
+                (scroll_offset.y - px(velocity.y * elapsed)).clamp(-scroll_max.y, px(0.));
+            if *scroll_offset == old_scroll_offset {
+                return;
+            }
+        }
+        window.request_animation_frame();
+    }
+
+    fn scroll_max(&self, bounds: Bounds<Pixels>, style: &Style, window: &Window) -> Point<Pixels> {
         fn round_to_two_decimals(pixels: Pixels) -> Pixels {
             const ROUNDING_FACTOR: f32 = 100.0;
             (pixels * ROUNDING_FACTOR).round() / ROUNDING_FACTOR
         }
 
+        let rem_size = window.rem_size();
+        let padding = style.padding.to_pixels(bounds.size.into(), rem_size);
+        let padding_size = size(padding.left + padding.right, padding.top + padding.bottom);
+        // The floating point values produced by Taffy and ours often vary
+        // slightly after ~5 decimal places. This can lead to cases where after
+        // subtracting these, the container becomes scrollable for less than
+        // 0.00000x pixels. As we generally don't benefit from a precision that
+        // high for the maximum scroll, we round the scroll max to 2 decimal
+        // places here.
+        let padded_content_size = self.content_size + padding_size;
+        Point::from(padded_content_size - bounds.size)
+            .map(round_to_two_decimals)
+            .max(&Default::default())
+    }
+
+    fn clamp_scroll_position(
+        &self,
+        bounds: Bounds<Pixels>,
+        style: &Style,
+        window: &mut Window,
+        _cx: &mut App,
+    ) -> Point<Pixels> {
         if let Some(scroll_offset) = self.scroll_offset.as_ref() {
             let mut scroll_to_bottom = false;
             let mut tracked_scroll_handle = self
@@ -1931,19 +2208,7 @@ impl Interactivity {
                 scroll_to_bottom = mem::take(&mut scroll_handle_state.scroll_to_bottom);
             }
 
-            let rem_size = window.rem_size();
-            let padding = style.padding.to_pixels(bounds.size.into(), rem_size);
-            let padding_size = size(padding.left + padding.right, padding.top + padding.bottom);
-            // The floating point values produced by Taffy and ours often vary
-            // slightly after ~5 decimal places. This can lead to cases where after
-            // subtracting these, the container becomes scrollable for less than
-            // 0.00000x pixels. As we generally don't benefit from a precision that
-            // high for the maximum scroll, we round the scroll max to 2 decimal
-            // places here.
-            let padded_content_size = self.content_size + padding_size;
-            let scroll_max = Point::from(padded_content_size - bounds.size)
-                .map(round_to_two_decimals)
-                .max(&Default::default());
+            let scroll_max = self.scroll_max(bounds, style, window);
             // Clamp scroll offset in case scroll max is smaller now (e.g., if children
             // were removed or the bounds became larger).
             let mut scroll_offset = scroll_offset.borrow_mut();
@@ -2012,7 +2277,11 @@ impl Interactivity {
                     tab_group = self.tab_index;
                 }
                 if let Some(focus_handle) = &self.tracked_focus_handle {
-                    window.next_frame.tab_stops.insert(focus_handle);
+                    let label = self
+                        .accessibility
+                        .as_ref()
+                        .and_then(|accessibility| accessibility.properties.label.clone());
+                    window.insert_tab_stop(focus_handle, bounds, label);
                 }
 
                 window.with_element_opacity(style.opacity, |window| {
@@ -2058,8 +2327,23 @@ impl Interactivity {
                                             self.paint_scroll_listener(hitbox, &style, window, cx);
                                         }
 
-                                        self.paint_keyboard_listeners(window, cx);
-                                        f(&style, window, cx);
+                                        window.with_focus_trap(
+                                            global_id,
+                                            self.focus_trap,
+                                            self.tracked_focus_handle
+                                                .as_ref()
+                                                .map(|focus_handle| focus_handle.id),
+                                            |window| {
+                                                window.with_focus_group(
+                                                    global_id,
+                                                    self.focus_group,
+                                                    |window| {
+                                                        self.paint_keyboard_listeners(window, cx);
+                                                        f(&style, window, cx);
+                                                    },
+                                                );
+                                            },
+                                        );
 
                                         if let Some(_hitbox) = hitbox {
                                             #[cfg(any(feature = "inspector", debug_assertions))]
@@ -2341,6 +2625,34 @@ impl Interactivity {
         let can_drop_predicate = mem::take(&mut self.can_drop_predicate);
 
         if !drop_listeners.is_empty() {
+            let drop_listeners = Rc::new(drop_listeners);
+            let can_drop_predicate = Rc::new(can_drop_predicate);
+            window.on_mouse_event({
+                let hitbox = hitbox.clone();
+                let drop_listeners = drop_listeners.clone();
+                let can_drop_predicate = can_drop_predicate.clone();
+                move |_: &MouseMoveEvent, phase, window, cx| {
+                    if let Some(drag) = &cx.active_drag
+                        && phase == DispatchPhase::Bubble
+                        && hitbox.is_hovered(window)
+                    {
+                        let drag_state_type = drag.value.as_ref().type_id();
+                        if drop_listeners
+                            .iter()
+                            .any(|(drop_state_type, _)| *drop_state_type == drag_state_type)
+                        {
+                            let value = drag.value.clone();
+                            let can_drop = can_drop_predicate
+                                .as_deref()
+                                .is_none_or(|predicate| predicate(value.as_ref(), window, cx));
+                            if can_drop {
+                                window.accept_drop();
+                            }
+                        }
+                    }
+                }
+            });
+
             let hitbox = hitbox.clone();
             window.on_mouse_event({
                 move |_: &MouseUpEvent, phase, window, cx| {
@@ -2349,7 +2661,7 @@ impl Interactivity {
                         && hitbox.is_hovered(window)
                     {
                         let drag_state_type = drag.value.as_ref().type_id();
-                        for (drop_state_type, listener) in &drop_listeners {
+                        for (drop_state_type, listener) in drop_listeners.iter() {
                             if *drop_state_type == drag_state_type {
                                 let drag = cx
                                     .active_drag
@@ -2357,7 +2669,7 @@ impl Interactivity {
                                     .expect("checked for type drag state type above");
 
                                 let mut can_drop = true;
-                                if let Some(predicate) = &can_drop_predicate {
+                                if let Some(predicate) = can_drop_predicate.as_deref() {
                                     can_drop = predicate(drag.value.as_ref(), window, cx);
                                 }
 
@@ -2870,6 +3182,13 @@ impl Interactivity {
     }
 }
 
+/// How an interactive element describes itself to assistive technologies.
+#[derive(Default)]
+pub(crate) struct InteractiveAccessibility {
+    pub(crate) properties: AccessibilityProperties,
+    pub(crate) action_listeners: Vec<(AccessibilityAction, AccessibilityActionListener)>,
+}
+
 /// The per-frame state of an interactive element. Used for tracking stateful interactions like clicks
 /// and scroll offsets.
 #[derive(Default)]
@@ -2881,6 +3200,7 @@ pub struct InteractiveElementState {
     pub(crate) pending_mouse_down: Option<Rc<RefCell<Option<MouseDownEvent>>>>,
     pub(crate) scroll_offset: Option<Rc<RefCell<Point<Pixels>>>>,
     pub(crate) active_tooltip: Option<Rc<RefCell<Option<ActiveTooltip>>>>,
+    pub(crate) drag_autoscroll_at: Option<Instant>,
 }
 
 /// Whether or not the element or a group that contains it is clicked by the mouse.
@@ -3579,6 +3899,8 @@ impl ScrollHandle {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{AppContext as _, Context, EmptyView, Modifiers, TestAppContext, VisualTestContext};
+    use std::time::Duration;
 
     #[test]
     fn scroll_handle_aligns_wide_children_to_left_edge() {
@@ -3617,4 +3939,78 @@ mod tests {
 
         assert_eq!(handle.offset().y, px(-25.));
     }
+
+    #[test]
+    fn drag_autoscroll_speeds_up_near_edges() {
+        let bounds = Bounds::new(point(px(0.), px(0.)), size(px(200.), px(200.)));
+
+        assert_eq!(
+            drag_autoscroll_velocity(point(px(100.), px(100.)), bounds),
+            Point::default()
+        );
+
+        let near_top = drag_autoscroll_velocity(point(px(100.), px(30.)), bounds);
+        let nearer_top = drag_autoscroll_velocity(point(px(100.), px(10.)), bounds);
+        assert_eq!(near_top.x, 0.);
+        assert!(near_top.y < 0.);
+        assert!(nearer_top.y < near_top.y);
+
+        let past_right = drag_autoscroll_velocity(point(px(250.), px(100.)), bounds);
+        assert_eq!(past_right, point(DRAG_AUTOSCROLL_MAX_SPEED, 0.));
+    }
+
+    #[crate::test]
+    fn drag_autoscroll_scrolls_near_edges(cx: &mut TestAppContext) {
+        struct ScrollView(ScrollHandle);
+
+        impl Render for ScrollView {
+            fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+                div()
+                    .id("scroll")
+                    .overflow_y_scroll()
+                    .track_scroll(&self.0)
+                    .w(px(100.))
+                    .h(px(100.))
+                    .child(
+                        div()
+                            .id("content")
+                            .w_full()
+                            .h(px(1000.))
+                            .on_drag((), |_, _, _, cx| cx.new(|_| EmptyView)),
+                    )
+            }
+        }
+
+        let scroll_handle = ScrollHandle::new();
+        let (_, cx) = cx.add_window_view(|_, _| ScrollView(scroll_handle.clone()));
+        let mut drag_to = |y: f32, cx: &mut VisualTestContext| {
+            cx.simulate_mouse_move(point(px(50.), px(y)), MouseButton::Left, Modifiers::none());
+            cx.executor().advance_clock(Duration::from_millis(100));
+            cx.update(|window, _| window.refresh());
+            scroll_handle.offset().y
+        };
+
+        // Away from the edges, the drag doesn't scroll.
+        cx.simulate_mouse_down(
+            point(px(50.), px(50.)),
+            MouseButton::Left,
+            Modifiers::none(),
+        );
+        assert_eq!(drag_to(55., cx), px(0.));
+        assert!(cx.update(|_, cx| cx.has_active_drag()));
+
+        // Near the bottom edge, it keeps scrolling down.
+        let scrolled = drag_to(95., cx);
+        assert!(scrolled < px(0.), "{scrolled:?}");
+        assert!(drag_to(95., cx) < scrolled);
+
+        // Without a drag, it doesn't.
+        cx.simulate_mouse_up(
+            point(px(50.), px(95.)),
+            MouseButton::Left,
+            Modifiers::none(),
+        );
+        let scrolled = scroll_handle.offset().y;
+        assert_eq!(drag_to(95., cx), scrolled);
+    }
 }
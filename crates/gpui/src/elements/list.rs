@@ -6,19 +6,30 @@
 //! on your own views, so that your code can coordinate directly with the list element's cached state.
 //!
 //! If all of your elements are the same height, see [`crate::UniformList`] for a simpler API
+//!
+//! Lists can also let the user reorder their items by dragging them, or with `alt-up` and
+//! `alt-down` when an item is focused. See [`ListState::set_reorder_handler`].
 
 use crate::{
-    AnyElement, App, AvailableSpace, Bounds, ContentMask, DispatchPhase, Edges, Element, EntityId,
-    FocusHandle, GlobalElementId, Hitbox, HitboxBehavior, InspectorElementId, IntoElement,
-    Overflow, Pixels, Point, ScrollDelta, ScrollWheelEvent, Size, Style, StyleRefinement, Styled,
-    Window, point, px, size,
+    AccessibilityProperties, AnyElement, App, AvailableSpace, Bounds, ContentMask, DispatchPhase,
+    Edges, Element, EntityId, FocusHandle, GlobalElementId, Hitbox, HitboxBehavior,
+    InspectorElementId, IntoElement, KeyDownEvent, MouseButton, MouseDownEvent, MouseMoveEvent,
+    MouseUpEvent, Overflow, Pixels, Point, Role, ScrollDelta, ScrollWheelEvent, Size, Style,
+    StyleRefinement, Styled, Window, drag_autoscroll_velocity, point, px, size,
 };
-use collections::VecDeque;
+use collections::{HashMap, VecDeque};
 use refineable::Refineable as _;
-use std::{cell::RefCell, ops::Range, rc::Rc};
+use scheduler::Instant;
+use std::{cell::RefCell, mem, ops::Range, rc::Rc};
 use sum_tree::{Bias, Dimensions, SumTree};
 
 type RenderItemFn = dyn FnMut(usize, &mut Window, &mut App) -> AnyElement + 'static;
+type RenderPlaceholderFn = dyn Fn(Size<Pixels>, &mut Window, &mut App) -> AnyElement + 'static;
+
+/// How far the pointer has to move before a pressed item starts being dragged.
+const REORDER_DRAG_THRESHOLD: Pixels = px(4.);
+/// How quickly neighbouring items slide out of the way of the dragged item, per second.
+const REORDER_ANIMATION_SPEED: f32 = 16.;
 
 /// Construct a new list element
 pub fn list(
@@ -28,6 +39,7 @@ pub fn list(
     List {
         state,
         render_item: Box::new(render_item),
+        render_placeholder: None,
         style: StyleRefinement::default(),
         sizing_behavior: ListSizingBehavior::default(),
     }
@@ -37,6 +49,7 @@ pub fn list(
 pub struct List {
     state: ListState,
     render_item: Box<RenderItemFn>,
+    render_placeholder: Option<Box<RenderPlaceholderFn>>,
     style: StyleRefinement,
     sizing_behavior: ListSizingBehavior,
 }
@@ -47,6 +60,25 @@ impl List {
         self.sizing_behavior = behavior;
         self
     }
+
+    /// Set whether the list scrolls when something is dragged near its top or bottom edge.
+    /// Enabled by default.
+    pub fn with_drag_autoscroll(mut self, enabled: bool) -> Self {
+        self.style.drag_autoscroll = Some(enabled);
+        self
+    }
+
+    /// Render the given element in the gap where a dragged item will land when it is dropped.
+    /// The callback receives the size of the dragged item.
+    ///
+    /// Only used when reordering is enabled with [`ListState::set_reorder_handler`].
+    pub fn with_reorder_placeholder(
+        mut self,
+        render_placeholder: impl Fn(Size<Pixels>, &mut Window, &mut App) -> AnyElement + 'static,
+    ) -> Self {
+        self.render_placeholder = Some(Box::new(render_placeholder));
+        self
+    }
 }
 
 /// The list state that views must hold on behalf of the list element.
@@ -72,6 +104,34 @@ struct StateInner {
     scrollbar_drag_start_height: Option<Pixels>,
     measuring_behavior: ListMeasuringBehavior,
     pending_scroll: Option<PendingScrollFraction>,
+    #[allow(clippy::type_complexity)]
+    reorder_handler: Option<Box<dyn FnMut(&ListReorderEvent, &mut Window, &mut App)>>,
+    pending_reorder: Option<PendingReorder>,
+    reorder_drag: Option<ReorderDrag>,
+    drag_autoscroll_at: Option<Instant>,
+}
+
+/// An item that has been pressed, but not dragged far enough to start reordering.
+struct PendingReorder {
+    item_ix: usize,
+    position: Point<Pixels>,
+}
+
+/// The state of an in-progress reorder drag.
+struct ReorderDrag {
+    /// The index of the item being dragged.
+    from_ix: usize,
+    /// The index the dragged item would be moved to if it were dropped now.
+    target_ix: usize,
+    /// The size of the dragged item.
+    item_size: Size<Pixels>,
+    /// The distance between the top of the dragged item and the pointer.
+    grab_offset: Pixels,
+    /// The last known pointer position, in window coordinates.
+    position: Point<Pixels>,
+    /// The current, animated vertical offset of each item that is moving out of the way.
+    item_offsets: HashMap<usize, Pixels>,
+    last_frame_at: Instant,
 }
 
 /// Keeps track of a fractional scroll position within an item for restoration
@@ -104,6 +164,20 @@ pub struct ListScrollEvent {
     pub is_scrolled: bool,
 }
 
+/// An event emitted when the user moves an item to a new position in the list.
+///
+/// The list has already moved its own record of the item by the time this event is emitted;
+/// the handler is expected to apply the same move to the underlying data, as if by
+/// `items.insert(to, items.remove(from))`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ListReorderEvent {
+    /// The index of the item before it was moved.
+    pub from: usize,
+
+    /// The index of the item after it was moved.
+    pub to: usize,
+}
+
 /// The sizing behavior to apply during layout.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ListSizingBehavior {
@@ -148,6 +222,13 @@ struct LayoutItemsResponse {
     max_item_width: Pixels,
     scroll_top: ListOffset,
     item_layouts: VecDeque<ItemLayout>,
+    reorder_layout: Option<ReorderLayout>,
+}
+
+/// The elements that are painted on top of the list's items during a reorder drag.
+struct ReorderLayout {
+    placeholder: Option<AnyElement>,
+    dragged_item: ItemLayout,
 }
 
 struct ItemLayout {
@@ -236,6 +317,10 @@ impl ListState {
             scrollbar_drag_start_height: None,
             measuring_behavior: ListMeasuringBehavior::default(),
             pending_scroll: None,
+            reorder_handler: None,
+            pending_reorder: None,
+            reorder_drag: None,
+            drag_autoscroll_at: None,
         })));
         this.splice(0..0, item_count);
         this
@@ -325,6 +410,7 @@ impl ListState {
         focus_handles: impl IntoIterator<Item = Option<FocusHandle>>,
     ) {
         let state = &mut *self.0.borrow_mut();
+        state.cancel_reorder();
 
         let mut old_items = state.items.cursor::<Count>(());
         let mut new_items = old_items.slice(&Count(old_range.start), Bias::Right);
@@ -364,6 +450,51 @@ impl ListState {
         self.0.borrow_mut().scroll_handler = Some(Box::new(handler))
     }
 
+    /// Allow the user to reorder the list's items, and set a handler that will be called when
+    /// an item has been moved.
+    ///
+    /// Items can be dragged with the mouse: neighbouring items slide out of the way of the
+    /// dragged item, and the list scrolls when the item is dragged near its top or bottom edge.
+    /// When an item registered with [`Self::splice_focusable`] is focused, `alt-up` and
+    /// `alt-down` move it by one position.
+    pub fn set_reorder_handler(
+        &self,
+        handler: impl FnMut(&ListReorderEvent, &mut Window, &mut App) + 'static,
+    ) {
+        self.0.borrow_mut().reorder_handler = Some(Box::new(handler))
+    }
+
+    /// Whether the user is currently dragging an item to reorder it.
+    pub fn is_reordering(&self) -> bool {
+        self.0.borrow().reorder_drag.is_some()
+    }
+
+    /// Move the item at `from` so that it ends up at index `to`, and notify the reorder
+    /// handler, as if the user had moved it.
+    pub fn reorder_item(&self, from: usize, to: usize, window: &mut Window, cx: &mut App) {
+        {
+            let state = &mut *self.0.borrow_mut();
+            let item_count = state.items.summary().count;
+            if from >= item_count || to >= item_count || from == to {
+                return;
+            }
+            state.move_item(from, to);
+        }
+        self.notify_reorder(&ListReorderEvent { from, to }, window, cx);
+    }
+
+    fn notify_reorder(&self, event: &ListReorderEvent, window: &mut Window, cx: &mut App) {
+        // Release the borrow while the handler runs, so that it can use this list state.
+        let handler = self.0.borrow_mut().reorder_handler.take();
+        if let Some(mut handler) = handler {
+            handler(event, window, cx);
+            let state = &mut *self.0.borrow_mut();
+            if state.reorder_handler.is_none() {
+                state.reorder_handler = Some(handler);
+            }
+        }
+    }
+
     /// Get the current scroll offset, in terms of the list's items.
     pub fn logical_scroll_top(&self) -> ListOffset {
         self.0.borrow().logical_scroll_top()
@@ -526,6 +657,181 @@ impl ListState {
 }
 
 impl StateInner {
+    fn cancel_reorder(&mut self) {
+        self.pending_reorder = None;
+        self.reorder_drag = None;
+    }
+
+    fn move_item(&mut self, from: usize, to: usize) {
+        let mut items = self.items.iter().cloned().collect::<Vec<_>>();
+        let item = items.remove(from);
+        items.insert(to, item);
+        self.items = SumTree::from_iter(items, ());
+    }
+
+    /// The distance from the top of the list's content to the top of the given item.
+    fn item_top(&self, ix: usize) -> Pixels {
+        let (start, ..) = self
+            .items
+            .find::<ListItemSummary, _>((), &Count(ix), Bias::Right);
+        start.height
+    }
+
+    /// Convert a vertical position in window coordinates to a position in the list's content.
+    fn content_y(&self, y: Pixels) -> Option<Pixels> {
+        let bounds = self.last_layout_bounds?;
+        let padding = self.last_padding.unwrap_or_default();
+        Some(y - bounds.top() - padding.top + self.scroll_top(&self.logical_scroll_top()))
+    }
+
+    fn item_ix_for_content_y(&self, content_y: Pixels) -> Option<usize> {
+        let item_count = self.items.summary().count;
+        if item_count == 0 || content_y < px(0.) {
+            return None;
+        }
+        let (start, ..) =
+            self.items
+                .find::<ListItemSummary, _>((), &Height(content_y), Bias::Right);
+        (start.count < item_count).then_some(start.count)
+    }
+
+    fn start_reorder(&mut self, item_ix: usize, position: Point<Pixels>, now: Instant) -> bool {
+        let Some(content_y) = self.content_y(position.y) else {
+            return false;
+        };
+        let mut cursor = self.items.cursor::<Count>(());
+        cursor.seek(&Count(item_ix), Bias::Right);
+        let Some(item_size) = cursor.item().and_then(|item| item.size()) else {
+            return false;
+        };
+
+        self.reorder_drag = Some(ReorderDrag {
+            from_ix: item_ix,
+            target_ix: item_ix,
+            item_size,
+            grab_offset: content_y - self.item_top(item_ix),
+            position,
+            item_offsets: HashMap::default(),
+            last_frame_at: now,
+        });
+        true
+    }
+
+    /// Recompute where the dragged item would land, based on its center.
+    fn update_reorder_target(&mut self) {
+        let Some(drag) = self.reorder_drag.as_ref() else {
+            return;
+        };
+        let Some(pointer_y) = self.content_y(drag.position.y) else {
+            return;
+        };
+        let center = (pointer_y - drag.grab_offset + drag.item_size.height / 2.).max(px(0.));
+        let item_count = self.items.summary().count;
+        let target_ix = self
+            .item_ix_for_content_y(center)
+            .unwrap_or(item_count.saturating_sub(1));
+        if let Some(drag) = self.reorder_drag.as_mut() {
+            drag.target_ix = target_ix;
+        }
+    }
+
+    /// Scroll the list while something is dragged near its top or bottom edge. Returns whether
+    /// another frame is needed to keep scrolling.
+    fn autoscroll_for_drag(
+        &mut self,
+        drag_position: Option<Point<Pixels>>,
+        bounds: Bounds<Pixels>,
+        padding: &Edges<Pixels>,
+        now: Instant,
+    ) -> bool {
+        let velocity =
+            drag_position.map_or(0., |position| drag_autoscroll_velocity(position, bounds).y);
+        if velocity == 0. {
+            self.drag_autoscroll_at = None;
+            return false;
+        }
+
+        let Some(last_autoscroll_at) = self.drag_autoscroll_at.replace(now) else {
+            return true;
+        };
+        let elapsed = now.saturating_duration_since(last_autoscroll_at);
+        let distance = px(velocity * elapsed.as_secs_f32());
+
+        let scroll_max = (self.items.summary().height + padding.top + padding.bottom
+            - bounds.size.height)
+            .max(px(0.));
+        let scroll_top = self.scroll_top(&self.logical_scroll_top());
+        let new_scroll_top = (scroll_top + distance).max(px(0.)).min(scroll_max);
+        if new_scroll_top == scroll_top {
+            return false;
+        }
+
+        let (start, ..) =
+            self.items
+                .find::<ListItemSummary, _>((), &Height(new_scroll_top), Bias::Right);
+        self.logical_scroll_top = Some(ListOffset {
+            item_ix: start.count,
+            offset_in_item: new_scroll_top - start.height,
+        });
+        self.update_reorder_target();
+        true
+    }
+
+    /// Move the animated offsets of the items around the dragged item one frame closer to their
+    /// targets. Returns whether any item is still moving.
+    fn animate_reorder_offsets(
+        &mut self,
+        visible_items: impl Iterator<Item = usize>,
+        now: Instant,
+    ) -> bool {
+        let Some(drag) = self.reorder_drag.as_mut() else {
+            return false;
+        };
+        let elapsed = now
+            .saturating_duration_since(mem::replace(&mut drag.last_frame_at, now))
+            .as_secs_f32();
+        let step = (elapsed * REORDER_ANIMATION_SPEED).clamp(0., 1.);
+
+        let mut animating = false;
+        for ix in visible_items {
+            let target = reorder_offset(ix, drag.from_ix, drag.target_ix, drag.item_size.height);
+            let offset = drag.item_offsets.entry(ix).or_default();
+            if (target - *offset).abs() < px(0.5) {
+                *offset = target;
+            } else {
+                *offset += (target - *offset) * step;
+                animating = true;
+            }
+        }
+        animating
+    }
+
+    fn finish_reorder(&mut self) -> Option<ListReorderEvent> {
+        let drag = self.reorder_drag.take()?;
+        if drag.from_ix == drag.target_ix {
+            return None;
+        }
+        self.move_item(drag.from_ix, drag.target_ix);
+        Some(ListReorderEvent {
+            from: drag.from_ix,
+            to: drag.target_ix,
+        })
+    }
+
+    fn focused_item_ix(&self, window: &Window, cx: &App) -> Option<usize> {
+        let mut cursor = self
+            .items
+            .filter::<_, Count>((), |summary| summary.has_focus_handles);
+        cursor.next();
+        while let Some(item) = cursor.item() {
+            if item.contains_focused(window, cx) {
+                return Some(cursor.start().0);
+            }
+            cursor.next();
+        }
+        None
+    }
+
     fn visible_range(&self, height: Pixels, scroll_top: &ListOffset) -> Range<usize> {
         let mut cursor = self.items.cursor::<ListItemSummary>(());
         cursor.seek(&Count(scroll_top.item_ix), Bias::Right);
@@ -839,6 +1145,7 @@ impl StateInner {
             max_item_width,
             scroll_top,
             item_layouts,
+            reorder_layout: None,
         }
     }
 
@@ -848,6 +1155,7 @@ impl StateInner {
         padding: Edges<Pixels>,
         autoscroll: bool,
         render_item: &mut RenderItemFn,
+        render_placeholder: Option<&RenderPlaceholderFn>,
         window: &mut Window,
         cx: &mut App,
     ) -> Result<LayoutItemsResponse, ListOffset> {
@@ -871,13 +1179,35 @@ impl StateInner {
             // Avoid honoring autoscroll requests from elements other than our children.
             window.take_autoscroll();
 
+            if self.reorder_drag.is_some() {
+                let visible_items = layout_response.item_layouts.iter().map(|item| item.index);
+                let now = cx.background_executor().now();
+                if self.animate_reorder_offsets(visible_items, now) {
+                    window.request_animation_frame();
+                }
+            }
+            let reorder_drag = self.reorder_drag.as_ref();
+
             // Only paint the visible items, if there is actually any space for them (taking padding into account)
             if bounds.size.height > padding.top + padding.bottom {
                 let mut item_origin = bounds.origin + Point::new(px(0.), padding.top);
                 item_origin.y -= layout_response.scroll_top.offset_in_item;
                 for item in &mut layout_response.item_layouts {
+                    // The dragged item is laid out separately, on top of the other items.
+                    if reorder_drag.is_some_and(|drag| drag.from_ix == item.index) {
+                        item_origin.y += item.size.height;
+                        continue;
+                    }
+
+                    let reorder_offset = reorder_drag
+                        .and_then(|drag| drag.item_offsets.get(&item.index).copied())
+                        .unwrap_or_default();
                     window.with_content_mask(Some(ContentMask { bounds }), |window| {
-                        item.element.prepaint_at(item_origin, window, cx);
+                        item.element.prepaint_at(
+                            item_origin + point(px(0.), reorder_offset),
+                            window,
+                            cx,
+                        );
                     });
 
                     if let Some(autoscroll_bounds) = window.take_autoscroll()
@@ -923,6 +1253,22 @@ impl StateInner {
 
                     item_origin.y += item.size.height;
                 }
+
+                if let Some(drag) = reorder_drag {
+                    layout_response
+                        .item_layouts
+                        .retain(|item| item.index != drag.from_ix);
+                    layout_response.reorder_layout = Some(self.prepaint_reorder(
+                        drag,
+                        bounds,
+                        &padding,
+                        &layout_response.scroll_top,
+                        render_item,
+                        render_placeholder,
+                        window,
+                        cx,
+                    ));
+                }
             } else {
                 layout_response.item_layouts.clear();
             }
@@ -931,6 +1277,57 @@ impl StateInner {
         })
     }
 
+    /// Lay out the dragged item under the pointer, and the placeholder in the gap where it
+    /// would land.
+    fn prepaint_reorder(
+        &self,
+        drag: &ReorderDrag,
+        bounds: Bounds<Pixels>,
+        padding: &Edges<Pixels>,
+        scroll_top: &ListOffset,
+        render_item: &mut RenderItemFn,
+        render_placeholder: Option<&RenderPlaceholderFn>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> ReorderLayout {
+        let content_origin =
+            bounds.origin + point(px(0.), padding.top - self.scroll_top(scroll_top));
+        let item_space = size(
+            AvailableSpace::Definite(drag.item_size.width),
+            AvailableSpace::Definite(drag.item_size.height),
+        );
+
+        let placeholder = render_placeholder.map(|render_placeholder| {
+            let gap_top = if drag.target_ix <= drag.from_ix {
+                self.item_top(drag.target_ix)
+            } else {
+                self.item_top(drag.target_ix + 1) - drag.item_size.height
+            };
+            let mut element = render_placeholder(drag.item_size, window, cx);
+            element.layout_as_root(item_space, window, cx);
+            window.with_content_mask(Some(ContentMask { bounds }), |window| {
+                element.prepaint_at(content_origin + point(px(0.), gap_top), window, cx);
+            });
+            element
+        });
+
+        let mut element = render_item(drag.from_ix, window, cx);
+        let size = element.layout_as_root(item_space, window, cx);
+        let origin = point(bounds.left(), drag.position.y - drag.grab_offset);
+        window.with_content_mask(Some(ContentMask { bounds }), |window| {
+            element.prepaint_at(origin, window, cx);
+        });
+
+        ReorderLayout {
+            placeholder,
+            dragged_item: ItemLayout {
+                index: drag.from_ix,
+                element,
+                size,
+            },
+        }
+    }
+
     // Scrollbar support
 
     fn set_offset_from_scrollbar(&mut self, point: Point<Pixels>) {
@@ -1104,16 +1501,56 @@ impl Element for List {
         let padding = style
             .padding
             .to_pixels(bounds.size.into(), window.rem_size());
-        let layout =
-            match state.prepaint_items(bounds, padding, true, &mut self.render_item, window, cx) {
-                Ok(layout) => layout,
-                Err(autoscroll_request) => {
-                    state.logical_scroll_top = Some(autoscroll_request);
-                    state
-                        .prepaint_items(bounds, padding, false, &mut self.render_item, window, cx)
-                        .unwrap()
+
+        let drag_position = if !style.drag_autoscroll {
+            None
+        } else if let Some(drag) = state.reorder_drag.as_ref() {
+            Some(drag.position)
+        } else if cx.has_active_drag() && bounds.contains(&window.mouse_position()) {
+            Some(window.mouse_position())
+        } else {
+            None
+        };
+        let now = cx.background_executor().now();
+        if state.autoscroll_for_drag(drag_position, bounds, &padding, now) {
+            window.request_animation_frame();
+        }
+
+        let render_placeholder = self.render_placeholder.as_deref();
+        let layout = window.with_accessibility_node(
+            AccessibilityProperties::new(Role::List),
+            bounds,
+            None,
+            |window| {
+                let accessibility_nodes_len = window.accessibility_nodes_len();
+                match state.prepaint_items(
+                    bounds,
+                    padding,
+                    true,
+                    &mut self.render_item,
+                    render_placeholder,
+                    window,
+                    cx,
+                ) {
+                    Ok(layout) => layout,
+                    Err(autoscroll_request) => {
+                        window.truncate_accessibility_nodes(accessibility_nodes_len);
+                        state.logical_scroll_top = Some(autoscroll_request);
+                        state
+                            .prepaint_items(
+                                bounds,
+                                padding,
+                                false,
+                                &mut self.render_item,
+                                render_placeholder,
+                                window,
+                                cx,
+                            )
+                            .unwrap()
+                    }
                 }
-            };
+            },
+        );
 
         state.last_layout_bounds = Some(bounds);
         state.last_padding = Some(padding);
@@ -1131,12 +1568,36 @@ impl Element for List {
         cx: &mut App,
     ) {
         let current_view = window.current_view();
+        let dispatch_node = window.next_frame.dispatch_tree.active_node_id();
         window.with_content_mask(Some(ContentMask { bounds }), |window| {
+            if let Some(placeholder) = prepaint
+                .layout
+                .reorder_layout
+                .as_mut()
+                .and_then(|layout| layout.placeholder.as_mut())
+            {
+                placeholder.paint(window, cx);
+            }
             for item in &mut prepaint.layout.item_layouts {
                 item.element.paint(window, cx);
             }
+            if let Some(reorder_layout) = prepaint.layout.reorder_layout.as_mut() {
+                reorder_layout.dragged_item.element.paint(window, cx);
+            }
         });
 
+        // Painting the items leaves the last one's dispatch node active, and the keyboard
+        // listeners have to be on the list's own node to see keys pressed in any of its items.
+        if let Some(dispatch_node) = dispatch_node {
+            window
+                .next_frame
+                .dispatch_tree
+                .set_active_node(dispatch_node);
+        }
+        if self.state.0.borrow().reorder_handler.is_some() {
+            self.paint_reorder_listeners(&prepaint.hitbox, current_view, window);
+        }
+
         let list_state = self.state.clone();
         let height = bounds.size.height;
         let scroll_top = prepaint.layout.scroll_top;
@@ -1159,6 +1620,136 @@ impl Element for List {
     }
 }
 
+impl List {
+    fn paint_reorder_listeners(
+        &self,
+        hitbox: &Hitbox,
+        current_view: EntityId,
+        window: &mut Window,
+    ) {
+        window.on_mouse_event({
+            let list_state = self.state.clone();
+            let hitbox = hitbox.clone();
+            move |event: &MouseDownEvent, phase, window, _| {
+                if phase == DispatchPhase::Bubble
+                    && event.button == MouseButton::Left
+                    && hitbox.is_hovered(window)
+                {
+                    let state = &mut *list_state.0.borrow_mut();
+                    state.pending_reorder = state
+                        .content_y(event.position.y)
+                        .and_then(|content_y| state.item_ix_for_content_y(content_y))
+                        .map(|item_ix| PendingReorder {
+                            item_ix,
+                            position: event.position,
+                        });
+                }
+            }
+        });
+
+        window.on_mouse_event({
+            let list_state = self.state.clone();
+            move |event: &MouseMoveEvent, phase, _, cx| {
+                if phase != DispatchPhase::Bubble {
+                    return;
+                }
+
+                let state = &mut *list_state.0.borrow_mut();
+                if let Some(drag) = state.reorder_drag.as_mut() {
+                    drag.position = event.position;
+                    state.update_reorder_target();
+                    cx.notify(current_view);
+                } else if let Some(pending) = state.pending_reorder.as_ref() {
+                    if !event.dragging() {
+                        state.pending_reorder = None;
+                    } else if !cx.has_active_drag()
+                        && (event.position - pending.position).magnitude()
+                            > f64::from(REORDER_DRAG_THRESHOLD)
+                    {
+                        let item_ix = pending.item_ix;
+                        state.pending_reorder = None;
+                        let now = cx.background_executor().now();
+                        if state.start_reorder(item_ix, event.position, now) {
+                            cx.notify(current_view);
+                        }
+                    }
+                }
+            }
+        });
+
+        // Handle the mouse up during the capture phase, so that elements inside the dragged item
+        // don't treat the end of the drag as a click.
+        window.on_mouse_event({
+            let list_state = self.state.clone();
+            move |event: &MouseUpEvent, phase, window, cx| {
+                if phase != DispatchPhase::Capture || event.button != MouseButton::Left {
+                    return;
+                }
+
+                let reorder_event = {
+                    let state = &mut *list_state.0.borrow_mut();
+                    state.pending_reorder = None;
+                    if state.reorder_drag.is_none() {
+                        return;
+                    }
+                    state.finish_reorder()
+                };
+
+                cx.stop_propagation();
+                cx.notify(current_view);
+                if let Some(reorder_event) = reorder_event {
+                    list_state.notify_reorder(&reorder_event, window, cx);
+                }
+            }
+        });
+
+        window.on_key_event({
+            let list_state = self.state.clone();
+            move |event: &KeyDownEvent, phase, window, cx| {
+                if phase != DispatchPhase::Bubble {
+                    return;
+                }
+
+                let keystroke = &event.keystroke;
+                if keystroke.key == "escape" && list_state.0.borrow().reorder_drag.is_some() {
+                    list_state.0.borrow_mut().cancel_reorder();
+                    cx.stop_propagation();
+                    cx.notify(current_view);
+                    return;
+                }
+
+                if !keystroke.modifiers.alt || keystroke.modifiers.number_of_modifiers() != 1 {
+                    return;
+                }
+                let Some(from) = list_state.0.borrow().focused_item_ix(window, cx) else {
+                    return;
+                };
+                let to = match keystroke.key.as_str() {
+                    "up" if from > 0 => from - 1,
+                    "down" if from + 1 < list_state.item_count() => from + 1,
+                    _ => return,
+                };
+
+                cx.stop_propagation();
+                list_state.reorder_item(from, to, window, cx);
+                list_state.scroll_to_reveal_item(to);
+                cx.notify(current_view);
+            }
+        });
+    }
+}
+
+/// The vertical offset of the item at `ix` while the item at `from_ix` is dragged to `target_ix`.
+fn reorder_offset(ix: usize, from_ix: usize, target_ix: usize, dragged_height: Pixels) -> Pixels {
+    if from_ix < target_ix && ix > from_ix && ix <= target_ix {
+        -dragged_height
+    } else if target_ix < from_ix && ix >= target_ix && ix < from_ix {
+        dragged_height
+    } else {
+        px(0.)
+    }
+}
+
 impl IntoElement for List {
     type Element = Self;
 
@@ -1248,12 +1839,16 @@ impl sum_tree::SeekTarget<'_, ListItemSummary, ListItemSummary> for Height {
 mod test {
 
     use gpui::{ScrollDelta, ScrollWheelEvent};
-    use std::cell::Cell;
+    use std::cell::{Cell, RefCell};
     use std::rc::Rc;
+    use std::time::Duration;
 
+    use super::reorder_offset;
     use crate::{
-        self as gpui, AppContext, Context, Element, IntoElement, ListState, Render, Styled,
-        TestAppContext, Window, div, list, point, px, size,
+        self as gpui, AppContext, Context, Element, EmptyView, FocusHandle,
+        InteractiveElement as _, IntoElement, ListReorderEvent, ListState, Modifiers, MouseButton,
+        Pixels, Render, StatefulInteractiveElement as _, Styled, TestAppContext, Window, div, list,
+        point, px, size,
     };
 
     #[gpui::test]
@@ -1374,6 +1969,217 @@ mod test {
         assert_eq!(state.max_offset_for_scrollbar().y, px(300.));
     }
 
+    #[gpui::test]
+    fn test_reorder_item(cx: &mut TestAppContext) {
+        let cx = cx.add_empty_window();
+
+        let state = ListState::new(5, crate::ListAlignment::Top, px(10.));
+        let events = Rc::new(RefCell::new(Vec::new()));
+        state.set_reorder_handler({
+            let events = events.clone();
+            move |event, _, _| events.borrow_mut().push(*event)
+        });
+
+        cx.update(|window, cx| {
+            state.reorder_item(1, 3, window, cx);
+            // Out of range and no-op moves are ignored.
+            state.reorder_item(0, 5, window, cx);
+            state.reorder_item(2, 2, window, cx);
+            state.reorder_item(4, 0, window, cx);
+        });
+
+        assert_eq!(
+            *events.borrow(),
+            [
+                ListReorderEvent { from: 1, to: 3 },
+                ListReorderEvent { from: 4, to: 0 },
+            ]
+        );
+        assert_eq!(state.item_count(), 5);
+        assert!(!state.is_reordering());
+    }
+
+    #[test]
+    fn test_reorder_offsets() {
+        let height = px(10.);
+
+        // Dragging down: the items between the source and the target move up.
+        assert_eq!(reorder_offset(0, 1, 3, height), px(0.));
+        assert_eq!(reorder_offset(2, 1, 3, height), -height);
+        assert_eq!(reorder_offset(3, 1, 3, height), -height);
+        assert_eq!(reorder_offset(4, 1, 3, height), px(0.));
+
+        // Dragging up: the items between the target and the source move down.
+        assert_eq!(reorder_offset(0, 3, 1, height), px(0.));
+        assert_eq!(reorder_offset(1, 3, 1, height), height);
+        assert_eq!(reorder_offset(2, 3, 1, height), height);
+        assert_eq!(reorder_offset(4, 3, 1, height), px(0.));
+    }
+
+    #[gpui::test]
+    fn test_reorder_drag(cx: &mut TestAppContext) {
+        struct TestView {
+            state: ListState,
+            handles: Rc<RefCell<Vec<FocusHandle>>>,
+        }
+
+        impl Render for TestView {
+            fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+                let handles = self.handles.clone();
+                list(self.state.clone(), move |ix, _, _| {
+                    div()
+                        .track_focus(&handles.borrow()[ix])
+                        .h(px(20.))
+                        .w_full()
+                        .into_any()
+                })
+                .w_full()
+                .h(px(200.))
+            }
+        }
+
+        let state = ListState::new(0, crate::ListAlignment::Top, px(10.));
+        let handles = Rc::new(RefCell::new(Vec::new()));
+        let events = Rc::new(RefCell::new(Vec::new()));
+        state.set_reorder_handler({
+            let handles = handles.clone();
+            let events = events.clone();
+            move |event, _, _| {
+                let mut handles = handles.borrow_mut();
+                let handle = handles.remove(event.from);
+                handles.insert(event.to, handle);
+                events.borrow_mut().push(*event);
+            }
+        });
+        let (_, cx) = cx.add_window_view(|_, cx| {
+            let item_handles = (0..5).map(|_| cx.focus_handle()).collect::<Vec<_>>();
+            state.splice_focusable(0..0, item_handles.iter().cloned().map(Some));
+            handles.replace(item_handles);
+            TestView {
+                state: state.clone(),
+                handles: handles.clone(),
+            }
+        });
+        let first_item = handles.borrow()[0].clone();
+        cx.update(|window, cx| first_item.focus(window, cx));
+
+        let offsets = |state: &ListState| -> Vec<Pixels> {
+            let state = state.0.borrow();
+            let drag = state.reorder_drag.as_ref().unwrap();
+            (1..4)
+                .map(|ix| drag.item_offsets.get(&ix).copied().unwrap_or_default())
+                .collect()
+        };
+        let start_drag = |cx: &mut gpui::VisualTestContext| {
+            cx.simulate_mouse_down(
+                point(px(10.), px(10.)),
+                MouseButton::Left,
+                Modifiers::none(),
+            );
+            cx.simulate_mouse_move(
+                point(px(10.), px(20.)),
+                MouseButton::Left,
+                Modifiers::none(),
+            );
+            // The center of the dragged item is over the third item.
+            cx.simulate_mouse_move(
+                point(px(10.), px(52.)),
+                MouseButton::Left,
+                Modifiers::none(),
+            );
+        };
+
+        // The neighbours slide out of the way of the dragged item, and settle.
+        start_drag(cx);
+        assert!(state.is_reordering());
+        assert_eq!(offsets(&state), [px(0.), px(0.), px(0.)]);
+        cx.executor().advance_clock(Duration::from_millis(30));
+        cx.update(|window, _| window.refresh());
+        let moving = offsets(&state);
+        assert!(moving[0] < px(0.) && moving[0] > px(-20.), "{moving:?}");
+        assert_eq!(moving[0], moving[1]);
+        cx.executor().advance_clock(Duration::from_millis(100));
+        cx.update(|window, _| window.refresh());
+        assert_eq!(offsets(&state), [px(-20.), px(-20.), px(0.)]);
+
+        // Escape cancels the drag.
+        cx.simulate_keystrokes("escape");
+        assert!(!state.is_reordering());
+        cx.simulate_mouse_up(
+            point(px(10.), px(52.)),
+            MouseButton::Left,
+            Modifiers::none(),
+        );
+        assert!(events.borrow().is_empty());
+
+        // Dropping the item moves it.
+        start_drag(cx);
+        cx.simulate_mouse_up(
+            point(px(10.), px(52.)),
+            MouseButton::Left,
+            Modifiers::none(),
+        );
+        assert!(!state.is_reordering());
+        assert_eq!(*events.borrow(), [ListReorderEvent { from: 0, to: 2 }]);
+
+        // The focused item moves with alt-up and alt-down.
+        events.borrow_mut().clear();
+        cx.simulate_keystrokes("alt-up alt-up alt-up alt-down");
+        assert_eq!(
+            *events.borrow(),
+            [
+                ListReorderEvent { from: 2, to: 1 },
+                ListReorderEvent { from: 1, to: 0 },
+                ListReorderEvent { from: 0, to: 1 },
+            ]
+        );
+        assert_eq!(handles.borrow()[1], first_item);
+    }
+
+    #[gpui::test]
+    fn test_drag_autoscroll(cx: &mut TestAppContext) {
+        struct TestView(ListState);
+
+        impl Render for TestView {
+            fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+                list(self.0.clone(), |ix, _, _| {
+                    div()
+                        .id(ix)
+                        .h(px(20.))
+                        .w_full()
+                        .on_drag((), |_, _, _, cx| cx.new(|_| EmptyView))
+                        .into_any()
+                })
+                .w_full()
+                .h(px(100.))
+            }
+        }
+
+        let state = ListState::new(50, crate::ListAlignment::Top, px(10.));
+        let (_, cx) = cx.add_window_view(|_, _| TestView(state.clone()));
+        let mut drag_to = |y: f32, cx: &mut gpui::VisualTestContext| {
+            cx.simulate_mouse_move(point(px(50.), px(y)), MouseButton::Left, Modifiers::none());
+            cx.executor().advance_clock(Duration::from_millis(100));
+            cx.update(|window, _| window.refresh());
+            let offset = state.logical_scroll_top();
+            offset.item_ix * 20 + f32::from(offset.offset_in_item) as usize
+        };
+
+        // Away from the edges, the drag doesn't scroll.
+        cx.simulate_mouse_down(
+            point(px(50.), px(50.)),
+            MouseButton::Left,
+            Modifiers::none(),
+        );
+        assert_eq!(drag_to(55., cx), 0);
+        assert!(cx.update(|_, cx| cx.has_active_drag()));
+
+        // Near the bottom edge, it keeps scrolling down.
+        let scrolled = drag_to(95., cx);
+        assert!(scrolled > 0);
+        assert!(drag_to(95., cx) > scrolled);
+    }
+
     #[gpui::test]
     fn test_remeasure(cx: &mut TestAppContext) {
         let cx = cx.add_empty_window();
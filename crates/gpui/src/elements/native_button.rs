@@ -164,10 +164,9 @@ impl Element for NativeButton {
         window: &mut Window,
         _cx: &mut App,
     ) {
-        let parent = window.raw_native_view_ptr();
-        if parent.is_null() {
+        let Some(parent) = window.native_controls_parent() else {
             return;
-        }
+        };
 
         let on_click = self.on_click.take();
         let label = self.label.clone();
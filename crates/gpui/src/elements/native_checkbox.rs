@@ -121,10 +121,9 @@ impl Element for NativeCheckbox {
         window: &mut Window,
         _cx: &mut App,
     ) {
-        let parent = window.raw_native_view_ptr();
-        if parent.is_null() {
+        let Some(parent) = window.native_controls_parent() else {
             return;
-        }
+        };
 
         let on_change = self.on_change.take();
         let label = self.label.clone();
@@ -176,10 +176,9 @@ impl Element for NativeCollectionView {
         window: &mut Window,
         _cx: &mut App,
     ) {
-        let parent = window.raw_native_view_ptr();
-        if parent.is_null() {
+        let Some(parent) = window.native_controls_parent() else {
             return;
-        }
+        };
 
         let on_select = self.on_select.take();
         let items = std::mem::take(&mut self.items);
@@ -136,10 +136,9 @@ impl Element for NativeGlassEffectView {
             return;
         }
 
-        let parent = window.raw_native_view_ptr();
-        if parent.is_null() {
+        let Some(parent) = window.native_controls_parent() else {
             return;
-        }
+        };
 
         let glass_style = self.glass_style;
         let corner_radius = self.corner_radius;
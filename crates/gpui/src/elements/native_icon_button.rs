@@ -133,10 +133,9 @@ impl Element for NativeIconButton {
         window: &mut Window,
         _cx: &mut App,
     ) {
-        let parent = window.raw_native_view_ptr();
-        if parent.is_null() {
+        let Some(parent) = window.native_controls_parent() else {
             return;
-        }
+        };
 
         let on_click = self.on_click.take();
         let sf_symbol = self.sf_symbol.clone();
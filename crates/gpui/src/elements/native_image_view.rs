@@ -372,10 +372,9 @@ impl Element for NativeImageView {
         window: &mut Window,
         cx: &mut App,
     ) {
-        let parent = window.raw_native_view_ptr();
-        if parent.is_null() {
+        let Some(parent) = window.native_controls_parent() else {
             return;
-        }
+        };
 
         let source = self.source.take();
         let scaling = self.scaling;
@@ -17,10 +17,9 @@ pub fn show_native_popup_menu(
     cx: &App,
     on_select: impl FnOnce(usize, &mut Window, &mut App) + 'static,
 ) {
-    let native_view = window.raw_native_view_ptr();
-    if native_view.is_null() {
+    let Some(native_view) = window.native_controls_parent() else {
         return;
-    }
+    };
 
     let nc = window.native_controls();
     let mapped = map_items(items);
@@ -260,10 +259,9 @@ impl Element for NativeMenuButton {
         window: &mut Window,
         _cx: &mut App,
     ) {
-        let parent = window.raw_native_view_ptr();
-        if parent.is_null() {
+        let Some(parent) = window.native_controls_parent() else {
             return;
-        }
+        };
 
         let on_select = self.on_select.take();
         let label = self.label.clone();
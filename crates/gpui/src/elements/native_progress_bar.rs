@@ -163,10 +163,9 @@ impl Element for NativeProgressBar {
         window: &mut Window,
         _cx: &mut App,
     ) {
-        let parent = window.raw_native_view_ptr();
-        if parent.is_null() {
+        let Some(parent) = window.native_controls_parent() else {
             return;
-        }
+        };
 
         let progress_style = self.progress_style;
         let displayed_when_stopped = self.displayed_when_stopped;
@@ -336,16 +336,15 @@ impl Element for NativeSidebar {
         &mut self,
         id: Option<&GlobalElementId>,
         _inspector_id: Option<&InspectorElementId>,
-        _bounds: Bounds<Pixels>,
+        bounds: Bounds<Pixels>,
         _request_layout: &mut Self::RequestLayoutState,
         _prepaint: &mut Self::PrepaintState,
         window: &mut Window,
         _cx: &mut App,
     ) {
-        let parent = window.raw_native_view_ptr();
-        if parent.is_null() {
+        let Some(parent) = window.native_controls_parent() else {
             return;
-        }
+        };
 
         let on_select = self.on_select.take();
         let sidebar_view = self.sidebar_view.take();
@@ -409,7 +408,10 @@ impl Element for NativeSidebar {
             // yet, or when config values actually changed. Unconditional
             // updates trigger heavy AppKit layout/table-reload work during
             // paint that causes RefCell reentrancy and render-loop hangs.
+            // Sidebars drawn by GPUI are painted by the update, so they're
+            // updated every frame.
             let needs_update = !state.native.is_initialized()
+                || window.platform_window.native_controls().is_none()
                 || state.prev_items != items
                 || state.prev_selected != selected_index
                 || state.prev_sidebar_width != sidebar_width
@@ -433,7 +435,7 @@ impl Element for NativeSidebar {
                 nc.update_sidebar(
                     &mut state.native,
                     parent,
-                    Bounds::default(),
+                    bounds,
                     scale,
                     SidebarViewConfig {
                         sidebar_width,
@@ -223,10 +223,9 @@ impl Element for NativeStackView {
         window: &mut Window,
         _cx: &mut App,
     ) {
-        let parent = window.raw_native_view_ptr();
-        if parent.is_null() {
+        let Some(parent) = window.native_controls_parent() else {
             return;
-        }
+        };
 
         let orientation = self.orientation;
         let spacing = self.spacing;
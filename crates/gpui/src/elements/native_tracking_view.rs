@@ -146,10 +146,9 @@ impl Element for NativeTrackingView {
         window: &mut Window,
         _cx: &mut App,
     ) {
-        let parent = window.raw_native_view_ptr();
-        if parent.is_null() {
+        let Some(parent) = window.native_controls_parent() else {
             return;
-        }
+        };
 
         let on_enter = self.on_mouse_enter.take();
         let on_exit = self.on_mouse_exit.take();
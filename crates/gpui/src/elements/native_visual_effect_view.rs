@@ -221,10 +221,9 @@ impl Element for NativeVisualEffectView {
         window: &mut Window,
         _cx: &mut App,
     ) {
-        let parent = window.raw_native_view_ptr();
-        if parent.is_null() {
+        let Some(parent) = window.native_controls_parent() else {
             return;
-        }
+        };
 
         let material = self.material;
         let blending_mode = self.blending_mode;
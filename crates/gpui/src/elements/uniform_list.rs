@@ -5,10 +5,10 @@
 //! elements with uniform height.
 
 use crate::{
-    AnyElement, App, AvailableSpace, Bounds, ContentMask, Element, ElementId, Entity,
-    GlobalElementId, Hitbox, InspectorElementId, InteractiveElement, Interactivity, IntoElement,
-    IsZero, LayoutId, ListSizingBehavior, Overflow, Pixels, Point, ScrollHandle, Size,
-    StyleRefinement, Styled, Window, point, size,
+    AccessibilityProperties, AnyElement, App, AvailableSpace, Bounds, ContentMask, Element,
+    ElementId, Entity, GlobalElementId, Hitbox, InspectorElementId, InteractiveAccessibility,
+    InteractiveElement, Interactivity, IntoElement, IsZero, LayoutId, ListSizingBehavior, Overflow,
+    Pixels, Point, Role, ScrollHandle, Size, StyleRefinement, Styled, Window, point, size,
 };
 use smallvec::SmallVec;
 use std::{cell::RefCell, cmp, ops::Range, rc::Rc, usize};
@@ -46,6 +46,10 @@ where
         interactivity: Interactivity {
             element_id: Some(id),
             base_style: Box::new(base_style),
+            accessibility: Some(Box::new(InteractiveAccessibility {
+                properties: AccessibilityProperties::new(Role::List),
+                ..Default::default()
+            })),
             ..Interactivity::new()
         },
         scroll_handle: None,
@@ -637,6 +641,13 @@ impl UniformList {
         self
     }
 
+    /// Sets whether the list scrolls when something is dragged near its edges.
+    /// Enabled by default.
+    pub fn with_drag_autoscroll(mut self, enabled: bool) -> Self {
+        self.interactivity.base_style.drag_autoscroll = Some(enabled);
+        self
+    }
+
     /// Adds a decoration element to the list.
     pub fn with_decoration(mut self, decoration: impl UniformListDecoration + 'static) -> Self {
         self.decorations.push(Box::new(decoration));
@@ -0,0 +1,645 @@
+use std::{ops::Range, time::Duration};
+
+use scheduler::Instant;
+
+use crate::{
+    App, Bounds, DispatchPhase, FocusHandle, FocusId, GlobalElementId, KeyDownEvent, Pixels,
+    SharedString, Window,
+};
+
+/// How long type-ahead keeps adding keystrokes to the same query.
+const TYPE_AHEAD_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Which arrow keys move focus among the members of a [`FocusGroup`], and how.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FocusGroupAxis {
+    /// Left and right move to the previous and next member, like in a toolbar or tab list.
+    #[default]
+    Horizontal,
+    /// Up and down move to the previous and next member, like in a menu or list.
+    Vertical,
+    /// Left and up move to the previous member, right and down to the next, like in a radio group.
+    Both,
+    /// Each arrow key moves to the nearest member in its direction, like in a grid.
+    Grid,
+}
+
+/// Makes the focusable descendants of an element a single tab stop, moving focus among them with
+/// the arrow keys. See [`crate::StatefulInteractiveElement::focus_group`].
+///
+/// Members are the tab stops painted within the element, outside of any nested focus group, in
+/// the order they're painted. Tab focuses the member that was focused last, or the first one.
+/// Home and End focus the first and last members, and with `type_ahead`, typing focuses the next
+/// member whose accessibility label (see [`crate::InteractiveElement::aria_label`]) starts with
+/// what was typed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FocusGroup {
+    /// Which arrow keys move focus.
+    pub axis: FocusGroupAxis,
+    /// Whether moving past the last member focuses the first, and vice versa.
+    pub wrap: bool,
+    /// Whether typing focuses members by their label.
+    pub type_ahead: bool,
+}
+
+impl FocusGroup {
+    /// A focus group navigated with the left and right arrow keys.
+    pub fn horizontal() -> Self {
+        Self::new(FocusGroupAxis::Horizontal)
+    }
+
+    /// A focus group navigated with the up and down arrow keys.
+    pub fn vertical() -> Self {
+        Self::new(FocusGroupAxis::Vertical)
+    }
+
+    /// A focus group navigated in two dimensions, by the members' bounds.
+    pub fn grid() -> Self {
+        Self::new(FocusGroupAxis::Grid)
+    }
+
+    /// A focus group navigated along the given axis, which wraps around and supports type-ahead.
+    pub fn new(axis: FocusGroupAxis) -> Self {
+        Self {
+            axis,
+            wrap: true,
+            type_ahead: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FocusGroupMove {
+    Left,
+    Right,
+    Up,
+    Down,
+    First,
+    Last,
+}
+
+/// The focus groups painted in a frame, and their members.
+#[derive(Default)]
+pub(crate) struct FocusGroupMap {
+    groups: Vec<FocusGroupState>,
+    members: Vec<FocusGroupMember>,
+    stack: Vec<usize>,
+}
+
+#[derive(Clone)]
+struct FocusGroupState {
+    id: GlobalElementId,
+    config: FocusGroup,
+    last_focused: Option<FocusId>,
+}
+
+#[derive(Clone)]
+struct FocusGroupMember {
+    group_ix: usize,
+    focus_handle: FocusHandle,
+    bounds: Bounds<Pixels>,
+    label: Option<SharedString>,
+}
+
+/// Where a frame's focus groups and members end, for reusing a range of them in the next frame.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct FocusGroupIndex {
+    groups: usize,
+    members: usize,
+}
+
+impl FocusGroupMap {
+    pub fn clear(&mut self) {
+        self.groups.clear();
+        self.members.clear();
+        self.stack.clear();
+    }
+
+    pub fn paint_index(&self) -> FocusGroupIndex {
+        FocusGroupIndex {
+            groups: self.groups.len(),
+            members: self.members.len(),
+        }
+    }
+
+    /// Start a group, remembering which member was last focused in `previous`, the last frame.
+    pub fn begin_group(&mut self, id: GlobalElementId, config: FocusGroup, previous: &Self) {
+        let last_focused = previous
+            .groups
+            .iter()
+            .find(|group| group.id == id)
+            .and_then(|group| group.last_focused);
+        self.stack.push(self.groups.len());
+        self.groups.push(FocusGroupState {
+            id,
+            config,
+            last_focused,
+        });
+    }
+
+    /// End the innermost group, returning its members and which of them should be its tab stop:
+    /// the focused member, or else the last focused one, or else the first.
+    pub fn end_group(&mut self, focused: Option<FocusId>) -> (Vec<FocusId>, Option<FocusId>) {
+        let Some(group_ix) = self.stack.pop() else {
+            return (Vec::new(), None);
+        };
+        let members = self
+            .members
+            .iter()
+            .filter(|member| member.group_ix == group_ix)
+            .map(|member| member.focus_handle.id)
+            .collect::<Vec<_>>();
+        let group = &mut self.groups[group_ix];
+        if let Some(focused) = focused.filter(|focused| members.contains(focused)) {
+            group.last_focused = Some(focused);
+        }
+        let tab_stop = group
+            .last_focused
+            .filter(|last_focused| members.contains(last_focused))
+            .or_else(|| members.first().copied());
+        (members, tab_stop)
+    }
+
+    /// Add a tab stop to the innermost group, returning whether there is one.
+    pub fn insert_member(
+        &mut self,
+        focus_handle: &FocusHandle,
+        bounds: Bounds<Pixels>,
+        label: Option<SharedString>,
+    ) -> bool {
+        let Some(&group_ix) = self.stack.last() else {
+            return false;
+        };
+        self.members.push(FocusGroupMember {
+            group_ix,
+            focus_handle: focus_handle.clone(),
+            bounds,
+            label,
+        });
+        true
+    }
+
+    /// Copy the groups and members painted in the given range of `previous`. Members of groups
+    /// that started before the range join the innermost group painting now.
+    pub fn reuse(&mut self, previous: &Self, range: Range<FocusGroupIndex>) {
+        let offset = self.groups.len();
+        let reused_groups = range.start.groups..range.end.groups;
+        self.groups
+            .extend_from_slice(&previous.groups[reused_groups.clone()]);
+        for member in &previous.members[range.start.members..range.end.members] {
+            let group_ix = if reused_groups.contains(&member.group_ix) {
+                member.group_ix - reused_groups.start + offset
+            } else if let Some(&group_ix) = self.stack.last() {
+                group_ix
+            } else {
+                continue;
+            };
+            self.members.push(FocusGroupMember {
+                group_ix,
+                ..member.clone()
+            });
+        }
+    }
+
+    fn group(&self, id: &GlobalElementId) -> Option<(usize, &FocusGroupState)> {
+        self.groups
+            .iter()
+            .enumerate()
+            .find(|(_, group)| group.id == *id)
+    }
+
+    fn members(&self, group_ix: usize) -> Vec<&FocusGroupMember> {
+        self.members
+            .iter()
+            .filter(|member| member.group_ix == group_ix)
+            .collect()
+    }
+
+    fn navigate(
+        &self,
+        id: &GlobalElementId,
+        focused: FocusId,
+        movement: FocusGroupMove,
+    ) -> Option<FocusHandle> {
+        let (group_ix, group) = self.group(id)?;
+        let members = self.members(group_ix);
+        let current = members
+            .iter()
+            .position(|member| member.focus_handle.id == focused)?;
+        let config = group.config;
+
+        let linear = |forward: bool| {
+            let target = if forward {
+                current + 1
+            } else {
+                current.checked_sub(1).unwrap_or(members.len())
+            };
+            if target < members.len() {
+                Some(target)
+            } else if config.wrap {
+                Some(if forward { 0 } else { members.len() - 1 })
+            } else {
+                None
+            }
+        };
+
+        use FocusGroupAxis::*;
+        use FocusGroupMove::*;
+        let target = match (movement, config.axis) {
+            (First, _) => Some(0),
+            (Last, _) => Some(members.len() - 1),
+            (Left, Horizontal | Both) | (Up, Vertical | Both) => linear(false),
+            (Right, Horizontal | Both) | (Down, Vertical | Both) => linear(true),
+            (_, Grid) => nearest_in_direction(&members, current, movement).or_else(|| {
+                if !config.wrap {
+                    None
+                } else if matches!(movement, Left | Right) {
+                    linear(movement == Right)
+                } else {
+                    farthest_in_direction(&members, current, movement)
+                }
+            }),
+            _ => None,
+        }?;
+        Some(members[target].focus_handle.clone())
+    }
+
+    /// Find the member after the focused one whose label starts with the query. A query that
+    /// repeats one character cycles through the members starting with it.
+    fn find_by_label(
+        &self,
+        id: &GlobalElementId,
+        focused: FocusId,
+        query: &str,
+    ) -> Option<FocusHandle> {
+        let (group_ix, _) = self.group(id)?;
+        let members = self.members(group_ix);
+        let current = members
+            .iter()
+            .position(|member| member.focus_handle.id == focused)?;
+
+        let mut query_chars = query.chars();
+        let first_char = query_chars.next()?;
+        let (query, start) = if query_chars.all(|char| char == first_char) {
+            (&query[..first_char.len_utf8()], current + 1)
+        } else {
+            (query, current)
+        };
+        let query = query.to_lowercase();
+        (0..members.len())
+            .map(|ix| &members[(start + ix) % members.len()])
+            .find(|member| {
+                member
+                    .label
+                    .as_ref()
+                    .is_some_and(|label| label.to_lowercase().starts_with(&query))
+            })
+            .map(|member| member.focus_handle.clone())
+    }
+}
+
+/// Of the members past the current one in the given direction, find the index of the nearest,
+/// preferring those in the same row or column.
+fn nearest_in_direction(
+    members: &[&FocusGroupMember],
+    current: usize,
+    movement: FocusGroupMove,
+) -> Option<usize> {
+    let from = members[current].bounds;
+    members
+        .iter()
+        .enumerate()
+        .filter_map(|(ix, member)| {
+            let (along, across) = offset_in_direction(from, member.bounds, movement);
+            (along > Pixels::ZERO).then_some((ix, (across, along)))
+        })
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(ix, _)| ix)
+}
+
+/// Find the index of the member farthest in the opposite direction, in the same row or column,
+/// for wrapping around.
+fn farthest_in_direction(
+    members: &[&FocusGroupMember],
+    current: usize,
+    movement: FocusGroupMove,
+) -> Option<usize> {
+    let from = members[current].bounds;
+    members
+        .iter()
+        .enumerate()
+        .filter_map(|(ix, member)| {
+            let (along, across) = offset_in_direction(from, member.bounds, movement);
+            (along < Pixels::ZERO && across == Pixels::ZERO).then_some((ix, along))
+        })
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(ix, _)| ix)
+}
+
+/// How far `to`'s center is from `from`'s in the given direction, and how far it is across it,
+/// which is zero when they're in the same row or column.
+fn offset_in_direction(
+    from: Bounds<Pixels>,
+    to: Bounds<Pixels>,
+    movement: FocusGroupMove,
+) -> (Pixels, Pixels) {
+    let (from_center, to_center) = (from.center(), to.center());
+    let horizontal_offset = if to.left() < from.right() && from.left() < to.right() {
+        Pixels::ZERO
+    } else {
+        (to_center.x - from_center.x).abs()
+    };
+    let vertical_offset = if to.top() < from.bottom() && from.top() < to.bottom() {
+        Pixels::ZERO
+    } else {
+        (to_center.y - from_center.y).abs()
+    };
+    match movement {
+        FocusGroupMove::Left => (from_center.x - to_center.x, vertical_offset),
+        FocusGroupMove::Right => (to_center.x - from_center.x, vertical_offset),
+        FocusGroupMove::Up => (from_center.y - to_center.y, horizontal_offset),
+        FocusGroupMove::Down => (to_center.y - from_center.y, horizontal_offset),
+        FocusGroupMove::First | FocusGroupMove::Last => (Pixels::ZERO, Pixels::ZERO),
+    }
+}
+
+/// What's been typed for type-ahead in a focus group.
+#[derive(Default)]
+pub(crate) struct FocusGroupTypeAhead {
+    query: String,
+    last_input: Option<Instant>,
+}
+
+/// Register a listener that moves focus among the members of the group with the given id, when
+/// one of them is focused and doesn't handle the key itself.
+pub(crate) fn register_focus_group_listener(id: GlobalElementId, window: &mut Window) {
+    window.on_key_event(move |event: &KeyDownEvent, phase, window, cx| {
+        if phase == DispatchPhase::Bubble && handle_key_down(&id, event, window, cx) {
+            cx.stop_propagation();
+        }
+    });
+}
+
+fn handle_key_down(
+    id: &GlobalElementId,
+    event: &KeyDownEvent,
+    window: &mut Window,
+    cx: &mut App,
+) -> bool {
+    let Some(focused) = window.focus else {
+        return false;
+    };
+    let focus_groups = &window.rendered_frame.focus_groups;
+    let Some((group_ix, group)) = focus_groups.group(id) else {
+        return false;
+    };
+    if !focus_groups
+        .members(group_ix)
+        .iter()
+        .any(|member| member.focus_handle.id == focused)
+    {
+        return false;
+    }
+    let keystroke = &event.keystroke;
+    let modifiers = keystroke.modifiers;
+    if modifiers.control || modifiers.alt || modifiers.platform {
+        return false;
+    }
+
+    let movement = match keystroke.key.as_str() {
+        _ if modifiers.shift => None,
+        "left" => Some(FocusGroupMove::Left),
+        "right" => Some(FocusGroupMove::Right),
+        "up" => Some(FocusGroupMove::Up),
+        "down" => Some(FocusGroupMove::Down),
+        "home" => Some(FocusGroupMove::First),
+        "end" => Some(FocusGroupMove::Last),
+        _ => None,
+    };
+    let target = if let Some(movement) = movement {
+        focus_groups.navigate(id, focused, movement)
+    } else if group.config.type_ahead
+        && let Some(key_char) = keystroke.key_char.as_deref()
+        && !key_char.chars().any(char::is_control)
+    {
+        let now = cx.background_executor().now();
+        let type_ahead = &mut window.focus_group_type_ahead;
+        if type_ahead
+            .last_input
+            .is_none_or(|last_input| now - last_input > TYPE_AHEAD_TIMEOUT)
+        {
+            type_ahead.query.clear();
+        }
+        // Space activates the focused member, unless it's part of a query.
+        if type_ahead.query.is_empty() && key_char == " " {
+            return false;
+        }
+        type_ahead.query.push_str(key_char);
+        type_ahead.last_input = Some(now);
+        window.rendered_frame.focus_groups.find_by_label(
+            id,
+            focused,
+            &window.focus_group_type_ahead.query,
+        )
+    } else {
+        None
+    };
+
+    let Some(target) = target else {
+        return false;
+    };
+    window.focus(&target, cx);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FocusGroup, FocusGroupMap, FocusGroupMove};
+    use crate::{
+        Bounds, Context, ElementId, FocusHandle, FocusMap, GlobalElementId, InteractiveElement,
+        IntoElement, ParentElement, Render, SharedString, StatefulInteractiveElement, Styled,
+        TestAppContext, Window, bounds, div, point, px, size,
+    };
+    use std::sync::Arc;
+
+    fn group_id() -> GlobalElementId {
+        GlobalElementId(Arc::from([ElementId::from("group")]))
+    }
+
+    fn build(
+        config: FocusGroup,
+        members: &[(Bounds<crate::Pixels>, &str)],
+    ) -> (FocusGroupMap, Vec<FocusHandle>) {
+        let focus_map = Arc::new(FocusMap::default());
+        let handles = members
+            .iter()
+            .map(|_| FocusHandle::new(&focus_map))
+            .collect::<Vec<_>>();
+        let mut map = FocusGroupMap::default();
+        map.begin_group(group_id(), config, &FocusGroupMap::default());
+        for (handle, (bounds, label)) in handles.iter().zip(members) {
+            map.insert_member(handle, *bounds, Some(SharedString::new(*label)));
+        }
+        let (ids, tab_stop) = map.end_group(None);
+        assert_eq!(ids.len(), members.len());
+        assert_eq!(tab_stop, Some(handles[0].id));
+        (map, handles)
+    }
+
+    fn cell(column: usize, row: usize) -> Bounds<crate::Pixels> {
+        bounds(
+            point(px(column as f32 * 10.), px(row as f32 * 10.)),
+            size(px(10.), px(10.)),
+        )
+    }
+
+    #[test]
+    fn test_linear_navigation() {
+        let members = [
+            (cell(0, 0), "Cut"),
+            (cell(1, 0), "Copy"),
+            (cell(2, 0), "Paste"),
+        ];
+        let (map, handles) = build(FocusGroup::horizontal(), &members);
+        let navigate = |from: usize, movement| {
+            let target = map.navigate(&group_id(), handles[from].id, movement)?;
+            handles.iter().position(|handle| *handle == target)
+        };
+        assert_eq!(navigate(0, FocusGroupMove::Right), Some(1));
+        assert_eq!(navigate(2, FocusGroupMove::Right), Some(0));
+        assert_eq!(navigate(0, FocusGroupMove::Left), Some(2));
+        assert_eq!(navigate(0, FocusGroupMove::Down), None);
+        assert_eq!(navigate(0, FocusGroupMove::Last), Some(2));
+
+        let (map, handles) = build(
+            FocusGroup {
+                wrap: false,
+                ..FocusGroup::horizontal()
+            },
+            &members,
+        );
+        assert_eq!(
+            map.navigate(&group_id(), handles[2].id, FocusGroupMove::Right),
+            None
+        );
+    }
+
+    #[test]
+    fn test_grid_navigation() {
+        // A 3x2 grid, missing its bottom right cell.
+        let members = [
+            (cell(0, 0), "a"),
+            (cell(1, 0), "b"),
+            (cell(2, 0), "c"),
+            (cell(0, 1), "d"),
+            (cell(1, 1), "e"),
+        ];
+        let (map, handles) = build(FocusGroup::grid(), &members);
+        let navigate = |from: usize, movement| {
+            let target = map.navigate(&group_id(), handles[from].id, movement)?;
+            handles.iter().position(|handle| *handle == target)
+        };
+        assert_eq!(navigate(0, FocusGroupMove::Right), Some(1));
+        assert_eq!(navigate(1, FocusGroupMove::Down), Some(4));
+        assert_eq!(navigate(4, FocusGroupMove::Up), Some(1));
+        // Moving past the end of a row continues on the next one.
+        assert_eq!(navigate(2, FocusGroupMove::Right), Some(3));
+        // Moving past the end of a column wraps to its start.
+        assert_eq!(navigate(3, FocusGroupMove::Down), Some(0));
+        // Without a cell right below, down moves to the nearest one.
+        assert_eq!(navigate(2, FocusGroupMove::Down), Some(4));
+    }
+
+    #[test]
+    fn test_type_ahead() {
+        let members = [
+            (cell(0, 0), "Save"),
+            (cell(0, 1), "Save As"),
+            (cell(0, 2), "Share"),
+            (cell(0, 3), "Open"),
+        ];
+        let (map, handles) = build(FocusGroup::vertical(), &members);
+        let find = |from: usize, query| {
+            let target = map.find_by_label(&group_id(), handles[from].id, query)?;
+            handles.iter().position(|handle| *handle == target)
+        };
+        assert_eq!(find(3, "s"), Some(0));
+        assert_eq!(find(0, "s"), Some(1));
+        assert_eq!(find(1, "ss"), Some(2));
+        assert_eq!(find(0, "sh"), Some(2));
+        assert_eq!(find(1, "save"), Some(1));
+        assert_eq!(find(0, "x"), None);
+    }
+
+    struct Toolbar {
+        buttons: Vec<FocusHandle>,
+        search: FocusHandle,
+    }
+
+    impl Render for Toolbar {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .size_full()
+                .child(
+                    div()
+                        .id("toolbar")
+                        .flex()
+                        .focus_group(FocusGroup::horizontal())
+                        .children(
+                            ["Bold", "Italic", "Underline"]
+                                .into_iter()
+                                .zip(&self.buttons)
+                                .map(|(label, handle)| {
+                                    div().track_focus(handle).aria_label(label).size(px(10.))
+                                }),
+                        ),
+                )
+                .child(div().track_focus(&self.search).size(px(10.)))
+        }
+    }
+
+    #[crate::test]
+    fn test_focus_group_in_window(cx: &mut TestAppContext) {
+        let (toolbar, cx) = cx.add_window_view(|_, cx| Toolbar {
+            buttons: (0..3).map(|_| cx.focus_handle().tab_stop(true)).collect(),
+            search: cx.focus_handle().tab_stop(true),
+        });
+        let focused = |cx: &mut crate::VisualTestContext| {
+            cx.update(|window, cx| {
+                let toolbar = toolbar.read(cx);
+                let focused = window.focused(cx)?;
+                Some(
+                    toolbar
+                        .buttons
+                        .iter()
+                        .position(|button| *button == focused)
+                        .unwrap_or(toolbar.buttons.len()),
+                )
+            })
+        };
+
+        // The group is a single tab stop.
+        cx.update(|window, cx| window.focus_next(cx));
+        assert_eq!(focused(cx), Some(0));
+        cx.simulate_keystrokes("right right");
+        assert_eq!(focused(cx), Some(2));
+        cx.simulate_keystrokes("right");
+        assert_eq!(focused(cx), Some(0));
+        cx.simulate_keystrokes("end");
+        assert_eq!(focused(cx), Some(2));
+        cx.update(|window, cx| window.focus_next(cx));
+        assert_eq!(focused(cx), Some(3));
+
+        // Tabbing back into the group focuses the member that was focused last.
+        cx.update(|window, cx| window.focus_prev(cx));
+        assert_eq!(focused(cx), Some(2));
+
+        cx.simulate_keystrokes("b");
+        assert_eq!(focused(cx), Some(0));
+        cx.simulate_keystrokes("u");
+        assert_eq!(focused(cx), Some(0));
+        cx.executor().advance_clock(super::TYPE_AHEAD_TIMEOUT * 2);
+        cx.simulate_keystrokes("u");
+        assert_eq!(focused(cx), Some(2));
+    }
+}
@@ -0,0 +1,252 @@
+use std::ops::Range;
+
+use crate::{FocusHandle, FocusId, GlobalElementId, TabStopMap, TabStopOperation};
+
+/// The focus traps painted in a frame. See [`crate::StatefulInteractiveElement::focus_trap`].
+#[derive(Default)]
+pub(crate) struct FocusTrapMap {
+    traps: Vec<FocusTrap>,
+    stack: Vec<usize>,
+}
+
+#[derive(Clone)]
+struct FocusTrap {
+    id: GlobalElementId,
+    /// The focus handle tracked by the trapping element itself.
+    root: Option<FocusId>,
+    /// The focus handles inserted into the frame's [`TabStopMap`] while painting the trap.
+    tab_stops: Range<usize>,
+    /// What was focused before the trap was first painted, to be focused again when it's gone.
+    restore_focus: Option<FocusId>,
+    /// Whether this is the first frame the trap was painted in.
+    is_new: bool,
+}
+
+/// Where a frame's focus traps end, for reusing a range of them in the next frame.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct FocusTrapIndex {
+    traps: usize,
+    tab_stops: usize,
+}
+
+impl FocusTrapMap {
+    pub fn clear(&mut self) {
+        self.traps.clear();
+        self.stack.clear();
+    }
+
+    pub fn paint_index(&self, tab_stops: &TabStopMap) -> FocusTrapIndex {
+        FocusTrapIndex {
+            traps: self.traps.len(),
+            tab_stops: tab_stops.paint_index(),
+        }
+    }
+
+    /// Start a trap, carrying its state over from `previous`, the last frame.
+    pub fn begin_trap(
+        &mut self,
+        id: GlobalElementId,
+        root: Option<FocusId>,
+        tab_stops: &TabStopMap,
+        previous: &Self,
+    ) {
+        let previous = previous.traps.iter().find(|trap| trap.id == id);
+        let start = tab_stops.paint_index();
+        self.stack.push(self.traps.len());
+        self.traps.push(FocusTrap {
+            id,
+            root,
+            tab_stops: start..start,
+            restore_focus: previous.and_then(|trap| trap.restore_focus),
+            is_new: previous.is_none(),
+        });
+    }
+
+    /// End the innermost trap. When it's new, `previous_focus`, what was focused in the last
+    /// frame, is restored once the trap is gone, unless it was inside the trap.
+    pub fn end_trap(&mut self, tab_stops: &TabStopMap, previous_focus: Option<FocusId>) {
+        let Some(trap_ix) = self.stack.pop() else {
+            return;
+        };
+        let trap = &mut self.traps[trap_ix];
+        trap.tab_stops.end = tab_stops.paint_index();
+        if trap.is_new {
+            trap.restore_focus =
+                previous_focus.filter(|&focus_id| !trap.contains(focus_id, tab_stops));
+        }
+    }
+
+    /// Copy the traps painted in the given range of `previous`, whose tab stops have been replayed
+    /// starting at `tab_stop_start`.
+    pub fn reuse(&mut self, previous: &Self, range: Range<FocusTrapIndex>, tab_stop_start: usize) {
+        for trap in &previous.traps[range.start.traps..range.end.traps] {
+            let start = trap.tab_stops.start - range.start.tab_stops + tab_stop_start;
+            let end = trap.tab_stops.end - range.start.tab_stops + tab_stop_start;
+            self.traps.push(FocusTrap {
+                tab_stops: start..end,
+                is_new: false,
+                ..trap.clone()
+            });
+        }
+    }
+
+    /// The trap that Tab cycles within: the innermost one containing the focused element, or
+    /// else the one painted last.
+    fn active_trap(&self, focused: Option<FocusId>, tab_stops: &TabStopMap) -> Option<&FocusTrap> {
+        focused
+            .and_then(|focused| {
+                self.traps
+                    .iter()
+                    .rev()
+                    .find(|trap| trap.contains(focused, tab_stops))
+            })
+            .or_else(|| self.traps.last())
+    }
+
+    /// Find the next or previous tab stop after the focused one, within the active trap.
+    pub fn next_tab_stop(
+        &self,
+        focused: Option<FocusId>,
+        tab_stops: &TabStopMap,
+        reverse: bool,
+    ) -> Option<FocusHandle> {
+        let Some(trap) = self.active_trap(focused, tab_stops) else {
+            return tab_stops.find(focused, reverse, |_| true);
+        };
+        tab_stops.find(focused, reverse, |candidate| {
+            trap.contains(candidate, tab_stops)
+        })
+    }
+
+    /// Find the tab stop to focus in traps painted for the first time: the first one inside the
+    /// trap painted last, unless the focused element is already inside it.
+    pub fn autofocus_target(
+        &self,
+        focused: Option<FocusId>,
+        tab_stops: &TabStopMap,
+    ) -> Option<FocusHandle> {
+        let trap = self.traps.iter().rev().find(|trap| trap.is_new)?;
+        if focused.is_some_and(|focused| trap.contains_tab_stop(focused, tab_stops)) {
+            return None;
+        }
+        tab_stops.find(None, false, |candidate| {
+            trap.contains_tab_stop(candidate, tab_stops)
+        })
+    }
+
+    /// Find the focus to restore once the traps that are in `self`, the last frame, but not in
+    /// `next`, have gone. That's when the focused element was inside one of them, or is gone too.
+    pub fn focus_to_restore(
+        &self,
+        tab_stops: &TabStopMap,
+        next: &Self,
+        next_tab_stops: &TabStopMap,
+        focused: Option<FocusId>,
+    ) -> Option<FocusId> {
+        let focus_is_gone = focused.is_none_or(|focused| !next_tab_stops.contains(focused));
+        self.traps
+            .iter()
+            .filter(|trap| !next.traps.iter().any(|next_trap| next_trap.id == trap.id))
+            .find(|trap| {
+                focus_is_gone || focused.is_some_and(|focused| trap.contains(focused, tab_stops))
+            })
+            .and_then(|trap| trap.restore_focus)
+    }
+}
+
+impl FocusTrap {
+    fn contains(&self, focus_id: FocusId, tab_stops: &TabStopMap) -> bool {
+        self.root == Some(focus_id) || self.contains_tab_stop(focus_id, tab_stops)
+    }
+
+    fn contains_tab_stop(&self, focus_id: FocusId, tab_stops: &TabStopMap) -> bool {
+        tab_stops.insertion_history[self.tab_stops.clone()]
+            .iter()
+            .any(|operation| {
+                matches!(operation, TabStopOperation::Insert(handle) if handle.id == focus_id)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Context, FocusHandle, InteractiveElement, IntoElement, ParentElement, Render,
+        StatefulInteractiveElement, Styled, TestAppContext, VisualTestContext, Window, div,
+        prelude::FluentBuilder as _, px,
+    };
+
+    struct Workspace {
+        buttons: Vec<FocusHandle>,
+        modal: FocusHandle,
+        modal_buttons: Vec<FocusHandle>,
+        show_modal: bool,
+    }
+
+    impl Render for Workspace {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let button = |handle: &FocusHandle| div().track_focus(handle).size(px(10.));
+            div()
+                .size_full()
+                .children(self.buttons.iter().map(button))
+                .when(self.show_modal, |this| {
+                    this.child(
+                        div()
+                            .id("modal")
+                            .track_focus(&self.modal)
+                            .focus_trap()
+                            .children(self.modal_buttons.iter().map(button)),
+                    )
+                })
+        }
+    }
+
+    #[crate::test]
+    fn test_focus_trap(cx: &mut TestAppContext) {
+        let (workspace, cx) = cx.add_window_view(|_, cx| Workspace {
+            buttons: (0..2).map(|_| cx.focus_handle().tab_stop(true)).collect(),
+            modal: cx.focus_handle(),
+            modal_buttons: (0..2).map(|_| cx.focus_handle().tab_stop(true)).collect(),
+            show_modal: false,
+        });
+        let focused = |cx: &mut VisualTestContext| {
+            cx.update(|window, cx| {
+                let workspace = workspace.read(cx);
+                let focused = window.focused(cx)?;
+                workspace
+                    .buttons
+                    .iter()
+                    .chain(&workspace.modal_buttons)
+                    .position(|button| *button == focused)
+            })
+        };
+
+        cx.update(|window, cx| window.focus_next(cx));
+        cx.update(|window, cx| window.focus_next(cx));
+        assert_eq!(focused(cx), Some(1));
+
+        // Opening the modal focuses its first tab stop, even when its root is focused.
+        workspace.update_in(cx, |workspace, window, cx| {
+            workspace.show_modal = true;
+            window.focus(&workspace.modal, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(focused(cx), Some(2));
+
+        // Tab cycles within the modal.
+        cx.update(|window, cx| window.focus_next(cx));
+        assert_eq!(focused(cx), Some(3));
+        cx.update(|window, cx| window.focus_next(cx));
+        assert_eq!(focused(cx), Some(2));
+        cx.update(|window, cx| window.focus_prev(cx));
+        assert_eq!(focused(cx), Some(3));
+
+        // Closing it restores focus.
+        workspace.update(cx, |workspace, cx| {
+            workspace.show_modal = false;
+            cx.notify();
+        });
+        cx.run_until_parked();
+        assert_eq!(focused(cx), Some(1));
+    }
+}
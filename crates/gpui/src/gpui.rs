@@ -8,6 +8,7 @@ extern crate self as gpui;
 #[doc(hidden)]
 pub static GPUI_MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
 
+mod accessibility;
 #[macro_use]
 mod action;
 mod app;
@@ -17,11 +18,14 @@ mod asset_cache;
 mod assets;
 mod bounds_tree;
 mod color;
+mod command_palette;
 /// The default colors used by GPUI.
 pub mod colors;
 mod element;
 mod elements;
 mod executor;
+mod focus_group;
+mod focus_trap;
 mod platform_scheduler;
 pub(crate) use platform_scheduler::PlatformScheduler;
 mod geometry;
@@ -51,6 +55,7 @@ mod taffy;
 #[cfg(any(test, feature = "test-support"))]
 pub mod test;
 mod text_system;
+mod undo;
 mod util;
 mod view;
 mod window;
@@ -77,6 +82,7 @@ mod seal {
     pub trait Sealed {}
 }
 
+pub use accessibility::*;
 pub use action::*;
 pub use anyhow::Result;
 pub use app::*;
@@ -84,10 +90,13 @@ pub(crate) use arena::*;
 pub use asset_cache::*;
 pub use assets::*;
 pub use color::*;
+pub use command_palette::*;
 pub use ctor::ctor;
 pub use element::*;
 pub use elements::*;
 pub use executor::*;
+pub use focus_group::*;
+pub(crate) use focus_trap::*;
 pub use geometry::*;
 pub use global::*;
 pub use gpui_macros::{
@@ -119,6 +128,7 @@ pub use taffy::{AvailableSpace, LayoutId};
 #[cfg(any(test, feature = "test-support"))]
 pub use test::*;
 pub use text_system::*;
+pub use undo::*;
 pub use gpui_util::arc_cow::ArcCow;
 pub use util::{FutureExt, Timeout};
 pub use view::*;
@@ -1,6 +1,6 @@
 use crate::{
-    Bounds, Capslock, Context, Empty, IntoElement, Keystroke, Modifiers, Pixels, Point, Render,
-    Window, point, seal::Sealed,
+    Bounds, Capslock, ClipboardEntry, ClipboardItem, Context, Empty, IntoElement, Keystroke,
+    Modifiers, Pixels, Point, Render, Window, point, seal::Sealed,
 };
 use smallvec::SmallVec;
 use std::{any::Any, fmt::Debug, ops::Deref, path::PathBuf};
@@ -654,6 +654,123 @@ impl InputEvent for FileDropEvent {
 }
 impl MouseEvent for FileDropEvent {}
 
+/// Data other than files dragged onto the window from another application, which is the dragged
+/// value of [`ExternalDropEvent`]s. Files are dragged as [`ExternalPaths`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalDrop(pub ClipboardItem);
+
+impl ExternalDrop {
+    /// The dragged data, in every representation the other application offers that can be read.
+    pub fn item(&self) -> &ClipboardItem {
+        &self.0
+    }
+}
+
+impl Render for ExternalDrop {
+    fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+        // the platform will render the dragged data
+        Empty
+    }
+}
+
+/// A drag and drop event from the platform, generated when another application drags any data it
+/// can offer over the window. The data is dropped as [`ExternalPaths`] when it holds files, and as
+/// an [`ExternalDrop`] otherwise.
+///
+/// The platform tells the other application the drop is accepted when the element under the mouse
+/// has a listener for it, and its [`crate::InteractiveElement::can_drop`] predicate allows it.
+#[derive(Debug, Clone)]
+pub enum ExternalDropEvent {
+    /// The data has entered the window.
+    Entered {
+        /// The position of the mouse relative to the window.
+        position: Point<Pixels>,
+        /// The data that is being dragged.
+        item: ClipboardItem,
+    },
+    /// The data is being dragged over the window.
+    Over {
+        /// The position of the mouse relative to the window.
+        position: Point<Pixels>,
+    },
+    /// The data has been dropped onto the window.
+    Dropped {
+        /// The position of the mouse relative to the window.
+        position: Point<Pixels>,
+    },
+    /// The user has stopped dragging the data over the window.
+    Exited,
+}
+
+impl Sealed for ExternalDropEvent {}
+impl InputEvent for ExternalDropEvent {
+    fn to_platform_input(self) -> PlatformInput {
+        PlatformInput::ExternalDrop(self)
+    }
+}
+impl MouseEvent for ExternalDropEvent {}
+
+/// Data dragged out of the window to other applications, see [`Window::start_external_drag`].
+#[derive(Debug, Clone)]
+pub struct ExternalDrag {
+    /// The data, offered in every representation the item holds.
+    pub item: ClipboardItem,
+    /// Whether the drop target may move the data, rather than only copy it.
+    pub allow_move: bool,
+}
+
+impl ExternalDrag {
+    /// Drag the given item, which drop targets may only copy.
+    pub fn new(item: ClipboardItem) -> Self {
+        Self {
+            item,
+            allow_move: false,
+        }
+    }
+
+    /// Drag the given files.
+    pub fn paths(paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self::new(ClipboardItem {
+            entries: vec![ClipboardEntry::ExternalPaths(ExternalPaths(
+                paths.into_iter().collect(),
+            ))],
+        })
+    }
+
+    /// Drag the given text.
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::new(ClipboardItem::new_string(text.into()))
+    }
+
+    /// Drag the given URL, which is offered as text too.
+    pub fn url(url: impl Into<String>) -> Self {
+        let url = url.into();
+        Self::new(
+            ClipboardItem::new_string(url.clone()).with_entry(ClipboardEntry::Custom {
+                mime: "text/uri-list".into(),
+                bytes: format!("{url}\r\n").into_bytes(),
+            }),
+        )
+    }
+
+    /// Let the drop target move the data, rather than only copy it.
+    pub fn allow_move(mut self) -> Self {
+        self.allow_move = true;
+        self
+    }
+}
+
+/// What the drop target did with data dragged out of the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalDragOutcome {
+    /// The data was copied.
+    Copied,
+    /// The data was moved, so the source should remove it.
+    Moved,
+    /// The data wasn't dropped, or the drop target refused it.
+    Cancelled,
+}
+
 /// An enum corresponding to all kinds of platform input events.
 #[derive(Clone, Debug)]
 pub enum PlatformInput {
@@ -681,6 +798,8 @@ pub enum PlatformInput {
     Rotation(RotationEvent),
     /// Files were dragged and dropped onto the window.
     FileDrop(FileDropEvent),
+    /// Data was dragged and dropped onto the window.
+    ExternalDrop(ExternalDropEvent),
 }
 
 impl PlatformInput {
@@ -698,6 +817,7 @@ impl PlatformInput {
             PlatformInput::Pinch(event) => Some(event),
             PlatformInput::Rotation(event) => Some(event),
             PlatformInput::FileDrop(event) => Some(event),
+            PlatformInput::ExternalDrop(event) => Some(event),
         }
     }
 
@@ -715,6 +835,7 @@ impl PlatformInput {
             PlatformInput::Pinch(_) => None,
             PlatformInput::Rotation(_) => None,
             PlatformInput::FileDrop(_) => None,
+            PlatformInput::ExternalDrop(_) => None,
         }
     }
 }
@@ -723,8 +844,10 @@ impl PlatformInput {
 mod test {
 
     use crate::{
-        self as gpui, AppContext as _, Context, FocusHandle, InteractiveElement, IntoElement,
-        KeyBinding, Keystroke, ParentElement, Render, TestAppContext, Window, div,
+        self as gpui, AppContext as _, ClipboardItem, Context, Empty, ExternalDrag,
+        ExternalDragOutcome, ExternalDrop, ExternalDropEvent, FocusHandle, InteractiveElement,
+        IntoElement, KeyBinding, Keystroke, Modifiers, MouseButton, ParentElement, PlatformInput,
+        Render, StatefulInteractiveElement, Styled, TestAppContext, Window, div, point, px,
     };
 
     struct TestView {
@@ -791,4 +914,129 @@ mod test {
             })
             .unwrap();
     }
+
+    struct DropTarget {
+        dropped: Vec<String>,
+    }
+
+    impl Render for DropTarget {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            div().size_full().child(
+                div()
+                    .size(px(10.))
+                    .can_drop(|value, _, _| {
+                        value
+                            .downcast_ref::<ExternalDrop>()
+                            .is_some_and(|drop| drop.item().text().is_some())
+                    })
+                    .on_drop(cx.listener(|this, drop: &ExternalDrop, _, _| {
+                        this.dropped.extend(drop.item().text());
+                    })),
+            )
+        }
+    }
+
+    #[gpui::test]
+    fn test_external_drop(cx: &mut TestAppContext) {
+        let (view, cx) = cx.add_window_view(|_, _| DropTarget {
+            dropped: Vec::new(),
+        });
+        let dispatch = |cx: &mut gpui::VisualTestContext, event: ExternalDropEvent| {
+            cx.update(|window, cx| window.dispatch_event(PlatformInput::ExternalDrop(event), cx))
+        };
+
+        let result = dispatch(
+            cx,
+            ExternalDropEvent::Entered {
+                position: point(px(50.), px(50.)),
+                item: ClipboardItem::new_string("https://zed.dev".into()),
+            },
+        );
+        assert!(!result.drop_accepted);
+        let result = dispatch(
+            cx,
+            ExternalDropEvent::Over {
+                position: point(px(5.), px(5.)),
+            },
+        );
+        assert!(result.drop_accepted);
+        dispatch(
+            cx,
+            ExternalDropEvent::Dropped {
+                position: point(px(5.), px(5.)),
+            },
+        );
+        view.read_with(cx, |view, _| assert_eq!(view.dropped, ["https://zed.dev"]));
+
+        // Data the target can't drop isn't accepted.
+        let html = ClipboardItem {
+            entries: vec![crate::ClipboardEntry::Html("<p></p>".into())],
+        };
+        let result = dispatch(
+            cx,
+            ExternalDropEvent::Entered {
+                position: point(px(5.), px(5.)),
+                item: html,
+            },
+        );
+        assert!(!result.drop_accepted);
+        dispatch(cx, ExternalDropEvent::Exited);
+        cx.update(|_, cx| assert!(!cx.has_active_drag()));
+    }
+
+    struct DragSource {
+        outcomes: Vec<ExternalDragOutcome>,
+    }
+
+    impl Render for DragSource {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            let this = cx.weak_entity();
+            div()
+                .size_full()
+                .child(div().id("source").size(px(10.)).on_drag(
+                    "dragged",
+                    move |text, _, window, cx| {
+                        let outcome =
+                            window.start_external_drag(ExternalDrag::text(*text).allow_move(), cx);
+                        let this = this.clone();
+                        window
+                            .spawn(cx, async move |cx| {
+                                let outcome = outcome.await;
+                                this.update(cx, |this, _| this.outcomes.push(outcome)).ok();
+                            })
+                            .detach();
+                        cx.new(|_| Empty)
+                    },
+                ))
+        }
+    }
+
+    #[gpui::test]
+    fn test_external_drag(cx: &mut TestAppContext) {
+        let (view, cx) = cx.add_window_view(|_, _| DragSource {
+            outcomes: Vec::new(),
+        });
+        let outcomes = [
+            ExternalDragOutcome::Copied,
+            ExternalDragOutcome::Moved,
+            ExternalDragOutcome::Cancelled,
+        ];
+
+        for outcome in outcomes {
+            cx.simulate_mouse_down(point(px(5.), px(5.)), MouseButton::Left, Modifiers::none());
+            cx.simulate_mouse_move(point(px(9.), px(9.)), MouseButton::Left, Modifiers::none());
+            let drag = cx.external_drag().unwrap();
+            assert_eq!(drag.item.text().as_deref(), Some("dragged"));
+            assert!(drag.allow_move);
+            cx.update(|_, cx| assert!(cx.has_active_drag()));
+
+            // The drag within the window ends along with the external one.
+            cx.simulate_external_drag_end(outcome);
+            cx.update(|_, cx| assert!(!cx.has_active_drag()));
+            cx.simulate_mouse_up(point(px(9.), px(9.)), MouseButton::Left, Modifiers::none());
+        }
+
+        view.read_with(cx, |view, _| assert_eq!(view.outcomes, outcomes));
+        assert!(cx.external_drag().is_none());
+    }
 }
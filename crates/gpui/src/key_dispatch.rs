@@ -626,12 +626,12 @@ mod tests {
     };
     use core::panic;
     use smallvec::SmallVec;
-    use std::{cell::RefCell, ops::Range, rc::Rc};
+    use std::{cell::RefCell, ops::Range, rc::Rc, time::Duration};
 
     use crate::{
         Action, ActionRegistry, App, Bounds, Context, DispatchTree, FocusHandle, InputHandler,
-        IntoElement, KeyBinding, KeyContext, Keymap, Pixels, Point, Render, Subscription,
-        TestAppContext, UTF16Selection, Window,
+        InteractiveElement, IntoElement, KeyBinding, KeyContext, Keymap, PendingInputTimeout,
+        Pixels, Point, Render, Subscription, TestAppContext, UTF16Selection, Window, div,
     };
 
     #[derive(PartialEq, Eq)]
@@ -1102,4 +1102,88 @@ mod tests {
         cx.simulate_keystrokes("ctrl-b [");
         test.update(cx, |test, _| assert_eq!(test.text.borrow().as_str(), "["))
     }
+
+    #[crate::test]
+    fn test_pending_input_timeout(cx: &mut TestAppContext) {
+        struct Terminal {
+            focus_handle: FocusHandle,
+            dispatched: usize,
+            pending: Vec<(Vec<String>, usize)>,
+            _subscription: Subscription,
+        }
+
+        impl Render for Terminal {
+            fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+                div()
+                    .key_context("Terminal")
+                    .track_focus(&self.focus_handle)
+                    .on_action(cx.listener(|this, _: &TestAction, _, _| this.dispatched += 1))
+            }
+        }
+
+        cx.update(|cx| {
+            cx.bind_keys([
+                KeyBinding::new("ctrl-b", TestAction, Some("Terminal")),
+                KeyBinding::new("ctrl-b h", TestAction, Some("Terminal")),
+            ]);
+            cx.set_pending_input_timeout(PendingInputTimeout::Never, Some("Terminal"))
+                .unwrap();
+            assert!(
+                cx.set_pending_input_timeout(PendingInputTimeout::Never, Some("Terminal &&"))
+                    .is_err()
+            );
+        });
+        let (terminal, cx) = cx.add_window_view(|window, cx| {
+            let subscription =
+                cx.observe_pending_keystrokes(window, |this: &mut Terminal, pending, _, _| {
+                    this.pending.push((
+                        pending.keystrokes.iter().map(Keystroke::unparse).collect(),
+                        pending.possible_bindings.len(),
+                    ));
+                });
+            Terminal {
+                focus_handle: cx.focus_handle(),
+                dispatched: 0,
+                pending: Vec::new(),
+                _subscription: subscription,
+            }
+        });
+        cx.update(|window, cx| {
+            let focus_handle = terminal.read(cx).focus_handle.clone();
+            window.focus(&focus_handle, cx);
+            window.activate_window();
+        });
+
+        // Terminals wait for the rest of the sequence indefinitely.
+        cx.simulate_keystrokes("ctrl-b");
+        cx.executor().advance_clock(Duration::from_secs(60));
+        cx.run_until_parked();
+        terminal.read_with(cx, |terminal, _| {
+            assert_eq!(terminal.dispatched, 0);
+            assert_eq!(terminal.pending, vec![(vec!["ctrl-b".to_string()], 1)]);
+        });
+        cx.simulate_keystrokes("h");
+        terminal.read_with(cx, |terminal, _| {
+            assert_eq!(terminal.dispatched, 1);
+            assert_eq!(terminal.pending.last(), Some(&(Vec::new(), 0)));
+        });
+
+        cx.update(|_, cx| {
+            cx.set_pending_input_timeout(
+                PendingInputTimeout::After(Duration::from_millis(200)),
+                Some("Terminal"),
+            )
+            .unwrap()
+        });
+        cx.simulate_keystrokes("ctrl-b");
+        cx.executor().advance_clock(Duration::from_millis(100));
+        cx.run_until_parked();
+        terminal.read_with(cx, |terminal, _| assert_eq!(terminal.dispatched, 1));
+        cx.executor().advance_clock(Duration::from_millis(100));
+        cx.run_until_parked();
+        terminal.read_with(cx, |terminal, _| {
+            assert_eq!(terminal.dispatched, 2);
+            assert_eq!(terminal.pending.len(), 4);
+        });
+    }
 }
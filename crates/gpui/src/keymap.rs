@@ -1,13 +1,17 @@
 mod binding;
+mod conflicts;
 mod context;
+mod file;
 
 pub use binding::*;
+pub use conflicts::*;
 pub use context::*;
+pub use file::*;
 
 use crate::{Action, AsKeystroke, Keystroke, is_no_action};
 use collections::{HashMap, HashSet};
 use smallvec::SmallVec;
-use std::any::TypeId;
+use std::{any::TypeId, time::Duration};
 
 /// An opaque identifier of which version of the keymap is currently active.
 /// The keymap's version is changed whenever bindings are added or removed.
@@ -23,6 +27,58 @@ pub struct Keymap {
     version: KeymapVersion,
 }
 
+/// How long a window waits for the rest of a multi-stroke binding before dispatching the
+/// keystrokes typed so far on their own. See [`crate::App::set_pending_input_timeout`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PendingInputTimeout {
+    /// Stop waiting after the given duration.
+    After(Duration),
+    /// Wait until another key is pressed or focus moves.
+    Never,
+}
+
+impl Default for PendingInputTimeout {
+    fn default() -> Self {
+        Self::After(Duration::from_secs(1))
+    }
+}
+
+/// The pending input timeouts configured for an app, by context.
+#[derive(Default)]
+pub(crate) struct PendingInputTimeouts {
+    default: PendingInputTimeout,
+    contextual: Vec<(KeyBindingContextPredicate, PendingInputTimeout)>,
+}
+
+impl PendingInputTimeouts {
+    pub(crate) fn set(
+        &mut self,
+        timeout: PendingInputTimeout,
+        predicate: Option<KeyBindingContextPredicate>,
+    ) {
+        let Some(predicate) = predicate else {
+            self.default = timeout;
+            return;
+        };
+        self.contextual
+            .retain(|(existing, _)| *existing != predicate);
+        self.contextual.push((predicate, timeout));
+    }
+
+    /// The timeout for input pending in the given context stack. Like bindings, the timeout for
+    /// the deepest matching context wins, and later ones win ties.
+    pub(crate) fn timeout_for(&self, context_stack: &[KeyContext]) -> PendingInputTimeout {
+        self.contextual
+            .iter()
+            .enumerate()
+            .filter_map(|(ix, (predicate, timeout))| {
+                Some((predicate.depth_of(context_stack)?, ix, *timeout))
+            })
+            .max_by_key(|(depth, ix, _)| (*depth, *ix))
+            .map_or(self.default, |(_, _, timeout)| timeout)
+    }
+}
+
 /// Index of a binding within a keymap.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub struct BindingIndex(usize);
@@ -747,4 +803,95 @@ mod tests {
         assert!(result[0].action.partial_eq(&ActionBeta {}));
         assert!(result[1].action.partial_eq(&ActionAlpha {}));
     }
+
+    #[test]
+    fn test_conflicts() {
+        let mut keymap = Keymap::default();
+        keymap.add_bindings([
+            // 0: shadowed by the context-free binding of the same keystrokes
+            KeyBinding::new("ctrl-a", ActionAlpha {}, Some("Editor")),
+            // 1: only shadows bindings in editors
+            KeyBinding::new("ctrl-b", ActionAlpha {}, Some("Editor")),
+            KeyBinding::new("ctrl-b", ActionBeta {}, Some("Editor && mode == full")),
+            // 3: a sequence that's cut short by a later prefix
+            KeyBinding::new("ctrl-k ctrl-c", ActionGamma {}, Some("Editor")),
+            KeyBinding::new("ctrl-k", ActionDelta {}, None),
+            // 5: a prefix of a later sequence, but removed by 11
+            KeyBinding::new("ctrl-x", ActionAlpha {}, Some("Editor")),
+            KeyBinding::new(
+                "ctrl-x ctrl-s",
+                ActionBeta {},
+                Some("Editor && mode == full"),
+            ),
+            KeyBinding::new("ctrl-a", ActionBeta {}, None),
+            // 8: repeated twice
+            KeyBinding::new("ctrl-d", ActionDelta {}, Some("Pane")),
+            KeyBinding::new("ctrl-d", ActionDelta {}, Some("Pane")),
+            KeyBinding::new("ctrl-d", ActionDelta {}, Some("Pane")),
+            // 11: removals are deliberate
+            KeyBinding::new("ctrl-x", NoAction {}, Some("Editor")),
+            // 12: a prefix of a later sequence
+            KeyBinding::new("ctrl-g", ActionAlpha {}, Some("Editor")),
+            KeyBinding::new("ctrl-g ctrl-g", ActionBeta {}, Some("Editor")),
+            // 14: removed bindings can't be shadowed
+            KeyBinding::new("ctrl-e", ActionAlpha {}, Some("Editor")),
+            KeyBinding::new("ctrl-e", ActionBeta {}, None),
+            KeyBinding::new("ctrl-e", NoAction {}, Some("Editor")),
+            // 17: enabled at the same depth as the later binding
+            KeyBinding::new("ctrl-f", ActionAlpha {}, Some("Pane > Editor")),
+            KeyBinding::new("ctrl-f", ActionBeta {}, Some("Editor")),
+            // 19: takes precedence over the later prefix in workspaces' editors, where the
+            // sequence stays pending
+            KeyBinding::new("ctrl-j", ActionAlpha {}, Some("Editor")),
+            KeyBinding::new("ctrl-j ctrl-c", ActionGamma {}, Some("Workspace")),
+            KeyBinding::new("ctrl-j", ActionDelta {}, Some("Workspace")),
+        ]);
+
+        assert_eq!(
+            keymap.conflicts(),
+            vec![
+                KeymapConflict::Shadowed {
+                    shadowed: BindingIndex(0),
+                    by: BindingIndex(7),
+                },
+                KeymapConflict::Shadowed {
+                    shadowed: BindingIndex(3),
+                    by: BindingIndex(4),
+                },
+                KeymapConflict::Duplicate {
+                    duplicate: BindingIndex(9),
+                    original: BindingIndex(8),
+                },
+                KeymapConflict::Duplicate {
+                    duplicate: BindingIndex(10),
+                    original: BindingIndex(8),
+                },
+                KeymapConflict::AmbiguousPrefix {
+                    prefix: BindingIndex(12),
+                    sequence: BindingIndex(13),
+                },
+                KeymapConflict::Shadowed {
+                    shadowed: BindingIndex(17),
+                    by: BindingIndex(18),
+                },
+            ]
+        );
+        // Dispatch agrees that the sequence can only be typed in editors.
+        let ctrl_j = [Keystroke::parse("ctrl-j").unwrap()];
+        let stack = [
+            KeyContext::parse("Workspace").unwrap(),
+            KeyContext::parse("Editor").unwrap(),
+        ];
+        let (result, pending) = keymap.bindings_for_input(&ctrl_j, &stack[..1]);
+        assert!(result[0].action.partial_eq(&ActionDelta {}));
+        assert!(!pending);
+        let (result, pending) = keymap.bindings_for_input(&ctrl_j, &stack);
+        assert!(result[0].action.partial_eq(&ActionAlpha {}));
+        assert!(pending);
+
+        assert_eq!(
+            keymap.describe_conflict(&keymap.conflicts()[0]),
+            "`ctrl-a` → test_only::ActionAlpha in `Editor` is shadowed by `ctrl-a` → test_only::ActionBeta"
+        );
+    }
 }
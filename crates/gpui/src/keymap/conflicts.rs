@@ -0,0 +1,291 @@
+use std::fmt;
+
+use collections::HashSet;
+
+use super::BindingIndex;
+use crate::{KeyBinding, KeyBindingContextPredicate, KeyContext, Keymap, is_no_action};
+
+/// A binding that's unlikely to behave as its author intended, found by
+/// [`Keymap::conflicts`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum KeymapConflict {
+    /// Wherever `shadowed` is enabled, `by` is too, so `shadowed` doesn't fire. `by` either has
+    /// the same keystrokes and a higher precedence, so `shadowed` only fires if `by`'s action
+    /// goes unhandled, or is a prefix of `shadowed`'s keystrokes that fires before they can be
+    /// completed.
+    Shadowed {
+        /// The binding that can't be reached.
+        shadowed: BindingIndex,
+        /// The binding that takes precedence over it.
+        by: BindingIndex,
+    },
+    /// `prefix` is bound on its own, and `sequence` starts with it in an overlapping context.
+    /// Typing `prefix` waits for the pending input to time out before it fires, in case
+    /// `sequence` is being typed.
+    AmbiguousPrefix {
+        /// The binding whose keystrokes begin the sequence.
+        prefix: BindingIndex,
+        /// The multi-stroke binding.
+        sequence: BindingIndex,
+    },
+    /// `duplicate` binds the same keystrokes to the same action in the same context as an
+    /// earlier binding.
+    Duplicate {
+        /// The later, redundant binding.
+        duplicate: BindingIndex,
+        /// The binding it repeats.
+        original: BindingIndex,
+    },
+}
+
+impl Keymap {
+    /// Get the binding at the given index.
+    pub fn binding(&self, index: BindingIndex) -> &KeyBinding {
+        &self.bindings[index.0]
+    }
+
+    /// Finds bindings that are shadowed by higher-precedence ones, bound both on their own and
+    /// as the start of a longer sequence, or bound more than once. Conflicts are returned in
+    /// the order their bindings were added.
+    ///
+    /// Context predicates are compared with [`KeyBindingContextPredicate::is_superset`], so only
+    /// conflicts that hold in every context are reported, and precedence is compared with
+    /// [`KeyBindingContextPredicate::depth_of`] and then the order bindings were added, as in
+    /// [`Keymap::bindings_for_input`]. Bindings to [`crate::NoAction`] are deliberate
+    /// removals, so neither they nor the bindings they disable are reported or treated as
+    /// shadowing other bindings.
+    pub fn conflicts(&self) -> Vec<KeymapConflict> {
+        let disabled = self.disabled_bindings();
+        let is_removed = |ix: usize, binding: &KeyBinding| {
+            is_no_action(binding.action()) || disabled.contains(&ix)
+        };
+
+        let mut conflicts = Vec::new();
+        let mut duplicates = HashSet::default();
+        for (ix, binding) in self.bindings.iter().enumerate() {
+            // A duplicate's conflicts were already reported for the binding it repeats.
+            if is_removed(ix, binding) || duplicates.contains(&ix) {
+                continue;
+            }
+            let mut shadowed = false;
+            for (later_ix, later) in self.bindings.iter().enumerate().skip(ix + 1).rev() {
+                if is_removed(later_ix, later) {
+                    continue;
+                }
+
+                if later.keystrokes == binding.keystrokes {
+                    if later.context_predicate == binding.context_predicate
+                        && later.action().partial_eq(binding.action())
+                    {
+                        duplicates.insert(later_ix);
+                        conflicts.push(KeymapConflict::Duplicate {
+                            duplicate: BindingIndex(later_ix),
+                            original: BindingIndex(ix),
+                        });
+                    } else if !shadowed && self.outranks(later_ix, ix) {
+                        shadowed = true;
+                        conflicts.push(KeymapConflict::Shadowed {
+                            shadowed: BindingIndex(ix),
+                            by: BindingIndex(later_ix),
+                        });
+                    }
+                } else if is_prefix(binding, later) && overlaps(binding, later) {
+                    conflicts.push(KeymapConflict::AmbiguousPrefix {
+                        prefix: BindingIndex(ix),
+                        sequence: BindingIndex(later_ix),
+                    });
+                } else if !shadowed
+                    && is_prefix(later, binding)
+                    && self.discards_pending(later_ix, ix)
+                {
+                    shadowed = true;
+                    conflicts.push(KeymapConflict::Shadowed {
+                        shadowed: BindingIndex(ix),
+                        by: BindingIndex(later_ix),
+                    });
+                }
+            }
+        }
+        conflicts.sort_by_key(|conflict| match *conflict {
+            KeymapConflict::Shadowed { shadowed, by } => (shadowed, by),
+            KeymapConflict::AmbiguousPrefix { prefix, sequence } => (prefix, sequence),
+            KeymapConflict::Duplicate {
+                duplicate,
+                original,
+            } => (original, duplicate),
+        });
+        conflicts
+    }
+
+    /// The bindings a later [`crate::NoAction`] binding of the same keystrokes disables wherever
+    /// they're enabled.
+    fn disabled_bindings(&self) -> HashSet<usize> {
+        let mut disabled = HashSet::default();
+        for &null_ix in &self.no_action_binding_indices {
+            let null_binding = &self.bindings[null_ix];
+            for (ix, binding) in self.bindings[..null_ix].iter().enumerate() {
+                if binding.keystrokes == null_binding.keystrokes && self.outranks(null_ix, ix) {
+                    disabled.insert(ix);
+                }
+            }
+        }
+        disabled
+    }
+
+    /// Whether the binding at `ix` takes precedence over the one at `other_ix` wherever the other
+    /// is enabled: it must be enabled there too, deeper in the context stack, or as deep and
+    /// added later.
+    fn outranks(&self, ix: usize, other_ix: usize) -> bool {
+        let (binding, other) = (&self.bindings[ix], &self.bindings[other_ix]);
+        covers(binding, other)
+            && matching_stacks(other).iter().all(|stack| {
+                (self.binding_enabled(binding, stack), ix)
+                    > (self.binding_enabled(other, stack), other_ix)
+            })
+    }
+
+    /// Whether typing the keystrokes of the binding at `prefix_ix` discards the pending sequence
+    /// at `ix` that starts with them, wherever that sequence is enabled.
+    /// [`Keymap::bindings_for_input`] discards pending sequences added before the completed
+    /// binding that takes precedence, unless it's a [`crate::NoAction`], so no removal or
+    /// binding added before the sequence can take precedence over the prefix.
+    fn discards_pending(&self, prefix_ix: usize, ix: usize) -> bool {
+        let prefix = &self.bindings[prefix_ix];
+        covers(prefix, &self.bindings[ix])
+            && self.bindings.iter().enumerate().all(|(other_ix, other)| {
+                other_ix == prefix_ix
+                    || other.keystrokes != prefix.keystrokes
+                    || (other_ix > ix && !is_no_action(other.action()))
+                    || self.outranks(prefix_ix, other_ix)
+            })
+    }
+
+    /// Describes a conflict found by [`Keymap::conflicts`], for reporting.
+    pub fn describe_conflict(&self, conflict: &KeymapConflict) -> String {
+        let describe = |index: BindingIndex| DescribeBinding(self.binding(index)).to_string();
+        match *conflict {
+            KeymapConflict::Shadowed { shadowed, by } => {
+                format!("{} is shadowed by {}", describe(shadowed), describe(by))
+            }
+            KeymapConflict::AmbiguousPrefix { prefix, sequence } => format!(
+                "{} waits for the pending input timeout, because {} starts with the same keystrokes",
+                describe(prefix),
+                describe(sequence)
+            ),
+            KeymapConflict::Duplicate {
+                duplicate,
+                original,
+            } => format!("{} duplicates {}", describe(duplicate), describe(original)),
+        }
+    }
+}
+
+struct DescribeBinding<'a>(&'a KeyBinding);
+
+impl fmt::Display for DescribeBinding<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let binding = self.0;
+        write!(f, "`")?;
+        for (ix, keystroke) in binding.keystrokes().iter().enumerate() {
+            if ix > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", keystroke.unparse())?;
+        }
+        write!(f, "` → {}", binding.action().name())?;
+        if let Some(predicate) = &binding.context_predicate {
+            write!(f, " in `{predicate}`")?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `shorter`'s keystrokes are a strict prefix of `longer`'s.
+fn is_prefix(shorter: &KeyBinding, longer: &KeyBinding) -> bool {
+    shorter.keystrokes.len() < longer.keystrokes.len()
+        && longer.keystrokes.starts_with(&shorter.keystrokes)
+}
+
+/// Whether `binding` is enabled everywhere `other` is. Bindings without a context are enabled
+/// everywhere.
+fn covers(binding: &KeyBinding, other: &KeyBinding) -> bool {
+    match (&binding.context_predicate, &other.context_predicate) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(predicate), Some(other)) => predicate.is_superset(other),
+    }
+}
+
+/// Whether one of the bindings is enabled everywhere the other is.
+fn overlaps(a: &KeyBinding, b: &KeyBinding) -> bool {
+    covers(a, b) || covers(b, a)
+}
+
+/// Context stacks in which `binding` is enabled, one for each way its predicate can be
+/// satisfied, for comparing the depths bindings are enabled at.
+fn matching_stacks(binding: &KeyBinding) -> Vec<Vec<KeyContext>> {
+    let Some(predicate) = &binding.context_predicate else {
+        return vec![vec![KeyContext::default()]];
+    };
+    predicate_stacks(predicate)
+        .into_iter()
+        .filter(|stack| predicate.depth_of(stack).is_some())
+        .collect()
+}
+
+/// Context stacks built to satisfy the predicate. Negations are satisfied by leaving keys out,
+/// which doesn't work when they're combined with the keys they negate, so the stacks need to be
+/// checked against the predicate.
+fn predicate_stacks(predicate: &KeyBindingContextPredicate) -> Vec<Vec<KeyContext>> {
+    match predicate {
+        KeyBindingContextPredicate::Identifier(name) => {
+            let mut context = KeyContext::default();
+            context.add(name.clone());
+            vec![vec![context]]
+        }
+        KeyBindingContextPredicate::Equal(key, value) => {
+            let mut context = KeyContext::default();
+            context.set(key.clone(), value.clone());
+            vec![vec![context]]
+        }
+        KeyBindingContextPredicate::NotEqual(_, _) | KeyBindingContextPredicate::Not(_) => {
+            vec![vec![KeyContext::default()]]
+        }
+        KeyBindingContextPredicate::Or(a, b) => {
+            let mut stacks = predicate_stacks(a);
+            stacks.extend(predicate_stacks(b));
+            stacks
+        }
+        KeyBindingContextPredicate::Descendant(parent, child) => {
+            combine_stacks(parent, child, |mut parent, child| {
+                parent.extend(child);
+                parent
+            })
+        }
+        // Both sides have to match the innermost context, so they're merged into it.
+        KeyBindingContextPredicate::And(a, b) => combine_stacks(a, b, |mut a, mut b| {
+            let mut innermost = a.pop().unwrap_or_default();
+            innermost.extend(&b.pop().unwrap_or_default());
+            a.extend(b);
+            a.push(innermost);
+            a
+        }),
+    }
+}
+
+/// Joins every stack satisfying `a` with every stack satisfying `b`.
+fn combine_stacks(
+    a: &KeyBindingContextPredicate,
+    b: &KeyBindingContextPredicate,
+    join: fn(Vec<KeyContext>, Vec<KeyContext>) -> Vec<KeyContext>,
+) -> Vec<Vec<KeyContext>> {
+    let b_stacks = predicate_stacks(b);
+    predicate_stacks(a)
+        .into_iter()
+        .flat_map(|a_stack| {
+            b_stacks
+                .iter()
+                .map(move |b_stack| join(a_stack.clone(), b_stack.clone()))
+        })
+        .collect()
+}
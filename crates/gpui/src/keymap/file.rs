@@ -0,0 +1,613 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{Duration, SystemTime},
+};
+
+use serde::{
+    Deserialize, Deserializer,
+    de::{MapAccess, Visitor},
+};
+use serde_json::value::RawValue;
+
+use crate::{App, KeyBinding, KeyBindingContextPredicate, NoAction, SharedString, Task};
+
+/// How often [`watch_keymap_file`] checks the keymap file for changes.
+pub const KEYMAP_FILE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How serious a problem found in a keymap file is.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum KeymapDiagnosticSeverity {
+    /// The entry couldn't be loaded and was skipped.
+    Error,
+    /// The entry was loaded, but should be updated.
+    Warning,
+}
+
+/// A problem found in a keymap file, along with where it was found.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeymapDiagnostic {
+    /// How serious the problem is.
+    pub severity: KeymapDiagnosticSeverity,
+    /// The 1-based line of the offending entry.
+    pub line: usize,
+    /// The 1-based column of the offending entry, in characters.
+    pub column: usize,
+    /// A description of the problem.
+    pub message: String,
+}
+
+impl KeymapDiagnostic {
+    fn at(
+        severity: KeymapDiagnosticSeverity,
+        source: &str,
+        offset: usize,
+        message: String,
+    ) -> Self {
+        let before = &source[..offset.min(source.len())];
+        let line_start = before.rfind('\n').map_or(0, |ix| ix + 1);
+        Self {
+            severity,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for KeymapDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            KeymapDiagnosticSeverity::Error => "error",
+            KeymapDiagnosticSeverity::Warning => "warning",
+        };
+        write!(
+            f,
+            "{}:{}: {severity}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+/// The bindings loaded from a keymap file, and the problems found while loading them.
+#[derive(Debug, Default)]
+pub struct KeymapFileContents {
+    /// The bindings that loaded successfully, in the order they appear in the file.
+    pub bindings: Vec<KeyBinding>,
+    /// Problems with individual entries, in the order they appear in the file.
+    pub diagnostics: Vec<KeymapDiagnostic>,
+}
+
+/// Loads key bindings from the source of a JSON keymap file. The file contains a list of
+/// sections, each binding keystrokes to actions in a context:
+///
+/// ```json
+/// [
+///   {
+///     "context": "Editor && mode == full",
+///     "use_key_equivalents": true,
+///     "bindings": {
+///       "cmd-s": "workspace::Save",
+///       "cmd-k cmd-t": ["theme::Select", { "preview": true }],
+///       "cmd-w": null
+///     }
+///   }
+/// ]
+/// ```
+///
+/// Actions are named the way they're registered, and their arguments are checked against the
+/// action's schema. A `null` action unbinds the keystrokes with [`NoAction`]. Entries that can't
+/// be loaded are skipped and reported with their line and column, as are deprecated action
+/// names, which are still bound. Returns an error if the file isn't a JSON list of sections.
+pub fn parse_keymap_file(
+    source: &str,
+    cx: &App,
+) -> std::result::Result<KeymapFileContents, KeymapDiagnostic> {
+    let sections = serde_json::from_str::<Vec<&RawValue>>(source)
+        .map_err(|error| serde_diagnostic(source, 0, source, &error))?;
+
+    let mut contents = KeymapFileContents::default();
+    for section in sections {
+        load_section(source, section, cx, &mut contents);
+    }
+    Ok(contents)
+}
+
+/// Loads the keymap file at the given path, then checks it for changes every
+/// [`KEYMAP_FILE_POLL_INTERVAL`]. Each time it loads, every binding in the app is replaced with
+/// `base_bindings` followed by the file's bindings in a single update, and `on_reload` is called
+/// with the file's diagnostics. If the file can't be read or parsed, the previous bindings are
+/// kept. Watching stops when the returned task is dropped.
+pub fn watch_keymap_file(
+    path: impl Into<PathBuf>,
+    base_bindings: Vec<KeyBinding>,
+    cx: &mut App,
+    on_reload: impl Fn(&[KeymapDiagnostic], &mut App) + 'static,
+) -> Task<()> {
+    let path: Rc<Path> = path.into().into();
+    cx.spawn(async move |cx| {
+        let mut last_fingerprint = None;
+        loop {
+            let read = cx.background_executor().spawn({
+                let path = path.to_path_buf();
+                async move { read_if_changed(&path, last_fingerprint) }
+            });
+            if let Some((fingerprint, source)) = read.await {
+                last_fingerprint = Some(fingerprint);
+                cx.update(|cx| {
+                    let diagnostics = match source {
+                        Ok(source) => match parse_keymap_file(&source, cx) {
+                            Ok(contents) => {
+                                cx.clear_key_bindings();
+                                cx.bind_keys(
+                                    base_bindings.iter().cloned().chain(contents.bindings),
+                                );
+                                contents.diagnostics
+                            }
+                            Err(diagnostic) => vec![diagnostic],
+                        },
+                        Err(message) => vec![KeymapDiagnostic {
+                            severity: KeymapDiagnosticSeverity::Error,
+                            line: 1,
+                            column: 1,
+                            message,
+                        }],
+                    };
+                    on_reload(&diagnostics, cx);
+                });
+            }
+            cx.background_executor()
+                .timer(KEYMAP_FILE_POLL_INTERVAL)
+                .await;
+        }
+    })
+}
+
+/// Identifies a version of a file without reading it: its modification time and length, or
+/// why its metadata couldn't be read.
+type Fingerprint = std::result::Result<(Option<SystemTime>, u64), std::io::ErrorKind>;
+
+fn read_if_changed(
+    path: &Path,
+    last_fingerprint: Option<Fingerprint>,
+) -> Option<(Fingerprint, std::result::Result<String, String>)> {
+    let fingerprint = std::fs::metadata(path)
+        .map(|metadata| (metadata.modified().ok(), metadata.len()))
+        .map_err(|error| error.kind());
+    if last_fingerprint == Some(fingerprint) {
+        return None;
+    }
+    let source = std::fs::read_to_string(path)
+        .map_err(|error| format!("failed to read {}: {error}", path.display()));
+    Some((fingerprint, source))
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Section<'a> {
+    #[serde(default, borrow)]
+    context: Option<&'a RawValue>,
+    #[serde(default)]
+    use_key_equivalents: bool,
+    #[serde(default, borrow)]
+    bindings: Bindings<'a>,
+}
+
+/// A section's bindings, in the order they appear in the file.
+#[derive(Default)]
+struct Bindings<'a>(Vec<(String, &'a RawValue)>);
+
+impl<'de: 'a, 'a> Deserialize<'de> for Bindings<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct BindingsVisitor;
+
+        impl<'de> Visitor<'de> for BindingsVisitor {
+            type Value = Bindings<'de>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map from keystrokes to actions")
+            }
+
+            fn visit_map<M: MapAccess<'de>>(
+                self,
+                mut map: M,
+            ) -> std::result::Result<Self::Value, M::Error> {
+                let mut bindings = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    bindings.push(entry);
+                }
+                Ok(Bindings(bindings))
+            }
+        }
+
+        deserializer.deserialize_map(BindingsVisitor)
+    }
+}
+
+enum ActionReference<'a> {
+    Name(String),
+    WithArguments(String, &'a RawValue),
+    Unbind,
+}
+
+impl<'a> ActionReference<'a> {
+    fn parse(action: &'a RawValue) -> Option<Self> {
+        let source = action.get();
+        if source.starts_with('"') {
+            serde_json::from_str(source).ok().map(ActionReference::Name)
+        } else if source.starts_with('[') {
+            serde_json::from_str::<(String, &RawValue)>(source)
+                .ok()
+                .map(|(name, arguments)| ActionReference::WithArguments(name, arguments))
+        } else {
+            (source == "null").then_some(ActionReference::Unbind)
+        }
+    }
+}
+
+fn load_section(source: &str, section: &RawValue, cx: &App, contents: &mut KeymapFileContents) {
+    let section_source = section.get();
+    let section_offset = offset_of(source, section_source);
+    let section = match serde_json::from_str::<Section>(section_source) {
+        Ok(section) => section,
+        Err(error) => {
+            contents.diagnostics.push(serde_diagnostic(
+                source,
+                section_offset,
+                section_source,
+                &error,
+            ));
+            return;
+        }
+    };
+
+    let error = |offset: usize, message: String| {
+        KeymapDiagnostic::at(KeymapDiagnosticSeverity::Error, source, offset, message)
+    };
+    let warning = |offset: usize, message: String| {
+        KeymapDiagnostic::at(KeymapDiagnosticSeverity::Warning, source, offset, message)
+    };
+
+    let context_predicate = match section.context {
+        None => None,
+        Some(context) => {
+            let offset = offset_of(source, context.get());
+            match serde_json::from_str::<String>(context.get()) {
+                Ok(context) if context.trim().is_empty() => None,
+                Ok(context) => match KeyBindingContextPredicate::parse(&context) {
+                    Ok(predicate) => Some(Rc::new(predicate)),
+                    Err(err) => {
+                        contents
+                            .diagnostics
+                            .push(error(offset, format!("invalid context `{context}`: {err}")));
+                        return;
+                    }
+                },
+                Err(_) => {
+                    contents
+                        .diagnostics
+                        .push(error(offset, "context must be a string".to_string()));
+                    return;
+                }
+            }
+        }
+    };
+
+    let deprecated_aliases = cx.deprecated_actions_to_preferred_actions();
+    let deprecation_messages = cx.action_deprecation_messages();
+    for (keystrokes, action) in section.bindings.0 {
+        let action_offset = offset_of(source, action.get());
+        let keystrokes_offset = key_offset(source, action_offset);
+
+        let (name, arguments) = match ActionReference::parse(action) {
+            Some(ActionReference::Name(name)) => (name, None),
+            Some(ActionReference::WithArguments(name, arguments)) => (name, Some(arguments)),
+            Some(ActionReference::Unbind) => {
+                match KeyBinding::load(
+                    &keystrokes,
+                    Box::new(NoAction),
+                    context_predicate.clone(),
+                    section.use_key_equivalents,
+                    None,
+                    cx.keyboard_mapper().as_ref(),
+                ) {
+                    Ok(binding) => contents.bindings.push(binding),
+                    Err(err) => contents
+                        .diagnostics
+                        .push(error(keystrokes_offset, err.to_string())),
+                }
+                continue;
+            }
+            None => {
+                contents.diagnostics.push(error(
+                    action_offset,
+                    "expected an action name, an `[action name, arguments]` pair or null"
+                        .to_string(),
+                ));
+                continue;
+            }
+        };
+
+        let Some(schema) =
+            cx.action_schema_by_name(&name, &mut schemars::SchemaGenerator::default())
+        else {
+            contents
+                .diagnostics
+                .push(error(action_offset, format!("unknown action `{name}`")));
+            continue;
+        };
+        if schema.is_none()
+            && let Some(arguments) = arguments
+        {
+            contents.diagnostics.push(warning(
+                offset_of(source, arguments.get()),
+                format!("`{name}` doesn't take arguments, so these are ignored"),
+            ));
+        }
+
+        let arguments_value = match arguments.map(|arguments| serde_json::from_str(arguments.get()))
+        {
+            Some(Ok(value)) => Some(value),
+            Some(Err(err)) => {
+                contents
+                    .diagnostics
+                    .push(error(action_offset, err.to_string()));
+                continue;
+            }
+            None => None,
+        };
+        let built = match cx.build_action(&name, arguments_value) {
+            Ok(action) => action,
+            Err(err) => {
+                let offset = arguments.map_or(action_offset, |arguments| {
+                    offset_of(source, arguments.get())
+                });
+                contents.diagnostics.push(error(offset, err.to_string()));
+                continue;
+            }
+        };
+
+        if let Some(preferred) = deprecated_aliases.get(name.as_str()) {
+            contents.diagnostics.push(warning(
+                action_offset,
+                format!("`{name}` is deprecated, use `{preferred}` instead"),
+            ));
+        } else if let Some(message) = deprecation_messages.get(name.as_str()) {
+            contents.diagnostics.push(warning(
+                action_offset,
+                format!("`{name}` is deprecated: {message}"),
+            ));
+        }
+
+        match KeyBinding::load(
+            &keystrokes,
+            built,
+            context_predicate.clone(),
+            section.use_key_equivalents,
+            arguments.map(|arguments| SharedString::from(arguments.get().to_string())),
+            cx.keyboard_mapper().as_ref(),
+        ) {
+            Ok(binding) => contents.bindings.push(binding),
+            Err(err) => contents
+                .diagnostics
+                .push(error(keystrokes_offset, err.to_string())),
+        }
+    }
+}
+
+/// The byte offset of `slice` within `source`, which it must borrow from.
+fn offset_of(source: &str, slice: &str) -> usize {
+    slice.as_ptr() as usize - source.as_ptr() as usize
+}
+
+/// Finds the start of the quoted key that precedes the value at `value_offset` in an object.
+fn key_offset(source: &str, value_offset: usize) -> usize {
+    let before = source[..value_offset].trim_end();
+    let Some(before) = before.strip_suffix(':') else {
+        return value_offset;
+    };
+    let before = before.trim_end();
+    let Some(key) = before.strip_suffix('"') else {
+        return value_offset;
+    };
+    key.match_indices('"')
+        .rev()
+        .map(|(ix, _)| ix)
+        .find(|&ix| key[..ix].chars().rev().take_while(|ch| *ch == '\\').count() % 2 == 0)
+        .unwrap_or(value_offset)
+}
+
+/// Converts a serde error, positioned relative to `slice`, into a diagnostic in `source`.
+fn serde_diagnostic(
+    source: &str,
+    slice_offset: usize,
+    slice: &str,
+    error: &serde_json::Error,
+) -> KeymapDiagnostic {
+    let line_offset = slice
+        .split_inclusive('\n')
+        .take(error.line().saturating_sub(1))
+        .map(str::len)
+        .sum::<usize>();
+    let mut offset = (line_offset + error.column().saturating_sub(1)).min(slice.len());
+    while !slice.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let message = error.to_string();
+    let message = match message.rfind(" at line ") {
+        Some(ix) => message[..ix].to_string(),
+        None => message,
+    };
+    KeymapDiagnostic::at(
+        KeymapDiagnosticSeverity::Error,
+        source,
+        slice_offset + offset,
+        message,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{self as gpui, Action, Keystroke, TestAppContext};
+    use schemars::JsonSchema;
+
+    #[derive(Clone, Debug, Default, PartialEq, Action)]
+    #[action(namespace = keymap_file_test)]
+    struct Save;
+
+    #[derive(Clone, Debug, Default, PartialEq, Action)]
+    #[action(namespace = keymap_file_test, deprecated_aliases = ["keymap_file_test::Quit"])]
+    struct Close;
+
+    #[derive(Clone, Debug, PartialEq, Deserialize, JsonSchema, Action)]
+    #[action(namespace = keymap_file_test)]
+    #[serde(deny_unknown_fields)]
+    struct Zoom {
+        level: u32,
+    }
+
+    fn messages(diagnostics: &[KeymapDiagnostic]) -> Vec<String> {
+        diagnostics.iter().map(ToString::to_string).collect()
+    }
+
+    #[gpui::test]
+    fn test_parse_keymap_file(cx: &mut TestAppContext) {
+        let source = r#"[
+  {
+    "bindings": {
+      "ctrl-s": "keymap_file_test::Save",
+      "ctrl-w": "keymap_file_test::Quit",
+      "ctrl-=": ["keymap_file_test::Zoom", { "level": 2 }],
+      "ctrl--": ["keymap_file_test::Zoom", { "level": "small" }],
+      "ctrl-o": "keymap_file_test::Open",
+      "ctrl-q": 42,
+      "ctrl-nope-x": "keymap_file_test::Save"
+    }
+  },
+  {
+    "context": "Editor && (",
+    "bindings": { "ctrl-s": "keymap_file_test::Save" }
+  },
+  {
+    "context": "Editor",
+    "bindings": { "ctrl-s": null }
+  },
+  { "bindngs": {} }
+]"#;
+        cx.update(|cx| {
+            let contents = parse_keymap_file(source, cx).unwrap();
+            assert_eq!(
+                contents
+                    .bindings
+                    .iter()
+                    .map(|binding| (
+                        binding.keystrokes()[0].unparse(),
+                        binding.action().name(),
+                        binding.predicate().map(|predicate| predicate.to_string())
+                    ))
+                    .collect::<Vec<_>>(),
+                vec![
+                    ("ctrl-s".to_string(), "keymap_file_test::Save", None),
+                    ("ctrl-w".to_string(), "keymap_file_test::Close", None),
+                    ("ctrl-=".to_string(), "keymap_file_test::Zoom", None),
+                    ("ctrl-s".to_string(), "zed::NoAction", Some("Editor".to_string())),
+                ]
+            );
+            assert!(contents.bindings[2].action().partial_eq(&Zoom { level: 2 }));
+            assert_eq!(
+                contents.bindings[2].action_input(),
+                Some(r#"{ "level": 2 }"#.into())
+            );
+
+            let diagnostics = messages(&contents.diagnostics);
+            assert_eq!(diagnostics.len(), 7, "{diagnostics:#?}");
+            assert_eq!(
+                diagnostics[0],
+                "5:17: warning: `keymap_file_test::Quit` is deprecated, use `keymap_file_test::Close` instead"
+            );
+            assert!(diagnostics[1].starts_with("7:44: error: "), "{}", diagnostics[1]);
+            assert_eq!(
+                diagnostics[2],
+                "8:17: error: unknown action `keymap_file_test::Open`"
+            );
+            assert!(diagnostics[3].starts_with("9:17: error: expected an action name"));
+            assert!(diagnostics[4].starts_with("10:7: error: "), "{}", diagnostics[4]);
+            assert!(diagnostics[5].starts_with("14:16: error: invalid context `Editor && (`"));
+            assert!(diagnostics[6].starts_with("21:13: error: unknown field `bindngs`"));
+        });
+    }
+
+    #[gpui::test]
+    fn test_parse_keymap_file_syntax_error(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let error =
+                parse_keymap_file("[\n  { \"bindings\": { \"ctrl-s\" } }\n]", cx).unwrap_err();
+            assert_eq!(error.severity, KeymapDiagnosticSeverity::Error);
+            assert_eq!((error.line, error.column), (2, 28));
+        });
+    }
+
+    #[gpui::test]
+    fn test_watch_keymap_file(cx: &mut TestAppContext) {
+        let path = std::env::temp_dir().join(format!("gpui-keymap-{}.json", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"[{ "bindings": { "ctrl-s": "keymap_file_test::Save" } }]"#,
+        )
+        .unwrap();
+
+        let reloads = Rc::new(std::cell::RefCell::new(Vec::new()));
+        let _watch = cx.update(|cx| {
+            let reloads = reloads.clone();
+            watch_keymap_file(
+                &path,
+                vec![KeyBinding::new("ctrl-w", Close, None)],
+                cx,
+                move |diagnostics, _| reloads.borrow_mut().push(messages(diagnostics)),
+            )
+        });
+        cx.run_until_parked();
+
+        let bound = |cx: &mut TestAppContext, keystroke: &str| {
+            cx.update(|cx| {
+                cx.all_bindings_for_input(&[Keystroke::parse(keystroke).unwrap()])
+                    .iter()
+                    .map(|binding| binding.action().name())
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(reloads.borrow().len(), 1);
+        assert_eq!(bound(cx, "ctrl-s"), vec!["keymap_file_test::Save"]);
+        assert_eq!(bound(cx, "ctrl-w"), vec!["keymap_file_test::Close"]);
+
+        // Broken files are reported without disturbing the current bindings.
+        std::fs::write(&path, "[{").unwrap();
+        cx.executor().advance_clock(KEYMAP_FILE_POLL_INTERVAL);
+        cx.run_until_parked();
+        assert_eq!(reloads.borrow().len(), 2);
+        assert_eq!(reloads.borrow()[1].len(), 1);
+        assert_eq!(bound(cx, "ctrl-s"), vec!["keymap_file_test::Save"]);
+
+        std::fs::write(
+            &path,
+            r#"[{ "bindings": { "ctrl-z": ["keymap_file_test::Zoom", { "level": 3 }] } }]"#,
+        )
+        .unwrap();
+        cx.executor().advance_clock(KEYMAP_FILE_POLL_INTERVAL);
+        cx.run_until_parked();
+        assert_eq!(reloads.borrow().len(), 3);
+        assert!(reloads.borrow()[2].is_empty());
+        assert!(bound(cx, "ctrl-s").is_empty());
+        assert_eq!(bound(cx, "ctrl-w"), vec!["keymap_file_test::Close"]);
+        assert_eq!(bound(cx, "ctrl-z"), vec!["keymap_file_test::Zoom"]);
+
+        // Unchanged files aren't reloaded.
+        cx.executor().advance_clock(KEYMAP_FILE_POLL_INTERVAL);
+        cx.run_until_parked();
+        assert_eq!(reloads.borrow().len(), 3);
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -3,6 +3,8 @@ mod keyboard;
 mod keystroke;
 #[allow(missing_docs)]
 pub mod native_controls;
+mod notification;
+mod status_item;
 
 #[cfg(all(target_os = "linux", feature = "wayland"))]
 #[expect(missing_docs)]
@@ -32,12 +34,13 @@ pub(crate) type PlatformScreenCaptureFrame = ();
 pub(crate) type PlatformScreenCaptureFrame = core_video::image_buffer::CVImageBuffer;
 
 use crate::{
-    Action, AnyWindowHandle, App, AsyncWindowContext, BackgroundExecutor, Bounds,
-    DEFAULT_WINDOW_SIZE, DevicePixels, DispatchEventResult, Edges, Font, FontId, FontMetrics, FontRun,
-    ForegroundExecutor, GlyphId, GpuSpecs, ImageSource, Keymap, LineLayout, Pixels, PlatformInput,
-    Point, Priority, RenderGlyphParams, RenderImage, RenderImageParams, RenderSvgParams, Scene,
-    ShapedGlyph, ShapedRun, SharedString, Size, SvgRenderer, SystemWindowTab, Task,
-    ThreadTaskTimings, Window, WindowControlArea, hash, point, px, size,
+    AccessibilityActionRequest, AccessibilityTree, Action, AnyWindowHandle, App,
+    AsyncWindowContext, BackgroundExecutor, Bounds, DEFAULT_WINDOW_SIZE, DevicePixels,
+    DispatchEventResult, Edges, ExternalDrag, ExternalDragOutcome, Font, FontId, FontMetrics,
+    FontRun, ForegroundExecutor, GlyphId, GpuSpecs, ImageSource, Keymap, LineLayout, Pixels,
+    PlatformInput, Point, Priority, RenderGlyphParams, RenderImage, RenderImageParams,
+    RenderSvgParams, Scene, ShapedGlyph, ShapedRun, SharedString, Size, SvgRenderer,
+    SystemWindowTab, Task, ThreadTaskTimings, Window, WindowControlArea, hash, point, px, size,
 };
 use anyhow::Result;
 use async_task::Runnable;
@@ -70,12 +73,18 @@ use uuid::Uuid;
 pub use app_menu::*;
 pub use keyboard::*;
 pub use keystroke::*;
+pub use notification::*;
+pub use status_item::*;
 
 #[cfg(any(test, feature = "test-support"))]
 pub(crate) use test::*;
 
 #[cfg(any(test, feature = "test-support"))]
-pub use test::{TestDispatcher, TestScreenCaptureSource, TestScreenCaptureStream};
+pub use test::{
+    TestDispatcher, TestNativeAlert, TestNativeControl, TestNativeControlEvent, TestNativeControlId,
+    TestNativeControlKind, TestNativeControlValue, TestNativePresentation, TestScreenCaptureSource,
+    TestScreenCaptureStream,
+};
 
 #[cfg(all(target_os = "macos", any(test, feature = "test-support")))]
 pub use visual_test::VisualTestPlatform;
@@ -183,8 +192,14 @@ pub trait Platform: 'static {
     }
 
     fn set_dock_menu(&self, menu: Vec<MenuItem>, keymap: &Keymap);
+    fn set_status_item(&self, _item: Option<StatusItem>, _keymap: &Keymap) {}
+    fn on_status_item_click(&self, _callback: Box<dyn FnMut(StatusItemClick)>) {}
     fn perform_dock_menu_action(&self, _action: usize) {}
     fn add_recent_document(&self, _path: &Path) {}
+    fn recent_documents(&self) -> Task<Result<Vec<PathBuf>>> {
+        Task::ready(Ok(Vec::new()))
+    }
+    fn clear_recent_documents(&self) {}
     fn update_jump_list(
         &self,
         _menus: Vec<MenuItem>,
@@ -196,6 +211,14 @@ pub trait Platform: 'static {
     fn on_will_open_app_menu(&self, callback: Box<dyn FnMut()>);
     fn on_validate_app_menu_command(&self, callback: Box<dyn FnMut(&dyn Action) -> bool>);
 
+    fn show_notification(&self, _id: NotificationId, _notification: Notification) {}
+    fn close_notification(&self, _id: NotificationId) {}
+    fn on_notification_response(
+        &self,
+        _callback: Box<dyn FnMut(NotificationId, NotificationResponse)>,
+    ) {
+    }
+
     fn thermal_state(&self) -> ThermalState;
     fn on_thermal_state_change(&self, callback: Box<dyn FnMut()>);
 
@@ -210,6 +233,11 @@ pub trait Platform: 'static {
 
     fn read_from_clipboard(&self) -> Option<ClipboardItem>;
     fn write_to_clipboard(&self, item: ClipboardItem);
+    /// Reads the clipboard's contents in the given MIME type. Platforms that don't read custom
+    /// types with the rest of the clipboard override this to read just the one asked for.
+    fn read_custom_from_clipboard(&self, mime: &str) -> Option<Vec<u8>> {
+        Some(self.read_from_clipboard()?.custom(mime)?.to_vec())
+    }
 
     #[cfg(any(target_os = "linux", target_os = "freebsd"))]
     fn read_from_primary(&self) -> Option<ClipboardItem>;
@@ -261,8 +289,8 @@ pub trait PlatformDisplay: Debug {
     }
 }
 
-/// Thermal state of the system
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Thermal state of the system, ordered from the least to the most constrained
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ThermalState {
     /// System has no thermal constraints
     Nominal,
@@ -967,6 +995,12 @@ pub trait PlatformWindow: HasWindowHandle + HasDisplayHandle {
     fn show_window_menu(&self, _position: Point<Pixels>) {}
     fn start_window_move(&self) {}
     fn start_window_resize(&self, _edge: ResizeEdge) {}
+    fn start_external_drag(
+        &self,
+        _drag: ExternalDrag,
+    ) -> Option<oneshot::Receiver<ExternalDragOutcome>> {
+        None
+    }
     fn window_decorations(&self) -> Decorations {
         Decorations::Server
     }
@@ -982,6 +1016,13 @@ pub trait PlatformWindow: HasWindowHandle + HasDisplayHandle {
 
     fn update_ime_position(&self, _bounds: Bounds<Pixels>);
 
+    // Accessibility
+    fn is_accessibility_active(&self) -> bool {
+        false
+    }
+    fn update_accessibility_tree(&self, _tree: &AccessibilityTree) {}
+    fn on_accessibility_action(&self, _callback: Box<dyn FnMut(AccessibilityActionRequest)>) {}
+
     #[cfg(any(test, feature = "test-support"))]
     fn as_test(&mut self) -> Option<&mut TestWindow> {
         None
@@ -1492,6 +1533,9 @@ impl PlatformInputHandler {
     pub fn replace_text_in_range(&mut self, replacement_range: Option<Range<usize>>, text: &str) {
         self.cx
             .update(|window, cx| {
+                if replacement_range.is_none() {
+                    record_macro_text(text, window, cx);
+                }
                 self.handler
                     .replace_text_in_range(replacement_range, text, window, cx);
             })
@@ -1536,6 +1580,7 @@ impl PlatformInputHandler {
     }
 
     pub fn dispatch_input(&mut self, input: &str, window: &mut Window, cx: &mut App) {
+        record_macro_text(input, window, cx);
         self.handler.replace_text_in_range(None, input, window, cx);
     }
 
@@ -1572,6 +1617,12 @@ impl PlatformInputHandler {
     }
 }
 
+fn record_macro_text(text: &str, window: &Window, cx: &mut App) {
+    if cx.macro_recorder.is_recording() {
+        cx.macro_recorder.record_text(text, window.focus, &window.context_stack());
+    }
+}
+
 /// A struct representing a selection in a text buffer, in UTF16 characters.
 /// This is different from a range because the head may be before the tail.
 #[derive(Debug)]
@@ -1785,7 +1836,8 @@ pub struct WindowParams {
 }
 
 /// Represents the status of how a window should be opened.
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum WindowBounds {
     /// Indicates that the window should open in a windowed state with the given bounds.
     Windowed(Bounds<Pixels>),
@@ -2122,7 +2174,8 @@ pub struct ClipboardItem {
     pub entries: Vec<ClipboardEntry>,
 }
 
-/// Either a ClipboardString or a ClipboardImage
+/// One representation of a clipboard item's contents. An item can hold several, such as a table
+/// as both HTML and plain text, and every one is offered to the app that pastes it.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ClipboardEntry {
     /// A string entry
@@ -2131,6 +2184,18 @@ pub enum ClipboardEntry {
     Image(Image),
     /// A file entry
     ExternalPaths(crate::ExternalPaths),
+    /// An HTML fragment, offered as `text/html`
+    Html(String),
+    /// A Rich Text Format document, offered as `text/rtf`
+    Rtf(String),
+    /// Data in any other format. Some platforms only read these when asked, see
+    /// [`crate::App::read_custom_from_clipboard`].
+    Custom {
+        /// The MIME type of the data, e.g. `application/vnd.oasis.opendocument.spreadsheet`
+        mime: String,
+        /// The data
+        bytes: Vec<u8>,
+    },
 }
 
 impl ClipboardItem {
@@ -2167,6 +2232,50 @@ impl ClipboardItem {
         }
     }
 
+    /// Create a new ClipboardItem with the given HTML, and the given text for apps that can't
+    /// paste HTML
+    pub fn new_html(html: String, text: String) -> Self {
+        Self {
+            entries: vec![
+                ClipboardEntry::Html(html),
+                ClipboardEntry::String(ClipboardString::new(text)),
+            ],
+        }
+    }
+
+    /// Add another representation of the item's contents
+    pub fn with_entry(mut self, entry: impl Into<ClipboardEntry>) -> Self {
+        self.entries.push(entry.into());
+        self
+    }
+
+    /// Returns the first ClipboardEntry::Html in the item
+    pub fn html(&self) -> Option<&str> {
+        self.entries.iter().find_map(|entry| match entry {
+            ClipboardEntry::Html(html) => Some(html.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Returns the first ClipboardEntry::Rtf in the item
+    pub fn rtf(&self) -> Option<&str> {
+        self.entries.iter().find_map(|entry| match entry {
+            ClipboardEntry::Rtf(rtf) => Some(rtf.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Returns the data of the first ClipboardEntry::Custom in the item with the given MIME type
+    pub fn custom(&self, mime: &str) -> Option<&[u8]> {
+        self.entries.iter().find_map(|entry| match entry {
+            ClipboardEntry::Custom {
+                mime: entry_mime,
+                bytes,
+            } if entry_mime == mime => Some(bytes.as_slice()),
+            _ => None,
+        })
+    }
+
     /// Concatenates together all the ClipboardString entries in the item.
     /// Returns None if there were no ClipboardString entries.
     pub fn text(&self) -> Option<String> {
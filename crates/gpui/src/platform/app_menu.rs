@@ -72,6 +72,10 @@ pub enum MenuItem {
 
         /// Whether this action is checked
         checked: bool,
+
+        /// Whether this action can be checked, which [`MenuItem::checked`] sets. Some platforms
+        /// show a checkbox for checkable actions even while they're unchecked.
+        checkable: bool,
     },
 }
 
@@ -101,6 +105,7 @@ impl MenuItem {
             action: Box::new(action),
             os_action: None,
             checked: false,
+            checkable: false,
         }
     }
 
@@ -115,6 +120,7 @@ impl MenuItem {
             action: Box::new(action),
             os_action: Some(os_action),
             checked: false,
+            checkable: false,
         }
     }
 
@@ -128,17 +134,19 @@ impl MenuItem {
                 action,
                 os_action,
                 checked,
+                checkable,
             } => OwnedMenuItem::Action {
                 name: name.into(),
                 action,
                 os_action,
                 checked,
+                checkable,
             },
             MenuItem::SystemMenu(os_menu) => OwnedMenuItem::SystemMenu(os_menu.owned()),
         }
     }
 
-    /// Set whether this menu item is checked
+    /// Set whether this menu item is checked, making it checkable
     ///
     /// Only for [`MenuItem::Action`], otherwise, will be ignored
     pub fn checked(mut self, checked: bool) -> Self {
@@ -153,6 +161,7 @@ impl MenuItem {
                 action,
                 os_action,
                 checked,
+                checkable: true,
             },
             _ => self,
         }
@@ -206,6 +215,10 @@ pub enum OwnedMenuItem {
 
         /// Whether this action is checked
         checked: bool,
+
+        /// Whether this action can be checked, which [`MenuItem::checked`] sets. Some platforms
+        /// show a checkbox for checkable actions even while they're unchecked.
+        checkable: bool,
     },
 }
 
@@ -219,11 +232,13 @@ impl Clone for OwnedMenuItem {
                 action,
                 os_action,
                 checked,
+                checkable,
             } => OwnedMenuItem::Action {
                 name: name.clone(),
                 action: action.boxed_clone(),
                 os_action: *os_action,
                 checked: *checked,
+                checkable: *checkable,
             },
             OwnedMenuItem::SystemMenu(os_menu) => OwnedMenuItem::SystemMenu(os_menu.clone()),
         }
@@ -1,5 +1,9 @@
 use crate::{Bounds, Pixels};
-use std::ffi::c_void;
+use std::{any::Any, cell::RefCell, ffi::c_void, rc::Rc};
+
+mod fallback;
+
+pub(crate) use fallback::FallbackNativeControls;
 
 // =============================================================================
 // NativeControlState — opaque handle stored by elements, cleaned up on Drop
@@ -62,6 +66,37 @@ impl NativeControlState {
     pub fn target(&self) -> *mut c_void {
         self.target
     }
+
+    /// Creates a state holding a control implemented in Rust rather than as a platform view,
+    /// which [`Self::rust_control`] hands back.
+    pub(crate) fn for_rust_control<T: 'static>(control: Rc<RefCell<T>>) -> Self {
+        let handle: Box<Rc<dyn Any>> = Box::new(control);
+        Self::new(
+            Box::into_raw(handle) as *mut c_void,
+            std::ptr::null_mut(),
+            release_rust_control,
+        )
+    }
+
+    /// Returns the control stored by [`Self::for_rust_control`], or `None` if the state is
+    /// empty or holds a different kind of control.
+    ///
+    /// Must only be called by implementations that create all of their states with
+    /// [`Self::for_rust_control`].
+    pub(crate) fn rust_control<T: 'static>(&self) -> Option<Rc<RefCell<T>>> {
+        if !self.is_initialized() {
+            return None;
+        }
+        // SAFETY: per the above, the view pointer is a boxed `Rc<dyn Any>`.
+        let handle = unsafe { &*(self.view as *const Rc<dyn Any>) };
+        handle.clone().downcast::<RefCell<T>>().ok()
+    }
+}
+
+unsafe fn release_rust_control(view: *mut c_void, _target: *mut c_void) {
+    if !view.is_null() {
+        drop(unsafe { Box::from_raw(view as *mut Rc<dyn Any>) });
+    }
 }
 
 // =============================================================================
@@ -399,6 +434,12 @@ pub struct PopoverConfig {
 /// platform implementation handles creation on first call and updates on
 /// subsequent calls by checking `state.is_initialized()`.
 pub trait PlatformNativeControls {
+    /// Whether controls are added to the window's native view, so they can't be shown while
+    /// there is none. Implementations that don't create platform views return `false`.
+    fn requires_native_view(&self) -> bool {
+        true
+    }
+
     // ── Lifecycle helpers ────────────────────────────────────────────────
 
     /// Attach a control's view to a parent and position it.
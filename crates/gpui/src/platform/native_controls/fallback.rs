@@ -0,0 +1,2219 @@
+//! GPUI-drawn native controls, used on platforms that don't provide their own
+//! [`PlatformNativeControls`] (Linux, Windows, the web and tests).
+//!
+//! Each control's state lives behind the [`NativeControlState`] its element keeps, so it
+//! survives between frames the same way a native view would. `update_*` calls record the
+//! latest config and queue the control, and the window paints it right after the element
+//! that updated it. Menus, dropdown lists and alert sheets are drawn as an overlay on top
+//! of the rest of the window.
+
+mod list;
+mod overlay;
+mod text_field;
+
+use std::cell::RefCell;
+use std::ffi::c_void;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use collections::FxHasher;
+use gpui_util::ResultExt as _;
+use scheduler::Instant;
+
+use super::*;
+use crate::{
+    AnyElement, App, BorderStyle, Bounds, ContentMask, Corners, DispatchPhase, Hsla, Image,
+    ImageFormat, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, PathBuilder, Pixels,
+    Point, RenderImage, Rgba, ShapedLine, SharedString, TextAlign, TextRun, Window,
+    WindowAppearance, hsla, point, px, quad, size, transparent_black, white,
+};
+
+use overlay::{Alert, Menu, MenuItem, Overlay};
+
+const FONT_SIZE: Pixels = px(13.);
+const CORNER_RADIUS: Pixels = px(5.);
+const AUTOREPEAT_DELAY: Duration = Duration::from_millis(400);
+const AUTOREPEAT_INTERVAL: Duration = Duration::from_millis(80);
+
+type QueuedPaint = Box<dyn FnOnce(&mut Window, &mut App)>;
+
+/// Draws native controls with GPUI primitives instead of platform views.
+pub(crate) struct FallbackNativeControls {
+    queued: RefCell<Vec<QueuedPaint>>,
+    overlay: RefCell<Option<Overlay>>,
+}
+
+impl FallbackNativeControls {
+    pub(crate) fn new() -> Self {
+        Self {
+            queued: RefCell::default(),
+            overlay: RefCell::default(),
+        }
+    }
+
+    /// Takes the paint callbacks of controls updated since the last call.
+    pub(crate) fn take_queued(&self) -> Vec<QueuedPaint> {
+        std::mem::take(&mut *self.queued.borrow_mut())
+    }
+
+    /// Builds the element for the open menu or alert, if any. It's laid out as a root above
+    /// everything else in the window.
+    pub(crate) fn render_overlay(&self, window: &Window) -> Option<AnyElement> {
+        let palette = Palette::new(window.appearance());
+        self.overlay
+            .borrow()
+            .as_ref()
+            .map(|overlay| overlay.render(palette))
+    }
+
+    fn queue(&self, paint: impl FnOnce(&mut Window, &mut App) + 'static) {
+        self.queued.borrow_mut().push(Box::new(paint));
+    }
+
+    fn open_overlay(&self, overlay: Overlay) {
+        // Opening a new menu dismisses the current one, the same way a native menu would.
+        if let Some(previous) = self.overlay.borrow_mut().replace(overlay) {
+            previous.dismiss();
+        }
+    }
+
+    fn take_overlay(&self) -> Option<Overlay> {
+        self.overlay.borrow_mut().take()
+    }
+
+    fn with_overlay<R>(&self, f: impl FnOnce(&mut Overlay) -> R) -> Option<R> {
+        self.overlay.borrow_mut().as_mut().map(f)
+    }
+}
+
+// =============================================================================
+// Control state
+// =============================================================================
+
+/// Returns the control stored in `state`, creating it if the state is empty or holds a
+/// different kind of control.
+fn control<T: Default + 'static>(state: &mut NativeControlState) -> Rc<RefCell<T>> {
+    if let Some(control) = existing_control(state) {
+        return control;
+    }
+    let control = Rc::new(RefCell::new(T::default()));
+    *state = NativeControlState::for_rust_control(control.clone());
+    control
+}
+
+fn existing_control<T: 'static>(state: &NativeControlState) -> Option<Rc<RefCell<T>>> {
+    // States handed to the fallback implementation are only ever initialized by `control`.
+    state.rust_control()
+}
+
+fn callback<T: ?Sized>(callback: Option<Box<T>>) -> Option<Rc<T>> {
+    callback.map(Rc::from)
+}
+
+#[derive(Default)]
+struct ButtonControl {
+    title: String,
+    symbol: Option<String>,
+    style: Option<ButtonStyle>,
+    tint: Option<Hsla>,
+    enabled: bool,
+    pressed: Option<usize>,
+    on_click: Option<Rc<dyn Fn()>>,
+}
+
+#[derive(Default)]
+struct CheckboxControl {
+    title: Option<String>,
+    checked: bool,
+    enabled: bool,
+    pressed: Option<usize>,
+    on_change: Option<Rc<dyn Fn(bool)>>,
+}
+
+#[derive(Default)]
+struct SliderControl {
+    min: f64,
+    max: f64,
+    value: f64,
+    continuous: bool,
+    tick_mark_count: i64,
+    snap_to_ticks: bool,
+    enabled: bool,
+    dragging: bool,
+    on_change: Option<Rc<dyn Fn(f64)>>,
+}
+
+impl SliderControl {
+    fn value_for_position(&self, bounds: Bounds<Pixels>, x: Pixels) -> f64 {
+        let track = slider_track(bounds);
+        let fraction = ((x - track.left()) / track.size.width).clamp(0., 1.) as f64;
+        let value = self.min + fraction * (self.max - self.min);
+        if self.snap_to_ticks && self.tick_mark_count > 1 {
+            let step = (self.max - self.min) / (self.tick_mark_count - 1) as f64;
+            self.min + ((value - self.min) / step).round() * step
+        } else {
+            value
+        }
+    }
+
+    fn fraction(&self) -> f32 {
+        if self.max > self.min {
+            ((self.value - self.min) / (self.max - self.min)).clamp(0., 1.) as f32
+        } else {
+            0.
+        }
+    }
+}
+
+#[derive(Default)]
+struct StepperControl {
+    min: f64,
+    max: f64,
+    value: f64,
+    increment: f64,
+    wraps: bool,
+    autorepeat: bool,
+    enabled: bool,
+    pressed: Option<usize>,
+    on_change: Option<Rc<dyn Fn(f64)>>,
+}
+
+impl StepperControl {
+    const UP: usize = 0;
+    const DOWN: usize = 1;
+
+    fn step(&mut self, part: usize) {
+        let delta = if part == Self::UP {
+            self.increment
+        } else {
+            -self.increment
+        };
+        let value = self.value + delta;
+        self.value = if value > self.max {
+            if self.wraps { self.min } else { self.max }
+        } else if value < self.min {
+            if self.wraps { self.max } else { self.min }
+        } else {
+            value
+        };
+        if let Some(on_change) = self.on_change.clone() {
+            on_change(self.value);
+        }
+    }
+}
+
+#[derive(Default)]
+struct SegmentedControl {
+    labels: Vec<String>,
+    images: Vec<(usize, String)>,
+    selected_index: Option<usize>,
+    capsule: bool,
+    enabled: bool,
+    pressed: Option<usize>,
+    on_select: Option<Rc<dyn Fn(usize)>>,
+}
+
+#[derive(Default)]
+struct PopupButtonControl {
+    items: Vec<String>,
+    selected_index: usize,
+    enabled: bool,
+    on_select: Option<Rc<dyn Fn(usize)>>,
+}
+
+#[derive(Default)]
+struct ProgressControl {
+    spinner: bool,
+    indeterminate: bool,
+    value: f64,
+    min: f64,
+    max: f64,
+    animating: bool,
+    display_when_stopped: bool,
+    animation_start: Option<Instant>,
+}
+
+#[derive(Default)]
+struct ImageViewControl {
+    symbol: Option<String>,
+    data_hash: Option<u64>,
+    image: Option<Arc<RenderImage>>,
+    scaling: i64,
+    tint: Option<Hsla>,
+    enabled: bool,
+}
+
+#[derive(Default)]
+struct TabViewControl {
+    labels: Vec<String>,
+    selected_index: usize,
+    enabled: bool,
+    pressed: Option<usize>,
+    on_select: Option<Rc<dyn Fn(usize)>>,
+}
+
+#[derive(Default)]
+struct MenuButtonControl {
+    title: String,
+    context_menu: bool,
+    items: Vec<MenuItem>,
+    enabled: bool,
+    on_select: Option<Rc<dyn Fn(usize)>>,
+}
+
+#[derive(Default)]
+struct EffectViewControl {
+    background: Hsla,
+    corner_radius: Pixels,
+}
+
+#[derive(Default)]
+struct TrackingViewControl {
+    hovered: bool,
+    on_enter: Option<Rc<dyn Fn()>>,
+    on_exit: Option<Rc<dyn Fn()>>,
+    on_move: Option<Rc<dyn Fn(f64, f64)>>,
+}
+
+#[derive(Default)]
+struct StackViewControl;
+
+#[derive(Default)]
+struct WindowControl {
+    visible: bool,
+    frame: Bounds<Pixels>,
+    on_show: Option<Rc<dyn Fn()>>,
+    on_close: Option<Rc<dyn Fn()>>,
+}
+
+// =============================================================================
+// PlatformNativeControls
+// =============================================================================
+
+impl PlatformNativeControls for FallbackNativeControls {
+    unsafe fn attach_and_position(
+        &self,
+        _state: &NativeControlState,
+        _parent: *mut c_void,
+        _bounds: Bounds<Pixels>,
+        _scale: f32,
+    ) {
+        // Controls are painted wherever their element is, so there's nothing to attach.
+    }
+
+    unsafe fn remove_from_parent(&self, _state: &NativeControlState) {}
+
+    fn update_button(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: ButtonConfig,
+    ) {
+        let control = control::<ButtonControl>(state);
+        {
+            let mut button = control.borrow_mut();
+            button.title = config.title.to_string();
+            button.symbol = config.sf_symbol.map(ToString::to_string);
+            button.style = Some(config.style);
+            button.tint = config.tint.map(color);
+            button.enabled = config.enabled;
+            button.on_click = callback(config.on_click);
+        }
+        self.queue(move |window, cx| paint_button(&control, bounds, window, cx));
+    }
+
+    fn update_checkbox(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: CheckboxConfig,
+    ) {
+        let control = control::<CheckboxControl>(state);
+        {
+            let mut checkbox = control.borrow_mut();
+            checkbox.title = Some(config.title.to_string());
+            checkbox.checked = config.checked;
+            checkbox.enabled = config.enabled;
+            checkbox.on_change = callback(config.on_change);
+        }
+        self.queue(move |window, cx| paint_checkbox(&control, bounds, window, cx));
+    }
+
+    fn update_switch(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: SwitchConfig,
+    ) {
+        let control = control::<CheckboxControl>(state);
+        {
+            let mut switch = control.borrow_mut();
+            switch.title = None;
+            switch.checked = config.checked;
+            switch.enabled = config.enabled;
+            switch.on_change = callback(config.on_change);
+        }
+        self.queue(move |window, cx| paint_switch(&control, bounds, window, cx));
+    }
+
+    fn update_slider(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: SliderConfig,
+    ) {
+        let control = control::<SliderControl>(state);
+        {
+            let mut slider = control.borrow_mut();
+            slider.min = config.min;
+            slider.max = config.max;
+            // Keep the value under the mouse while dragging, even if the element hasn't
+            // caught up with it yet.
+            if !slider.dragging {
+                slider.value = config.value;
+            }
+            slider.continuous = config.continuous;
+            slider.tick_mark_count = config.tick_mark_count;
+            slider.snap_to_ticks = config.snap_to_ticks;
+            slider.enabled = config.enabled;
+            slider.on_change = callback(config.on_change);
+        }
+        self.queue(move |window, cx| paint_slider(&control, bounds, window, cx));
+    }
+
+    fn update_stepper(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: StepperConfig,
+    ) {
+        let control = control::<StepperControl>(state);
+        {
+            let mut stepper = control.borrow_mut();
+            stepper.min = config.min;
+            stepper.max = config.max;
+            stepper.value = config.value;
+            stepper.increment = config.increment;
+            stepper.wraps = config.wraps;
+            stepper.autorepeat = config.autorepeat;
+            stepper.enabled = config.enabled;
+            stepper.on_change = callback(config.on_change);
+        }
+        self.queue(move |window, cx| paint_stepper(&control, bounds, window, cx));
+    }
+
+    fn update_segmented_control(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: SegmentedControlConfig,
+    ) {
+        let control = control::<SegmentedControl>(state);
+        {
+            let mut segmented = control.borrow_mut();
+            segmented.labels = config.labels.iter().map(ToString::to_string).collect();
+            segmented.images = config
+                .images
+                .iter()
+                .map(|(index, symbol)| (*index, symbol.to_string()))
+                .collect();
+            segmented.selected_index = config.selected_index;
+            segmented.capsule = config.border_shape == 1;
+            segmented.enabled = config.enabled;
+            segmented.on_select = callback(config.on_select);
+        }
+        self.queue(move |window, cx| paint_segmented_control(&control, bounds, window, cx));
+    }
+
+    fn update_popup_button(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: PopupButtonConfig,
+    ) {
+        let control = control::<PopupButtonControl>(state);
+        {
+            let mut popup = control.borrow_mut();
+            popup.items = config.items.iter().map(ToString::to_string).collect();
+            popup.selected_index = config.selected_index;
+            popup.enabled = config.enabled;
+            popup.on_select = callback(config.on_select);
+        }
+        self.queue(move |window, cx| paint_popup_button(&control, bounds, window, cx));
+    }
+
+    fn update_progress(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: ProgressConfig,
+    ) {
+        let control = control::<ProgressControl>(state);
+        {
+            let mut progress = control.borrow_mut();
+            progress.spinner = config.style == 1;
+            progress.indeterminate = config.indeterminate;
+            progress.value = config.value;
+            progress.min = config.min;
+            progress.max = config.max;
+            progress.animating = config.animating;
+            progress.display_when_stopped = config.display_when_stopped;
+            if !config.animating {
+                progress.animation_start = None;
+            }
+        }
+        self.queue(move |window, cx| paint_progress(&control, bounds, window, cx));
+    }
+
+    fn update_search_field(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: SearchFieldConfig,
+    ) {
+        let control = control::<text_field::TextFieldControl>(state);
+        control.borrow_mut().update_search_field(config);
+        self.queue(move |window, cx| text_field::paint_text_field(&control, bounds, window, cx));
+    }
+
+    fn update_text_field(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: TextFieldConfig,
+    ) {
+        let control = control::<text_field::TextFieldControl>(state);
+        control.borrow_mut().update_text_field(config);
+        self.queue(move |window, cx| text_field::paint_text_field(&control, bounds, window, cx));
+    }
+
+    fn update_combo_box(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: ComboBoxConfig,
+    ) {
+        let control = control::<text_field::TextFieldControl>(state);
+        control.borrow_mut().update_combo_box(config);
+        self.queue(move |window, cx| text_field::paint_text_field(&control, bounds, window, cx));
+    }
+
+    fn update_image_view(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: ImageViewConfig,
+    ) {
+        let control = control::<ImageViewControl>(state);
+        let data = config.image_data.map(<[u8]>::to_vec);
+        {
+            let mut image_view = control.borrow_mut();
+            image_view.symbol = config
+                .sf_symbol
+                .or(config.sf_symbol_config.map(|(name, _, _)| name))
+                .map(ToString::to_string);
+            image_view.scaling = config.scaling.unwrap_or(1);
+            image_view.tint = config.tint_color.map(color);
+            image_view.enabled = config.enabled;
+        }
+        self.queue(move |window, cx| paint_image_view(&control, data, bounds, window, cx));
+    }
+
+    fn update_tab_view(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: TabViewConfig,
+    ) {
+        let control = control::<TabViewControl>(state);
+        {
+            let mut tab_view = control.borrow_mut();
+            tab_view.labels = config.labels.iter().map(ToString::to_string).collect();
+            tab_view.selected_index = config.selected_index;
+            tab_view.enabled = config.enabled;
+            tab_view.on_select = callback(config.on_select);
+        }
+        self.queue(move |window, cx| paint_tab_view(&control, bounds, window, cx));
+    }
+
+    fn update_table_view(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: TableViewConfig,
+    ) {
+        let control = control::<list::ListControl>(state);
+        control.borrow_mut().update_table(config);
+        self.queue(move |window, cx| list::paint_list(&control, bounds, window, cx));
+    }
+
+    fn update_outline_view(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: OutlineViewConfig,
+    ) {
+        let control = control::<list::ListControl>(state);
+        control.borrow_mut().update_outline(config);
+        self.queue(move |window, cx| list::paint_list(&control, bounds, window, cx));
+    }
+
+    fn update_collection_view(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: CollectionViewConfig,
+    ) {
+        let control = control::<list::ListControl>(state);
+        control.borrow_mut().update_collection(config);
+        self.queue(move |window, cx| list::paint_list(&control, bounds, window, cx));
+    }
+
+    fn update_menu_button(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: MenuButtonConfig,
+    ) {
+        let control = control::<MenuButtonControl>(state);
+        {
+            let mut menu_button = control.borrow_mut();
+            menu_button.title = config.title.to_string();
+            menu_button.context_menu = config.context_menu;
+            menu_button.items = MenuItem::from_native(config.items);
+            menu_button.enabled = config.enabled;
+            menu_button.on_select = callback(config.on_select);
+        }
+        self.queue(move |window, cx| paint_menu_button(&control, bounds, window, cx));
+    }
+
+    fn update_visual_effect_view(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: VisualEffectViewConfig,
+    ) {
+        let control = control::<EffectViewControl>(state);
+        {
+            let mut effect = control.borrow_mut();
+            // There's no backdrop blur to sample from, so materials become a translucent fill.
+            let opacity = if config.emphasized { 0.92 } else { 0.8 };
+            effect.background = transparent_black().opacity(opacity);
+            effect.corner_radius = px(config.corner_radius as f32);
+        }
+        self.queue(move |window, _| paint_effect_view(&control, bounds, window));
+    }
+
+    fn update_glass_effect_view(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: GlassEffectViewConfig,
+    ) {
+        let control = control::<EffectViewControl>(state);
+        {
+            let mut effect = control.borrow_mut();
+            effect.background = config
+                .tint_color
+                .map(color)
+                .unwrap_or(white().opacity(0.35));
+            effect.corner_radius = px(config.corner_radius as f32);
+        }
+        self.queue(move |window, _| paint_effect_view(&control, bounds, window));
+    }
+
+    fn update_tracking_view(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: TrackingViewConfig,
+    ) {
+        let control = control::<TrackingViewControl>(state);
+        {
+            let mut tracking = control.borrow_mut();
+            tracking.on_enter = callback(config.callbacks.on_enter);
+            tracking.on_exit = callback(config.callbacks.on_exit);
+            tracking.on_move = callback(config.callbacks.on_move);
+        }
+        self.queue(move |window, _| paint_tracking_view(&control, bounds, window));
+    }
+
+    fn update_stack_view(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        _bounds: Bounds<Pixels>,
+        _scale: f32,
+        _config: StackViewConfig,
+    ) {
+        // Stack views only arrange native subviews and draw nothing themselves. Fallback
+        // controls are laid out by their own elements.
+        control::<StackViewControl>(state);
+    }
+
+    fn update_sidebar(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: SidebarViewConfig,
+    ) {
+        // The sidebar is a column along the leading edge of the element.
+        let bounds = Bounds::new(
+            bounds.origin,
+            size(
+                px(config.sidebar_width as f32).min(bounds.size.width),
+                bounds.size.height,
+            ),
+        );
+        let control = control::<list::ListControl>(state);
+        control.borrow_mut().update_sidebar(config);
+        self.queue(move |window, cx| list::paint_list(&control, bounds, window, cx));
+    }
+
+    fn is_glass_effect_available(&self) -> bool {
+        false
+    }
+
+    fn get_text_field_value(&self, state: &NativeControlState) -> String {
+        existing_control::<text_field::TextFieldControl>(state)
+            .map(|control| control.borrow().text())
+            .unwrap_or_default()
+    }
+
+    fn get_combo_box_value(&self, state: &NativeControlState) -> String {
+        self.get_text_field_value(state)
+    }
+
+    fn show_context_menu(
+        &self,
+        items: &[NativeMenuItemData],
+        _view: *mut c_void,
+        x: f64,
+        y: f64,
+        on_result: Box<dyn FnOnce(Option<usize>)>,
+    ) {
+        self.open_overlay(Overlay::Menu(Menu::new(
+            point(px(x as f32), px(y as f32)),
+            px(0.),
+            MenuItem::from_native(items),
+            on_result,
+        )));
+    }
+
+    fn show_alert_modal(&self, config: AlertConfig) -> i64 {
+        // Nothing can block while GPUI draws the alert, so answer with the default button.
+        log::warn!(
+            "modal alerts aren't supported by GPUI-drawn controls, answering {:?} with its default button",
+            config.message
+        );
+        ALERT_FIRST_BUTTON_RETURN
+    }
+
+    fn show_alert_sheet(
+        &self,
+        config: AlertConfig,
+        _parent_window: *mut c_void,
+        callback: Option<Box<dyn FnOnce(i64)>>,
+    ) {
+        self.open_overlay(Overlay::Alert(Alert::new(config, callback)));
+    }
+
+    fn create_panel(&self, config: PanelConfig) -> NativeControlState {
+        let mut state = NativeControlState::default();
+        let control = control::<WindowControl>(&mut state);
+        {
+            let mut panel = control.borrow_mut();
+            panel.frame = Bounds::new(
+                Point::default(),
+                size(px(config.width as f32), px(config.height as f32)),
+            );
+            panel.on_close = callback(config.on_close);
+        }
+        state
+    }
+
+    fn get_panel_content_view(&self, _state: &NativeControlState) -> *mut c_void {
+        std::ptr::null_mut()
+    }
+
+    fn show_panel(&self, state: &NativeControlState) {
+        set_window_visible(state, true);
+    }
+
+    fn show_panel_centered(&self, state: &NativeControlState) {
+        set_window_visible(state, true);
+    }
+
+    fn set_panel_origin(&self, state: &NativeControlState, x: f64, y: f64) {
+        if let Some(panel) = existing_control::<WindowControl>(state) {
+            panel.borrow_mut().frame.origin = point(px(x as f32), px(y as f32));
+        }
+    }
+
+    fn set_panel_top_left(&self, state: &NativeControlState, x: f64, y: f64) {
+        self.set_panel_origin(state, x, y);
+    }
+
+    fn set_panel_size(&self, state: &NativeControlState, width: f64, height: f64) {
+        if let Some(panel) = existing_control::<WindowControl>(state) {
+            panel.borrow_mut().frame.size = size(px(width as f32), px(height as f32));
+        }
+    }
+
+    fn set_panel_frame(
+        &self,
+        state: &NativeControlState,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        _animate: bool,
+    ) {
+        self.set_panel_origin(state, x, y);
+        self.set_panel_size(state, width, height);
+    }
+
+    fn close_panel(&self, state: &NativeControlState) {
+        set_window_visible(state, false);
+    }
+
+    fn hide_panel(&self, state: &NativeControlState) {
+        if let Some(panel) = existing_control::<WindowControl>(state) {
+            panel.borrow_mut().visible = false;
+        }
+    }
+
+    fn is_panel_visible(&self, state: &NativeControlState) -> bool {
+        existing_control::<WindowControl>(state).is_some_and(|panel| panel.borrow().visible)
+    }
+
+    fn get_toolbar_item_frame(
+        &self,
+        _window: *mut c_void,
+        _item_id: &str,
+    ) -> Option<Bounds<Pixels>> {
+        None
+    }
+
+    fn create_popover(&self, config: PopoverConfig) -> NativeControlState {
+        let mut state = NativeControlState::default();
+        let control = control::<WindowControl>(&mut state);
+        {
+            let mut popover = control.borrow_mut();
+            popover.frame = Bounds::new(
+                Point::default(),
+                size(px(config.width as f32), px(config.height as f32)),
+            );
+            popover.on_show = callback(config.on_show);
+            popover.on_close = callback(config.on_close);
+        }
+        state
+    }
+
+    fn get_popover_content_view(&self, _state: &NativeControlState) -> *mut c_void {
+        std::ptr::null_mut()
+    }
+
+    fn show_popover_at_toolbar_item(&self, state: &NativeControlState, _toolbar_item: *mut c_void) {
+        set_window_visible(state, true);
+    }
+
+    fn dismiss_popover(&self, state: &NativeControlState) {
+        set_window_visible(state, false);
+    }
+}
+
+/// Shows or closes a panel or popover, running its show and close callbacks on changes.
+fn set_window_visible(state: &NativeControlState, visible: bool) {
+    let Some(control) = existing_control::<WindowControl>(state) else {
+        return;
+    };
+    let callback = {
+        let mut window = control.borrow_mut();
+        if window.visible == visible {
+            return;
+        }
+        window.visible = visible;
+        if visible {
+            window.on_show.clone()
+        } else {
+            window.on_close.clone()
+        }
+    };
+    if let Some(callback) = callback {
+        callback();
+    }
+}
+
+// =============================================================================
+// Painting
+// =============================================================================
+
+/// The colors fallback controls are drawn with, following the window's appearance.
+#[derive(Clone, Copy)]
+struct Palette {
+    text: Hsla,
+    muted_text: Hsla,
+    control: Hsla,
+    control_pressed: Hsla,
+    border: Hsla,
+    field: Hsla,
+    track: Hsla,
+    accent: Hsla,
+    on_accent: Hsla,
+    selection: Hsla,
+    stripe: Hsla,
+    menu: Hsla,
+}
+
+impl Palette {
+    fn new(appearance: WindowAppearance) -> Self {
+        let accent = hsla(211. / 360., 0.9, 0.52, 1.);
+        match appearance {
+            WindowAppearance::Light | WindowAppearance::VibrantLight => Self {
+                text: hsla(0., 0., 0.1, 1.),
+                muted_text: hsla(0., 0., 0.5, 1.),
+                control: hsla(0., 0., 0.98, 1.),
+                control_pressed: hsla(0., 0., 0.86, 1.),
+                border: hsla(0., 0., 0.77, 1.),
+                field: white(),
+                track: hsla(0., 0., 0.86, 1.),
+                accent,
+                on_accent: white(),
+                selection: accent.opacity(0.25),
+                stripe: hsla(0., 0., 0., 0.035),
+                menu: hsla(0., 0., 0.99, 1.),
+            },
+            WindowAppearance::Dark | WindowAppearance::VibrantDark => Self {
+                text: hsla(0., 0., 0.92, 1.),
+                muted_text: hsla(0., 0., 0.58, 1.),
+                control: hsla(0., 0., 0.3, 1.),
+                control_pressed: hsla(0., 0., 0.4, 1.),
+                border: hsla(0., 0., 0.38, 1.),
+                field: hsla(0., 0., 0.16, 1.),
+                track: hsla(0., 0., 0.32, 1.),
+                accent,
+                on_accent: white(),
+                selection: accent.opacity(0.4),
+                stripe: hsla(0., 0., 1., 0.04),
+                menu: hsla(0., 0., 0.2, 1.),
+            },
+        }
+    }
+
+    fn text(&self, enabled: bool) -> Hsla {
+        if enabled { self.text } else { self.muted_text }
+    }
+}
+
+fn palette(window: &Window) -> Palette {
+    Palette::new(window.appearance())
+}
+
+fn color((r, g, b, a): (f64, f64, f64, f64)) -> Hsla {
+    Rgba {
+        r: r as f32,
+        g: g as f32,
+        b: b as f32,
+        a: a as f32,
+    }
+    .into()
+}
+
+fn line_height(font_size: Pixels) -> Pixels {
+    (font_size * 1.3).round()
+}
+
+fn shape_text(text: &str, font_size: Pixels, color: Hsla, window: &Window) -> ShapedLine {
+    let text = SharedString::from(text.replace('\n', " "));
+    let run = TextRun {
+        len: text.len(),
+        font: window.text_style().font(),
+        color,
+        background_color: None,
+        underline: None,
+        strikethrough: None,
+    };
+    window
+        .text_system()
+        .shape_line(text, font_size, &[run], None)
+}
+
+/// Paints a single line of text vertically centered in `bounds`, clipped to them.
+fn paint_text(
+    text: &str,
+    bounds: Bounds<Pixels>,
+    align: TextAlign,
+    color: Hsla,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    if text.is_empty() {
+        return;
+    }
+    let line = shape_text(text, FONT_SIZE, color, window);
+    let line_height = line_height(FONT_SIZE);
+    let origin = point(bounds.left(), bounds.center().y - line_height / 2.);
+    window.with_content_mask(Some(ContentMask { bounds }), |window| {
+        line.paint(
+            origin,
+            line_height,
+            align,
+            Some(bounds.size.width),
+            window,
+            cx,
+        )
+        .log_err();
+    });
+}
+
+fn paint_rect(
+    bounds: Bounds<Pixels>,
+    corner_radius: impl Into<Corners<Pixels>>,
+    background: Hsla,
+    border: Option<Hsla>,
+    window: &mut Window,
+) {
+    window.paint_quad(quad(
+        bounds,
+        corner_radius,
+        background,
+        if border.is_some() { px(1.) } else { px(0.) },
+        border.unwrap_or_default(),
+        BorderStyle::Solid,
+    ));
+}
+
+fn paint_polyline(points: &[Point<Pixels>], width: Pixels, color: Hsla, window: &mut Window) {
+    let mut builder = PathBuilder::stroke(width);
+    builder.add_polygon(points, false);
+    if let Some(path) = builder.build().log_err() {
+        window.paint_path(path, color);
+    }
+}
+
+/// A small downward chevron centered in `bounds`.
+fn paint_chevron(bounds: Bounds<Pixels>, up: bool, color: Hsla, window: &mut Window) {
+    let center = bounds.center();
+    let (dx, dy) = (px(3.5), if up { px(-2.) } else { px(2.) });
+    paint_polyline(
+        &[
+            point(center.x - dx, center.y - dy),
+            point(center.x, center.y + dy),
+            point(center.x + dx, center.y - dy),
+        ],
+        px(1.5),
+        color,
+        window,
+    );
+}
+
+fn paint_checkmark(bounds: Bounds<Pixels>, color: Hsla, window: &mut Window) {
+    let origin = bounds.origin;
+    let (width, height) = (bounds.size.width, bounds.size.height);
+    paint_polyline(
+        &[
+            point(origin.x + width * 0.22, origin.y + height * 0.52),
+            point(origin.x + width * 0.42, origin.y + height * 0.72),
+            point(origin.x + width * 0.78, origin.y + height * 0.3),
+        ],
+        px(1.8),
+        color,
+        window,
+    );
+}
+
+/// Text stand-ins for the SF Symbols commonly used in native controls.
+fn symbol_glyph(symbol: &str) -> &'static str {
+    match symbol.split('.').next().unwrap_or_default() {
+        "plus" => "+",
+        "minus" => "−",
+        "xmark" | "multiply" => "×",
+        "checkmark" => "✓",
+        "chevron" if symbol.contains("left") => "‹",
+        "chevron" if symbol.contains("right") => "›",
+        "chevron" if symbol.contains("up") => "˄",
+        "chevron" => "˅",
+        "arrow" if symbol.contains("left") => "←",
+        "arrow" if symbol.contains("right") => "→",
+        "arrow" if symbol.contains("up") => "↑",
+        "arrow" => "↓",
+        "ellipsis" => "…",
+        "magnifyingglass" => "⌕",
+        "gear" | "gearshape" => "⚙",
+        "star" => "★",
+        "heart" => "♥",
+        "trash" => "⌫",
+        "sidebar" => "☰",
+        "line" => "☰",
+        _ => "•",
+    }
+}
+
+/// The area of `bounds` that responds to the mouse: whatever isn't clipped away by the
+/// current content mask.
+fn hit_region(bounds: Bounds<Pixels>, window: &Window) -> Bounds<Pixels> {
+    bounds.intersect(&window.content_mask().bounds)
+}
+
+fn on_mouse_down(
+    region: Bounds<Pixels>,
+    button: MouseButton,
+    window: &mut Window,
+    listener: impl Fn(&MouseDownEvent, &mut Window, &mut App) + 'static,
+) {
+    window.on_mouse_event(move |event: &MouseDownEvent, phase, window, cx| {
+        if phase == DispatchPhase::Bubble
+            && event.button == button
+            && region.contains(&event.position)
+        {
+            listener(event, window, cx);
+            cx.stop_propagation();
+        }
+    });
+}
+
+/// Tracks a press on one part of a control, running `on_click` if the mouse is released
+/// over the same part.
+fn on_click<T: 'static>(
+    control: &Rc<RefCell<T>>,
+    pressed: fn(&mut T) -> &mut Option<usize>,
+    region: Bounds<Pixels>,
+    part: usize,
+    window: &mut Window,
+    on_click: impl Fn(&mut T, &mut Window, &mut App) + 'static,
+) {
+    let down_control = control.clone();
+    on_mouse_down(region, MouseButton::Left, window, move |_, window, _| {
+        *pressed(&mut down_control.borrow_mut()) = Some(part);
+        window.refresh();
+    });
+
+    let control = control.clone();
+    window.on_mouse_event(move |event: &MouseUpEvent, phase, window, cx| {
+        if phase != DispatchPhase::Bubble || event.button != MouseButton::Left {
+            return;
+        }
+        let mut control = control.borrow_mut();
+        if *pressed(&mut control) != Some(part) {
+            return;
+        }
+        *pressed(&mut control) = None;
+        window.refresh();
+        if region.contains(&event.position) {
+            on_click(&mut control, window, cx);
+        }
+    });
+}
+
+fn paint_button(
+    control: &Rc<RefCell<ButtonControl>>,
+    bounds: Bounds<Pixels>,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let palette = palette(window);
+    let button = control.borrow();
+    let pressed = button.pressed.is_some();
+    let style = button.style.unwrap_or(ButtonStyle::Rounded);
+    let accent = button.tint.unwrap_or(palette.accent);
+
+    let (background, border, text_color) = match style {
+        ButtonStyle::Rounded => (
+            if pressed {
+                palette.control_pressed
+            } else {
+                palette.control
+            },
+            Some(palette.border),
+            button.tint.unwrap_or(palette.text(button.enabled)),
+        ),
+        ButtonStyle::Filled => (
+            if pressed {
+                accent.blend(transparent_black().opacity(0.2))
+            } else {
+                accent
+            },
+            None,
+            palette.on_accent,
+        ),
+        ButtonStyle::Inline => (
+            if pressed {
+                palette.control_pressed
+            } else {
+                palette.track.opacity(0.6)
+            },
+            None,
+            button.tint.unwrap_or(palette.text(button.enabled)),
+        ),
+        ButtonStyle::Borderless => (
+            if pressed {
+                palette.control_pressed
+            } else {
+                transparent_black()
+            },
+            None,
+            button.tint.unwrap_or(palette.text(button.enabled)),
+        ),
+    };
+    let radius = match style {
+        ButtonStyle::Inline => bounds.size.height / 2.,
+        _ => CORNER_RADIUS,
+    };
+    let opacity = if button.enabled { 1. } else { 0.5 };
+    paint_rect(
+        bounds,
+        radius,
+        background.opacity(background.a * opacity),
+        border,
+        window,
+    );
+
+    let label = match (&button.symbol, button.title.is_empty()) {
+        (Some(symbol), true) => symbol_glyph(symbol).to_string(),
+        (Some(symbol), false) => format!("{} {}", symbol_glyph(symbol), button.title),
+        (None, _) => button.title.clone(),
+    };
+    paint_text(
+        &label,
+        bounds,
+        TextAlign::Center,
+        text_color.opacity(text_color.a * opacity),
+        window,
+        cx,
+    );
+
+    if !button.enabled {
+        return;
+    }
+    drop(button);
+    on_click(
+        control,
+        |button| &mut button.pressed,
+        hit_region(bounds, window),
+        0,
+        window,
+        |button, _, _| {
+            if let Some(on_click) = button.on_click.clone() {
+                on_click();
+            }
+        },
+    );
+}
+
+fn toggle(checkbox: &mut CheckboxControl, _: &mut Window, _: &mut App) {
+    checkbox.checked = !checkbox.checked;
+    if let Some(on_change) = checkbox.on_change.clone() {
+        on_change(checkbox.checked);
+    }
+}
+
+fn paint_checkbox(
+    control: &Rc<RefCell<CheckboxControl>>,
+    bounds: Bounds<Pixels>,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let palette = palette(window);
+    let checkbox = control.borrow();
+    let box_size = px(14.);
+    let box_bounds = Bounds::new(
+        point(bounds.left(), bounds.center().y - box_size / 2.),
+        size(box_size, box_size),
+    );
+    let opacity = if checkbox.enabled { 1. } else { 0.5 };
+    if checkbox.checked {
+        paint_rect(
+            box_bounds,
+            px(3.),
+            palette.accent.opacity(opacity),
+            None,
+            window,
+        );
+        paint_checkmark(box_bounds, palette.on_accent, window);
+    } else {
+        let background = if checkbox.pressed.is_some() {
+            palette.control_pressed
+        } else {
+            palette.field
+        };
+        paint_rect(box_bounds, px(3.), background, Some(palette.border), window);
+    }
+
+    if let Some(title) = checkbox.title.as_deref() {
+        let label_bounds = Bounds::from_corners(
+            point(box_bounds.right() + px(6.), bounds.top()),
+            bounds.bottom_right(),
+        );
+        paint_text(
+            title,
+            label_bounds,
+            TextAlign::Left,
+            palette.text(checkbox.enabled),
+            window,
+            cx,
+        );
+    }
+
+    if !checkbox.enabled {
+        return;
+    }
+    drop(checkbox);
+    on_click(
+        control,
+        |checkbox| &mut checkbox.pressed,
+        hit_region(bounds, window),
+        0,
+        window,
+        toggle,
+    );
+}
+
+fn paint_switch(
+    control: &Rc<RefCell<CheckboxControl>>,
+    bounds: Bounds<Pixels>,
+    window: &mut Window,
+    _cx: &mut App,
+) {
+    let palette = palette(window);
+    let switch = control.borrow();
+    let height = bounds.size.height.min(bounds.size.width / 1.6);
+    let track = Bounds::new(
+        point(bounds.left(), bounds.center().y - height / 2.),
+        size(bounds.size.width.min(height * 1.8), height),
+    );
+    let opacity = if switch.enabled { 1. } else { 0.5 };
+    let track_color = if switch.checked {
+        palette.accent
+    } else {
+        palette.track
+    };
+    paint_rect(
+        track,
+        height / 2.,
+        track_color.opacity(opacity),
+        None,
+        window,
+    );
+
+    let inset = px(2.);
+    let knob_size = height - inset * 2.;
+    let knob_x = if switch.checked {
+        track.right() - inset - knob_size
+    } else {
+        track.left() + inset
+    };
+    paint_rect(
+        Bounds::new(
+            point(knob_x, track.top() + inset),
+            size(knob_size, knob_size),
+        ),
+        knob_size / 2.,
+        white(),
+        Some(transparent_black().opacity(0.1)),
+        window,
+    );
+
+    if !switch.enabled {
+        return;
+    }
+    drop(switch);
+    on_click(
+        control,
+        |switch| &mut switch.pressed,
+        hit_region(bounds, window),
+        0,
+        window,
+        toggle,
+    );
+}
+
+fn slider_track(bounds: Bounds<Pixels>) -> Bounds<Pixels> {
+    let inset = px(8.);
+    Bounds::new(
+        point(bounds.left() + inset, bounds.center().y - px(2.)),
+        size((bounds.size.width - inset * 2.).max(px(0.)), px(4.)),
+    )
+}
+
+fn paint_slider(
+    control: &Rc<RefCell<SliderControl>>,
+    bounds: Bounds<Pixels>,
+    window: &mut Window,
+    _cx: &mut App,
+) {
+    let palette = palette(window);
+    let slider = control.borrow();
+    let opacity = if slider.enabled { 1. } else { 0.5 };
+    let track = slider_track(bounds);
+    paint_rect(track, px(2.), palette.track, None, window);
+    let knob_x = track.left() + track.size.width * slider.fraction();
+    paint_rect(
+        Bounds::from_corners(track.origin, point(knob_x, track.bottom())),
+        px(2.),
+        palette.accent.opacity(opacity),
+        None,
+        window,
+    );
+
+    if slider.tick_mark_count > 1 {
+        let intervals = (slider.tick_mark_count - 1) as f32;
+        for tick in 0..slider.tick_mark_count {
+            let x = track.left() + track.size.width * (tick as f32 / intervals);
+            paint_rect(
+                Bounds::new(
+                    point(x - px(0.5), track.bottom() + px(5.)),
+                    size(px(1.), px(4.)),
+                ),
+                px(0.),
+                palette.border,
+                None,
+                window,
+            );
+        }
+    }
+
+    let knob_size = px(16.);
+    paint_rect(
+        Bounds::new(
+            point(knob_x - knob_size / 2., bounds.center().y - knob_size / 2.),
+            size(knob_size, knob_size),
+        ),
+        knob_size / 2.,
+        white(),
+        Some(palette.border),
+        window,
+    );
+
+    if !slider.enabled {
+        return;
+    }
+    drop(slider);
+
+    let down_control = control.clone();
+    on_mouse_down(
+        hit_region(bounds, window),
+        MouseButton::Left,
+        window,
+        move |event, window, _| {
+            let mut slider = down_control.borrow_mut();
+            slider.dragging = true;
+            let value = slider.value_for_position(bounds, event.position.x);
+            set_slider_value(&mut slider, value, false);
+            window.refresh();
+        },
+    );
+
+    let move_control = control.clone();
+    window.on_mouse_event(move |event: &MouseMoveEvent, phase, window, _| {
+        let mut slider = move_control.borrow_mut();
+        if phase != DispatchPhase::Bubble || !slider.dragging {
+            return;
+        }
+        if event.pressed_button != Some(MouseButton::Left) {
+            // The button was released outside the window.
+            slider.dragging = false;
+            let value = slider.value;
+            set_slider_value(&mut slider, value, true);
+            return;
+        }
+        let value = slider.value_for_position(bounds, event.position.x);
+        set_slider_value(&mut slider, value, false);
+        window.refresh();
+    });
+
+    let control = control.clone();
+    window.on_mouse_event(move |event: &MouseUpEvent, phase, window, _| {
+        let mut slider = control.borrow_mut();
+        if phase != DispatchPhase::Bubble || !slider.dragging {
+            return;
+        }
+        slider.dragging = false;
+        let value = slider.value_for_position(bounds, event.position.x);
+        set_slider_value(&mut slider, value, true);
+        window.refresh();
+    });
+}
+
+/// Moves the slider, reporting the change right away if it's continuous, or once the drag
+/// finishes otherwise.
+fn set_slider_value(slider: &mut SliderControl, value: f64, finished: bool) {
+    let changed = slider.value != value;
+    slider.value = value;
+    if (changed && slider.continuous) || (finished && !slider.continuous) {
+        if let Some(on_change) = slider.on_change.clone() {
+            on_change(value);
+        }
+    }
+}
+
+fn paint_stepper(
+    control: &Rc<RefCell<StepperControl>>,
+    bounds: Bounds<Pixels>,
+    window: &mut Window,
+    _cx: &mut App,
+) {
+    let palette = palette(window);
+    let stepper = control.borrow();
+    let width = bounds.size.width.min(px(18.));
+    let height = bounds.size.height.min(px(26.));
+    let frame = Bounds::new(
+        point(
+            bounds.center().x - width / 2.,
+            bounds.center().y - height / 2.,
+        ),
+        size(width, height),
+    );
+    paint_rect(
+        frame,
+        CORNER_RADIUS,
+        palette.control,
+        Some(palette.border),
+        window,
+    );
+
+    let parts = [
+        (
+            StepperControl::UP,
+            Bounds::new(frame.origin, size(width, height / 2.)),
+        ),
+        (
+            StepperControl::DOWN,
+            Bounds::new(
+                point(frame.left(), frame.top() + height / 2.),
+                size(width, height / 2.),
+            ),
+        ),
+    ];
+    for (part, part_bounds) in parts {
+        if stepper.pressed == Some(part) {
+            paint_rect(
+                part_bounds,
+                CORNER_RADIUS,
+                palette.control_pressed,
+                None,
+                window,
+            );
+        }
+        paint_chevron(
+            part_bounds,
+            part == StepperControl::UP,
+            palette.text(stepper.enabled),
+            window,
+        );
+    }
+
+    if !stepper.enabled {
+        return;
+    }
+    let autorepeat = stepper.autorepeat;
+    drop(stepper);
+    for (part, part_bounds) in parts {
+        let region = hit_region(part_bounds, window);
+        let down_control = control.clone();
+        on_mouse_down(region, MouseButton::Left, window, move |_, window, cx| {
+            {
+                let mut stepper = down_control.borrow_mut();
+                stepper.pressed = Some(part);
+                stepper.step(part);
+            }
+            window.refresh();
+            if autorepeat {
+                autorepeat_stepper(down_control.clone(), part, window, cx);
+            }
+        });
+        let control = control.clone();
+        window.on_mouse_event(move |event: &MouseUpEvent, phase, window, _| {
+            if phase == DispatchPhase::Bubble && event.button == MouseButton::Left {
+                let mut stepper = control.borrow_mut();
+                if stepper.pressed == Some(part) {
+                    stepper.pressed = None;
+                    window.refresh();
+                }
+            }
+        });
+    }
+}
+
+/// Keeps stepping while the stepper stays pressed.
+fn autorepeat_stepper(
+    control: Rc<RefCell<StepperControl>>,
+    part: usize,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    window
+        .spawn(cx, async move |cx| {
+            let mut delay = AUTOREPEAT_DELAY;
+            loop {
+                cx.background_executor().timer(delay).await;
+                delay = AUTOREPEAT_INTERVAL;
+                if control.borrow().pressed != Some(part) {
+                    break;
+                }
+                control.borrow_mut().step(part);
+                if cx.update(|window, _| window.refresh()).is_err() {
+                    break;
+                }
+            }
+        })
+        .detach();
+}
+
+/// Splits `bounds` into `count` equally sized segments laid out horizontally.
+fn segments(bounds: Bounds<Pixels>, count: usize) -> impl Iterator<Item = Bounds<Pixels>> {
+    let width = bounds.size.width / count.max(1) as f32;
+    (0..count).map(move |index| {
+        Bounds::new(
+            point(bounds.left() + width * index as f32, bounds.top()),
+            size(width, bounds.size.height),
+        )
+    })
+}
+
+fn paint_segmented_control(
+    control: &Rc<RefCell<SegmentedControl>>,
+    bounds: Bounds<Pixels>,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let palette = palette(window);
+    let segmented = control.borrow();
+    let radius = if segmented.capsule {
+        bounds.size.height / 2.
+    } else {
+        CORNER_RADIUS
+    };
+    paint_rect(
+        bounds,
+        radius,
+        palette.control,
+        Some(palette.border),
+        window,
+    );
+
+    let segment_bounds = segments(bounds, segmented.labels.len()).collect::<Vec<_>>();
+    for (index, segment) in segment_bounds.iter().enumerate() {
+        let selected = segmented.selected_index == Some(index);
+        if selected {
+            paint_rect(
+                segment.dilate(px(-2.)),
+                radius,
+                palette.accent,
+                None,
+                window,
+            );
+        } else if segmented.pressed == Some(index) {
+            paint_rect(
+                segment.dilate(px(-2.)),
+                radius,
+                palette.control_pressed,
+                None,
+                window,
+            );
+        }
+        if index > 0 && !selected && segmented.selected_index != Some(index - 1) {
+            paint_rect(
+                Bounds::new(
+                    point(segment.left(), segment.top() + px(5.)),
+                    size(px(1.), segment.size.height - px(10.)),
+                ),
+                px(0.),
+                palette.border,
+                None,
+                window,
+            );
+        }
+
+        let label = &segmented.labels[index];
+        let label = match segmented.images.iter().find(|(image, _)| *image == index) {
+            Some((_, symbol)) if label.is_empty() => symbol_glyph(symbol).to_string(),
+            Some((_, symbol)) => format!("{} {label}", symbol_glyph(symbol)),
+            None => label.clone(),
+        };
+        let text_color = if selected {
+            palette.on_accent
+        } else {
+            palette.text(segmented.enabled)
+        };
+        paint_text(&label, *segment, TextAlign::Center, text_color, window, cx);
+    }
+
+    if !segmented.enabled {
+        return;
+    }
+    drop(segmented);
+    for (index, segment) in segment_bounds.into_iter().enumerate() {
+        on_click(
+            control,
+            |segmented| &mut segmented.pressed,
+            hit_region(segment, window),
+            index,
+            window,
+            move |segmented, _, _| {
+                segmented.selected_index = Some(index);
+                if let Some(on_select) = segmented.on_select.clone() {
+                    on_select(index);
+                }
+            },
+        );
+    }
+}
+
+/// Paints the frame and label shared by buttons that open a menu.
+fn paint_menu_field(
+    label: &str,
+    bounds: Bounds<Pixels>,
+    enabled: bool,
+    double_chevron: bool,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let palette = palette(window);
+    paint_rect(
+        bounds,
+        CORNER_RADIUS,
+        palette.control,
+        Some(palette.border),
+        window,
+    );
+    let chevron_bounds = Bounds::from_corners(
+        point(bounds.right() - px(20.), bounds.top()),
+        bounds.bottom_right(),
+    );
+    let label_bounds = Bounds::from_corners(
+        point(bounds.left() + px(8.), bounds.top()),
+        point(chevron_bounds.left(), bounds.bottom()),
+    );
+    paint_text(
+        label,
+        label_bounds,
+        TextAlign::Left,
+        palette.text(enabled),
+        window,
+        cx,
+    );
+    let chevron_color = palette.text(enabled);
+    if double_chevron {
+        let half = chevron_bounds.size.height / 2.;
+        let upper = Bounds::new(
+            chevron_bounds.origin + point(px(0.), px(3.)),
+            size(chevron_bounds.size.width, half - px(3.)),
+        );
+        let lower = Bounds::new(
+            point(chevron_bounds.left(), chevron_bounds.top() + half),
+            size(chevron_bounds.size.width, half - px(3.)),
+        );
+        paint_chevron(upper, true, chevron_color, window);
+        paint_chevron(lower, false, chevron_color, window);
+    } else {
+        paint_chevron(chevron_bounds, false, chevron_color, window);
+    }
+}
+
+fn paint_popup_button(
+    control: &Rc<RefCell<PopupButtonControl>>,
+    bounds: Bounds<Pixels>,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let popup = control.borrow();
+    let label = popup
+        .items
+        .get(popup.selected_index)
+        .cloned()
+        .unwrap_or_default();
+    paint_menu_field(&label, bounds, popup.enabled, true, window, cx);
+
+    if !popup.enabled {
+        return;
+    }
+    drop(popup);
+    let control = control.clone();
+    on_mouse_down(
+        hit_region(bounds, window),
+        MouseButton::Left,
+        window,
+        move |_, window, _| {
+            let popup = control.borrow();
+            let items = MenuItem::from_list(&popup.items, Some(popup.selected_index));
+            let control = control.clone();
+            window
+                .fallback_native_controls
+                .open_overlay(Overlay::Menu(Menu::new(
+                    bounds.bottom_left(),
+                    bounds.size.width,
+                    items,
+                    Box::new(move |index| {
+                        if let Some(index) = index {
+                            let on_select = {
+                                let mut popup = control.borrow_mut();
+                                popup.selected_index = index;
+                                popup.on_select.clone()
+                            };
+                            if let Some(on_select) = on_select {
+                                on_select(index);
+                            }
+                        }
+                    }),
+                )));
+            window.refresh();
+        },
+    );
+}
+
+fn paint_menu_button(
+    control: &Rc<RefCell<MenuButtonControl>>,
+    bounds: Bounds<Pixels>,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let menu_button = control.borrow();
+    paint_menu_field(
+        &menu_button.title,
+        bounds,
+        menu_button.enabled,
+        false,
+        window,
+        cx,
+    );
+
+    if !menu_button.enabled {
+        return;
+    }
+    let buttons: &[MouseButton] = if menu_button.context_menu {
+        &[MouseButton::Left, MouseButton::Right]
+    } else {
+        &[MouseButton::Left]
+    };
+    drop(menu_button);
+    let region = hit_region(bounds, window);
+    for button in buttons {
+        let control = control.clone();
+        on_mouse_down(region, *button, window, move |event, window, _| {
+            let menu_button = control.borrow();
+            let position = if event.button == MouseButton::Right {
+                event.position
+            } else {
+                bounds.bottom_left()
+            };
+            let on_select = menu_button.on_select.clone();
+            window
+                .fallback_native_controls
+                .open_overlay(Overlay::Menu(Menu::new(
+                    position,
+                    bounds.size.width,
+                    menu_button.items.clone(),
+                    Box::new(move |index| {
+                        if let Some((index, on_select)) = index.zip(on_select) {
+                            on_select(index);
+                        }
+                    }),
+                )));
+            window.refresh();
+        });
+    }
+}
+
+fn paint_progress(
+    control: &Rc<RefCell<ProgressControl>>,
+    bounds: Bounds<Pixels>,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let palette = palette(window);
+    let now = cx.background_executor().now();
+    let mut progress = control.borrow_mut();
+    if progress.animating && progress.animation_start.is_none() {
+        progress.animation_start = Some(now);
+    }
+    let indeterminate = progress.indeterminate || progress.spinner;
+    if indeterminate && !progress.animating && !progress.display_when_stopped {
+        return;
+    }
+    // Animations loop once a second.
+    let phase = progress
+        .animation_start
+        .map(|start| now.saturating_duration_since(start).as_secs_f32().fract())
+        .unwrap_or(0.);
+
+    if progress.spinner {
+        let radius = bounds.size.width.min(bounds.size.height) / 2.;
+        let center = bounds.center();
+        let spokes = 8;
+        for spoke in 0..spokes {
+            let angle = spoke as f32 / spokes as f32 * std::f32::consts::TAU;
+            let position = point(
+                center.x + radius * 0.65 * angle.sin(),
+                center.y - radius * 0.65 * angle.cos(),
+            );
+            let age = ((spoke as f32 / spokes as f32) - phase).rem_euclid(1.);
+            let opacity = if progress.animating {
+                0.25 + 0.75 * (1. - age)
+            } else {
+                0.4
+            };
+            let dot = radius * 0.3;
+            paint_rect(
+                Bounds::new(position - point(dot / 2., dot / 2.), size(dot, dot)),
+                dot / 2.,
+                palette.muted_text.opacity(opacity),
+                None,
+                window,
+            );
+        }
+    } else {
+        let height = bounds.size.height.min(px(6.));
+        let track = Bounds::new(
+            point(bounds.left(), bounds.center().y - height / 2.),
+            size(bounds.size.width, height),
+        );
+        paint_rect(track, height / 2., palette.track, None, window);
+        let fill = if progress.indeterminate {
+            progress.animating.then(|| {
+                let width = track.size.width * 0.3;
+                let x = track.left() - width + (track.size.width + width) * phase;
+                Bounds::new(point(x, track.top()), size(width, height)).intersect(&track)
+            })
+        } else {
+            let range = progress.max - progress.min;
+            let fraction = if range > 0. {
+                ((progress.value - progress.min) / range).clamp(0., 1.) as f32
+            } else {
+                0.
+            };
+            Some(Bounds::new(
+                track.origin,
+                size(track.size.width * fraction, height),
+            ))
+        };
+        if let Some(fill) = fill {
+            paint_rect(fill, height / 2., palette.accent, None, window);
+        }
+    }
+
+    if progress.animating {
+        window.request_animation_frame();
+    }
+}
+
+fn paint_image_view(
+    control: &Rc<RefCell<ImageViewControl>>,
+    data: Option<Vec<u8>>,
+    bounds: Bounds<Pixels>,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let palette = palette(window);
+    let mut image_view = control.borrow_mut();
+
+    let data_hash = data.as_ref().map(|data| {
+        let mut hasher = FxHasher::default();
+        data.hash(&mut hasher);
+        hasher.finish()
+    });
+    if image_view.data_hash != data_hash {
+        image_view.data_hash = data_hash;
+        image_view.image = data.and_then(|data| {
+            let format = match image::guess_format(&data).ok()? {
+                image::ImageFormat::Png => ImageFormat::Png,
+                image::ImageFormat::Jpeg => ImageFormat::Jpeg,
+                image::ImageFormat::WebP => ImageFormat::Webp,
+                image::ImageFormat::Gif => ImageFormat::Gif,
+                image::ImageFormat::Bmp => ImageFormat::Bmp,
+                image::ImageFormat::Tiff => ImageFormat::Tiff,
+                image::ImageFormat::Ico => ImageFormat::Ico,
+                _ => return None,
+            };
+            Image::from_bytes(format, data)
+                .to_image_data(cx.svg_renderer.clone())
+                .log_err()
+        });
+    }
+
+    if let Some(image) = image_view.image.clone() {
+        let image_size = image.size(0);
+        let natural = size(
+            px(image_size.width.0 as f32),
+            px(image_size.height.0 as f32),
+        );
+        let fit = (bounds.size.width / natural.width).min(bounds.size.height / natural.height);
+        let image_bounds_size = match image_view.scaling {
+            0 => natural,
+            2 => bounds.size,
+            3 => size(natural.width * fit, natural.height * fit),
+            _ => size(natural.width * fit.min(1.), natural.height * fit.min(1.)),
+        };
+        let image_bounds = Bounds::new(
+            bounds.center() - point(image_bounds_size.width / 2., image_bounds_size.height / 2.),
+            image_bounds_size,
+        );
+        window.with_content_mask(Some(ContentMask { bounds }), |window| {
+            window
+                .paint_image(
+                    image_bounds,
+                    Corners::default(),
+                    image,
+                    0,
+                    !image_view.enabled,
+                )
+                .log_err();
+        });
+    } else if let Some(symbol) = image_view.symbol.as_deref() {
+        let color = image_view.tint.unwrap_or(palette.text(image_view.enabled));
+        paint_text(
+            symbol_glyph(symbol),
+            bounds,
+            TextAlign::Center,
+            color,
+            window,
+            cx,
+        );
+    }
+}
+
+fn paint_tab_view(
+    control: &Rc<RefCell<TabViewControl>>,
+    bounds: Bounds<Pixels>,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let palette = palette(window);
+    let tab_view = control.borrow();
+    let strip_height = px(24.);
+    let content = Bounds::from_corners(
+        point(bounds.left(), bounds.top() + strip_height / 2.),
+        bounds.bottom_right(),
+    );
+    paint_rect(
+        content,
+        CORNER_RADIUS,
+        palette.stripe,
+        Some(palette.border),
+        window,
+    );
+
+    let tab_width = px(90.).min(bounds.size.width / tab_view.labels.len().max(1) as f32);
+    let strip_width = tab_width * tab_view.labels.len() as f32;
+    let strip = Bounds::new(
+        point(bounds.center().x - strip_width / 2., bounds.top()),
+        size(strip_width, strip_height),
+    );
+    paint_rect(
+        strip,
+        CORNER_RADIUS,
+        palette.control,
+        Some(palette.border),
+        window,
+    );
+
+    let tabs = segments(strip, tab_view.labels.len()).collect::<Vec<_>>();
+    for (index, tab) in tabs.iter().enumerate() {
+        let selected = tab_view.selected_index == index;
+        if selected {
+            paint_rect(
+                tab.dilate(px(-2.)),
+                CORNER_RADIUS,
+                palette.accent,
+                None,
+                window,
+            );
+        } else if tab_view.pressed == Some(index) {
+            paint_rect(
+                tab.dilate(px(-2.)),
+                CORNER_RADIUS,
+                palette.control_pressed,
+                None,
+                window,
+            );
+        }
+        let text_color = if selected {
+            palette.on_accent
+        } else {
+            palette.text(tab_view.enabled)
+        };
+        paint_text(
+            &tab_view.labels[index],
+            *tab,
+            TextAlign::Center,
+            text_color,
+            window,
+            cx,
+        );
+    }
+
+    if !tab_view.enabled {
+        return;
+    }
+    drop(tab_view);
+    for (index, tab) in tabs.into_iter().enumerate() {
+        on_click(
+            control,
+            |tab_view| &mut tab_view.pressed,
+            hit_region(tab, window),
+            index,
+            window,
+            move |tab_view, _, _| {
+                tab_view.selected_index = index;
+                if let Some(on_select) = tab_view.on_select.clone() {
+                    on_select(index);
+                }
+            },
+        );
+    }
+}
+
+fn paint_effect_view(
+    control: &Rc<RefCell<EffectViewControl>>,
+    bounds: Bounds<Pixels>,
+    window: &mut Window,
+) {
+    let palette = palette(window);
+    let effect = control.borrow();
+    // Effect backgrounds are defined against black, so tint them with the window's own
+    // control color instead.
+    let background = if effect.background.s == 0. && effect.background.l == 0. {
+        palette.control.opacity(effect.background.a)
+    } else {
+        effect.background
+    };
+    paint_rect(bounds, effect.corner_radius, background, None, window);
+}
+
+fn paint_tracking_view(
+    control: &Rc<RefCell<TrackingViewControl>>,
+    bounds: Bounds<Pixels>,
+    window: &mut Window,
+) {
+    let region = hit_region(bounds, window);
+    let control = control.clone();
+    window.on_mouse_event(move |event: &MouseMoveEvent, phase, _, _| {
+        if phase != DispatchPhase::Bubble {
+            return;
+        }
+        let mut tracking = control.borrow_mut();
+        let hovered = region.contains(&event.position);
+        let callback = match (tracking.hovered, hovered) {
+            (false, true) => tracking.on_enter.clone(),
+            (true, false) => tracking.on_exit.clone(),
+            _ => None,
+        };
+        tracking.hovered = hovered;
+        if let Some(callback) = callback {
+            callback();
+        }
+        if hovered && let Some(on_move) = tracking.on_move.clone() {
+            let position = event.position - bounds.origin;
+            on_move(position.x.0 as f64, position.y.0 as f64);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slider_snaps_to_ticks() {
+        let mut slider = SliderControl {
+            min: 0.,
+            max: 10.,
+            tick_mark_count: 3,
+            snap_to_ticks: true,
+            ..Default::default()
+        };
+        let bounds = Bounds::new(point(px(0.), px(0.)), size(px(116.), px(20.)));
+        // The track is inset by 8px on each side, leaving 100px for the full range.
+        assert_eq!(slider.value_for_position(bounds, px(58.)), 5.);
+        assert_eq!(slider.value_for_position(bounds, px(20.)), 0.);
+        assert_eq!(slider.value_for_position(bounds, px(500.)), 10.);
+
+        slider.snap_to_ticks = false;
+        assert_eq!(slider.value_for_position(bounds, px(33.)), 2.5);
+    }
+
+    #[test]
+    fn test_stepper_clamps_and_wraps() {
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let mut stepper = StepperControl {
+            min: 0.,
+            max: 2.,
+            value: 1.,
+            increment: 1.,
+            on_change: Some(Rc::new({
+                let changes = changes.clone();
+                move |value| changes.borrow_mut().push(value)
+            })),
+            ..Default::default()
+        };
+        stepper.step(StepperControl::UP);
+        stepper.step(StepperControl::UP);
+        assert_eq!(stepper.value, 2.);
+
+        stepper.wraps = true;
+        stepper.step(StepperControl::UP);
+        assert_eq!(stepper.value, 0.);
+        stepper.step(StepperControl::DOWN);
+        assert_eq!(stepper.value, 2.);
+        assert_eq!(*changes.borrow(), vec![2., 2., 0., 2.]);
+    }
+
+    #[test]
+    fn test_control_state_keeps_control_alive() {
+        let mut state = NativeControlState::default();
+        let slider = control::<SliderControl>(&mut state);
+        slider.borrow_mut().value = 4.;
+        assert_eq!(control::<SliderControl>(&mut state).borrow().value, 4.);
+
+        // Asking for another kind of control replaces the stored one.
+        control::<StepperControl>(&mut state);
+        assert!(existing_control::<SliderControl>(&state).is_none());
+        assert_eq!(Rc::strong_count(&slider), 1);
+
+        drop(state);
+    }
+}
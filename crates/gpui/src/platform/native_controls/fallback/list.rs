@@ -0,0 +1,625 @@
+//! Scrolling lists of items: table views, outline views, collection views and sidebars.
+
+use collections::HashSet;
+
+use super::*;
+use crate::ScrollWheelEvent;
+
+const DISCLOSURE_WIDTH: Pixels = px(14.);
+const INDENT_WIDTH: Pixels = px(16.);
+const HEADER_HEIGHT: Pixels = px(24.);
+const HEADER_BUTTON_WIDTH: Pixels = px(22.);
+
+#[derive(Clone, Copy, Default, PartialEq)]
+enum Kind {
+    #[default]
+    Table,
+    Outline,
+    Collection {
+        columns: usize,
+        item_height: Pixels,
+        spacing: Pixels,
+        cards: bool,
+    },
+    Sidebar,
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+enum Highlight {
+    #[default]
+    Regular,
+    SourceList,
+    None,
+}
+
+struct OutlineNode {
+    title: String,
+    children: Vec<OutlineNode>,
+}
+
+impl From<&NativeOutlineNodeData> for OutlineNode {
+    fn from(node: &NativeOutlineNodeData) -> Self {
+        Self {
+            title: node.title.clone(),
+            children: node.children.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Row {
+    title: String,
+    depth: usize,
+    /// The position of the row's node at each level of an outline, outermost first.
+    path: Vec<usize>,
+    expandable: bool,
+    expanded: bool,
+}
+
+impl Row {
+    fn flat(items: &[&str]) -> Vec<Row> {
+        items
+            .iter()
+            .enumerate()
+            .map(|(index, title)| Row {
+                title: title.to_string(),
+                depth: 0,
+                path: vec![index],
+                expandable: false,
+                expanded: false,
+            })
+            .collect()
+    }
+}
+
+/// The state of a table, outline, collection or sidebar.
+#[derive(Default)]
+pub(super) struct ListControl {
+    kind: Kind,
+    header: Option<String>,
+    header_buttons: Vec<String>,
+    rows: Vec<Row>,
+    selected: Option<usize>,
+    row_height: Pixels,
+    alternating_rows: bool,
+    horizontal_grid: bool,
+    highlight: Highlight,
+    background: Option<Hsla>,
+    collapsed: bool,
+    outline: Vec<OutlineNode>,
+    expanded: HashSet<Vec<usize>>,
+    expand_all: bool,
+    /// The last `expand_all` the element asked for, so collapsing a row sticks until it
+    /// asks again.
+    expand_all_requested: bool,
+    scroll_offset: Pixels,
+    on_select: Option<Rc<dyn Fn(usize)>>,
+    on_select_titled: Option<Rc<dyn Fn((usize, String))>>,
+    on_header_button: Option<Rc<dyn Fn(usize)>>,
+}
+
+impl ListControl {
+    pub(super) fn update_table(&mut self, config: TableViewConfig) {
+        self.kind = Kind::Table;
+        self.header = config
+            .column_title
+            .filter(|_| config.show_header)
+            .map(ToString::to_string);
+        self.header_buttons.clear();
+        self.rows = Row::flat(config.items);
+        self.selected = config.selected_index;
+        self.row_height = match (config.row_height, config.row_size_style) {
+            (Some(height), _) => px(height as f32),
+            (None, Some(1)) => px(20.),
+            (None, Some(3)) => px(32.),
+            _ => px(24.),
+        };
+        self.alternating_rows = config.alternating_rows;
+        // NSTableViewSolidHorizontalGridLineMask
+        self.horizontal_grid = config.grid_style.is_some_and(|style| style & 2 != 0);
+        self.highlight = match config.highlight_style {
+            Some(-1) => Highlight::None,
+            _ => Highlight::Regular,
+        };
+        self.background = None;
+        self.on_select = callback(config.on_select);
+        self.on_select_titled = None;
+        self.on_header_button = None;
+    }
+
+    pub(super) fn update_outline(&mut self, config: OutlineViewConfig) {
+        self.kind = Kind::Outline;
+        self.header = None;
+        self.header_buttons.clear();
+        self.outline = config.nodes.iter().map(Into::into).collect();
+        if self.expand_all_requested != config.expand_all {
+            self.expand_all_requested = config.expand_all;
+            self.expand_all = config.expand_all;
+        }
+        self.rebuild_outline_rows();
+        self.selected = config.selected_row;
+        self.row_height = px(config.row_height.unwrap_or(22.) as f32);
+        self.alternating_rows = false;
+        self.horizontal_grid = false;
+        self.highlight = match config.highlight_style {
+            Some(1) => Highlight::SourceList,
+            Some(-1) => Highlight::None,
+            _ => Highlight::Regular,
+        };
+        self.background = None;
+        self.on_select = None;
+        self.on_select_titled = callback(config.on_select);
+        self.on_header_button = None;
+    }
+
+    pub(super) fn update_collection(&mut self, config: CollectionViewConfig) {
+        self.kind = Kind::Collection {
+            columns: config.columns.max(1),
+            item_height: px(config.item_height as f32),
+            spacing: px(config.spacing as f32),
+            cards: config.item_style == CollectionItemStyle::Card,
+        };
+        self.header = None;
+        self.header_buttons.clear();
+        self.rows = Row::flat(config.items);
+        self.selected = config.selected;
+        self.highlight = Highlight::Regular;
+        self.background = None;
+        self.on_select = callback(config.on_select);
+        self.on_select_titled = None;
+        self.on_header_button = None;
+    }
+
+    pub(super) fn update_sidebar(&mut self, config: SidebarViewConfig) {
+        self.kind = Kind::Sidebar;
+        self.header = config.header_title.map(ToString::to_string);
+        self.header_buttons = config
+            .header_button_symbols
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        self.rows = Row::flat(config.items);
+        self.selected = config.selected_index;
+        self.row_height = px(28.);
+        self.alternating_rows = false;
+        self.horizontal_grid = false;
+        self.highlight = Highlight::SourceList;
+        self.background = config.background_color.map(color);
+        self.collapsed = config.collapsed;
+        self.on_select = None;
+        self.on_select_titled = callback(config.on_select);
+        self.on_header_button = callback(config.on_header_button);
+    }
+
+    fn rebuild_outline_rows(&mut self) {
+        fn visit(
+            nodes: &[OutlineNode],
+            path: &mut Vec<usize>,
+            list: &ListControl,
+            rows: &mut Vec<Row>,
+        ) {
+            for (position, node) in nodes.iter().enumerate() {
+                path.push(position);
+                let expandable = !node.children.is_empty();
+                let expanded = expandable && (list.expand_all || list.expanded.contains(path));
+                rows.push(Row {
+                    title: node.title.clone(),
+                    depth: path.len() - 1,
+                    path: path.clone(),
+                    expandable,
+                    expanded,
+                });
+                if expanded {
+                    visit(&node.children, path, list, rows);
+                }
+                path.pop();
+            }
+        }
+
+        let mut rows = Vec::new();
+        visit(&self.outline, &mut Vec::new(), self, &mut rows);
+        self.rows = rows;
+    }
+
+    /// Expands or collapses the outline row at `index`, keeping the same node selected.
+    fn toggle_expanded(&mut self, index: usize) {
+        let Some(row) = self.rows.get(index) else {
+            return;
+        };
+        let path = row.path.clone();
+        let selected_path = self
+            .selected
+            .and_then(|selected| self.rows.get(selected))
+            .map(|row| row.path.clone());
+        if row.expanded {
+            self.expanded.remove(&path);
+            // Collapsing ignores `expand_all` from then on, the same way a native outline
+            // only expands everything when it's (re)loaded.
+            if self.expand_all {
+                self.expand_all = false;
+                self.expanded = self
+                    .rows
+                    .iter()
+                    .filter(|row| row.expanded && row.path != path)
+                    .map(|row| row.path.clone())
+                    .collect();
+            }
+        } else {
+            self.expanded.insert(path);
+        }
+        self.rebuild_outline_rows();
+        self.selected = selected_path
+            .and_then(|selected_path| self.rows.iter().position(|row| row.path == selected_path));
+    }
+
+    fn header_height(&self) -> Pixels {
+        if self.header.is_some() || !self.header_buttons.is_empty() {
+            HEADER_HEIGHT
+        } else {
+            px(0.)
+        }
+    }
+
+    /// The bounds of item `index`, relative to the top left of the unscrolled content.
+    fn item_bounds(&self, index: usize, width: Pixels) -> Bounds<Pixels> {
+        match self.kind {
+            Kind::Collection {
+                columns,
+                item_height,
+                spacing,
+                ..
+            } => {
+                let item_width =
+                    ((width - spacing * (columns - 1) as f32) / columns as f32).max(px(0.));
+                let (row, column) = (index / columns, index % columns);
+                Bounds::new(
+                    point(
+                        (item_width + spacing) * column as f32,
+                        (item_height + spacing) * row as f32,
+                    ),
+                    size(item_width, item_height),
+                )
+            }
+            _ => Bounds::new(
+                point(px(0.), self.row_height * index as f32),
+                size(width, self.row_height),
+            ),
+        }
+    }
+
+    fn content_height(&self, width: Pixels) -> Pixels {
+        self.rows
+            .len()
+            .checked_sub(1)
+            .map_or(px(0.), |last| self.item_bounds(last, width).bottom())
+    }
+
+    fn item_at(&self, position: Point<Pixels>, width: Pixels) -> Option<usize> {
+        let position = position + point(px(0.), self.scroll_offset);
+        (0..self.rows.len()).find(|&index| self.item_bounds(index, width).contains(&position))
+    }
+
+    fn select(&mut self, index: usize) {
+        if self.selected == Some(index) {
+            return;
+        }
+        self.selected = Some(index);
+        if let Some(on_select) = self.on_select.clone() {
+            on_select(index);
+        }
+        if let Some(on_select) = self.on_select_titled.clone() {
+            on_select((index, self.rows[index].title.clone()));
+        }
+    }
+}
+
+pub(super) fn paint_list(
+    control: &Rc<RefCell<ListControl>>,
+    bounds: Bounds<Pixels>,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let palette = palette(window);
+    let mut list = control.borrow_mut();
+    if list.kind == Kind::Sidebar && list.collapsed {
+        return;
+    }
+
+    match list.kind {
+        Kind::Collection { .. } => {}
+        Kind::Sidebar => {
+            let background = list.background.unwrap_or(palette.stripe);
+            paint_rect(bounds, px(0.), background, None, window);
+        }
+        Kind::Table | Kind::Outline => {
+            paint_rect(bounds, px(0.), palette.field, Some(palette.border), window);
+        }
+    }
+
+    let header_height = list.header_height();
+    let header = Bounds::new(bounds.origin, size(bounds.size.width, header_height));
+    let content =
+        Bounds::from_corners(point(bounds.left(), header.bottom()), bounds.bottom_right());
+    let header_buttons = (0..list.header_buttons.len())
+        .map(|index| {
+            Bounds::new(
+                point(
+                    header.right()
+                        - HEADER_BUTTON_WIDTH * (list.header_buttons.len() - index) as f32,
+                    header.top(),
+                ),
+                size(HEADER_BUTTON_WIDTH, header_height),
+            )
+        })
+        .collect::<Vec<_>>();
+    if header_height > px(0.) {
+        if let Some(title) = list.header.as_deref() {
+            let title_bounds = Bounds::from_corners(
+                point(header.left() + px(8.), header.top()),
+                point(
+                    header_buttons
+                        .first()
+                        .map_or(header.right(), |button| button.left()),
+                    header.bottom(),
+                ),
+            );
+            paint_text(
+                title,
+                title_bounds,
+                TextAlign::Left,
+                palette.muted_text,
+                window,
+                cx,
+            );
+        }
+        for (symbol, button) in list.header_buttons.iter().zip(&header_buttons) {
+            paint_text(
+                symbol_glyph(symbol),
+                *button,
+                TextAlign::Center,
+                palette.text,
+                window,
+                cx,
+            );
+        }
+        if list.kind == Kind::Table {
+            paint_rect(
+                Bounds::new(
+                    point(header.left(), header.bottom() - px(1.)),
+                    size(header.size.width, px(1.)),
+                ),
+                px(0.),
+                palette.border,
+                None,
+                window,
+            );
+        }
+    }
+
+    let max_scroll = (list.content_height(content.size.width) - content.size.height).max(px(0.));
+    list.scroll_offset = list.scroll_offset.clamp(px(0.), max_scroll);
+
+    window.with_content_mask(Some(ContentMask { bounds: content }), |window| {
+        for (index, row) in list.rows.iter().enumerate() {
+            let item = list.item_bounds(index, content.size.width);
+            let item = Bounds::new(
+                content.origin + item.origin - point(px(0.), list.scroll_offset),
+                item.size,
+            );
+            if item.bottom() < content.top() {
+                continue;
+            }
+            if item.top() > content.bottom() {
+                break;
+            }
+
+            let selected = list.selected == Some(index) && list.highlight != Highlight::None;
+            let mut text_color = palette.text;
+            if let Kind::Collection { cards, .. } = list.kind {
+                if cards || selected {
+                    let (background, border) = match (cards, selected) {
+                        (_, true) => (palette.selection, palette.accent),
+                        (_, false) => (palette.field, palette.border),
+                    };
+                    paint_rect(item, CORNER_RADIUS, background, Some(border), window);
+                }
+            } else if selected {
+                match list.highlight {
+                    Highlight::SourceList => {
+                        paint_rect(
+                            item.dilate(px(-2.)),
+                            CORNER_RADIUS,
+                            palette.selection,
+                            None,
+                            window,
+                        );
+                    }
+                    _ => {
+                        paint_rect(item, px(0.), palette.accent, None, window);
+                        text_color = palette.on_accent;
+                    }
+                }
+            } else if list.alternating_rows && index % 2 == 1 {
+                paint_rect(item, px(0.), palette.stripe, None, window);
+            }
+            if list.horizontal_grid {
+                paint_rect(
+                    Bounds::new(
+                        point(item.left(), item.bottom() - px(1.)),
+                        size(item.size.width, px(1.)),
+                    ),
+                    px(0.),
+                    palette.stripe,
+                    None,
+                    window,
+                );
+            }
+
+            let mut label = item;
+            if matches!(list.kind, Kind::Collection { .. }) {
+                paint_text(&row.title, label, TextAlign::Center, text_color, window, cx);
+                continue;
+            }
+            label.origin.x += px(8.);
+            label.size.width -= px(8.);
+            if list.kind == Kind::Outline {
+                let disclosure = Bounds::new(
+                    point(label.left() + INDENT_WIDTH * row.depth as f32, label.top()),
+                    size(DISCLOSURE_WIDTH, label.size.height),
+                );
+                if row.expandable {
+                    paint_disclosure(disclosure, row.expanded, text_color, window);
+                }
+                label = Bounds::from_corners(
+                    point(disclosure.right() + px(2.), label.top()),
+                    label.bottom_right(),
+                );
+            }
+            paint_text(&row.title, label, TextAlign::Left, text_color, window, cx);
+        }
+    });
+    drop(list);
+
+    let header_region = hit_region(header, window);
+    let content_region = hit_region(content, window);
+    let down_control = control.clone();
+    on_mouse_down(
+        header_region.union(&content_region),
+        MouseButton::Left,
+        window,
+        move |event, window, _| {
+            let mut list = down_control.borrow_mut();
+            if header_region.contains(&event.position) {
+                let button = header_buttons
+                    .iter()
+                    .position(|button| button.contains(&event.position));
+                if let Some((button, on_header_button)) = button.zip(list.on_header_button.clone())
+                {
+                    on_header_button(button);
+                }
+                return;
+            }
+            let Some(index) = list.item_at(event.position - content.origin, content.size.width)
+            else {
+                return;
+            };
+            let row = &list.rows[index];
+            let disclosure_left = content.left() + px(8.) + INDENT_WIDTH * row.depth as f32;
+            if list.kind == Kind::Outline
+                && row.expandable
+                && (event.position.x >= disclosure_left
+                    && event.position.x < disclosure_left + DISCLOSURE_WIDTH
+                    || event.click_count == 2)
+            {
+                list.toggle_expanded(index);
+            } else {
+                list.select(index);
+            }
+            window.refresh();
+        },
+    );
+
+    let control = control.clone();
+    window.on_mouse_event(move |event: &ScrollWheelEvent, phase, window, cx| {
+        if phase != DispatchPhase::Bubble || !content_region.contains(&event.position) {
+            return;
+        }
+        let mut list = control.borrow_mut();
+        let delta = event.delta.pixel_delta(list.row_height.max(px(20.)));
+        let max_scroll =
+            (list.content_height(content.size.width) - content.size.height).max(px(0.));
+        let scroll_offset = (list.scroll_offset - delta.y).clamp(px(0.), max_scroll);
+        if scroll_offset != list.scroll_offset {
+            list.scroll_offset = scroll_offset;
+            window.refresh();
+            cx.stop_propagation();
+        }
+    });
+}
+
+/// A disclosure triangle, pointing right when collapsed and down when expanded.
+fn paint_disclosure(bounds: Bounds<Pixels>, expanded: bool, color: Hsla, window: &mut Window) {
+    let center = bounds.center();
+    let (long, short) = (px(3.5), px(2.));
+    let points = if expanded {
+        [
+            point(center.x - long, center.y - short),
+            point(center.x, center.y + short),
+            point(center.x + long, center.y - short),
+        ]
+    } else {
+        [
+            point(center.x - short, center.y - long),
+            point(center.x + short, center.y),
+            point(center.x - short, center.y + long),
+        ]
+    };
+    paint_polyline(&points, px(1.5), color, window);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(title: &str, children: Vec<NativeOutlineNodeData>) -> NativeOutlineNodeData {
+        NativeOutlineNodeData {
+            title: title.to_string(),
+            children,
+        }
+    }
+
+    fn titles(list: &ListControl) -> Vec<&str> {
+        list.rows.iter().map(|row| row.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_outline_rows_follow_expansion() {
+        let mut list = ListControl::default();
+        list.update_outline(OutlineViewConfig {
+            nodes: &[
+                node("Fruit", vec![node("Apple", vec![]), node("Pear", vec![])]),
+                node("Vegetables", vec![node("Leek", vec![])]),
+            ],
+            selected_row: Some(1),
+            expand_all: false,
+            highlight_style: None,
+            row_height: None,
+            on_select: None,
+        });
+        assert_eq!(titles(&list), vec!["Fruit", "Vegetables"]);
+
+        list.toggle_expanded(0);
+        assert_eq!(titles(&list), vec!["Fruit", "Apple", "Pear", "Vegetables"]);
+        assert_eq!(list.rows[2].depth, 1);
+        // The selection stays on the same node.
+        assert_eq!(list.selected, Some(3));
+
+        list.toggle_expanded(0);
+        assert_eq!(titles(&list), vec!["Fruit", "Vegetables"]);
+        assert_eq!(list.selected, Some(1));
+    }
+
+    #[test]
+    fn test_collection_layout() {
+        let mut list = ListControl::default();
+        list.update_collection(CollectionViewConfig {
+            width: 0.,
+            columns: 3,
+            item_height: 40.,
+            spacing: 10.,
+            items: &["a", "b", "c", "d"],
+            selected: None,
+            item_style: CollectionItemStyle::Card,
+            on_select: None,
+        });
+        let width = px(320.);
+        assert_eq!(
+            list.item_bounds(4, width),
+            Bounds::new(point(px(110.), px(50.)), size(px(100.), px(40.)))
+        );
+        assert_eq!(list.content_height(width), px(90.));
+        assert_eq!(list.item_at(point(px(115.), px(10.)), width), Some(1));
+        assert_eq!(list.item_at(point(px(105.), px(10.)), width), None);
+        assert_eq!(list.item_at(point(px(205.), px(60.)), width), None);
+    }
+}
@@ -0,0 +1,434 @@
+//! Menus and alert sheets, drawn above the window's content while they're open.
+
+use super::*;
+use crate::{
+    ClickEvent, InteractiveElement as _, IntoElement as _, ParentElement as _,
+    StatefulInteractiveElement as _, Styled as _, anchored, div, prelude::FluentBuilder as _,
+};
+
+/// The menu or alert currently open in a window.
+pub(crate) enum Overlay {
+    Menu(Menu),
+    Alert(Alert),
+}
+
+impl Overlay {
+    pub(super) fn render(&self, palette: Palette) -> AnyElement {
+        match self {
+            Overlay::Menu(menu) => menu.render(palette),
+            Overlay::Alert(alert) => alert.render(palette),
+        }
+    }
+
+    /// Closes the overlay without a choice being made.
+    pub(super) fn dismiss(self) {
+        match self {
+            Overlay::Menu(menu) => (menu.on_result)(None),
+            // Alerts can only be closed with one of their buttons, so one that's replaced by
+            // another alert never reports a response.
+            Overlay::Alert(_) => {}
+        }
+    }
+}
+
+/// Closes the open overlay, then reports the result of the menu or alert on the next
+/// turn of the event loop, so callers can't observe the window mid-dispatch.
+fn close_overlay(response: Option<usize>, window: &mut Window, cx: &mut App) {
+    let Some(overlay) = window.fallback_native_controls.take_overlay() else {
+        return;
+    };
+    window.refresh();
+    cx.foreground_executor()
+        .spawn(async move {
+            match overlay {
+                Overlay::Menu(menu) => (menu.on_result)(response),
+                Overlay::Alert(alert) => {
+                    if let Some((callback, response)) = alert.callback.zip(response) {
+                        callback(ALERT_FIRST_BUTTON_RETURN + response as i64);
+                    }
+                }
+            }
+        })
+        .detach();
+}
+
+/// An entry of a fallback menu.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum MenuItem {
+    Action {
+        title: String,
+        icon: Option<String>,
+        enabled: bool,
+        checked: bool,
+        /// The index reported when the item is chosen.
+        index: usize,
+    },
+    Submenu {
+        title: String,
+        icon: Option<String>,
+        enabled: bool,
+        items: Vec<MenuItem>,
+    },
+    Separator,
+}
+
+impl MenuItem {
+    /// Converts native menu items, numbering actions depth-first like the platform menus do.
+    pub(super) fn from_native(items: &[NativeMenuItemData]) -> Vec<MenuItem> {
+        fn convert(items: &[NativeMenuItemData], next_index: &mut usize) -> Vec<MenuItem> {
+            items
+                .iter()
+                .map(|item| match item {
+                    NativeMenuItemData::Action {
+                        title,
+                        enabled,
+                        icon,
+                    } => {
+                        let index = *next_index;
+                        *next_index += 1;
+                        MenuItem::Action {
+                            title: title.clone(),
+                            icon: icon.clone(),
+                            enabled: *enabled,
+                            checked: false,
+                            index,
+                        }
+                    }
+                    NativeMenuItemData::Submenu {
+                        title,
+                        enabled,
+                        icon,
+                        items,
+                    } => MenuItem::Submenu {
+                        title: title.clone(),
+                        icon: icon.clone(),
+                        enabled: *enabled,
+                        items: convert(items, next_index),
+                    },
+                    NativeMenuItemData::Separator => MenuItem::Separator,
+                })
+                .collect()
+        }
+
+        convert(items, &mut 0)
+    }
+
+    /// A flat menu choosing between `items`, with a checkmark next to the selected one.
+    pub(super) fn from_list(items: &[String], selected_index: Option<usize>) -> Vec<MenuItem> {
+        items
+            .iter()
+            .enumerate()
+            .map(|(index, title)| MenuItem::Action {
+                title: title.clone(),
+                icon: None,
+                enabled: true,
+                checked: selected_index == Some(index),
+                index,
+            })
+            .collect()
+    }
+}
+
+/// An open dropdown or context menu.
+pub(crate) struct Menu {
+    position: Point<Pixels>,
+    min_width: Pixels,
+    items: Vec<MenuItem>,
+    /// The position of the open submenu at each level, outermost first.
+    open_submenus: Vec<usize>,
+    on_result: Box<dyn FnOnce(Option<usize>)>,
+}
+
+impl Menu {
+    pub(super) fn new(
+        position: Point<Pixels>,
+        min_width: Pixels,
+        items: Vec<MenuItem>,
+        on_result: Box<dyn FnOnce(Option<usize>)>,
+    ) -> Self {
+        Self {
+            position,
+            min_width,
+            items,
+            open_submenus: Vec::new(),
+            on_result,
+        }
+    }
+
+    fn render(&self, palette: Palette) -> AnyElement {
+        let dismiss = |_: &MouseDownEvent, window: &mut Window, cx: &mut App| {
+            close_overlay(None, window, cx);
+            cx.stop_propagation();
+        };
+        div()
+            .size_full()
+            .occlude()
+            .on_mouse_down(MouseButton::Left, dismiss)
+            .on_mouse_down(MouseButton::Right, dismiss)
+            .child(
+                anchored()
+                    .position(self.position)
+                    .snap_to_window_with_margin(px(8.))
+                    .child(self.render_items(&self.items, 0, self.min_width, palette)),
+            )
+            .into_any_element()
+    }
+
+    fn render_items(
+        &self,
+        items: &[MenuItem],
+        depth: usize,
+        min_width: Pixels,
+        palette: Palette,
+    ) -> AnyElement {
+        let open_submenu = self.open_submenus.get(depth).copied();
+        div()
+            .id(("fallback-menu", depth))
+            .occlude()
+            .min_w(min_width.max(px(120.)))
+            .py_1()
+            .flex()
+            .flex_col()
+            .bg(palette.menu)
+            .border_1()
+            .border_color(palette.border)
+            .rounded_md()
+            .shadow_md()
+            .text_size(FONT_SIZE)
+            .text_color(palette.text)
+            .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+            .on_mouse_down(MouseButton::Right, |_, _, cx| cx.stop_propagation())
+            .children(items.iter().enumerate().map(|(position, item)| {
+                let (title, icon, enabled) = match item {
+                    MenuItem::Action {
+                        title,
+                        icon,
+                        enabled,
+                        ..
+                    }
+                    | MenuItem::Submenu {
+                        title,
+                        icon,
+                        enabled,
+                        ..
+                    } => (title, icon, *enabled),
+                    MenuItem::Separator => {
+                        return div().my_1().h(px(1.)).bg(palette.border).into_any_element();
+                    }
+                };
+                let checked = matches!(item, MenuItem::Action { checked: true, .. });
+                let label = match icon {
+                    Some(icon) => format!("{} {title}", symbol_glyph(icon)),
+                    None => title.clone(),
+                };
+                let submenu_open = open_submenu == Some(position);
+
+                div()
+                    .id(("fallback-menu-item", position))
+                    .relative()
+                    .mx_1()
+                    .px_1()
+                    .py(px(2.))
+                    .rounded_sm()
+                    .flex()
+                    .flex_row()
+                    .gap_1()
+                    .whitespace_nowrap()
+                    .when(!enabled, |this| this.text_color(palette.muted_text))
+                    .when(enabled, |this| {
+                        this.hover(|style| style.bg(palette.accent).text_color(palette.on_accent))
+                    })
+                    .when(submenu_open, |this| {
+                        this.bg(palette.accent).text_color(palette.on_accent)
+                    })
+                    .child(div().w(px(12.)).child(if checked { "✓" } else { "" }))
+                    .child(div().flex_1().child(label))
+                    .on_hover(move |hovered, window, _| {
+                        if !hovered {
+                            return;
+                        }
+                        window.fallback_native_controls.with_overlay(|overlay| {
+                            if let Overlay::Menu(menu) = overlay {
+                                menu.open_submenus.truncate(depth);
+                                if enabled
+                                    && let MenuItem::Submenu { .. } =
+                                        item_kind(menu, depth, position)
+                                {
+                                    menu.open_submenus.push(position);
+                                }
+                            }
+                        });
+                        window.refresh();
+                    })
+                    .map(|this| match item {
+                        MenuItem::Action { index, .. } if enabled => {
+                            let index = *index;
+                            this.on_click(move |_: &ClickEvent, window, cx| {
+                                close_overlay(Some(index), window, cx);
+                                cx.stop_propagation();
+                            })
+                        }
+                        MenuItem::Submenu { items, .. } => {
+                            this.child(div().child("›")).when(submenu_open, |this| {
+                                this.child(
+                                    div().absolute().top(px(-5.)).left_full().child(
+                                        self.render_items(items, depth + 1, px(0.), palette),
+                                    ),
+                                )
+                            })
+                        }
+                        _ => this,
+                    })
+                    .into_any_element()
+            }))
+            .into_any_element()
+    }
+}
+
+/// Finds the kind of the item at `position` in the submenu open at `depth`.
+fn item_kind(menu: &Menu, depth: usize, position: usize) -> &MenuItem {
+    let mut items = &menu.items;
+    for &open in &menu.open_submenus[..depth] {
+        if let Some(MenuItem::Submenu { items: submenu, .. }) = items.get(open) {
+            items = submenu;
+        }
+    }
+    &items[position]
+}
+
+/// An alert sheet, shown centered over the window until one of its buttons is clicked.
+pub(crate) struct Alert {
+    style: AlertStyle,
+    message: String,
+    informative_text: Option<String>,
+    button_titles: Vec<String>,
+    callback: Option<Box<dyn FnOnce(i64)>>,
+}
+
+impl Alert {
+    pub(super) fn new(config: AlertConfig, callback: Option<Box<dyn FnOnce(i64)>>) -> Self {
+        let mut button_titles = config
+            .button_titles
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        if button_titles.is_empty() {
+            button_titles.push("OK".to_string());
+        }
+        Self {
+            style: config.style,
+            message: config.message.to_string(),
+            informative_text: config.informative_text.map(ToString::to_string),
+            button_titles,
+            callback,
+        }
+    }
+
+    fn render(&self, palette: Palette) -> AnyElement {
+        let default_color = match self.style {
+            AlertStyle::Critical => hsla(0., 0.75, 0.5, 1.),
+            AlertStyle::Warning | AlertStyle::Informational => palette.accent,
+        };
+        let card = div()
+            .occlude()
+            .w(px(280.))
+            .p_4()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .bg(palette.menu)
+            .border_1()
+            .border_color(palette.border)
+            .rounded_lg()
+            .shadow_lg()
+            .text_size(FONT_SIZE)
+            .text_color(palette.text)
+            .child(
+                div()
+                    .font_weight(crate::FontWeight::BOLD)
+                    .child(self.message.clone()),
+            )
+            .children(
+                self.informative_text
+                    .clone()
+                    .map(|text| div().text_color(palette.muted_text).child(text)),
+            )
+            .child(div().mt_2().flex().flex_row_reverse().gap_2().children(
+                self.button_titles.iter().enumerate().map(|(index, title)| {
+                    let (background, text_color) = if index == 0 {
+                        (default_color, palette.on_accent)
+                    } else {
+                        (palette.control, palette.text)
+                    };
+                    div()
+                        .id(("fallback-alert-button", index))
+                        .px_3()
+                        .py_1()
+                        .rounded_md()
+                        .border_1()
+                        .border_color(palette.border)
+                        .bg(background)
+                        .text_color(text_color)
+                        .child(title.clone())
+                        .on_click(move |_: &ClickEvent, window, cx| {
+                            close_overlay(Some(index), window, cx);
+                            cx.stop_propagation();
+                        })
+                }),
+            ));
+
+        div()
+            .size_full()
+            .occlude()
+            .bg(transparent_black().opacity(0.25))
+            .flex()
+            .flex_col()
+            .items_center()
+            .justify_center()
+            .child(card)
+            .into_any_element()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(title: &str) -> NativeMenuItemData {
+        NativeMenuItemData::Action {
+            title: title.to_string(),
+            enabled: true,
+            icon: None,
+        }
+    }
+
+    #[test]
+    fn test_menu_items_are_numbered_depth_first() {
+        let items = MenuItem::from_native(&[
+            action("Cut"),
+            NativeMenuItemData::Separator,
+            NativeMenuItemData::Submenu {
+                title: "Share".to_string(),
+                enabled: true,
+                icon: None,
+                items: vec![action("Mail"), action("Messages")],
+            },
+            action("Delete"),
+        ]);
+
+        let indices = |items: &[MenuItem]| {
+            items
+                .iter()
+                .filter_map(|item| match item {
+                    MenuItem::Action { index, .. } => Some(*index),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(indices(&items), vec![0, 3]);
+        let MenuItem::Submenu { items: submenu, .. } = &items[2] else {
+            panic!("expected a submenu");
+        };
+        assert_eq!(indices(submenu), vec![1, 2]);
+    }
+}
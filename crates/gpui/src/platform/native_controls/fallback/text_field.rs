@@ -0,0 +1,918 @@
+//! Editable single-line fields: text fields, search fields and combo boxes.
+
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation as _;
+
+use super::*;
+use crate::{ClipboardItem, FocusHandle, InputHandler, KeyDownEvent, UTF16Selection};
+
+const BULLET: &str = "•";
+
+#[derive(Default)]
+struct Callbacks {
+    on_change: Option<Rc<dyn Fn(String)>>,
+    on_begin_editing: Option<Rc<dyn Fn()>>,
+    on_end_editing: Option<Rc<dyn Fn(String)>>,
+    on_submit: Option<Rc<dyn Fn(String)>>,
+    on_move_up: Option<Rc<dyn Fn()>>,
+    on_move_down: Option<Rc<dyn Fn()>>,
+    on_cancel: Option<Rc<dyn Fn()>>,
+    on_select: Option<Rc<dyn Fn(usize)>>,
+}
+
+impl From<TextFieldCallbacks> for Callbacks {
+    fn from(callbacks: TextFieldCallbacks) -> Self {
+        Self {
+            on_change: callback(callbacks.on_change),
+            on_begin_editing: callback(callbacks.on_begin_editing),
+            on_end_editing: callback(callbacks.on_end_editing),
+            on_submit: callback(callbacks.on_submit),
+            on_move_up: callback(callbacks.on_move_up),
+            on_move_down: callback(callbacks.on_move_down),
+            on_cancel: callback(callbacks.on_cancel),
+            on_select: None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct ComboBox {
+    items: Vec<String>,
+    selected_index: usize,
+    editable: bool,
+    completes: bool,
+}
+
+/// The state of a text field, search field or combo box.
+#[derive(Default)]
+pub(super) struct TextFieldControl {
+    text: String,
+    /// The selection, in UTF-8 offsets into `text`.
+    selected_range: Range<usize>,
+    selection_reversed: bool,
+    marked_range: Option<Range<usize>>,
+    /// The value most recently provided by the element. Edits are kept until the element
+    /// provides a different value, so a field isn't reset while its owner catches up.
+    applied_value: Option<String>,
+    placeholder: String,
+    secure: bool,
+    search: bool,
+    rounded: bool,
+    font_size: Option<Pixels>,
+    enabled: bool,
+    combo: Option<ComboBox>,
+    callbacks: Callbacks,
+    focus_handle: Option<FocusHandle>,
+    editing: bool,
+    selecting: bool,
+    scroll_offset: Pixels,
+    /// Where the text was last painted and how it was shaped, for hit testing.
+    layout: Option<(Point<Pixels>, ShapedLine)>,
+}
+
+impl TextFieldControl {
+    pub(super) fn update_text_field(&mut self, config: TextFieldConfig) {
+        self.placeholder = config.placeholder.to_string();
+        self.secure = config.secure;
+        self.search = false;
+        self.rounded = config.bezel_style == Some(1);
+        self.font_size = config.font_size.map(|size| px(size as f32));
+        self.enabled = config.enabled;
+        self.combo = None;
+        self.callbacks = config.callbacks.into();
+        self.apply_value(config.value);
+    }
+
+    pub(super) fn update_search_field(&mut self, config: SearchFieldConfig) {
+        self.placeholder = config.placeholder.to_string();
+        self.secure = false;
+        self.search = true;
+        self.rounded = true;
+        self.font_size = None;
+        self.enabled = config.enabled;
+        self.combo = None;
+        self.callbacks = config.callbacks.into();
+        self.apply_value(config.value);
+    }
+
+    pub(super) fn update_combo_box(&mut self, config: ComboBoxConfig) {
+        let items = config
+            .items
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        let value = config
+            .value
+            .map(ToString::to_string)
+            .or_else(|| items.get(config.selected_index).cloned())
+            .unwrap_or_default();
+        self.placeholder.clear();
+        self.secure = false;
+        self.search = false;
+        self.rounded = false;
+        self.font_size = None;
+        self.enabled = config.enabled;
+        self.combo = Some(ComboBox {
+            items,
+            selected_index: config.selected_index,
+            editable: config.editable,
+            completes: config.completes,
+        });
+        self.callbacks = Callbacks {
+            on_change: callback(config.callbacks.on_change),
+            on_submit: callback(config.callbacks.on_submit),
+            on_select: callback(config.callbacks.on_select),
+            ..Default::default()
+        };
+        self.apply_value(&value);
+    }
+
+    fn apply_value(&mut self, value: &str) {
+        if self.applied_value.as_deref() == Some(value) {
+            return;
+        }
+        self.applied_value = Some(value.to_string());
+        if self.text != value {
+            self.text = value.to_string();
+            self.selected_range = self.text.len()..self.text.len();
+            self.marked_range = None;
+        }
+    }
+
+    pub(super) fn text(&self) -> String {
+        self.text.clone()
+    }
+
+    fn editable(&self) -> bool {
+        self.enabled && self.combo.as_ref().is_none_or(|combo| combo.editable)
+    }
+
+    fn cursor_offset(&self) -> usize {
+        if self.selection_reversed {
+            self.selected_range.start
+        } else {
+            self.selected_range.end
+        }
+    }
+
+    fn move_to(&mut self, offset: usize) {
+        self.selected_range = offset..offset;
+        self.selection_reversed = false;
+    }
+
+    fn select_to(&mut self, offset: usize) {
+        if self.selection_reversed {
+            self.selected_range.start = offset;
+        } else {
+            self.selected_range.end = offset;
+        }
+        if self.selected_range.end < self.selected_range.start {
+            self.selection_reversed = !self.selection_reversed;
+            self.selected_range = self.selected_range.end..self.selected_range.start;
+        }
+    }
+
+    fn select_all(&mut self) {
+        self.selected_range = 0..self.text.len();
+        self.selection_reversed = false;
+    }
+
+    fn select_word(&mut self, offset: usize) {
+        let word = self
+            .text
+            .split_word_bound_indices()
+            .map(|(start, word)| start..start + word.len())
+            .find(|range| range.contains(&offset) || range.end == offset);
+        if let Some(word) = word {
+            self.selected_range = word;
+            self.selection_reversed = false;
+        }
+    }
+
+    fn previous_boundary(&self, offset: usize) -> usize {
+        self.text
+            .grapheme_indices(true)
+            .rev()
+            .find_map(|(index, _)| (index < offset).then_some(index))
+            .unwrap_or(0)
+    }
+
+    fn next_boundary(&self, offset: usize) -> usize {
+        self.text
+            .grapheme_indices(true)
+            .find_map(|(index, _)| (index > offset).then_some(index))
+            .unwrap_or(self.text.len())
+    }
+
+    /// Replaces `range` with `new_text`, leaving the cursor after it. Combo boxes that
+    /// complete then select the rest of the first item starting with the typed text.
+    fn replace(&mut self, range: Range<usize>, new_text: &str) {
+        self.text.replace_range(range.clone(), new_text);
+        self.move_to(range.start + new_text.len());
+        self.marked_range = None;
+
+        if let Some(combo) = self.combo.as_ref().filter(|combo| combo.completes)
+            && !new_text.is_empty()
+            && self.cursor_offset() == self.text.len()
+        {
+            let typed = self.text.to_lowercase();
+            if let Some(item) = combo.items.iter().find(|item| {
+                item.len() > self.text.len() && item.to_lowercase().starts_with(&typed)
+            }) {
+                let typed_len = self.text.len();
+                self.text = item.clone();
+                self.selected_range = typed_len..self.text.len();
+            }
+        }
+
+        if let Some(on_change) = self.callbacks.on_change.clone() {
+            on_change(self.text.clone());
+        }
+    }
+
+    fn delete_backward(&mut self) {
+        if self.selected_range.is_empty() {
+            self.select_to(self.previous_boundary(self.cursor_offset()));
+        }
+        self.replace(self.selected_range.clone(), "");
+    }
+
+    fn delete_forward(&mut self) {
+        if self.selected_range.is_empty() {
+            self.select_to(self.next_boundary(self.cursor_offset()));
+        }
+        self.replace(self.selected_range.clone(), "");
+    }
+
+    /// Handles a key press while the field is focused, returning whether it was consumed.
+    fn handle_key(&mut self, event: &KeyDownEvent, cx: &mut App) -> bool {
+        let modifiers = event.keystroke.modifiers;
+        let extend = modifiers.shift;
+        let editable = self.editable();
+        match event.keystroke.key.as_str() {
+            "left" | "right" | "home" | "end" => {
+                let key = event.keystroke.key.as_str();
+                let offset = match key {
+                    "home" => 0,
+                    "end" => self.text.len(),
+                    "left" if modifiers.secondary() => 0,
+                    "right" if modifiers.secondary() => self.text.len(),
+                    "left" if !extend && !self.selected_range.is_empty() => {
+                        self.selected_range.start
+                    }
+                    "right" if !extend && !self.selected_range.is_empty() => {
+                        self.selected_range.end
+                    }
+                    "left" => self.previous_boundary(self.cursor_offset()),
+                    _ => self.next_boundary(self.cursor_offset()),
+                };
+                if extend {
+                    self.select_to(offset);
+                } else {
+                    self.move_to(offset);
+                }
+            }
+            "backspace" if editable => self.delete_backward(),
+            "delete" if editable => self.delete_forward(),
+            "enter" => {
+                if let Some(on_submit) = self.callbacks.on_submit.clone() {
+                    on_submit(self.text.clone());
+                }
+            }
+            "escape" => {
+                if self.search && !self.text.is_empty() {
+                    self.select_all();
+                    self.replace(self.selected_range.clone(), "");
+                }
+                if let Some(on_cancel) = self.callbacks.on_cancel.clone() {
+                    on_cancel();
+                }
+            }
+            "up" => match self.callbacks.on_move_up.clone() {
+                Some(on_move_up) => on_move_up(),
+                None => self.move_to(0),
+            },
+            "down" => match self.callbacks.on_move_down.clone() {
+                Some(on_move_down) => on_move_down(),
+                None => self.move_to(self.text.len()),
+            },
+            "a" if modifiers.secondary() => self.select_all(),
+            "c" | "x" if modifiers.secondary() => {
+                // Like native secure fields, passwords can't be copied out.
+                if self.secure || self.selected_range.is_empty() {
+                    return true;
+                }
+                cx.write_to_clipboard(ClipboardItem::new_string(
+                    self.text[self.selected_range.clone()].to_string(),
+                ));
+                if event.keystroke.key == "x" && editable {
+                    self.replace(self.selected_range.clone(), "");
+                }
+            }
+            "v" if modifiers.secondary() && editable => {
+                if let Some(text) = cx.read_from_clipboard().and_then(|item| item.text()) {
+                    self.replace(self.selected_range.clone(), &text.replace('\n', " "));
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// The text as it's drawn, with secure fields hiding their characters.
+    fn display_text(&self) -> String {
+        if self.secure {
+            BULLET.repeat(self.text.chars().count())
+        } else {
+            self.text.clone()
+        }
+    }
+
+    fn display_offset(&self, offset: usize) -> usize {
+        if self.secure {
+            self.text[..offset].chars().count() * BULLET.len()
+        } else {
+            offset
+        }
+    }
+
+    fn offset_for_display(&self, display_offset: usize) -> usize {
+        if self.secure {
+            self.text
+                .char_indices()
+                .nth(display_offset / BULLET.len())
+                .map_or(self.text.len(), |(index, _)| index)
+        } else {
+            display_offset
+        }
+    }
+
+    fn offset_for_position(&self, position: Point<Pixels>) -> usize {
+        let Some((origin, line)) = self.layout.as_ref() else {
+            return self.text.len();
+        };
+        let display_offset = line.closest_index_for_x(position.x - origin.x);
+        self.offset_for_display(display_offset)
+    }
+
+    fn offset_to_utf16(&self, offset: usize) -> usize {
+        self.text[..offset].encode_utf16().count()
+    }
+
+    fn offset_from_utf16(&self, offset: usize) -> usize {
+        let mut utf16_count = 0;
+        for (index, ch) in self.text.char_indices() {
+            if utf16_count >= offset {
+                return index;
+            }
+            utf16_count += ch.len_utf16();
+        }
+        self.text.len()
+    }
+
+    fn range_to_utf16(&self, range: &Range<usize>) -> Range<usize> {
+        self.offset_to_utf16(range.start)..self.offset_to_utf16(range.end)
+    }
+
+    fn range_from_utf16(&self, range: &Range<usize>) -> Range<usize> {
+        self.offset_from_utf16(range.start)..self.offset_from_utf16(range.end)
+    }
+}
+
+/// Feeds platform text input into a fallback field.
+struct FieldInputHandler(Rc<RefCell<TextFieldControl>>);
+
+impl InputHandler for FieldInputHandler {
+    fn selected_text_range(
+        &mut self,
+        _ignore_disabled_input: bool,
+        _window: &mut Window,
+        _cx: &mut App,
+    ) -> Option<UTF16Selection> {
+        let field = self.0.borrow();
+        Some(UTF16Selection {
+            range: field.range_to_utf16(&field.selected_range),
+            reversed: field.selection_reversed,
+        })
+    }
+
+    fn marked_text_range(&mut self, _window: &mut Window, _cx: &mut App) -> Option<Range<usize>> {
+        let field = self.0.borrow();
+        field
+            .marked_range
+            .as_ref()
+            .map(|range| field.range_to_utf16(range))
+    }
+
+    fn text_for_range(
+        &mut self,
+        range_utf16: Range<usize>,
+        adjusted_range: &mut Option<Range<usize>>,
+        _window: &mut Window,
+        _cx: &mut App,
+    ) -> Option<String> {
+        let field = self.0.borrow();
+        let range = field.range_from_utf16(&range_utf16);
+        adjusted_range.replace(field.range_to_utf16(&range));
+        Some(field.text[range].to_string())
+    }
+
+    fn replace_text_in_range(
+        &mut self,
+        replacement_range: Option<Range<usize>>,
+        text: &str,
+        window: &mut Window,
+        _cx: &mut App,
+    ) {
+        let mut field = self.0.borrow_mut();
+        if !field.editable() {
+            return;
+        }
+        let range = replacement_range
+            .map(|range| field.range_from_utf16(&range))
+            .or(field.marked_range.clone())
+            .unwrap_or(field.selected_range.clone());
+        field.replace(range, &text.replace('\n', " "));
+        window.refresh();
+    }
+
+    fn replace_and_mark_text_in_range(
+        &mut self,
+        range_utf16: Option<Range<usize>>,
+        new_text: &str,
+        new_selected_range: Option<Range<usize>>,
+        window: &mut Window,
+        _cx: &mut App,
+    ) {
+        let mut field = self.0.borrow_mut();
+        if !field.editable() {
+            return;
+        }
+        let range = range_utf16
+            .map(|range| field.range_from_utf16(&range))
+            .or(field.marked_range.clone())
+            .unwrap_or(field.selected_range.clone());
+        field.text.replace_range(range.clone(), new_text);
+        field.marked_range =
+            (!new_text.is_empty()).then(|| range.start..range.start + new_text.len());
+        field.selected_range = new_selected_range
+            .map(|selected| {
+                let selected = field.range_from_utf16(&selected);
+                range.start + selected.start..range.start + selected.end
+            })
+            .unwrap_or_else(|| {
+                let end = range.start + new_text.len();
+                end..end
+            });
+        field.selection_reversed = false;
+        window.refresh();
+    }
+
+    fn unmark_text(&mut self, _window: &mut Window, _cx: &mut App) {
+        self.0.borrow_mut().marked_range = None;
+    }
+
+    fn bounds_for_range(
+        &mut self,
+        range_utf16: Range<usize>,
+        _window: &mut Window,
+        _cx: &mut App,
+    ) -> Option<Bounds<Pixels>> {
+        let field = self.0.borrow();
+        let (origin, line) = field.layout.as_ref()?;
+        let range = field.range_from_utf16(&range_utf16);
+        let start = line.x_for_index(field.display_offset(range.start));
+        let end = line.x_for_index(field.display_offset(range.end));
+        Some(Bounds::from_corners(
+            point(origin.x + start, origin.y),
+            point(origin.x + end, origin.y + line_height(line.font_size)),
+        ))
+    }
+
+    fn character_index_for_point(
+        &mut self,
+        point: Point<Pixels>,
+        _window: &mut Window,
+        _cx: &mut App,
+    ) -> Option<usize> {
+        let field = self.0.borrow();
+        let offset = field.offset_for_position(point);
+        Some(field.offset_to_utf16(offset))
+    }
+}
+
+pub(super) fn paint_text_field(
+    control: &Rc<RefCell<TextFieldControl>>,
+    bounds: Bounds<Pixels>,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let palette = palette(window);
+    let focus_handle = control
+        .borrow_mut()
+        .focus_handle
+        .get_or_insert_with(|| cx.focus_handle())
+        .clone();
+    let focused = focus_handle.is_focused(window);
+    if control.borrow().enabled {
+        if focused {
+            window.next_frame.focus = Some(focus_handle.id);
+        }
+        window
+            .next_frame
+            .dispatch_tree
+            .set_focus_id(focus_handle.id);
+    }
+    track_editing(control, focused);
+
+    let mut field = control.borrow_mut();
+    let radius = if field.search {
+        bounds.size.height / 2.
+    } else if field.rounded || field.combo.is_some() {
+        CORNER_RADIUS
+    } else {
+        px(2.)
+    };
+    let border = if focused {
+        palette.accent
+    } else {
+        palette.border
+    };
+    let background = if field.editable() || field.combo.is_some() {
+        palette.field
+    } else {
+        palette.control
+    };
+    paint_rect(bounds, radius, background, Some(border), window);
+
+    let mut text_bounds = bounds.dilate(px(-1.));
+    text_bounds.origin.x += px(6.);
+    text_bounds.size.width -= px(12.);
+    if field.search {
+        let icon_bounds = Bounds::new(
+            point(bounds.left() + px(4.), bounds.top()),
+            size(px(16.), bounds.size.height),
+        );
+        paint_text(
+            symbol_glyph("magnifyingglass"),
+            icon_bounds,
+            TextAlign::Center,
+            palette.muted_text,
+            window,
+            cx,
+        );
+        text_bounds.origin.x += px(14.);
+        text_bounds.size.width -= px(14.);
+    }
+    let accessory = if (field.search && !field.text.is_empty()) || field.combo.is_some() {
+        text_bounds.size.width -= px(16.);
+        Some(Bounds::from_corners(
+            point(bounds.right() - px(20.), bounds.top()),
+            bounds.bottom_right(),
+        ))
+    } else {
+        None
+    };
+    if let Some(accessory) = accessory {
+        if field.combo.is_some() {
+            paint_chevron(accessory, false, palette.text(field.enabled), window);
+        } else {
+            paint_text(
+                symbol_glyph("xmark"),
+                accessory,
+                TextAlign::Center,
+                palette.muted_text,
+                window,
+                cx,
+            );
+        }
+    }
+
+    let font_size = field.font_size.unwrap_or(FONT_SIZE);
+    let line_height = line_height(font_size);
+    let text_top = text_bounds.center().y - line_height / 2.;
+    if field.text.is_empty() && field.marked_range.is_none() {
+        field.layout = None;
+        field.scroll_offset = px(0.);
+        if !field.placeholder.is_empty() {
+            let line = shape_text(&field.placeholder, font_size, palette.muted_text, window);
+            window.with_content_mask(
+                Some(ContentMask {
+                    bounds: text_bounds,
+                }),
+                |window| {
+                    line.paint(
+                        point(text_bounds.left(), text_top),
+                        line_height,
+                        TextAlign::Left,
+                        None,
+                        window,
+                        cx,
+                    )
+                    .log_err();
+                },
+            );
+        }
+    } else {
+        let line = shape_text(
+            &field.display_text(),
+            font_size,
+            palette.text(field.enabled),
+            window,
+        );
+
+        // Scroll just enough to keep the cursor visible.
+        let cursor_x = line.x_for_index(field.display_offset(field.cursor_offset()));
+        if cursor_x - field.scroll_offset > text_bounds.size.width {
+            field.scroll_offset = cursor_x - text_bounds.size.width;
+        } else if cursor_x < field.scroll_offset {
+            field.scroll_offset = cursor_x;
+        }
+        field.scroll_offset = field
+            .scroll_offset
+            .min((line.width - text_bounds.size.width).max(px(0.)));
+        let origin = point(text_bounds.left() - field.scroll_offset, text_top);
+
+        window.with_content_mask(
+            Some(ContentMask {
+                bounds: text_bounds,
+            }),
+            |window| {
+                if focused && !field.selected_range.is_empty() {
+                    let start = line.x_for_index(field.display_offset(field.selected_range.start));
+                    let end = line.x_for_index(field.display_offset(field.selected_range.end));
+                    paint_rect(
+                        Bounds::from_corners(
+                            point(origin.x + start, origin.y),
+                            point(origin.x + end, origin.y + line_height),
+                        ),
+                        px(0.),
+                        palette.selection,
+                        None,
+                        window,
+                    );
+                }
+                line.paint(origin, line_height, TextAlign::Left, None, window, cx)
+                    .log_err();
+                if let Some(marked) = field.marked_range.clone() {
+                    let start = line.x_for_index(field.display_offset(marked.start));
+                    let end = line.x_for_index(field.display_offset(marked.end));
+                    paint_rect(
+                        Bounds::from_corners(
+                            point(origin.x + start, origin.y + line_height - px(1.)),
+                            point(origin.x + end, origin.y + line_height),
+                        ),
+                        px(0.),
+                        palette.text,
+                        None,
+                        window,
+                    );
+                }
+            },
+        );
+        field.layout = Some((origin, line));
+    }
+
+    if focused && field.selected_range.is_empty() && field.editable() {
+        let cursor_x = field
+            .layout
+            .as_ref()
+            .map_or(text_bounds.left(), |(origin, line)| {
+                origin.x + line.x_for_index(field.display_offset(field.cursor_offset()))
+            });
+        paint_rect(
+            Bounds::new(point(cursor_x, text_top), size(px(1.5), line_height)),
+            px(0.),
+            palette.accent,
+            None,
+            window,
+        );
+    }
+
+    if !field.enabled {
+        return;
+    }
+    let opens_list = field.combo.as_ref().is_some_and(|combo| !combo.editable);
+    drop(field);
+
+    window.handle_input(&focus_handle, FieldInputHandler(control.clone()), cx);
+
+    let region = hit_region(bounds, window);
+    let accessory_region = accessory.map(|accessory| hit_region(accessory, window));
+    let down_control = control.clone();
+    let down_focus = focus_handle.clone();
+    on_mouse_down(
+        region,
+        MouseButton::Left,
+        window,
+        move |event, window, cx| {
+            down_focus.focus(window, cx);
+            window.refresh();
+            if opens_list
+                || accessory_region.is_some_and(|accessory| accessory.contains(&event.position))
+            {
+                let mut field = down_control.borrow_mut();
+                if field.combo.is_some() {
+                    drop(field);
+                    open_combo_list(&down_control, bounds, window);
+                } else {
+                    field.select_all();
+                    let range = field.selected_range.clone();
+                    field.replace(range, "");
+                }
+                return;
+            }
+
+            let mut field = down_control.borrow_mut();
+            let offset = field.offset_for_position(event.position);
+            match event.click_count {
+                1 if event.modifiers.shift => field.select_to(offset),
+                1 => field.move_to(offset),
+                2 => field.select_word(offset),
+                _ => field.select_all(),
+            }
+            field.selecting = true;
+        },
+    );
+
+    let move_control = control.clone();
+    window.on_mouse_event(move |event: &MouseMoveEvent, phase, window, _| {
+        let mut field = move_control.borrow_mut();
+        if phase != DispatchPhase::Bubble || !field.selecting {
+            return;
+        }
+        if event.pressed_button != Some(MouseButton::Left) {
+            field.selecting = false;
+            return;
+        }
+        let offset = field.offset_for_position(event.position);
+        field.select_to(offset);
+        window.refresh();
+    });
+
+    let up_control = control.clone();
+    window.on_mouse_event(move |_: &MouseUpEvent, phase, _, _| {
+        if phase == DispatchPhase::Bubble {
+            up_control.borrow_mut().selecting = false;
+        }
+    });
+
+    let key_control = control.clone();
+    window.on_key_event(move |event: &KeyDownEvent, phase, window, cx| {
+        if phase != DispatchPhase::Bubble || !focus_handle.is_focused(window) {
+            return;
+        }
+        let opens_list = event.keystroke.key == "down"
+            && key_control
+                .borrow()
+                .combo
+                .as_ref()
+                .is_some_and(|combo| !combo.items.is_empty());
+        if opens_list {
+            open_combo_list(&key_control, bounds, window);
+        } else if !key_control.borrow_mut().handle_key(event, cx) {
+            return;
+        }
+        window.refresh();
+        cx.stop_propagation();
+    });
+}
+
+/// Reports when the field gains or loses focus.
+fn track_editing(control: &Rc<RefCell<TextFieldControl>>, focused: bool) {
+    let mut field = control.borrow_mut();
+    if field.editing == focused {
+        return;
+    }
+    field.editing = focused;
+    if focused {
+        if let Some(on_begin_editing) = field.callbacks.on_begin_editing.clone() {
+            on_begin_editing();
+        }
+    } else if let Some(on_end_editing) = field.callbacks.on_end_editing.clone() {
+        on_end_editing(field.text.clone());
+    }
+}
+
+fn open_combo_list(
+    control: &Rc<RefCell<TextFieldControl>>,
+    bounds: Bounds<Pixels>,
+    window: &mut Window,
+) {
+    let items = {
+        let field = control.borrow();
+        let Some(combo) = field.combo.as_ref() else {
+            return;
+        };
+        MenuItem::from_list(&combo.items, Some(combo.selected_index))
+    };
+    let control = control.clone();
+    window
+        .fallback_native_controls
+        .open_overlay(Overlay::Menu(Menu::new(
+            bounds.bottom_left(),
+            bounds.size.width,
+            items,
+            Box::new(move |index| {
+                let Some(index) = index else {
+                    return;
+                };
+                let on_select = {
+                    let mut field = control.borrow_mut();
+                    let Some(combo) = field.combo.as_mut() else {
+                        return;
+                    };
+                    combo.selected_index = index;
+                    let Some(item) = combo.items.get(index).cloned() else {
+                        return;
+                    };
+                    let end = item.len();
+                    field.text = item;
+                    field.move_to(end);
+                    field.callbacks.on_select.clone()
+                };
+                if let Some(on_select) = on_select {
+                    on_select(index);
+                }
+            }),
+        )));
+    window.refresh();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(text: &str) -> TextFieldControl {
+        let mut field = TextFieldControl {
+            enabled: true,
+            ..Default::default()
+        };
+        field.apply_value(text);
+        field
+    }
+
+    #[test]
+    fn test_editing() {
+        let mut field = field("héllo");
+        assert_eq!(field.selected_range, 6..6);
+
+        field.delete_backward();
+        assert_eq!(field.text, "héll");
+        field.move_to(1);
+        field.select_to(field.next_boundary(1));
+        assert_eq!(field.selected_range, 1..3);
+        field.replace(field.selected_range.clone(), "e");
+        assert_eq!(field.text, "hell");
+        assert_eq!(field.selected_range, 2..2);
+
+        field.select_to(0);
+        assert!(field.selection_reversed);
+        assert_eq!(field.selected_range, 0..2);
+        field.delete_forward();
+        assert_eq!(field.text, "ll");
+
+        // Re-applying the same value doesn't clobber edits.
+        field.apply_value("héllo");
+        assert_eq!(field.text, "ll");
+        field.apply_value("new");
+        assert_eq!(field.text, "new");
+    }
+
+    #[test]
+    fn test_utf16_offsets() {
+        let field = field("a😀b");
+        assert_eq!(field.offset_to_utf16(5), 3);
+        assert_eq!(field.offset_from_utf16(3), 5);
+        assert_eq!(field.range_from_utf16(&(1..3)), 1..5);
+    }
+
+    #[test]
+    fn test_secure_display_offsets() {
+        let mut field = field("pä");
+        field.secure = true;
+        assert_eq!(field.display_text(), "••");
+        assert_eq!(field.display_offset(3), 2 * BULLET.len());
+        assert_eq!(field.offset_for_display(BULLET.len()), 1);
+    }
+
+    #[test]
+    fn test_combo_box_completion() {
+        let mut field = field("");
+        field.combo = Some(ComboBox {
+            items: vec!["Apple".into(), "Apricot".into()],
+            editable: true,
+            completes: true,
+            ..Default::default()
+        });
+        field.replace(0..0, "a");
+        field.replace(field.selected_range.clone(), "p");
+        assert_eq!(field.text, "Apple");
+        assert_eq!(field.selected_range, 2..5);
+
+        field.replace(field.selected_range.clone(), "r");
+        assert_eq!(field.text, "Apricot");
+        assert_eq!(field.selected_range, 3..7);
+    }
+}
@@ -0,0 +1,166 @@
+use crate::SharedString;
+
+/// Identifies a notification shown with [`crate::App::show_notification`], so it can be replaced
+/// or closed later.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NotificationId(pub u64);
+
+/// How urgently a notification needs the user's attention.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NotificationUrgency {
+    /// The notification can be shown without interrupting the user.
+    Low,
+    /// The notification is shown as usual.
+    #[default]
+    Normal,
+    /// The notification stays visible until the user responds to it.
+    Critical,
+}
+
+/// A button shown on a notification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NotificationAction {
+    /// Identifies the action in [`NotificationResponse::Action`].
+    pub id: SharedString,
+    /// The label of the button.
+    pub label: SharedString,
+}
+
+/// A system notification, see [`crate::App::show_notification`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Notification {
+    /// The title of the notification.
+    pub title: SharedString,
+    /// The text of the notification, below its title.
+    pub body: Option<SharedString>,
+    /// The icon of the notification, either the name of an icon in the system's icon theme or
+    /// the path or URL of an image.
+    pub icon: Option<SharedString>,
+    /// How urgently the notification needs the user's attention.
+    pub urgency: NotificationUrgency,
+    /// The buttons shown on the notification, when the platform supports them.
+    pub actions: Vec<NotificationAction>,
+    /// The notification this one replaces, rather than being shown alongside it.
+    pub replaces: Option<NotificationId>,
+}
+
+impl Notification {
+    /// Create a notification with the given title.
+    pub fn new(title: impl Into<SharedString>) -> Self {
+        Self {
+            title: title.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the text of the notification.
+    pub fn body(mut self, body: impl Into<SharedString>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// Set the icon of the notification.
+    pub fn icon(mut self, icon: impl Into<SharedString>) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
+
+    /// Set how urgently the notification needs the user's attention.
+    pub fn urgency(mut self, urgency: NotificationUrgency) -> Self {
+        self.urgency = urgency;
+        self
+    }
+
+    /// Add a button to the notification.
+    pub fn action(mut self, id: impl Into<SharedString>, label: impl Into<SharedString>) -> Self {
+        self.actions.push(NotificationAction {
+            id: id.into(),
+            label: label.into(),
+        });
+        self
+    }
+
+    /// Replace a notification that was shown before, rather than showing another one.
+    pub fn replace(mut self, id: NotificationId) -> Self {
+        self.replaces = Some(id);
+        self
+    }
+}
+
+/// How the user responded to a notification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NotificationResponse {
+    /// The notification itself was clicked.
+    Activated,
+    /// One of the notification's buttons was clicked, with the id of its action.
+    Action(SharedString),
+    /// The notification was dismissed or expired, or closed with
+    /// [`crate::App::close_notification`]. No further responses follow.
+    Closed,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::{Notification, NotificationResponse, NotificationUrgency};
+    use crate::{self as gpui, TestAppContext};
+
+    #[gpui::test]
+    fn test_notifications(cx: &mut TestAppContext) {
+        let responses = Rc::new(RefCell::new(Vec::new()));
+        let id = cx.update(|cx| {
+            let responses = responses.clone();
+            cx.show_notification(
+                Notification::new("Build finished")
+                    .body("3 warnings")
+                    .action("open", "Open Log"),
+                move |response, _| responses.borrow_mut().push(response),
+            )
+        });
+        let notifications = cx.shown_notifications();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].0, id);
+        assert_eq!(notifications[0].1.body, Some("3 warnings".into()));
+
+        cx.simulate_notification_response(id, NotificationResponse::Action("open".into()));
+        cx.simulate_notification_response(id, NotificationResponse::Activated);
+        assert_eq!(
+            *responses.borrow(),
+            [
+                NotificationResponse::Action("open".into()),
+                NotificationResponse::Activated
+            ]
+        );
+
+        let replacement = cx.update(|cx| {
+            cx.show_notification(
+                Notification::new("Build failed")
+                    .urgency(NotificationUrgency::Critical)
+                    .replace(id),
+                |_, _| {},
+            )
+        });
+        assert_eq!(replacement, id);
+        let notifications = cx.shown_notifications();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].1.title, "Build failed");
+
+        cx.update(|cx| cx.close_notification(id));
+        cx.run_until_parked();
+        assert!(cx.shown_notifications().is_empty());
+        assert_eq!(responses.borrow().len(), 2);
+
+        let other = cx.update(|cx| {
+            let responses = responses.clone();
+            cx.show_notification(Notification::new("Tests passed"), move |response, _| {
+                responses.borrow_mut().push(response)
+            })
+        });
+        assert_ne!(other, id);
+        cx.simulate_notification_response(other, NotificationResponse::Closed);
+        cx.simulate_notification_response(other, NotificationResponse::Activated);
+        assert_eq!(responses.borrow().last(), Some(&NotificationResponse::Closed));
+        assert_eq!(responses.borrow().len(), 3);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{MenuItem, SharedString};
+
+/// An icon in the system tray or status area, see [`crate::App::set_status_item`].
+pub struct StatusItem {
+    /// The icon, either the name of an icon in the system's icon theme or the path of an image.
+    pub icon: SharedString,
+    /// The text shown when hovering over the icon.
+    pub tooltip: Option<SharedString>,
+    /// The items in the menu shown when the icon is right-clicked. Their actions are dispatched
+    /// like those of the application menus.
+    pub menu: Vec<MenuItem>,
+}
+
+impl StatusItem {
+    /// Create a status item with the given icon.
+    pub fn new(icon: impl Into<SharedString>) -> Self {
+        Self {
+            icon: icon.into(),
+            tooltip: None,
+            menu: Vec::new(),
+        }
+    }
+
+    /// Set the text shown when hovering over the icon.
+    pub fn tooltip(mut self, tooltip: impl Into<SharedString>) -> Self {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+
+    /// Set the items in the menu shown when the icon is right-clicked.
+    pub fn menu(mut self, menu: Vec<MenuItem>) -> Self {
+        self.menu = menu;
+        self
+    }
+}
+
+/// How a status item was clicked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusItemClick {
+    /// The icon was clicked, usually with the left mouse button.
+    Primary,
+    /// The icon was clicked in another way, usually with the middle mouse button.
+    Secondary,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::{StatusItem, StatusItemClick};
+    use crate::{self as gpui, MenuItem, NoAction, TestAppContext};
+
+    #[gpui::test]
+    fn test_status_item(cx: &mut TestAppContext) {
+        let clicks = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|cx| {
+            let clicks = clicks.clone();
+            cx.set_status_item(
+                StatusItem::new("sync-idle")
+                    .tooltip("Up to date")
+                    .menu(vec![MenuItem::action("Sync Now", NoAction)]),
+                move |click, _| clicks.borrow_mut().push(click),
+            );
+        });
+        cx.read_status_item(|item| {
+            let item = item.unwrap();
+            assert_eq!(item.icon, "sync-idle");
+            assert_eq!(item.tooltip, Some("Up to date".into()));
+            assert_eq!(item.menu.len(), 1);
+        });
+
+        cx.simulate_status_item_click(StatusItemClick::Primary);
+        cx.simulate_status_item_click(StatusItemClick::Secondary);
+        assert_eq!(
+            *clicks.borrow(),
+            [StatusItemClick::Primary, StatusItemClick::Secondary]
+        );
+
+        cx.update(|cx| cx.remove_status_item());
+        cx.read_status_item(|item| assert!(item.is_none()));
+        cx.simulate_status_item_click(StatusItemClick::Primary);
+        assert_eq!(clicks.borrow().len(), 2);
+    }
+}
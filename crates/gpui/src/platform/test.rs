@@ -1,11 +1,17 @@
 mod dispatcher;
 mod display;
+mod native_controls;
 mod platform;
 mod window;
 
 pub use dispatcher::*;
 pub(crate) use display::*;
+pub(crate) use native_controls::*;
 pub(crate) use platform::*;
 pub(crate) use window::*;
 
+pub use native_controls::{
+    TestNativeAlert, TestNativeControl, TestNativeControlEvent, TestNativeControlId,
+    TestNativeControlKind, TestNativeControlValue, TestNativePresentation,
+};
 pub use platform::{TestScreenCaptureSource, TestScreenCaptureStream};
@@ -0,0 +1,1337 @@
+use crate::{
+    Bounds, Pixels, Point, Size,
+    platform::native_controls::{
+        ALERT_FIRST_BUTTON_RETURN, AlertConfig, AlertStyle, ButtonConfig, CheckboxConfig,
+        CollectionViewConfig, ComboBoxConfig, GlassEffectViewConfig, ImageViewConfig,
+        MenuButtonConfig, NativeControlState, NativeMenuItemData, NativeOutlineNodeData,
+        OutlineViewConfig, PanelConfig, PlatformNativeControls, PopoverConfig, PopupButtonConfig,
+        ProgressConfig, SearchFieldConfig, SegmentedControlConfig, SidebarViewConfig, SliderConfig,
+        StackViewConfig, StepperConfig, SwitchConfig, TabViewConfig, TableViewConfig,
+        TextFieldCallbacks, TextFieldConfig, TrackingViewConfig, VisualEffectViewConfig,
+    },
+    point, px, size,
+};
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    ffi::c_void,
+    rc::{Rc, Weak},
+};
+
+/// Identifies a native control recorded by a test window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TestNativeControlId(usize);
+
+/// The kind of native control an element asked the test platform for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[expect(missing_docs)]
+pub enum TestNativeControlKind {
+    Button,
+    Checkbox,
+    Switch,
+    Slider,
+    Stepper,
+    SegmentedControl,
+    PopupButton,
+    Progress,
+    SearchField,
+    TextField,
+    ComboBox,
+    ImageView,
+    TabView,
+    TableView,
+    OutlineView,
+    CollectionView,
+    MenuButton,
+    VisualEffectView,
+    GlassEffectView,
+    TrackingView,
+    StackView,
+    Sidebar,
+}
+
+/// The value a native control displays.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TestNativeControlValue {
+    /// The state of a checkbox or switch.
+    Bool(bool),
+    /// The value of a slider, stepper or progress indicator.
+    Number(f64),
+    /// The contents of a text field, search field or combo box.
+    Text(String),
+}
+
+/// The most recent configuration of a native control in a test window.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestNativeControl {
+    /// Identifies the control for [`VisualTestContext::simulate_native_control_event`](crate::VisualTestContext::simulate_native_control_event).
+    pub id: TestNativeControlId,
+    /// The kind of control.
+    pub kind: TestNativeControlKind,
+    /// Where the control was laid out, in window coordinates.
+    pub bounds: Bounds<Pixels>,
+    /// The control's title, label or placeholder.
+    pub title: Option<String>,
+    /// Whether the control accepts input.
+    pub enabled: bool,
+    /// The value the control displays.
+    pub value: Option<TestNativeControlValue>,
+    /// The items the control chooses between. Outline views list every node depth-first,
+    /// as if fully expanded.
+    pub items: Vec<String>,
+    /// The selected item, if any.
+    pub selected_index: Option<usize>,
+}
+
+/// An interaction simulated on a native control. Each event invokes the matching callback
+/// the control was last configured with, the same way the platform would.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TestNativeControlEvent {
+    /// Clicks a button.
+    Click,
+    /// Changes the control's value, e.g. toggling a checkbox or typing into a text field.
+    Change(TestNativeControlValue),
+    /// Selects one of the control's items.
+    Select(usize),
+    /// Presses enter in a text field, submitting its current value.
+    Submit,
+    /// Presses escape in a text field.
+    Cancel,
+    /// Focuses a text field.
+    BeginEditing,
+    /// Moves focus out of a text field.
+    EndEditing,
+    /// Clicks one of a sidebar's header buttons.
+    HeaderButton(usize),
+}
+
+/// An alert shown through the test window's native controls.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestNativeAlert {
+    /// How the alert was styled.
+    pub style: AlertStyle,
+    /// The alert's message.
+    pub message: String,
+    /// The secondary text below the message.
+    pub informative_text: Option<String>,
+    /// The titles of the alert's buttons.
+    pub button_titles: Vec<String>,
+    /// Whether the alert was shown as a sheet rather than a modal dialog.
+    pub sheet: bool,
+}
+
+/// Something a test window's native controls presented on top of its content.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TestNativePresentation {
+    /// A panel was shown.
+    Panel {
+        /// The panel's size when it was shown.
+        size: Size<Pixels>,
+        /// Whether the panel was centered on the screen.
+        centered: bool,
+    },
+    /// A popover was shown from a toolbar item.
+    Popover {
+        /// The popover's content size.
+        size: Size<Pixels>,
+    },
+    /// An alert was shown.
+    Alert(TestNativeAlert),
+    /// A context menu was shown.
+    ContextMenu {
+        /// The titles of the menu's actions, numbered the way selections are reported.
+        items: Vec<String>,
+        /// Where the menu was shown, in window coordinates.
+        position: Point<Pixels>,
+    },
+}
+
+#[derive(Default)]
+struct Callbacks {
+    on_click: Option<Rc<dyn Fn()>>,
+    on_toggle: Option<Rc<dyn Fn(bool)>>,
+    on_number: Option<Rc<dyn Fn(f64)>>,
+    on_text: Option<Rc<dyn Fn(String)>>,
+    on_submit: Option<Rc<dyn Fn(String)>>,
+    on_cancel: Option<Rc<dyn Fn()>>,
+    on_begin_editing: Option<Rc<dyn Fn()>>,
+    on_end_editing: Option<Rc<dyn Fn(String)>>,
+    on_select: Option<Rc<dyn Fn(usize)>>,
+    on_header_button: Option<Rc<dyn Fn(usize)>>,
+}
+
+impl From<TextFieldCallbacks> for Callbacks {
+    fn from(callbacks: TextFieldCallbacks) -> Self {
+        Self {
+            on_text: callbacks.on_change.map(Rc::from),
+            on_submit: callbacks.on_submit.map(Rc::from),
+            on_cancel: callbacks.on_cancel.map(Rc::from),
+            on_begin_editing: callbacks.on_begin_editing.map(Rc::from),
+            on_end_editing: callbacks.on_end_editing.map(Rc::from),
+            ..Default::default()
+        }
+    }
+}
+
+struct RecordedControl {
+    control: TestNativeControl,
+    callbacks: Callbacks,
+}
+
+#[derive(Default)]
+struct RecordedWindow {
+    visible: bool,
+    size: Size<Pixels>,
+    on_show: Option<Rc<dyn Fn()>>,
+    on_close: Option<Rc<dyn Fn()>>,
+}
+
+type ContextMenuCallback = Box<dyn FnOnce(Option<usize>)>;
+type AlertCallback = Box<dyn FnOnce(i64)>;
+
+/// Records the native controls a test window's elements configure, so tests can inspect
+/// them and simulate interactions with them.
+#[derive(Default)]
+pub(crate) struct TestNativeControls {
+    next_id: Cell<usize>,
+    controls: RefCell<Vec<Weak<RefCell<RecordedControl>>>>,
+    presentations: RefCell<Vec<TestNativePresentation>>,
+    pending_context_menus: RefCell<VecDeque<ContextMenuCallback>>,
+    pending_alert_sheets: RefCell<VecDeque<Option<AlertCallback>>>,
+}
+
+impl TestNativeControls {
+    pub(crate) fn controls(&self) -> Vec<TestNativeControl> {
+        let mut controls = self.controls.borrow_mut();
+        controls.retain(|control| control.strong_count() > 0);
+        controls
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|control| control.borrow().control.clone())
+            .collect()
+    }
+
+    pub(crate) fn presentations(&self) -> Vec<TestNativePresentation> {
+        self.presentations.borrow().clone()
+    }
+
+    pub(crate) fn simulate_event(&self, id: TestNativeControlId, event: TestNativeControlEvent) {
+        let control = self
+            .controls
+            .borrow()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .find(|control| control.borrow().control.id == id)
+            .unwrap_or_else(|| panic!("no native control with id {id:?}"));
+        let mut recorded = control.borrow_mut();
+        let RecordedControl { control, callbacks } = &mut *recorded;
+
+        // Callbacks are invoked after the control is released, so they can re-enter the
+        // recorder.
+        macro_rules! invoke {
+            ($callback:ident $(, $arg:ident)?) => {{
+                let Some(callback) = callbacks.$callback.clone() else {
+                    panic!(
+                        "{:?} {:?} has no callback for {event:?}",
+                        control.kind, control.title
+                    );
+                };
+                Box::new(move || callback($($arg)?)) as Box<dyn FnOnce()>
+            }};
+        }
+
+        let text = match &control.value {
+            Some(TestNativeControlValue::Text(text)) => text.clone(),
+            _ => String::new(),
+        };
+        let invoke = match event.clone() {
+            TestNativeControlEvent::Click => invoke!(on_click),
+            TestNativeControlEvent::Change(value) => {
+                control.value = Some(value.clone());
+                match value {
+                    TestNativeControlValue::Bool(checked) => invoke!(on_toggle, checked),
+                    TestNativeControlValue::Number(number) => invoke!(on_number, number),
+                    TestNativeControlValue::Text(text) => invoke!(on_text, text),
+                }
+            }
+            TestNativeControlEvent::Select(index) => {
+                assert!(
+                    index < control.items.len(),
+                    "{:?} has no item {index}",
+                    control.kind
+                );
+                control.selected_index = Some(index);
+                if control.kind == TestNativeControlKind::ComboBox {
+                    control.value =
+                        Some(TestNativeControlValue::Text(control.items[index].clone()));
+                }
+                invoke!(on_select, index)
+            }
+            TestNativeControlEvent::Submit => invoke!(on_submit, text),
+            TestNativeControlEvent::Cancel => invoke!(on_cancel),
+            TestNativeControlEvent::BeginEditing => invoke!(on_begin_editing),
+            TestNativeControlEvent::EndEditing => invoke!(on_end_editing, text),
+            TestNativeControlEvent::HeaderButton(index) => invoke!(on_header_button, index),
+        };
+        drop(recorded);
+        invoke();
+    }
+
+    pub(crate) fn simulate_context_menu_selection(&self, index: Option<usize>) {
+        let callback = self
+            .pending_context_menus
+            .borrow_mut()
+            .pop_front()
+            .expect("no context menu is open");
+        callback(index);
+    }
+
+    pub(crate) fn simulate_alert_response(&self, button_index: usize) {
+        let callback = self
+            .pending_alert_sheets
+            .borrow_mut()
+            .pop_front()
+            .expect("no alert sheet is open");
+        if let Some(callback) = callback {
+            callback(ALERT_FIRST_BUTTON_RETURN + button_index as i64);
+        }
+    }
+
+    fn update(
+        &self,
+        state: &mut NativeControlState,
+        kind: TestNativeControlKind,
+        bounds: Bounds<Pixels>,
+        configure: impl FnOnce(&mut TestNativeControl) -> Callbacks,
+    ) {
+        let recorded = match recorded::<RecordedControl>(state) {
+            Some(recorded) => recorded,
+            None => {
+                let id = TestNativeControlId(self.next_id.get());
+                self.next_id.set(id.0 + 1);
+                let recorded = Rc::new(RefCell::new(RecordedControl {
+                    control: TestNativeControl {
+                        id,
+                        kind,
+                        bounds,
+                        title: None,
+                        enabled: true,
+                        value: None,
+                        items: Vec::new(),
+                        selected_index: None,
+                    },
+                    callbacks: Callbacks::default(),
+                }));
+                self.controls.borrow_mut().push(Rc::downgrade(&recorded));
+                *state = record(recorded.clone());
+                recorded
+            }
+        };
+        let mut recorded = recorded.borrow_mut();
+        let mut control = TestNativeControl {
+            id: recorded.control.id,
+            kind,
+            bounds,
+            title: None,
+            enabled: true,
+            value: None,
+            items: Vec::new(),
+            selected_index: None,
+        };
+        recorded.callbacks = configure(&mut control);
+        recorded.control = control;
+    }
+
+    fn create_window(
+        &self,
+        size: Size<Pixels>,
+        on_show: Option<Rc<dyn Fn()>>,
+        on_close: Option<Rc<dyn Fn()>>,
+    ) -> NativeControlState {
+        record(Rc::new(RefCell::new(RecordedWindow {
+            visible: false,
+            size,
+            on_show,
+            on_close,
+        })))
+    }
+
+    fn show_window(
+        &self,
+        state: &NativeControlState,
+        presentation: impl FnOnce(Size<Pixels>) -> TestNativePresentation,
+    ) {
+        let Some(window) = recorded::<RecordedWindow>(state) else {
+            return;
+        };
+        let on_show = {
+            let mut window = window.borrow_mut();
+            window.visible = true;
+            self.presentations
+                .borrow_mut()
+                .push(presentation(window.size));
+            window.on_show.clone()
+        };
+        if let Some(on_show) = on_show {
+            on_show();
+        }
+    }
+
+    fn close_window(&self, state: &NativeControlState) {
+        let Some(window) = recorded::<RecordedWindow>(state) else {
+            return;
+        };
+        let on_close = {
+            let mut window = window.borrow_mut();
+            if !window.visible {
+                return;
+            }
+            window.visible = false;
+            window.on_close.clone()
+        };
+        if let Some(on_close) = on_close {
+            on_close();
+        }
+    }
+}
+
+fn record<T: 'static>(recorded: Rc<RefCell<T>>) -> NativeControlState {
+    NativeControlState::for_rust_control(recorded)
+}
+
+fn recorded<T: 'static>(state: &NativeControlState) -> Option<Rc<RefCell<T>>> {
+    // The test platform only hands out states created by `record`.
+    state.rust_control()
+}
+
+fn strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(ToString::to_string).collect()
+}
+
+fn menu_actions(items: &[NativeMenuItemData], actions: &mut Vec<String>) {
+    for item in items {
+        match item {
+            NativeMenuItemData::Action { title, .. } => actions.push(title.clone()),
+            NativeMenuItemData::Submenu { items, .. } => menu_actions(items, actions),
+            NativeMenuItemData::Separator => {}
+        }
+    }
+}
+
+fn outline_titles(nodes: &[NativeOutlineNodeData], titles: &mut Vec<String>) {
+    for node in nodes {
+        titles.push(node.title.clone());
+        outline_titles(&node.children, titles);
+    }
+}
+
+/// Adapts a callback that's passed the selected item's title along with its index.
+fn titled_select(
+    callback: Option<Box<dyn Fn((usize, String))>>,
+    items: &[String],
+) -> Option<Rc<dyn Fn(usize)>> {
+    let items = items.to_vec();
+    callback.map(|callback| {
+        Rc::new(move |index: usize| callback((index, items[index].clone()))) as Rc<dyn Fn(usize)>
+    })
+}
+
+impl PlatformNativeControls for TestNativeControls {
+    fn requires_native_view(&self) -> bool {
+        false
+    }
+
+    unsafe fn attach_and_position(
+        &self,
+        _state: &NativeControlState,
+        _parent: *mut c_void,
+        _bounds: Bounds<Pixels>,
+        _scale: f32,
+    ) {
+    }
+
+    unsafe fn remove_from_parent(&self, _state: &NativeControlState) {}
+
+    fn update_button(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: ButtonConfig,
+    ) {
+        self.update(state, TestNativeControlKind::Button, bounds, |control| {
+            control.title = Some(config.title.to_string());
+            control.enabled = config.enabled;
+            Callbacks {
+                on_click: config.on_click.map(Rc::from),
+                ..Default::default()
+            }
+        });
+    }
+
+    fn update_checkbox(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: CheckboxConfig,
+    ) {
+        self.update(state, TestNativeControlKind::Checkbox, bounds, |control| {
+            control.title = Some(config.title.to_string());
+            control.enabled = config.enabled;
+            control.value = Some(TestNativeControlValue::Bool(config.checked));
+            Callbacks {
+                on_toggle: config.on_change.map(Rc::from),
+                ..Default::default()
+            }
+        });
+    }
+
+    fn update_switch(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: SwitchConfig,
+    ) {
+        self.update(state, TestNativeControlKind::Switch, bounds, |control| {
+            control.enabled = config.enabled;
+            control.value = Some(TestNativeControlValue::Bool(config.checked));
+            Callbacks {
+                on_toggle: config.on_change.map(Rc::from),
+                ..Default::default()
+            }
+        });
+    }
+
+    fn update_slider(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: SliderConfig,
+    ) {
+        self.update(state, TestNativeControlKind::Slider, bounds, |control| {
+            control.enabled = config.enabled;
+            control.value = Some(TestNativeControlValue::Number(config.value));
+            Callbacks {
+                on_number: config.on_change.map(Rc::from),
+                ..Default::default()
+            }
+        });
+    }
+
+    fn update_stepper(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: StepperConfig,
+    ) {
+        self.update(state, TestNativeControlKind::Stepper, bounds, |control| {
+            control.enabled = config.enabled;
+            control.value = Some(TestNativeControlValue::Number(config.value));
+            Callbacks {
+                on_number: config.on_change.map(Rc::from),
+                ..Default::default()
+            }
+        });
+    }
+
+    fn update_segmented_control(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: SegmentedControlConfig,
+    ) {
+        self.update(
+            state,
+            TestNativeControlKind::SegmentedControl,
+            bounds,
+            |control| {
+                control.enabled = config.enabled;
+                control.items = strings(config.labels);
+                control.selected_index = config.selected_index;
+                Callbacks {
+                    on_select: config.on_select.map(Rc::from),
+                    ..Default::default()
+                }
+            },
+        );
+    }
+
+    fn update_popup_button(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: PopupButtonConfig,
+    ) {
+        self.update(
+            state,
+            TestNativeControlKind::PopupButton,
+            bounds,
+            |control| {
+                control.enabled = config.enabled;
+                control.items = strings(config.items);
+                control.selected_index = Some(config.selected_index);
+                Callbacks {
+                    on_select: config.on_select.map(Rc::from),
+                    ..Default::default()
+                }
+            },
+        );
+    }
+
+    fn update_progress(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: ProgressConfig,
+    ) {
+        self.update(state, TestNativeControlKind::Progress, bounds, |control| {
+            control.value =
+                (!config.indeterminate).then_some(TestNativeControlValue::Number(config.value));
+            Callbacks::default()
+        });
+    }
+
+    fn update_search_field(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: SearchFieldConfig,
+    ) {
+        self.update(
+            state,
+            TestNativeControlKind::SearchField,
+            bounds,
+            |control| {
+                control.title = Some(config.placeholder.to_string());
+                control.enabled = config.enabled;
+                control.value = Some(TestNativeControlValue::Text(config.value.to_string()));
+                config.callbacks.into()
+            },
+        );
+    }
+
+    fn update_text_field(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: TextFieldConfig,
+    ) {
+        self.update(state, TestNativeControlKind::TextField, bounds, |control| {
+            control.title = Some(config.placeholder.to_string());
+            control.enabled = config.enabled;
+            control.value = Some(TestNativeControlValue::Text(config.value.to_string()));
+            config.callbacks.into()
+        });
+    }
+
+    fn update_combo_box(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: ComboBoxConfig,
+    ) {
+        self.update(state, TestNativeControlKind::ComboBox, bounds, |control| {
+            control.enabled = config.enabled;
+            control.items = strings(config.items);
+            control.selected_index = Some(config.selected_index);
+            let value = config
+                .value
+                .map(ToString::to_string)
+                .or_else(|| control.items.get(config.selected_index).cloned())
+                .unwrap_or_default();
+            control.value = Some(TestNativeControlValue::Text(value));
+            Callbacks {
+                on_text: config.callbacks.on_change.map(Rc::from),
+                on_submit: config.callbacks.on_submit.map(Rc::from),
+                on_select: config.callbacks.on_select.map(Rc::from),
+                ..Default::default()
+            }
+        });
+    }
+
+    fn update_image_view(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: ImageViewConfig,
+    ) {
+        self.update(state, TestNativeControlKind::ImageView, bounds, |control| {
+            control.title = config.sf_symbol.map(ToString::to_string);
+            control.enabled = config.enabled;
+            Callbacks::default()
+        });
+    }
+
+    fn update_tab_view(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: TabViewConfig,
+    ) {
+        self.update(state, TestNativeControlKind::TabView, bounds, |control| {
+            control.enabled = config.enabled;
+            control.items = strings(config.labels);
+            control.selected_index = Some(config.selected_index);
+            Callbacks {
+                on_select: config.on_select.map(Rc::from),
+                ..Default::default()
+            }
+        });
+    }
+
+    fn update_table_view(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: TableViewConfig,
+    ) {
+        self.update(state, TestNativeControlKind::TableView, bounds, |control| {
+            control.title = config.column_title.map(ToString::to_string);
+            control.items = strings(config.items);
+            control.selected_index = config.selected_index;
+            Callbacks {
+                on_select: config.on_select.map(Rc::from),
+                ..Default::default()
+            }
+        });
+    }
+
+    fn update_outline_view(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: OutlineViewConfig,
+    ) {
+        self.update(
+            state,
+            TestNativeControlKind::OutlineView,
+            bounds,
+            |control| {
+                outline_titles(config.nodes, &mut control.items);
+                control.selected_index = config.selected_row;
+                Callbacks {
+                    on_select: titled_select(config.on_select, &control.items),
+                    ..Default::default()
+                }
+            },
+        );
+    }
+
+    fn update_collection_view(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: CollectionViewConfig,
+    ) {
+        self.update(
+            state,
+            TestNativeControlKind::CollectionView,
+            bounds,
+            |control| {
+                control.items = strings(config.items);
+                control.selected_index = config.selected;
+                Callbacks {
+                    on_select: config.on_select.map(Rc::from),
+                    ..Default::default()
+                }
+            },
+        );
+    }
+
+    fn update_menu_button(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: MenuButtonConfig,
+    ) {
+        self.update(
+            state,
+            TestNativeControlKind::MenuButton,
+            bounds,
+            |control| {
+                control.title = Some(config.title.to_string());
+                control.enabled = config.enabled;
+                menu_actions(config.items, &mut control.items);
+                Callbacks {
+                    on_select: config.on_select.map(Rc::from),
+                    ..Default::default()
+                }
+            },
+        );
+    }
+
+    fn update_visual_effect_view(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        _config: VisualEffectViewConfig,
+    ) {
+        self.update(
+            state,
+            TestNativeControlKind::VisualEffectView,
+            bounds,
+            |_| Callbacks::default(),
+        );
+    }
+
+    fn update_glass_effect_view(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        _config: GlassEffectViewConfig,
+    ) {
+        self.update(
+            state,
+            TestNativeControlKind::GlassEffectView,
+            bounds,
+            |_| Callbacks::default(),
+        );
+    }
+
+    fn update_tracking_view(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        _config: TrackingViewConfig,
+    ) {
+        self.update(state, TestNativeControlKind::TrackingView, bounds, |_| {
+            Callbacks::default()
+        });
+    }
+
+    fn update_stack_view(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        _config: StackViewConfig,
+    ) {
+        self.update(state, TestNativeControlKind::StackView, bounds, |_| {
+            Callbacks::default()
+        });
+    }
+
+    fn update_sidebar(
+        &self,
+        state: &mut NativeControlState,
+        _parent: *mut c_void,
+        bounds: Bounds<Pixels>,
+        _scale: f32,
+        config: SidebarViewConfig,
+    ) {
+        self.update(state, TestNativeControlKind::Sidebar, bounds, |control| {
+            control.title = config.header_title.map(ToString::to_string);
+            control.items = strings(config.items);
+            control.selected_index = config.selected_index;
+            Callbacks {
+                on_select: titled_select(config.on_select, &control.items),
+                on_header_button: config.on_header_button.map(Rc::from),
+                ..Default::default()
+            }
+        });
+    }
+
+    fn is_glass_effect_available(&self) -> bool {
+        false
+    }
+
+    fn get_text_field_value(&self, state: &NativeControlState) -> String {
+        recorded::<RecordedControl>(state)
+            .and_then(|recorded| match &recorded.borrow().control.value {
+                Some(TestNativeControlValue::Text(text)) => Some(text.clone()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    fn get_combo_box_value(&self, state: &NativeControlState) -> String {
+        self.get_text_field_value(state)
+    }
+
+    fn show_context_menu(
+        &self,
+        items: &[NativeMenuItemData],
+        _view: *mut c_void,
+        x: f64,
+        y: f64,
+        on_result: Box<dyn FnOnce(Option<usize>)>,
+    ) {
+        let mut actions = Vec::new();
+        menu_actions(items, &mut actions);
+        self.presentations
+            .borrow_mut()
+            .push(TestNativePresentation::ContextMenu {
+                items: actions,
+                position: point(px(x as f32), px(y as f32)),
+            });
+        self.pending_context_menus.borrow_mut().push_back(on_result);
+    }
+
+    fn show_alert_modal(&self, config: AlertConfig) -> i64 {
+        self.presentations
+            .borrow_mut()
+            .push(TestNativePresentation::Alert(alert(&config, false)));
+        ALERT_FIRST_BUTTON_RETURN
+    }
+
+    fn show_alert_sheet(
+        &self,
+        config: AlertConfig,
+        _parent_window: *mut c_void,
+        callback: Option<Box<dyn FnOnce(i64)>>,
+    ) {
+        self.presentations
+            .borrow_mut()
+            .push(TestNativePresentation::Alert(alert(&config, true)));
+        self.pending_alert_sheets.borrow_mut().push_back(callback);
+    }
+
+    fn create_panel(&self, config: PanelConfig) -> NativeControlState {
+        self.create_window(
+            size(px(config.width as f32), px(config.height as f32)),
+            None,
+            config.on_close.map(Rc::from),
+        )
+    }
+
+    fn get_panel_content_view(&self, _state: &NativeControlState) -> *mut c_void {
+        std::ptr::null_mut()
+    }
+
+    fn show_panel(&self, state: &NativeControlState) {
+        self.show_window(state, |size| TestNativePresentation::Panel {
+            size,
+            centered: false,
+        });
+    }
+
+    fn show_panel_centered(&self, state: &NativeControlState) {
+        self.show_window(state, |size| TestNativePresentation::Panel {
+            size,
+            centered: true,
+        });
+    }
+
+    fn set_panel_origin(&self, _state: &NativeControlState, _x: f64, _y: f64) {}
+
+    fn set_panel_top_left(&self, _state: &NativeControlState, _x: f64, _y: f64) {}
+
+    fn set_panel_size(&self, state: &NativeControlState, width: f64, height: f64) {
+        if let Some(window) = recorded::<RecordedWindow>(state) {
+            window.borrow_mut().size = size(px(width as f32), px(height as f32));
+        }
+    }
+
+    fn set_panel_frame(
+        &self,
+        state: &NativeControlState,
+        _x: f64,
+        _y: f64,
+        width: f64,
+        height: f64,
+        _animate: bool,
+    ) {
+        self.set_panel_size(state, width, height);
+    }
+
+    fn close_panel(&self, state: &NativeControlState) {
+        self.close_window(state);
+    }
+
+    fn hide_panel(&self, state: &NativeControlState) {
+        if let Some(window) = recorded::<RecordedWindow>(state) {
+            window.borrow_mut().visible = false;
+        }
+    }
+
+    fn is_panel_visible(&self, state: &NativeControlState) -> bool {
+        recorded::<RecordedWindow>(state).is_some_and(|window| window.borrow().visible)
+    }
+
+    fn get_toolbar_item_frame(
+        &self,
+        _window: *mut c_void,
+        _item_id: &str,
+    ) -> Option<Bounds<Pixels>> {
+        None
+    }
+
+    fn create_popover(&self, config: PopoverConfig) -> NativeControlState {
+        self.create_window(
+            size(px(config.width as f32), px(config.height as f32)),
+            config.on_show.map(Rc::from),
+            config.on_close.map(Rc::from),
+        )
+    }
+
+    fn get_popover_content_view(&self, _state: &NativeControlState) -> *mut c_void {
+        std::ptr::null_mut()
+    }
+
+    fn show_popover_at_toolbar_item(&self, state: &NativeControlState, _toolbar_item: *mut c_void) {
+        self.show_window(state, |size| TestNativePresentation::Popover { size });
+    }
+
+    fn dismiss_popover(&self, state: &NativeControlState) {
+        self.close_window(state);
+    }
+}
+
+fn alert(config: &AlertConfig, sheet: bool) -> TestNativeAlert {
+    TestNativeAlert {
+        style: config.style,
+        message: config.message.to_string(),
+        informative_text: config.informative_text.map(ToString::to_string),
+        button_titles: strings(config.button_titles),
+        sheet,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Context, IntoElement, NativeMenuItem, ParentElement, Render, SearchChangeEvent,
+        SearchSubmitEvent, Styled, TableRowSelectEvent, TestAppContext, TextChangeEvent,
+        TextSubmitEvent, Window, div, native_button, native_checkbox, native_search_field,
+        native_table_view, native_text_field,
+        platform::native_controls::{PanelLevel, PanelStyle},
+        show_native_popup_menu,
+    };
+
+    #[derive(Default)]
+    struct Editor {
+        saves: usize,
+        wrap: bool,
+        chosen: Option<usize>,
+    }
+
+    impl Render for Editor {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .size_full()
+                .child(
+                    native_button("save", "Save")
+                        .w(px(80.))
+                        .h(px(20.))
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.saves += 1;
+                            let editor = cx.entity();
+                            show_native_popup_menu(
+                                &[
+                                    NativeMenuItem::action("Copy"),
+                                    NativeMenuItem::action("Paste"),
+                                ],
+                                point(px(10.), px(20.)),
+                                window,
+                                cx,
+                                move |index, _, cx| {
+                                    editor.update(cx, |this, _| this.chosen = Some(index))
+                                },
+                            );
+                        })),
+                )
+                .child(
+                    native_checkbox("wrap", "Wrap")
+                        .checked(self.wrap)
+                        .w(px(80.))
+                        .h(px(20.))
+                        .on_change(cx.listener(
+                            |this, event: &crate::CheckboxChangeEvent, _, cx| {
+                                this.wrap = event.checked;
+                                cx.notify();
+                            },
+                        )),
+                )
+        }
+    }
+
+    #[crate::test]
+    fn test_recorded_native_controls(cx: &mut TestAppContext) {
+        let (editor, cx) = cx.add_window_view(|_, _| Editor::default());
+
+        let controls = cx.native_controls();
+        assert_eq!(
+            controls
+                .iter()
+                .map(|control| (
+                    control.kind,
+                    control.title.as_deref(),
+                    control.value.clone()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (TestNativeControlKind::Button, Some("Save"), None),
+                (
+                    TestNativeControlKind::Checkbox,
+                    Some("Wrap"),
+                    Some(TestNativeControlValue::Bool(false))
+                ),
+            ]
+        );
+
+        cx.simulate_native_control_event(controls[0].id, TestNativeControlEvent::Click);
+        editor.read_with(cx, |editor, _| assert_eq!(editor.saves, 1));
+        assert_eq!(
+            cx.native_presentations(),
+            vec![TestNativePresentation::ContextMenu {
+                items: vec!["Copy".to_string(), "Paste".to_string()],
+                position: point(px(10.), px(20.)),
+            }]
+        );
+        cx.simulate_native_context_menu_selection(Some(1));
+        editor.read_with(cx, |editor, _| assert_eq!(editor.chosen, Some(1)));
+
+        cx.simulate_native_control_event(
+            controls[1].id,
+            TestNativeControlEvent::Change(TestNativeControlValue::Bool(true)),
+        );
+        editor.read_with(cx, |editor, _| assert!(editor.wrap));
+        assert_eq!(
+            cx.native_control(TestNativeControlKind::Checkbox, "Wrap")
+                .value,
+            Some(TestNativeControlValue::Bool(true))
+        );
+    }
+
+    #[derive(Default)]
+    struct Finder {
+        query: String,
+        searches: Vec<String>,
+        new_name: String,
+        renames: Vec<String>,
+        selected: Option<usize>,
+    }
+
+    impl Render for Finder {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .size_full()
+                .child(
+                    native_search_field("search")
+                        .placeholder("Find")
+                        .value(self.query.clone())
+                        .w(px(120.))
+                        .h(px(20.))
+                        .on_change(cx.listener(|this, event: &SearchChangeEvent, _, cx| {
+                            this.query = event.text.clone();
+                            cx.notify();
+                        }))
+                        .on_submit(cx.listener(|this, event: &SearchSubmitEvent, _, _| {
+                            this.searches.push(event.text.clone());
+                        })),
+                )
+                .child(
+                    native_text_field("rename")
+                        .placeholder("New name")
+                        .value(self.new_name.clone())
+                        .w(px(120.))
+                        .h(px(20.))
+                        .on_change(cx.listener(|this, event: &TextChangeEvent, _, cx| {
+                            this.new_name = event.text.clone();
+                            cx.notify();
+                        }))
+                        .on_submit(cx.listener(|this, event: &TextSubmitEvent, _, _| {
+                            this.renames.push(event.text.clone());
+                        })),
+                )
+                .child(
+                    native_table_view("files", &["a.rs", "b.rs", "c.rs"])
+                        .column_title("Files")
+                        .selected_index(self.selected)
+                        .w(px(120.))
+                        .h(px(100.))
+                        .on_select(cx.listener(|this, event: &TableRowSelectEvent, _, cx| {
+                            this.selected = Some(event.index);
+                            cx.notify();
+                        })),
+                )
+        }
+    }
+
+    #[crate::test]
+    fn test_recorded_text_fields(cx: &mut TestAppContext) {
+        let (finder, cx) = cx.add_window_view(|_, _| Finder::default());
+
+        let search = cx.native_control(TestNativeControlKind::SearchField, "Find");
+        assert_eq!(
+            search.value,
+            Some(TestNativeControlValue::Text(String::new()))
+        );
+        cx.simulate_native_control_event(
+            search.id,
+            TestNativeControlEvent::Change(TestNativeControlValue::Text("todo".into())),
+        );
+        finder.read_with(cx, |finder, _| assert_eq!(finder.query, "todo"));
+        assert_eq!(
+            cx.native_control(TestNativeControlKind::SearchField, "Find")
+                .value,
+            Some(TestNativeControlValue::Text("todo".into()))
+        );
+        cx.simulate_native_control_event(search.id, TestNativeControlEvent::Submit);
+        finder.read_with(cx, |finder, _| assert_eq!(finder.searches, ["todo"]));
+
+        let rename = cx.native_control(TestNativeControlKind::TextField, "New name");
+        cx.simulate_native_control_event(
+            rename.id,
+            TestNativeControlEvent::Change(TestNativeControlValue::Text("main.rs".into())),
+        );
+        cx.simulate_native_control_event(rename.id, TestNativeControlEvent::Submit);
+        finder.read_with(cx, |finder, _| assert_eq!(finder.renames, ["main.rs"]));
+    }
+
+    #[crate::test]
+    fn test_recorded_table_view(cx: &mut TestAppContext) {
+        let (finder, cx) = cx.add_window_view(|_, _| Finder::default());
+
+        let table = cx.native_control(TestNativeControlKind::TableView, "Files");
+        assert_eq!(table.items, ["a.rs", "b.rs", "c.rs"]);
+        assert_eq!(table.selected_index, None);
+
+        cx.simulate_native_control_event(table.id, TestNativeControlEvent::Select(2));
+        finder.read_with(cx, |finder, _| assert_eq!(finder.selected, Some(2)));
+        assert_eq!(
+            cx.native_control(TestNativeControlKind::TableView, "Files")
+                .selected_index,
+            Some(2)
+        );
+    }
+
+    #[crate::test]
+    fn test_recorded_panels_popovers_and_alerts(cx: &mut TestAppContext) {
+        let (_, cx) = cx.add_window_view(|_, _| Finder::default());
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let event = |name: &'static str| {
+            let events = events.clone();
+            Some(Box::new(move || events.borrow_mut().push(name)) as Box<dyn Fn()>)
+        };
+
+        let panel = cx.update(|window, _| {
+            let controls = window.native_controls();
+            let panel = controls.create_panel(PanelConfig {
+                width: 200.,
+                height: 100.,
+                style: PanelStyle::Utility,
+                level: PanelLevel::Floating,
+                non_activating: false,
+                has_shadow: true,
+                corner_radius: 0.,
+                material: None,
+                on_close: event("panel closed"),
+            });
+            assert!(!controls.is_panel_visible(&panel));
+            controls.show_panel_centered(&panel);
+            assert!(controls.is_panel_visible(&panel));
+            controls.close_panel(&panel);
+            assert!(!controls.is_panel_visible(&panel));
+            panel
+        });
+
+        let popover = cx.update(|window, _| {
+            let controls = window.native_controls();
+            let popover = controls.create_popover(PopoverConfig {
+                width: 80.,
+                height: 40.,
+                behavior: 0,
+                on_close: event("popover closed"),
+                on_show: event("popover shown"),
+            });
+            controls.show_popover_at_toolbar_item(&popover, std::ptr::null_mut());
+            controls.dismiss_popover(&popover);
+            popover
+        });
+        assert_eq!(
+            *events.borrow(),
+            ["panel closed", "popover shown", "popover closed"]
+        );
+
+        let response = Rc::new(Cell::new(None));
+        cx.update(|window, _| {
+            let controls = window.native_controls();
+            let config = |message| AlertConfig {
+                style: AlertStyle::Warning,
+                message,
+                informative_text: Some("This can't be undone."),
+                button_titles: &["Delete", "Cancel"],
+                shows_suppression_button: false,
+            };
+            assert_eq!(
+                controls.show_alert_modal(config("Delete file?")),
+                ALERT_FIRST_BUTTON_RETURN
+            );
+            let response = response.clone();
+            controls.show_alert_sheet(
+                config("Delete folder?"),
+                std::ptr::null_mut(),
+                Some(Box::new(move |button| response.set(Some(button)))),
+            );
+        });
+        cx.simulate_native_alert_response(1);
+        assert_eq!(response.get(), Some(ALERT_FIRST_BUTTON_RETURN + 1));
+
+        let alert = |message: &str, sheet| {
+            TestNativePresentation::Alert(TestNativeAlert {
+                style: AlertStyle::Warning,
+                message: message.to_string(),
+                informative_text: Some("This can't be undone.".to_string()),
+                button_titles: vec!["Delete".to_string(), "Cancel".to_string()],
+                sheet,
+            })
+        };
+        assert_eq!(
+            cx.native_presentations(),
+            vec![
+                TestNativePresentation::Panel {
+                    size: size(px(200.), px(100.)),
+                    centered: true,
+                },
+                TestNativePresentation::Popover {
+                    size: size(px(80.), px(40.)),
+                },
+                alert("Delete file?", false),
+                alert("Delete folder?", true),
+            ]
+        );
+        drop((panel, popover));
+    }
+}
@@ -1,9 +1,11 @@
 use crate::{
     AnyWindowHandle, BackgroundExecutor, ClipboardItem, CursorStyle, DevicePixels,
-    DummyKeyboardMapper, ForegroundExecutor, Keymap, NoopTextSystem, Platform, PlatformDisplay,
-    PlatformHeadlessRenderer, PlatformKeyboardLayout, PlatformKeyboardMapper, PlatformTextSystem, PromptButton,
-    ScreenCaptureFrame, ScreenCaptureSource, ScreenCaptureStream, SourceMetadata, Task,
-    TestDisplay, TestWindow, ThermalState, WindowAppearance, WindowParams, size,
+    DummyKeyboardMapper, ForegroundExecutor, Keymap, NoopTextSystem, Notification, NotificationId,
+    NotificationResponse, Platform, PlatformDisplay, PlatformHeadlessRenderer,
+    PlatformKeyboardLayout, PlatformKeyboardMapper, PlatformTextSystem, PromptButton,
+    ScreenCaptureFrame, ScreenCaptureSource, ScreenCaptureStream, SourceMetadata, StatusItem,
+    StatusItemClick, Task, TestDisplay, TestWindow, ThermalState, WindowAppearance, WindowParams,
+    size,
 };
 use anyhow::Result;
 use collections::VecDeque;
@@ -36,6 +38,12 @@ pub(crate) struct TestPlatform {
     current_find_pasteboard_item: Mutex<Option<ClipboardItem>>,
     pub(crate) prompts: RefCell<TestPrompts>,
     screen_capture_sources: RefCell<Vec<TestScreenCaptureSource>>,
+    notifications: RefCell<Vec<(NotificationId, Notification)>>,
+    notification_response_callback:
+        RefCell<Option<Box<dyn FnMut(NotificationId, NotificationResponse)>>>,
+    pub(crate) status_item: RefCell<Option<StatusItem>>,
+    status_item_click_callback: RefCell<Option<Box<dyn FnMut(StatusItemClick)>>>,
+    recent_documents: RefCell<Vec<PathBuf>>,
     pub opened_url: RefCell<Option<String>>,
     pub text_system: Arc<dyn PlatformTextSystem>,
     pub expect_restart: RefCell<Option<oneshot::Sender<Option<PathBuf>>>>,
@@ -127,6 +135,11 @@ impl TestPlatform {
             foreground_executor,
             prompts: Default::default(),
             screen_capture_sources: Default::default(),
+            notifications: Default::default(),
+            notification_response_callback: Default::default(),
+            status_item: Default::default(),
+            status_item_click_callback: Default::default(),
+            recent_documents: Default::default(),
             active_cursor: Default::default(),
             active_display: Rc::new(TestDisplay::new()),
             active_window: Default::default(),
@@ -236,6 +249,42 @@ impl TestPlatform {
     pub(crate) fn did_prompt_for_new_path(&self) -> bool {
         !self.prompts.borrow().new_path.is_empty()
     }
+
+    pub(crate) fn simulate_status_item_click(&self, click: StatusItemClick) {
+        if self.status_item.borrow().is_none() {
+            return;
+        }
+        let callback = self.status_item_click_callback.borrow_mut().take();
+        if let Some(mut callback) = callback {
+            callback(click);
+            self.status_item_click_callback
+                .borrow_mut()
+                .get_or_insert(callback);
+        }
+    }
+
+    pub(crate) fn shown_notifications(&self) -> Vec<(NotificationId, Notification)> {
+        self.notifications.borrow().clone()
+    }
+
+    pub(crate) fn simulate_notification_response(
+        &self,
+        id: NotificationId,
+        response: NotificationResponse,
+    ) {
+        if response == NotificationResponse::Closed {
+            self.notifications
+                .borrow_mut()
+                .retain(|(shown_id, _)| *shown_id != id);
+        }
+        let callback = self.notification_response_callback.borrow_mut().take();
+        if let Some(mut callback) = callback {
+            callback(id, response);
+            self.notification_response_callback
+                .borrow_mut()
+                .get_or_insert(callback);
+        }
+    }
 }
 
 impl Platform for TestPlatform {
@@ -393,7 +442,27 @@ impl Platform for TestPlatform {
     fn set_menus(&self, _menus: Vec<crate::Menu>, _keymap: &Keymap) {}
     fn set_dock_menu(&self, _menu: Vec<crate::MenuItem>, _keymap: &Keymap) {}
 
-    fn add_recent_document(&self, _paths: &Path) {}
+    fn set_status_item(&self, item: Option<StatusItem>, _keymap: &Keymap) {
+        *self.status_item.borrow_mut() = item;
+    }
+
+    fn on_status_item_click(&self, callback: Box<dyn FnMut(StatusItemClick)>) {
+        *self.status_item_click_callback.borrow_mut() = Some(callback);
+    }
+
+    fn add_recent_document(&self, path: &Path) {
+        let mut recent_documents = self.recent_documents.borrow_mut();
+        recent_documents.retain(|recent| recent != path);
+        recent_documents.insert(0, path.to_path_buf());
+    }
+
+    fn recent_documents(&self) -> Task<Result<Vec<PathBuf>>> {
+        Task::ready(Ok(self.recent_documents.borrow().clone()))
+    }
+
+    fn clear_recent_documents(&self) {
+        self.recent_documents.borrow_mut().clear();
+    }
 
     fn on_app_menu_action(&self, _callback: Box<dyn FnMut(&dyn crate::Action)>) {}
 
@@ -401,6 +470,36 @@ impl Platform for TestPlatform {
 
     fn on_validate_app_menu_command(&self, _callback: Box<dyn FnMut(&dyn crate::Action) -> bool>) {}
 
+    fn show_notification(&self, id: NotificationId, notification: Notification) {
+        let mut notifications = self.notifications.borrow_mut();
+        if let Some(shown) = notifications
+            .iter_mut()
+            .find(|(shown_id, _)| *shown_id == id)
+        {
+            shown.1 = notification;
+        } else {
+            notifications.push((id, notification));
+        }
+    }
+
+    fn close_notification(&self, id: NotificationId) {
+        let weak = self.weak.clone();
+        self.foreground_executor
+            .spawn(async move {
+                if let Some(this) = weak.upgrade() {
+                    this.simulate_notification_response(id, NotificationResponse::Closed);
+                }
+            })
+            .detach();
+    }
+
+    fn on_notification_response(
+        &self,
+        callback: Box<dyn FnMut(NotificationId, NotificationResponse)>,
+    ) {
+        *self.notification_response_callback.borrow_mut() = Some(callback);
+    }
+
     fn app_path(&self) -> Result<std::path::PathBuf> {
         unimplemented!()
     }
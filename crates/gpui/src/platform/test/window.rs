@@ -1,10 +1,13 @@
 use crate::{
-    AnyWindowHandle, AtlasKey, AtlasTextureId, AtlasTile, Bounds, DispatchEventResult, GpuSpecs,
-    Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow,
-    Point, PromptButton, RequestFrameOptions, Size, TestPlatform, TileId, WindowAppearance,
-    WindowBackgroundAppearance, WindowBounds, WindowControlArea, WindowParams,
+    AccessibilityActionRequest, AccessibilityTree, AnyWindowHandle, AtlasKey, AtlasTextureId,
+    AtlasTile, Bounds, DispatchEventResult, ExternalDrag, ExternalDragOutcome, GpuSpecs, Pixels,
+    PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow, Point,
+    PromptButton, RequestFrameOptions, Size, TestNativeControls, TestPlatform, TileId,
+    WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControlArea, WindowParams,
+    platform::native_controls::PlatformNativeControls,
 };
 use collections::HashMap;
+use futures::channel::oneshot;
 use parking_lot::Mutex;
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 use std::{
@@ -29,10 +32,17 @@ pub(crate) struct TestWindowState {
     moved_callback: Option<Box<dyn FnMut()>>,
     input_handler: Option<PlatformInputHandler>,
     is_fullscreen: bool,
+    accessibility_active: bool,
+    pub(crate) accessibility_tree: Option<AccessibilityTree>,
+    accessibility_action_callback: Option<Box<dyn FnMut(AccessibilityActionRequest)>>,
+    external_drag: Option<(ExternalDrag, oneshot::Sender<ExternalDragOutcome>)>,
 }
 
 #[derive(Clone)]
-pub struct TestWindow(pub(crate) Rc<Mutex<TestWindowState>>);
+pub struct TestWindow(
+    pub(crate) Rc<Mutex<TestWindowState>>,
+    pub(crate) Rc<TestNativeControls>,
+);
 
 impl HasWindowHandle for TestWindow {
     fn window_handle(
@@ -57,24 +67,31 @@ impl TestWindow {
         platform: Weak<TestPlatform>,
         display: Rc<dyn PlatformDisplay>,
     ) -> Self {
-        Self(Rc::new(Mutex::new(TestWindowState {
-            bounds: params.bounds,
-            display,
-            platform,
-            handle,
-            sprite_atlas: Arc::new(TestAtlas::new()),
-            title: Default::default(),
-            edited: false,
-            should_close_handler: None,
-            hit_test_window_control_callback: None,
-            input_callback: None,
-            active_status_change_callback: None,
-            hover_status_change_callback: None,
-            resize_callback: None,
-            moved_callback: None,
-            input_handler: None,
-            is_fullscreen: false,
-        })))
+        Self(
+            Rc::new(Mutex::new(TestWindowState {
+                bounds: params.bounds,
+                display,
+                platform,
+                handle,
+                sprite_atlas: Arc::new(TestAtlas::new()),
+                title: Default::default(),
+                edited: false,
+                should_close_handler: None,
+                hit_test_window_control_callback: None,
+                input_callback: None,
+                active_status_change_callback: None,
+                hover_status_change_callback: None,
+                resize_callback: None,
+                moved_callback: None,
+                input_handler: None,
+                is_fullscreen: false,
+                accessibility_active: false,
+                accessibility_tree: None,
+                accessibility_action_callback: None,
+                external_drag: None,
+            })),
+            Rc::default(),
+        )
     }
 
     pub fn simulate_resize(&mut self, size: Size<Pixels>) {
@@ -109,6 +126,38 @@ impl TestWindow {
         self.0.lock().input_callback = Some(callback);
         !result.propagate
     }
+
+    /// Simulates an assistive technology starting to listen. Until then, the window doesn't
+    /// build an accessibility tree.
+    pub fn simulate_accessibility_activation(&mut self) {
+        self.0.lock().accessibility_active = true;
+    }
+
+    pub fn simulate_accessibility_action(&mut self, request: AccessibilityActionRequest) {
+        let mut lock = self.0.lock();
+        let Some(mut callback) = lock.accessibility_action_callback.take() else {
+            return;
+        };
+        drop(lock);
+        callback(request);
+        self.0.lock().accessibility_action_callback = Some(callback);
+    }
+
+    /// Returns the data being dragged out of the window, if a drag is in progress.
+    pub fn external_drag(&self) -> Option<ExternalDrag> {
+        Some(self.0.lock().external_drag.as_ref()?.0.clone())
+    }
+
+    /// Simulates the drag out of the window ending, with the drop target doing the given thing
+    /// with the data. Returns the data, or `None` if no drag was in progress.
+    pub fn simulate_external_drag_end(
+        &mut self,
+        outcome: ExternalDragOutcome,
+    ) -> Option<ExternalDrag> {
+        let (drag, outcome_tx) = self.0.lock().external_drag.take()?;
+        outcome_tx.send(outcome).ok();
+        Some(drag)
+    }
 }
 
 impl PlatformWindow for TestWindow {
@@ -284,6 +333,10 @@ impl PlatformWindow for TestWindow {
         Some(self)
     }
 
+    fn native_controls(&self) -> Option<&dyn PlatformNativeControls> {
+        Some(&*self.1)
+    }
+
     #[cfg(target_os = "windows")]
     fn get_raw_handle(&self) -> windows::Win32::Foundation::HWND {
         unimplemented!()
@@ -297,8 +350,29 @@ impl PlatformWindow for TestWindow {
         unimplemented!()
     }
 
+    fn start_external_drag(
+        &self,
+        drag: ExternalDrag,
+    ) -> Option<oneshot::Receiver<ExternalDragOutcome>> {
+        let (outcome_tx, outcome_rx) = oneshot::channel();
+        self.0.lock().external_drag = Some((drag, outcome_tx));
+        Some(outcome_rx)
+    }
+
     fn update_ime_position(&self, _bounds: Bounds<Pixels>) {}
 
+    fn is_accessibility_active(&self) -> bool {
+        self.0.lock().accessibility_active
+    }
+
+    fn update_accessibility_tree(&self, tree: &AccessibilityTree) {
+        self.0.lock().accessibility_tree = Some(tree.clone());
+    }
+
+    fn on_accessibility_action(&self, callback: Box<dyn FnMut(AccessibilityActionRequest)>) {
+        self.0.lock().accessibility_action_callback = Some(callback);
+    }
+
     fn gpu_specs(&self) -> Option<GpuSpecs> {
         None
     }
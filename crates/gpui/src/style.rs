@@ -178,6 +178,8 @@ pub struct Style {
     /// Ideally we would match the web's behavior and not have a need for this, but right now we're adding this opt-in
     /// style property to limit the potential blast radius.
     pub restrict_scroll_to_axis: bool,
+    /// Whether a scrollable element should scroll when something is dragged near its edges.
+    pub drag_autoscroll: bool,
 
     // Position properties
     /// What should the `position` value of this struct use as a base offset?
@@ -758,6 +760,7 @@ impl Default for Style {
             },
             allow_concurrent_scroll: false,
             restrict_scroll_to_axis: false,
+            drag_autoscroll: true,
             scrollbar_width: AbsoluteLength::default(),
             position: Position::Relative,
             inset: Edges::auto(),
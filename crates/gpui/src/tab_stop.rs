@@ -89,6 +89,56 @@ impl TabStopMap {
         self.order.insert_or_replace(order, ());
     }
 
+    /// Change whether an inserted focus handle is a tab stop, including when it's replayed.
+    pub fn set_tab_stop(&mut self, focus_id: FocusId, tab_stop: bool) {
+        let Some(node) = self.by_id.get_mut(&focus_id) else {
+            return;
+        };
+        if node.tab_stop == tab_stop {
+            return;
+        }
+        self.order.remove(node, ());
+        node.tab_stop = tab_stop;
+        if let Some(TabStopOperation::Insert(focus_handle)) =
+            self.insertion_history.get_mut(node.node_insertion_index)
+        {
+            focus_handle.tab_stop = tab_stop;
+        }
+        self.order.insert_or_replace(node.clone(), ());
+    }
+
+    pub fn contains(&self, focus_id: FocusId) -> bool {
+        self.by_id.contains_key(&focus_id)
+    }
+
+    /// Find the next tab stop after the focused one, or the previous one when `reverse` is set,
+    /// that matches the predicate, wrapping around.
+    pub fn find(
+        &self,
+        focused: Option<FocusId>,
+        reverse: bool,
+        predicate: impl Fn(FocusId) -> bool,
+    ) -> Option<FocusHandle> {
+        let mut current = focused;
+        let mut first = None;
+        loop {
+            let candidate = if reverse {
+                self.prev(current.as_ref())
+            } else {
+                self.next(current.as_ref())
+            }?;
+            if predicate(candidate.id) {
+                return Some(candidate);
+            }
+            match first {
+                Some(first) if first == candidate.id => return None,
+                None => first = Some(candidate.id),
+                _ => {}
+            }
+            current = Some(candidate.id);
+        }
+    }
+
     pub fn begin_group(&mut self, tab_index: isize) {
         self.insertion_history
             .push(TabStopOperation::Group(tab_index));
@@ -0,0 +1,510 @@
+use std::{cell::RefCell, mem, rc::Rc, time::Duration};
+
+use scheduler::Instant;
+
+use crate::{App, Context, Entity, SubscriberSet, Subscription, WeakEntity};
+
+actions!(
+    undo,
+    [
+        /// Undo the last transaction of the undo history handling it, see
+        /// [`crate::InteractiveElement::undo_manager`].
+        Undo,
+        /// Redo the last undone transaction of the undo history handling it, see
+        /// [`crate::InteractiveElement::undo_manager`].
+        Redo
+    ]
+);
+
+/// How soon a transaction has to start after the previous one ends to be undone together with it,
+/// unless set with [`UndoManager::set_group_interval`].
+const DEFAULT_GROUP_INTERVAL: Duration = Duration::from_millis(300);
+
+/// An entity whose whole state can be saved and restored, to record changes to it with
+/// [`UndoManager::update_with_snapshot`] instead of writing operations that reverse them.
+pub trait Undoable: 'static + Sized {
+    /// The saved state of the entity, compared to tell whether an update changed it.
+    type Snapshot: Clone + PartialEq + 'static;
+
+    /// Save the state of the entity.
+    fn snapshot(&self) -> Self::Snapshot;
+
+    /// Restore a state saved with [`Undoable::snapshot`].
+    fn restore(&mut self, snapshot: Self::Snapshot, cx: &mut Context<Self>);
+}
+
+/// A history of changes to any number of entities, which can be undone and redone.
+///
+/// Changes are recorded as operations reversing them, with [`UndoManager::record`], or as
+/// snapshots of an entity's state, with [`UndoManager::update_with_snapshot`]. The changes made
+/// within [`UndoManager::transact`] are undone and redone together, whichever entities they were
+/// made to, and so are transactions that start soon after the previous one ends, like the
+/// characters of a word being typed.
+///
+/// This is a cheap handle to a shared history, so views can each keep a clone of it. Views that
+/// show what can be undone can be notified when that changes, with [`UndoManager::on_change`].
+#[derive(Clone)]
+pub struct UndoManager(Rc<RefCell<UndoState>>);
+
+struct UndoState {
+    undo_stack: Vec<Transaction>,
+    redo_stack: Vec<Transaction>,
+    /// The transaction being recorded, and how many calls to
+    /// [`UndoManager::start_transaction`] it's nested in.
+    current: Option<(Transaction, usize)>,
+    group_interval: Duration,
+    /// Whether the next transaction is kept apart from the last one, however soon it starts.
+    last_finalized: bool,
+    /// Whether a transaction is being undone or redone, which doesn't record changes.
+    applying: bool,
+    change_observers: SubscriberSet<(), Box<dyn FnMut(&mut App) -> bool>>,
+}
+
+struct Transaction {
+    changes: Vec<Box<dyn Change>>,
+    start: Instant,
+    end: Instant,
+}
+
+trait Change {
+    fn undo(&mut self, cx: &mut App);
+    fn redo(&mut self, cx: &mut App);
+}
+
+struct Operation<T, U, R> {
+    entity: WeakEntity<T>,
+    undo: U,
+    redo: R,
+}
+
+impl<T, U, R> Change for Operation<T, U, R>
+where
+    T: 'static,
+    U: FnMut(&mut T, &mut Context<T>),
+    R: FnMut(&mut T, &mut Context<T>),
+{
+    fn undo(&mut self, cx: &mut App) {
+        self.entity
+            .update(cx, |this, cx| (self.undo)(this, cx))
+            .ok();
+    }
+
+    fn redo(&mut self, cx: &mut App) {
+        self.entity
+            .update(cx, |this, cx| (self.redo)(this, cx))
+            .ok();
+    }
+}
+
+struct SnapshotChange<T: Undoable> {
+    entity: WeakEntity<T>,
+    before: T::Snapshot,
+    after: T::Snapshot,
+}
+
+impl<T: Undoable> Change for SnapshotChange<T> {
+    fn undo(&mut self, cx: &mut App) {
+        let before = self.before.clone();
+        self.entity
+            .update(cx, |this, cx| this.restore(before, cx))
+            .ok();
+    }
+
+    fn redo(&mut self, cx: &mut App) {
+        let after = self.after.clone();
+        self.entity
+            .update(cx, |this, cx| this.restore(after, cx))
+            .ok();
+    }
+}
+
+impl Default for UndoManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UndoManager {
+    /// Create an empty history.
+    pub fn new() -> Self {
+        Self(Rc::new(RefCell::new(UndoState {
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            current: None,
+            group_interval: DEFAULT_GROUP_INTERVAL,
+            last_finalized: false,
+            applying: false,
+            change_observers: SubscriberSet::new(),
+        })))
+    }
+
+    /// Set how soon a transaction has to start after the previous one ends to be undone together
+    /// with it. Zero keeps every transaction apart.
+    pub fn set_group_interval(&self, interval: Duration) {
+        self.0.borrow_mut().group_interval = interval;
+    }
+
+    /// Record the changes made by the given function as a single transaction. Transactions can be
+    /// nested, in which case the outermost one is recorded.
+    pub fn transact<R>(&self, cx: &mut App, f: impl FnOnce(&mut App) -> R) -> R {
+        self.start_transaction(cx);
+        let result = f(cx);
+        self.end_transaction(cx);
+        result
+    }
+
+    /// Start recording changes as a single transaction, until the matching call to
+    /// [`UndoManager::end_transaction`]. Prefer [`UndoManager::transact`] where possible.
+    pub fn start_transaction(&self, cx: &App) {
+        let mut state = self.0.borrow_mut();
+        match &mut state.current {
+            Some((_, depth)) => *depth += 1,
+            None => {
+                let now = cx.background_executor().now();
+                state.current = Some((
+                    Transaction {
+                        changes: Vec::new(),
+                        start: now,
+                        end: now,
+                    },
+                    1,
+                ));
+            }
+        }
+    }
+
+    /// Finish recording the transaction started with [`UndoManager::start_transaction`], adding
+    /// it to the history if it changed anything. Anything that was undone can't be redone anymore.
+    pub fn end_transaction(&self, cx: &mut App) {
+        let mut state = self.0.borrow_mut();
+        let Some((mut transaction, depth)) = state.current.take() else {
+            debug_assert!(false, "no transaction to end");
+            return;
+        };
+        if depth > 1 {
+            state.current = Some((transaction, depth - 1));
+            return;
+        }
+        if transaction.changes.is_empty() {
+            return;
+        }
+        transaction.end = cx.background_executor().now();
+        state.redo_stack.clear();
+
+        let last_finalized = mem::take(&mut state.last_finalized);
+        let group_interval = state.group_interval;
+        if let Some(last) = state.undo_stack.last_mut()
+            && !last_finalized
+            && transaction.start.saturating_duration_since(last.end) < group_interval
+        {
+            last.changes.append(&mut transaction.changes);
+            last.end = transaction.end;
+        } else {
+            state.undo_stack.push(transaction);
+        }
+        drop(state);
+        self.notify_change(cx);
+    }
+
+    /// Keep the next transaction apart from the last one, however soon it starts.
+    pub fn finalize_last_transaction(&self) {
+        self.0.borrow_mut().last_finalized = true;
+    }
+
+    /// Record a change to the given entity as the operations that undo and redo it. Outside of a
+    /// transaction, the change is a transaction of its own.
+    pub fn record<T: 'static>(
+        &self,
+        entity: &Entity<T>,
+        undo: impl FnMut(&mut T, &mut Context<T>) + 'static,
+        redo: impl FnMut(&mut T, &mut Context<T>) + 'static,
+        cx: &mut App,
+    ) {
+        self.push_change(
+            Box::new(Operation {
+                entity: entity.downgrade(),
+                undo,
+                redo,
+            }),
+            cx,
+        );
+    }
+
+    /// Update the given entity, recording the change as snapshots of its state from before and
+    /// after the update. Outside of a transaction, the change is a transaction of its own. Nothing
+    /// is recorded when the update leaves the state as it was.
+    pub fn update_with_snapshot<T: Undoable, R>(
+        &self,
+        entity: &Entity<T>,
+        cx: &mut App,
+        update: impl FnOnce(&mut T, &mut Context<T>) -> R,
+    ) -> R {
+        let before = entity.read(cx).snapshot();
+        let result = entity.update(cx, update);
+        let after = entity.read(cx).snapshot();
+        if after == before {
+            return result;
+        }
+        self.push_change(
+            Box::new(SnapshotChange {
+                entity: entity.downgrade(),
+                before,
+                after,
+            }),
+            cx,
+        );
+        result
+    }
+
+    /// Whether there's a transaction to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.0.borrow().undo_stack.is_empty()
+    }
+
+    /// Whether there's an undone transaction to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.0.borrow().redo_stack.is_empty()
+    }
+
+    /// Undo the last transaction, returning whether there was one.
+    pub fn undo(&self, cx: &mut App) -> bool {
+        let Some(mut transaction) = self.0.borrow_mut().undo_stack.pop() else {
+            return false;
+        };
+        self.apply(cx, |cx| {
+            for change in transaction.changes.iter_mut().rev() {
+                change.undo(cx);
+            }
+        });
+        let mut state = self.0.borrow_mut();
+        state.redo_stack.push(transaction);
+        state.last_finalized = true;
+        drop(state);
+        self.notify_change(cx);
+        true
+    }
+
+    /// Redo the last undone transaction, returning whether there was one.
+    pub fn redo(&self, cx: &mut App) -> bool {
+        let Some(mut transaction) = self.0.borrow_mut().redo_stack.pop() else {
+            return false;
+        };
+        self.apply(cx, |cx| {
+            for change in &mut transaction.changes {
+                change.redo(cx);
+            }
+        });
+        let mut state = self.0.borrow_mut();
+        state.undo_stack.push(transaction);
+        state.last_finalized = true;
+        drop(state);
+        self.notify_change(cx);
+        true
+    }
+
+    /// Forget every transaction.
+    pub fn clear(&self, cx: &mut App) {
+        let mut state = self.0.borrow_mut();
+        state.undo_stack.clear();
+        state.redo_stack.clear();
+        drop(state);
+        self.notify_change(cx);
+    }
+
+    /// Invoke the given callback whenever a transaction is recorded, undone or redone, or the
+    /// history is cleared, changing what can be undone and redone. It's invoked once the current
+    /// update is done, so it can update the entities that changed.
+    pub fn on_change(&self, mut callback: impl FnMut(&mut App) + 'static) -> Subscription {
+        let (subscription, activate) = self.0.borrow().change_observers.insert(
+            (),
+            Box::new(move |cx| {
+                callback(cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    fn notify_change(&self, cx: &mut App) {
+        let observers = self.0.borrow().change_observers.clone();
+        cx.defer(move |cx| observers.retain(&(), |callback| callback(cx)));
+    }
+
+    fn push_change(&self, change: Box<dyn Change>, cx: &mut App) {
+        if self.0.borrow().applying {
+            return;
+        }
+        let in_transaction = self.0.borrow().current.is_some();
+        if !in_transaction {
+            self.start_transaction(cx);
+        }
+        if let Some((transaction, _)) = &mut self.0.borrow_mut().current {
+            transaction.changes.push(change);
+        }
+        if !in_transaction {
+            self.end_transaction(cx);
+        }
+    }
+
+    /// Undo or redo changes, without recording what that changes in turn.
+    fn apply(&self, cx: &mut App, f: impl FnOnce(&mut App)) {
+        self.0.borrow_mut().applying = true;
+        f(cx);
+        self.0.borrow_mut().applying = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        AppContext as _, FocusHandle, InteractiveElement, IntoElement, Render, TestAppContext,
+        Window, div,
+    };
+
+    struct Counter(i32);
+
+    impl Counter {
+        fn add(&mut self, amount: i32, history: &UndoManager, cx: &mut Context<Self>) {
+            self.0 += amount;
+            history.record(
+                &cx.entity(),
+                move |this, _| this.0 -= amount,
+                move |this, _| this.0 += amount,
+                cx,
+            );
+        }
+    }
+
+    struct List(Vec<&'static str>);
+
+    impl Undoable for List {
+        type Snapshot = Vec<&'static str>;
+
+        fn snapshot(&self) -> Self::Snapshot {
+            self.0.clone()
+        }
+
+        fn restore(&mut self, snapshot: Self::Snapshot, cx: &mut Context<Self>) {
+            self.0 = snapshot;
+            cx.notify();
+        }
+    }
+
+    #[crate::test]
+    fn test_undo_transactions(cx: &mut TestAppContext) {
+        let history = UndoManager::new();
+        let counter = cx.new(|_| Counter(0));
+        let list = cx.new(|_| List(Vec::new()));
+        let state =
+            |cx: &mut TestAppContext| cx.read(|cx| (counter.read(cx).0, list.read(cx).0.clone()));
+
+        // Changes to several entities in a transaction are undone together.
+        cx.update(|cx| {
+            history.transact(cx, |cx| {
+                counter.update(cx, |counter, cx| counter.add(2, &history, cx));
+                history.update_with_snapshot(&list, cx, |list, _| list.0.push("a"));
+                counter.update(cx, |counter, cx| counter.add(3, &history, cx));
+            })
+        });
+        assert_eq!(state(cx), (5, vec!["a"]));
+
+        // Transactions starting soon after the previous one are grouped with it.
+        cx.executor().advance_clock(DEFAULT_GROUP_INTERVAL);
+        cx.update(|cx| history.update_with_snapshot(&list, cx, |list, _| list.0.push("b")));
+        cx.executor().advance_clock(DEFAULT_GROUP_INTERVAL / 2);
+        cx.update(|cx| history.update_with_snapshot(&list, cx, |list, _| list.0.push("c")));
+        history.finalize_last_transaction();
+        cx.update(|cx| history.update_with_snapshot(&list, cx, |list, _| list.0.push("d")));
+        assert_eq!(state(cx), (5, vec!["a", "b", "c", "d"]));
+
+        assert!(cx.update(|cx| history.undo(cx)));
+        assert_eq!(state(cx), (5, vec!["a", "b", "c"]));
+        assert!(cx.update(|cx| history.undo(cx)));
+        assert_eq!(state(cx), (5, vec!["a"]));
+        assert!(cx.update(|cx| history.undo(cx)));
+        assert_eq!(state(cx), (0, vec![]));
+        assert!(!history.can_undo());
+        assert!(!cx.update(|cx| history.undo(cx)));
+
+        assert!(cx.update(|cx| history.redo(cx)));
+        assert_eq!(state(cx), (5, vec!["a"]));
+        assert!(cx.update(|cx| history.redo(cx)));
+        assert_eq!(state(cx), (5, vec!["a", "b", "c"]));
+
+        // Updates that don't change anything aren't recorded, leaving what's left to redo.
+        cx.update(|cx| history.update_with_snapshot(&list, cx, |list, _| list.0.retain(|_| true)));
+        assert!(history.can_redo());
+
+        // A new change can't be grouped with what was redone, and discards what's left to redo.
+        counter.update(cx, |counter, cx| counter.add(1, &history, cx));
+        assert!(!history.can_redo());
+        assert!(cx.update(|cx| history.undo(cx)));
+        assert_eq!(state(cx), (5, vec!["a", "b", "c"]));
+    }
+
+    struct Editor {
+        focus_handle: FocusHandle,
+        history: UndoManager,
+        counter: Entity<Counter>,
+        _subscription: Subscription,
+    }
+
+    impl Render for Editor {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .track_focus(&self.focus_handle)
+                .undo_manager(&self.history)
+        }
+    }
+
+    #[crate::test]
+    fn test_undo_actions(cx: &mut TestAppContext) {
+        let history = UndoManager::new();
+        let counter = cx.new(|_| Counter(0));
+        let (editor, cx) = cx.add_window_view(|_, cx| {
+            let editor = cx.weak_entity();
+            Editor {
+                focus_handle: cx.focus_handle(),
+                history: history.clone(),
+                counter: counter.clone(),
+                _subscription: history.on_change(move |cx| {
+                    editor.update(cx, |_, cx| cx.notify()).ok();
+                }),
+            }
+        });
+        editor.update_in(cx, |editor, window, cx| {
+            window.focus(&editor.focus_handle, cx)
+        });
+        let available = |cx: &mut crate::VisualTestContext| {
+            cx.update(|window, cx| {
+                (
+                    window.is_action_available(&Undo, cx),
+                    window.is_action_available(&Redo, cx),
+                )
+            })
+        };
+        cx.run_until_parked();
+        assert_eq!(available(cx), (false, false));
+
+        // The editor is notified when the change is recorded, making undo available.
+        editor.update(cx, |editor, cx| {
+            editor
+                .counter
+                .update(cx, |counter, cx| counter.add(1, &editor.history, cx));
+        });
+        cx.run_until_parked();
+        assert_eq!(available(cx), (true, false));
+
+        cx.dispatch_action(Undo);
+        cx.run_until_parked();
+        assert_eq!(counter.read_with(cx, |counter, _| counter.0), 0);
+        assert_eq!(available(cx), (false, true));
+
+        cx.dispatch_action(Redo);
+        cx.run_until_parked();
+        assert_eq!(counter.read_with(cx, |counter, _| counter.0), 1);
+        assert_eq!(available(cx), (true, false));
+    }
+}
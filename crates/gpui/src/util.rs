@@ -1,6 +1,9 @@
 use crate::{BackgroundExecutor, Task};
 use std::{
+    fs,
     future::Future,
+    io,
+    path::Path,
     pin::Pin,
     sync::atomic::{AtomicUsize, Ordering::SeqCst},
     task,
@@ -125,6 +128,28 @@ pub(crate) fn atomic_incr_if_not_zero(counter: &AtomicUsize) -> usize {
     }
 }
 
+/// Write the file at the given path, creating its directory if needed. The contents are written to
+/// a temporary file that then replaces it, so it's never left half written.
+pub(crate) fn write_atomically(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    fs::create_dir_all(dir)?;
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let temp_path = dir.join(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+    fs::write(&temp_path, contents)?;
+    fs::rename(&temp_path, path).inspect_err(|_| {
+        fs::remove_file(&temp_path).ok();
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use crate::TestAppContext;
@@ -1,26 +1,30 @@
 #[cfg(any(feature = "inspector", debug_assertions))]
 use crate::Inspector;
+use crate::accessibility::AccessibilityNodeEntry;
+use crate::platform::native_controls::FallbackNativeControls;
 use crate::{
-    Action, AnyDrag, AnyElement, AnyImageCache, AnyTooltip, AnyView, App, AppContext, Arena, Asset,
-    AsyncWindowContext, AvailableSpace, Background, BorderStyle, Bounds, BoxShadow, Capslock,
-    Context, Corners, CursorStyle, Decorations, DevicePixels, DispatchActionListener,
-    DispatchNodeId, DispatchTree, DisplayId, Edges, Effect, Entity, EntityId, EventEmitter,
-    FileDropEvent, FontId, Global, GlobalElementId, GlyphId, GpuSpecs, Hsla, InputHandler, IsZero,
+    AccessibilityAction, AccessibilityActionRequest, AccessibilityNodeId, AccessibilityProperties,
+    AccessibilityTree, Action, AnyDrag, AnyElement, AnyImageCache, AnyTooltip, AnyView, App,
+    AppContext, Arena, Asset, AsyncWindowContext, AvailableSpace, Background, BorderStyle, Bounds,
+    BoxShadow, Capslock, ClipboardEntry, Context, Corners, CursorStyle, Decorations, DevicePixels,
+    DispatchActionListener, DispatchNodeId, DispatchTree, DisplayId, Edges, Effect, Entity,
+    EntityId, EventEmitter, ExternalDrag, ExternalDragOutcome, ExternalDrop, ExternalDropEvent,
+    FileDropEvent, FocusGroup, FocusGroupIndex, FocusGroupMap, FocusGroupTypeAhead, FocusTrapIndex,
+    FocusTrapMap, FontId, Global, GlobalElementId, GlyphId, GpuSpecs, Hsla, InputHandler, IsZero,
     KeyBinding, KeyContext, KeyDownEvent, KeyEvent, Keystroke, KeystrokeEvent, LayoutId,
-    LineLayoutIndex, Modifiers, ModifiersChangedEvent, MonochromeSprite, MouseButton, MouseEvent,
-    MouseMoveEvent, MouseUpEvent, Path, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput,
-    PlatformInputHandler, PlatformNativeSearchFieldTarget, PlatformNativeToolbar,
-    PlatformNativeToolbarButtonItem,
-    PlatformNativeToolbarComboBoxItem, PlatformNativeToolbarDisplayMode, PlatformNativeToolbarItem,
-    PlatformNativeToolbarLabelItem, PlatformNativeToolbarMenuButtonItem,
-    PlatformNativeToolbarMenuItemData,
-    PlatformNativeToolbarPopUpItem, PlatformNativeToolbarSearchFieldItem,
-    PlatformNativeToolbarSegmentedItem, PlatformNativeToolbarSizeMode,
-    PlatformNativeAlertStyle, PlatformNativeAlert,
-    PlatformNativeColor, PlatformNativePanel, PlatformNativePanelAnchor,
+    LineLayoutIndex, MacroRecording, MacroReplayStep, MacroReplayStepKind, Modifiers,
+    ModifiersChangedEvent, MonochromeSprite, MouseButton, MouseDownEvent, MouseEvent,
+    MouseMoveEvent, MouseUpEvent, Path, PendingInputTimeout, Pixels, PlatformAtlas,
+    PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformNativeAlert,
+    PlatformNativeAlertStyle, PlatformNativeColor, PlatformNativePanel, PlatformNativePanelAnchor,
     PlatformNativePanelLevel, PlatformNativePanelMaterial, PlatformNativePanelStyle,
-    PlatformNativePopover, PlatformNativePopoverAnchor,
-    PlatformNativePopoverBehavior, PlatformNativePopoverContentItem, PlatformWindow, Point,
+    PlatformNativePopover, PlatformNativePopoverAnchor, PlatformNativePopoverBehavior,
+    PlatformNativePopoverContentItem, PlatformNativeSearchFieldTarget, PlatformNativeToolbar,
+    PlatformNativeToolbarButtonItem, PlatformNativeToolbarComboBoxItem,
+    PlatformNativeToolbarDisplayMode, PlatformNativeToolbarItem, PlatformNativeToolbarLabelItem,
+    PlatformNativeToolbarMenuButtonItem, PlatformNativeToolbarMenuItemData,
+    PlatformNativeToolbarPopUpItem, PlatformNativeToolbarSearchFieldItem,
+    PlatformNativeToolbarSegmentedItem, PlatformNativeToolbarSizeMode, PlatformWindow, Point,
     PolychromeSprite, Priority, PromptButton, PromptLevel, Quad, Render, RenderGlyphParams,
     RenderImage, RenderImageParams, RenderSvgParams, Replay, ResizeEdge, SMOOTH_SVG_SCALE_FACTOR,
     SUBPIXEL_VARIANTS_X, SUBPIXEL_VARIANTS_Y, ScaledPixels, Scene, Shadow, SharedString, Size,
@@ -28,11 +32,11 @@ use crate::{
     SystemWindowTabController, TabStopMap, TaffyLayoutEngine, Task, TextRenderingMode, TextStyle,
     TextStyleRefinement, ThermalState, TransformationMatrix, Underline, UnderlineStyle,
     WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControls, WindowDecorations,
-    WindowOptions, WindowParams, WindowTextSystem, point, prelude::*, px, rems, size,
-    transparent_black,
+    WindowOptions, WindowParams, WindowTextSystem, point, prelude::*, px,
+    register_focus_group_listener, rems, size, transparent_black,
 };
 use anyhow::{Context as _, Result, anyhow};
-use collections::{FxHashMap, FxHashSet};
+use collections::{FxHashMap, FxHashSet, FxHasher};
 #[cfg(target_os = "macos")]
 use core_video::pixel_buffer::CVPixelBuffer;
 use derive_more::{Deref, DerefMut};
@@ -67,9 +71,11 @@ use gpui_util::{ResultExt, measure, post_inc};
 use uuid::Uuid;
 
 mod prompts;
+mod restoration;
 
 use crate::util::atomic_incr_if_not_zero;
 pub use prompts::*;
+pub use restoration::*;
 
 fn cursor_debug_enabled() -> bool {
     static ENABLED: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
@@ -3046,6 +3052,10 @@ pub(crate) struct Frame {
     #[cfg(any(feature = "inspector", debug_assertions))]
     pub(crate) inspector_hitboxes: FxHashMap<HitboxId, crate::InspectorElementId>,
     pub(crate) tab_stops: TabStopMap,
+    pub(crate) focus_groups: FocusGroupMap,
+    pub(crate) focus_traps: FocusTrapMap,
+    pub(crate) accessibility_nodes: Vec<AccessibilityNodeEntry>,
+    accessibility_id_counts: FxHashMap<u64, usize>,
 }
 
 #[derive(Clone, Default)]
@@ -3055,6 +3065,7 @@ pub(crate) struct PrepaintStateIndex {
     deferred_draws_index: usize,
     dispatch_tree_index: usize,
     accessed_element_states_index: usize,
+    accessibility_nodes_index: usize,
     line_layout_index: LineLayoutIndex,
 }
 
@@ -3066,6 +3077,8 @@ pub(crate) struct PaintIndex {
     cursor_styles_index: usize,
     accessed_element_states_index: usize,
     tab_handle_index: usize,
+    focus_group_index: FocusGroupIndex,
+    focus_trap_index: FocusTrapIndex,
     line_layout_index: LineLayoutIndex,
 }
 
@@ -3095,6 +3108,10 @@ impl Frame {
             #[cfg(any(feature = "inspector", debug_assertions))]
             inspector_hitboxes: FxHashMap::default(),
             tab_stops: TabStopMap::default(),
+            focus_groups: FocusGroupMap::default(),
+            focus_traps: FocusTrapMap::default(),
+            accessibility_nodes: Vec::new(),
+            accessibility_id_counts: FxHashMap::default(),
         }
     }
 
@@ -3111,6 +3128,10 @@ impl Frame {
         self.window_control_hitboxes.clear();
         self.deferred_draws.clear();
         self.tab_stops.clear();
+        self.focus_groups.clear();
+        self.focus_traps.clear();
+        self.accessibility_nodes.clear();
+        self.accessibility_id_counts.clear();
         self.focus = None;
 
         #[cfg(any(feature = "inspector", debug_assertions))]
@@ -3219,6 +3240,7 @@ pub struct Window {
     focus_listeners: SubscriberSet<(), AnyWindowFocusListener>,
     pub(crate) focus_lost_listeners: SubscriberSet<(), AnyObserver>,
     default_prevented: bool,
+    drop_accepted: bool,
     mouse_position: Point<Pixels>,
     mouse_hit_test: HitTest,
     modifiers: Modifiers,
@@ -3238,11 +3260,15 @@ pub struct Window {
     pub(crate) activation_observers: SubscriberSet<(), AnyObserver>,
     pub(crate) focus: Option<FocusId>,
     focus_enabled: bool,
+    pub(crate) focus_group_type_ahead: FocusGroupTypeAhead,
     pending_input: Option<PendingInput>,
     pending_modifier: ModifierState,
     pub(crate) pending_input_observers: SubscriberSet<(), AnyObserver>,
     prompt: Option<RenderablePromptHandle>,
     pub(crate) client_inset: Option<Pixels>,
+    accessibility_active: bool,
+    accessibility_node_stack: Vec<AccessibilityNodeId>,
+    accessibility_tree: Option<AccessibilityTree>,
     #[cfg(any(feature = "inspector", debug_assertions))]
     inspector: Option<Entity<Inspector>>,
     /// Stack of native view pointers for surface rendering. When a surface is being
@@ -3251,6 +3277,8 @@ pub struct Window {
     /// native controls (NativeButton, NativeToggleGroup, etc.) work inside surfaces
     /// with zero changes.
     native_view_override_stack: Vec<*mut std::ffi::c_void>,
+    /// GPUI-drawn native controls, used when the platform doesn't provide its own.
+    pub(crate) fallback_native_controls: FallbackNativeControls,
     /// Secondary GPUI rendering surfaces, each with their own Metal layer and element tree.
     #[cfg(target_os = "macos")]
     pub(crate) surfaces: FxHashMap<SurfaceId, SurfaceState>,
@@ -3319,6 +3347,16 @@ pub(crate) enum DrawPhase {
     Focus,
 }
 
+/// The keystrokes a window is holding on to because they begin a multi-stroke binding. See
+/// [`Context::observe_pending_keystrokes`].
+#[derive(Clone, Debug, Default)]
+pub struct PendingKeystrokes {
+    /// The keystrokes typed so far, or none if no input is pending.
+    pub keystrokes: Vec<Keystroke>,
+    /// The bindings that could complete the keystrokes, highest precedence first.
+    pub possible_bindings: Vec<KeyBinding>,
+}
+
 #[derive(Default, Debug)]
 struct PendingInput {
     keystrokes: SmallVec<[Keystroke; 1]>,
@@ -3640,6 +3678,16 @@ impl Window {
                 DispatchEventResult::default()
             })
         });
+        platform_window.on_accessibility_action({
+            let mut cx = cx.to_async();
+            Box::new(move |request| {
+                handle
+                    .update(&mut cx, |_, window, cx| {
+                        window.dispatch_accessibility_action(request, cx)
+                    })
+                    .log_err();
+            })
+        });
         platform_window.on_hit_test_window_control({
             let mut cx = cx.to_async();
             Box::new(move || {
@@ -3745,6 +3793,7 @@ impl Window {
             focus_listeners: SubscriberSet::new(),
             focus_lost_listeners: SubscriberSet::new(),
             default_prevented: true,
+            drop_accepted: false,
             mouse_position,
             mouse_hit_test: HitTest::default(),
             modifiers,
@@ -3762,15 +3811,20 @@ impl Window {
             activation_observers: SubscriberSet::new(),
             focus: None,
             focus_enabled: true,
+            focus_group_type_ahead: FocusGroupTypeAhead::default(),
             pending_input: None,
             pending_modifier: ModifierState::default(),
             pending_input_observers: SubscriberSet::new(),
             prompt: None,
             client_inset: None,
+            accessibility_active: false,
+            accessibility_node_stack: Vec::new(),
+            accessibility_tree: None,
             image_cache_stack: Vec::new(),
             #[cfg(any(feature = "inspector", debug_assertions))]
             inspector: None,
             native_view_override_stack: Vec::new(),
+            fallback_native_controls: FallbackNativeControls::new(),
             #[cfg(target_os = "macos")]
             surfaces: FxHashMap::default(),
             #[cfg(target_os = "macos")]
@@ -3793,6 +3847,9 @@ impl Window {
 pub struct DispatchEventResult {
     pub propagate: bool,
     pub default_prevented: bool,
+    /// Whether the drag over the window would be dropped, for replying to other applications
+    /// dragging data over it.
+    pub drop_accepted: bool,
 }
 
 /// Indicates which region of the window is visible. Content falling outside of this mask will not be
@@ -3945,7 +4002,11 @@ impl Window {
             return;
         }
 
-        if let Some(handle) = self.rendered_frame.tab_stops.next(self.focus.as_ref()) {
+        let frame = &self.rendered_frame;
+        if let Some(handle) = frame
+            .focus_traps
+            .next_tab_stop(self.focus, &frame.tab_stops, false)
+        {
             self.focus(&handle, cx)
         }
     }
@@ -3956,7 +4017,11 @@ impl Window {
             return;
         }
 
-        if let Some(handle) = self.rendered_frame.tab_stops.prev(self.focus.as_ref()) {
+        let frame = &self.rendered_frame;
+        if let Some(handle) = frame
+            .focus_traps
+            .next_tab_stop(self.focus, &frame.tab_stops, true)
+        {
             self.focus(&handle, cx)
         }
     }
@@ -3998,6 +4063,50 @@ impl Window {
         self.platform_window.window_bounds()
     }
 
+    /// Return the `WindowState` to restore the window with when it's opened again, see
+    /// [`App::open_restorable_window`].
+    pub fn window_state(&self, cx: &App) -> WindowState {
+        WindowState {
+            bounds: self.window_bounds(),
+            display: self.display(cx).and_then(|display| display.uuid().ok()),
+            scale_factor: self.scale_factor(),
+        }
+    }
+
+    /// Save the state of the window with the given identifier whenever it's moved or resized, to
+    /// the store set with [`App::set_window_state_store`].
+    pub fn persist_state(&mut self, id: impl Into<SharedString>) {
+        let id = id.into();
+        let last_change = Rc::new(Cell::new(0_usize));
+        let (subscription, activate) = self.bounds_observers.insert(
+            (),
+            Box::new(move |window, cx| {
+                let state = window.window_state(cx);
+                let change = last_change.get() + 1;
+                last_change.set(change);
+                let last_change = last_change.clone();
+                let id = id.clone();
+                // Not spawned on the window, so the last change is still saved when it closes.
+                cx.spawn(async move |cx| {
+                    cx.background_executor()
+                        .timer(SAVE_WINDOW_STATE_DELAY)
+                        .await;
+                    if last_change.get() == change {
+                        cx.update(|cx| {
+                            if let Some(store) = window_state_store(cx) {
+                                store.save(&id, &state).log_err();
+                            }
+                        });
+                    }
+                })
+                .detach();
+                true
+            }),
+        );
+        activate();
+        subscription.detach();
+    }
+
     /// Return the `WindowBounds` excluding insets (Wayland and X11)
     pub fn inner_window_bounds(&self) -> WindowBounds {
         self.platform_window.inner_window_bounds()
@@ -4006,18 +4115,90 @@ impl Window {
     /// Dispatch the given action on the currently focused element.
     pub fn dispatch_action(&mut self, action: Box<dyn Action>, cx: &mut App) {
         let focus_id = self.focused(cx).map(|handle| handle.id);
+        // Actions dispatched by an action's handler aren't recorded, even though they run later.
+        let nested = cx.macro_recorder.is_dispatching();
 
         let window = self.handle;
         cx.defer(move |cx| {
             window
                 .update(cx, |_, window, cx| {
                     let node_id = window.focus_node_id_in_rendered_frame(focus_id);
+                    if nested {
+                        cx.macro_recorder.begin_dispatch();
+                    }
                     window.dispatch_action_on_node(node_id, action.as_ref(), cx);
+                    if nested {
+                        cx.macro_recorder.end_dispatch();
+                    }
                 })
                 .log_err();
         })
     }
 
+    /// Replay a recorded macro `repeat` times, dispatching its actions on the focused element and
+    /// inserting its text into the focused input handler. Each step runs in its own update, after
+    /// the window has drawn the previous step's changes, so focus can move in between. The
+    /// returned task fails without replaying anything if one of the recorded actions can't be
+    /// built, and fails partway through if focus isn't in the same kind of element a step was
+    /// recorded in, going by its innermost key context. It stops early if the window is closed.
+    pub fn replay_macro(
+        &self,
+        recording: &MacroRecording,
+        repeat: usize,
+        cx: &App,
+    ) -> Task<Result<()>> {
+        let steps = match recording
+            .steps
+            .iter()
+            .map(|step| MacroReplayStep::build(step, cx))
+            .collect::<Result<Vec<_>>>()
+        {
+            Ok(steps) => steps,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        self.spawn(cx, async move |cx| {
+            for _ in 0..repeat {
+                for step in &steps {
+                    let next_frame = cx.update(|window, _| {
+                        window.invalidator.is_dirty().then(|| {
+                            let (tx, rx) = oneshot::channel();
+                            window.on_next_frame(move |_, _| {
+                                tx.send(()).ok();
+                            });
+                            rx
+                        })
+                    })?;
+                    // The window draws right after running its next frame callbacks.
+                    if let Some(next_frame) = next_frame {
+                        next_frame.await.ok();
+                    }
+                    cx.update(|window, cx| window.replay_macro_step(step, cx))??;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn replay_macro_step(&mut self, step: &MacroReplayStep, cx: &mut App) -> Result<()> {
+        step.check_context(&self.context_stack())?;
+        cx.macro_recorder.begin_dispatch();
+        match &step.kind {
+            MacroReplayStepKind::Action(action) => {
+                let node_id = self.focus_node_id_in_rendered_frame(self.focus);
+                self.dispatch_action_on_node(node_id, action.as_ref(), cx);
+            }
+            MacroReplayStepKind::Text(text) => {
+                if let Some(mut input_handler) = self.platform_window.take_input_handler() {
+                    input_handler.dispatch_input(text, self, cx);
+                    self.platform_window.set_input_handler(input_handler);
+                }
+            }
+        }
+        cx.macro_recorder.end_dispatch();
+        Ok(())
+    }
+
     pub(crate) fn dispatch_keystroke_observers(
         &mut self,
         event: &dyn Any,
@@ -4356,6 +4537,34 @@ impl Window {
         self.platform_window.start_window_move()
     }
 
+    /// Drag data out of the window to other applications, resolving to what the drop target did
+    /// with it. Call this from the constructor passed to
+    /// [`crate::StatefulInteractiveElement::on_drag`], while the mouse button is still held. The
+    /// view it returns previews the drag within the window, and the drag it starts ends along with
+    /// the external one.
+    ///
+    /// Resolves to [`ExternalDragOutcome::Cancelled`] on platforms that can't drag out of the
+    /// window (currently all but Wayland and X11).
+    pub fn start_external_drag(
+        &mut self,
+        drag: ExternalDrag,
+        cx: &mut App,
+    ) -> Task<ExternalDragOutcome> {
+        let Some(outcome) = self.platform_window.start_external_drag(drag) else {
+            return Task::ready(ExternalDragOutcome::Cancelled);
+        };
+        self.spawn(cx, async move |cx| {
+            let outcome = outcome.await.unwrap_or(ExternalDragOutcome::Cancelled);
+            cx.update(|window, cx| {
+                if cx.active_drag.take().is_some() {
+                    window.refresh();
+                }
+            })
+            .ok();
+            outcome
+        })
+    }
+
     /// When using client side decorations, set this to the width of the invisible decorations (Wayland and X11)
     pub fn set_client_inset(&mut self, inset: Pixels) {
         self.client_inset = Some(inset);
@@ -4603,10 +4812,36 @@ impl Window {
 
     /// Returns the platform's native controls implementation for creating
     /// and updating native UI elements (buttons, text fields, etc.).
+    ///
+    /// Platforms without native controls get controls drawn by GPUI instead.
     pub fn native_controls(&self) -> &dyn crate::platform::native_controls::PlatformNativeControls {
         self.platform_window
             .native_controls()
-            .expect("native controls not available on this platform")
+            .unwrap_or(&self.fallback_native_controls)
+    }
+
+    /// Returns the view native controls should be added to, or `None` if they can't be
+    /// shown right now. Controls drawn by GPUI don't need a native view, so they're always
+    /// shown on platforms without native controls.
+    pub(crate) fn native_controls_parent(&self) -> Option<*mut std::ffi::c_void> {
+        let parent = self.raw_native_view_ptr();
+        let requires_native_view = self
+            .platform_window
+            .native_controls()
+            .is_some_and(|controls| controls.requires_native_view());
+        if parent.is_null() && requires_native_view {
+            None
+        } else {
+            Some(parent)
+        }
+    }
+
+    /// Paints the GPUI-drawn controls updated by the element that just painted, so they
+    /// appear in place among the rest of its content.
+    pub(crate) fn paint_queued_native_controls(&mut self, cx: &mut App) {
+        for paint in self.fallback_native_controls.take_queued() {
+            paint(self, cx);
+        }
     }
 
     pub(crate) fn configure_hosted_content(
@@ -4804,6 +5039,12 @@ impl Window {
         self.default_prevented
     }
 
+    /// Mark the drag in progress as one that would be dropped where the mouse is, which is
+    /// reported back to the application the drag came from.
+    pub(crate) fn accept_drop(&mut self) {
+        self.drop_accepted = true;
+    }
+
     /// Determine whether the given action is available along the dispatch path to the currently focused element.
     pub fn is_action_available(&self, action: &dyn Action, cx: &App) -> bool {
         let node_id =
@@ -4860,6 +5101,14 @@ impl Window {
         self.invalidator.set_dirty(false);
         self.requested_autoscroll = None;
 
+        // Views cached while no assistive technology was listening didn't record any
+        // accessibility nodes, so redraw everything once one starts listening.
+        let accessibility_active = self.platform_window.is_accessibility_active();
+        if accessibility_active && !self.accessibility_active {
+            self.refreshing = true;
+        }
+        self.accessibility_active = accessibility_active;
+
         // Restore the previously-used input handler.
         if let Some(input_handler) = self.platform_window.take_input_handler() {
             self.rendered_frame.input_handlers.push(Some(input_handler));
@@ -4883,6 +5132,12 @@ impl Window {
         self.layout_engine.as_mut().unwrap().clear();
         self.text_system().finish_frame();
         self.next_frame.finish(&mut self.rendered_frame);
+        let restore_focus = self.rendered_frame.focus_traps.focus_to_restore(
+            &self.rendered_frame.tab_stops,
+            &self.next_frame.focus_traps,
+            &self.next_frame.tab_stops,
+            self.focus,
+        );
 
         self.invalidator.set_phase(DrawPhase::Focus);
         let previous_focus_path = self.rendered_frame.focus_path();
@@ -4919,15 +5174,33 @@ impl Window {
         }
 
         debug_assert!(self.rendered_entity_stack.is_empty());
+        self.update_accessibility_tree();
         self.record_entities_accessed(cx);
         self.reset_cursor_style(cx);
         self.refreshing = false;
         self.invalidator.set_phase(DrawPhase::None);
         self.needs_present.set(true);
+        self.update_trapped_focus(restore_focus, cx);
 
         ArenaClearNeeded::new(&cx.element_arena)
     }
 
+    /// Restore the focus from before a focus trap was painted, now that it's gone, or focus the
+    /// first tab stop in a trap painted for the first time.
+    fn update_trapped_focus(&mut self, restore_focus: Option<FocusId>, cx: &mut App) {
+        let handle = restore_focus
+            .and_then(|focus_id| FocusHandle::for_id(focus_id, &cx.focus_handles))
+            .or_else(|| {
+                let frame = &self.rendered_frame;
+                frame
+                    .focus_traps
+                    .autofocus_target(self.focus, &frame.tab_stops)
+            });
+        if let Some(handle) = handle {
+            self.focus(&handle, cx);
+        }
+    }
+
     fn record_entities_accessed(&mut self, cx: &mut App) {
         let mut entities_ref = cx.entities.accessed_entities.borrow_mut();
         let mut entities = mem::take(entities_ref.deref_mut());
@@ -5008,6 +5281,13 @@ impl Window {
         sorted_deferred_draws.sort_by_key(|ix| self.next_frame.deferred_draws[*ix].priority);
         self.prepaint_deferred_draws(&sorted_deferred_draws, cx);
 
+        // Menus and alerts opened by GPUI-drawn native controls sit above everything but
+        // prompts.
+        let mut native_controls_overlay = self.fallback_native_controls.render_overlay(self);
+        if let Some(element) = native_controls_overlay.as_mut() {
+            element.prepaint_as_root(Point::default(), root_size.into(), self, cx);
+        }
+
         let mut prompt_element = None;
         let mut active_drag_element = None;
         let mut tooltip_element = None;
@@ -5037,6 +5317,10 @@ impl Window {
 
         self.paint_deferred_draws(&sorted_deferred_draws, cx);
 
+        if let Some(mut element) = native_controls_overlay {
+            element.paint(self, cx);
+        }
+
         if let Some(mut prompt_element) = prompt_element {
             prompt_element.paint(self, cx);
         } else if let Some(mut drag_element) = active_drag_element {
@@ -5323,6 +5607,7 @@ impl Window {
             deferred_draws_index: self.next_frame.deferred_draws.len(),
             dispatch_tree_index: self.next_frame.dispatch_tree.len(),
             accessed_element_states_index: self.next_frame.accessed_element_states.len(),
+            accessibility_nodes_index: self.next_frame.accessibility_nodes.len(),
             line_layout_index: self.text_system.layout_index(),
         }
     }
@@ -5347,6 +5632,9 @@ impl Window {
         );
         self.text_system
             .reuse_layouts(range.start.line_layout_index..range.end.line_layout_index);
+        self.reuse_accessibility_nodes(
+            range.start.accessibility_nodes_index..range.end.accessibility_nodes_index,
+        );
 
         let reused_subtree = self.next_frame.dispatch_tree.reuse_subtree(
             range.start.dispatch_tree_index..range.end.dispatch_tree_index,
@@ -5378,6 +5666,29 @@ impl Window {
         );
     }
 
+    fn reuse_accessibility_nodes(&mut self, range: Range<usize>) {
+        let reused_nodes = &self.rendered_frame.accessibility_nodes[range];
+        let parent = self.accessibility_node_stack.last().copied();
+        let mut reused_ids = FxHashSet::default();
+        for node in reused_nodes {
+            let mut node = node.clone();
+            // Nodes whose parent was outside of the reused range attach to the current node.
+            if !node
+                .parent
+                .is_some_and(|parent| reused_ids.contains(&parent))
+            {
+                node.parent = parent;
+            }
+            reused_ids.insert(node.id);
+            *self
+                .next_frame
+                .accessibility_id_counts
+                .entry(node.id_base)
+                .or_default() += 1;
+            self.next_frame.accessibility_nodes.push(node);
+        }
+    }
+
     pub(crate) fn paint_index(&self) -> PaintIndex {
         PaintIndex {
             scene_index: self.next_frame.scene.len(),
@@ -5386,6 +5697,11 @@ impl Window {
             cursor_styles_index: self.next_frame.cursor_styles.len(),
             accessed_element_states_index: self.next_frame.accessed_element_states.len(),
             tab_handle_index: self.next_frame.tab_stops.paint_index(),
+            focus_group_index: self.next_frame.focus_groups.paint_index(),
+            focus_trap_index: self
+                .next_frame
+                .focus_traps
+                .paint_index(&self.next_frame.tab_stops),
             line_layout_index: self.text_system.layout_index(),
         }
     }
@@ -5415,10 +5731,20 @@ impl Window {
                 .iter()
                 .map(|(id, type_id)| (id.clone(), *type_id)),
         );
+        let tab_stop_start = self.next_frame.tab_stops.paint_index();
         self.next_frame.tab_stops.replay(
             &self.rendered_frame.tab_stops.insertion_history
                 [range.start.tab_handle_index..range.end.tab_handle_index],
         );
+        self.next_frame.focus_traps.reuse(
+            &self.rendered_frame.focus_traps,
+            range.start.focus_trap_index..range.end.focus_trap_index,
+            tab_stop_start,
+        );
+        self.next_frame.focus_groups.reuse(
+            &self.rendered_frame.focus_groups,
+            range.start.focus_group_index..range.end.focus_group_index,
+        );
 
         self.text_system
             .reuse_layouts(range.start.line_layout_index..range.end.line_layout_index);
@@ -5834,6 +6160,80 @@ impl Window {
         }
     }
 
+    /// Executes the given closure within a focus group, making the tab stops it paints into
+    /// members that are navigated with the arrow keys. See [`FocusGroup`].
+    pub(crate) fn with_focus_group<R>(
+        &mut self,
+        id: Option<&GlobalElementId>,
+        focus_group: Option<FocusGroup>,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        let (Some(id), Some(focus_group)) = (id, focus_group) else {
+            return f(self);
+        };
+        self.next_frame
+            .focus_groups
+            .begin_group(id.clone(), focus_group, &self.rendered_frame.focus_groups);
+        // Registered before painting the members, so that they handle keys first.
+        register_focus_group_listener(id.clone(), self);
+        let result = f(self);
+        let (members, tab_stop) = self.next_frame.focus_groups.end_group(self.focus);
+        for member in members {
+            self.next_frame
+                .tab_stops
+                .set_tab_stop(member, Some(member) == tab_stop);
+        }
+        result
+    }
+
+    /// Executes the given closure within a focus trap, which keeps [`Window::focus_next`] and
+    /// [`Window::focus_prev`] cycling through the tab stops it paints. See
+    /// [`crate::StatefulInteractiveElement::focus_trap`].
+    pub(crate) fn with_focus_trap<R>(
+        &mut self,
+        id: Option<&GlobalElementId>,
+        focus_trap: bool,
+        root: Option<FocusId>,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        let Some(id) = id.filter(|_| focus_trap) else {
+            return f(self);
+        };
+        self.next_frame.focus_traps.begin_trap(
+            id.clone(),
+            root,
+            &self.next_frame.tab_stops,
+            &self.rendered_frame.focus_traps,
+        );
+        let result = f(self);
+        self.next_frame
+            .focus_traps
+            .end_trap(&self.next_frame.tab_stops, self.rendered_frame.focus);
+        result
+    }
+
+    /// Inserts a tab stop, as a member of the innermost focus group if there is one.
+    pub(crate) fn insert_tab_stop(
+        &mut self,
+        focus_handle: &FocusHandle,
+        bounds: Bounds<Pixels>,
+        label: Option<SharedString>,
+    ) {
+        if focus_handle.tab_stop
+            && self
+                .next_frame
+                .focus_groups
+                .insert_member(focus_handle, bounds, label)
+        {
+            // Whether it's a tab stop is decided once the whole group has been painted.
+            let mut focus_handle = focus_handle.clone();
+            focus_handle.tab_stop = false;
+            self.next_frame.tab_stops.insert(&focus_handle);
+        } else {
+            self.next_frame.tab_stops.insert(focus_handle);
+        }
+    }
+
     /// Executes the given closure within the context of a tab group.
     #[inline]
     pub fn with_tab_group<R>(&mut self, index: Option<isize>, f: impl FnOnce(&mut Self) -> R) -> R {
@@ -6493,6 +6893,198 @@ impl Window {
         self.next_frame.dispatch_tree.set_view_id(view_id);
     }
 
+    /// Whether an assistive technology is listening to this window. Elements only contribute
+    /// to the accessibility tree while this is true, so expensive descriptions can be skipped
+    /// otherwise.
+    pub fn is_accessibility_active(&self) -> bool {
+        self.accessibility_active
+    }
+
+    /// Adds a node to the window's accessibility tree, describing the element with the given
+    /// bounds. Nodes added while `f` runs become children of this node. If a focus handle is
+    /// given, the node reports it as focusable and can be focused by assistive technologies.
+    ///
+    /// This method should only be called as part of the prepaint phase of element drawing.
+    pub fn with_accessibility_node<R>(
+        &mut self,
+        properties: AccessibilityProperties,
+        bounds: Bounds<Pixels>,
+        focus_handle: Option<&FocusHandle>,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        self.invalidator.debug_assert_prepaint();
+        if !self.accessibility_active {
+            return f(self);
+        }
+
+        let parent = self.accessibility_node_stack.last().copied();
+        let mut hasher = FxHasher::default();
+        self.element_id_stack.hash(&mut hasher);
+        parent.hash(&mut hasher);
+        let id_base = hasher.finish();
+        let count = self
+            .next_frame
+            .accessibility_id_counts
+            .entry(id_base)
+            .or_default();
+        let mut hasher = FxHasher::default();
+        (id_base, *count).hash(&mut hasher);
+        *count += 1;
+        let id = AccessibilityNodeId(hasher.finish().max(1));
+
+        let mut properties = properties;
+        if focus_handle.is_some() {
+            properties.add_action(AccessibilityAction::Focus);
+        }
+        self.next_frame
+            .accessibility_nodes
+            .push(AccessibilityNodeEntry {
+                id,
+                id_base,
+                parent,
+                bounds,
+                focus_handle: focus_handle.cloned(),
+                properties,
+                action_listeners: SmallVec::new(),
+            });
+
+        self.accessibility_node_stack.push(id);
+        let result = f(self);
+        self.accessibility_node_stack.pop();
+        result
+    }
+
+    /// Registers a listener for requests from assistive technologies to perform the given action
+    /// on the current accessibility node, as pushed by [`Window::with_accessibility_node`]. The
+    /// action is also added to the node's supported actions.
+    ///
+    /// This method should only be called as part of the prepaint phase of element drawing.
+    pub fn on_accessibility_action(
+        &mut self,
+        action: AccessibilityAction,
+        listener: impl Fn(&AccessibilityActionRequest, &mut Window, &mut App) + 'static,
+    ) {
+        self.invalidator.debug_assert_prepaint();
+        let Some(id) = self.accessibility_node_stack.last() else {
+            return;
+        };
+        if let Some(entry) = self
+            .next_frame
+            .accessibility_nodes
+            .iter_mut()
+            .rev()
+            .find(|entry| entry.id == *id)
+        {
+            entry.properties.add_action(action);
+            entry.action_listeners.push((action, Rc::new(listener)));
+        }
+    }
+
+    pub(crate) fn accessibility_nodes_len(&self) -> usize {
+        self.next_frame.accessibility_nodes.len()
+    }
+
+    /// Discard the accessibility nodes added since [`Self::accessibility_nodes_len`] returned
+    /// `len`, e.g. when an element prepaints its children a second time.
+    pub(crate) fn truncate_accessibility_nodes(&mut self, len: usize) {
+        for node in self.next_frame.accessibility_nodes.drain(len..) {
+            if let Some(count) = self
+                .next_frame
+                .accessibility_id_counts
+                .get_mut(&node.id_base)
+            {
+                *count -= 1;
+            }
+        }
+    }
+
+    /// The accessibility tree of the last drawn frame, if an assistive technology is listening.
+    pub fn accessibility_tree(&self) -> Option<&AccessibilityTree> {
+        self.accessibility_tree.as_ref()
+    }
+
+    /// Perform an action requested by an assistive technology. Listeners registered with
+    /// [`Window::on_accessibility_action`] take precedence; otherwise focus requests focus the
+    /// node's [`FocusHandle`] and click requests simulate a mouse click at the node's center.
+    pub fn dispatch_accessibility_action(
+        &mut self,
+        request: AccessibilityActionRequest,
+        cx: &mut App,
+    ) {
+        let Some(entry) = self
+            .rendered_frame
+            .accessibility_nodes
+            .iter()
+            .find(|entry| entry.id == request.target)
+        else {
+            return;
+        };
+
+        let listener = entry
+            .action_listeners
+            .iter()
+            .find(|(action, _)| *action == request.action)
+            .map(|(_, listener)| listener.clone());
+        if let Some(listener) = listener {
+            listener(&request, self, cx);
+            return;
+        }
+
+        match request.action {
+            AccessibilityAction::Focus => {
+                if let Some(focus_handle) = entry.focus_handle.clone() {
+                    self.focus(&focus_handle, cx);
+                }
+            }
+            AccessibilityAction::Click
+                if entry
+                    .properties
+                    .actions
+                    .contains(&AccessibilityAction::Click) =>
+            {
+                let position = entry.bounds.center();
+                self.dispatch_event(
+                    PlatformInput::MouseDown(MouseDownEvent {
+                        button: MouseButton::Left,
+                        position,
+                        modifiers: Modifiers::default(),
+                        click_count: 1,
+                        first_mouse: false,
+                    }),
+                    cx,
+                );
+                self.dispatch_event(
+                    PlatformInput::MouseUp(MouseUpEvent {
+                        button: MouseButton::Left,
+                        position,
+                        modifiers: Modifiers::default(),
+                        click_count: 1,
+                    }),
+                    cx,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn update_accessibility_tree(&mut self) {
+        if !self.accessibility_active {
+            self.accessibility_tree = None;
+            return;
+        }
+
+        let window_bounds = Bounds::new(Point::default(), self.viewport_size);
+        let tree = AccessibilityTree::new(
+            window_bounds,
+            &self.rendered_frame.accessibility_nodes,
+            |focus_handle| Some(focus_handle.id) == self.focus,
+        );
+        if self.accessibility_tree.as_ref() != Some(&tree) {
+            self.platform_window.update_accessibility_tree(&tree);
+            self.accessibility_tree = Some(tree);
+        }
+    }
+
     /// Get the entity ID for the currently rendering view
     pub fn current_view(&self) -> EntityId {
         self.invalidator.debug_assert_paint_or_prepaint();
@@ -6741,6 +7333,8 @@ impl Window {
         cx.propagate_event = true;
         // Handlers may set this to true by calling `prevent_default`.
         self.default_prevented = false;
+        // Drop targets under the mouse set this to true by calling `accept_drop`.
+        self.drop_accepted = false;
 
         let event = match event {
             // Track the mouse position with our own state, since accessing the platform
@@ -6828,6 +7422,59 @@ impl Window {
                     PlatformInput::FileDrop(FileDropEvent::Exited)
                 }
             },
+            // Translate dragging and dropping of any data from other applications to internal
+            // drag and drop events too, dragging files as they are above.
+            PlatformInput::ExternalDrop(external_drop) => match external_drop {
+                ExternalDropEvent::Entered { position, item } => {
+                    self.mouse_position = position;
+                    if cx.active_drag.is_none() {
+                        let paths = item.entries().iter().find_map(|entry| match entry {
+                            ClipboardEntry::ExternalPaths(paths) => Some(paths.clone()),
+                            _ => None,
+                        });
+                        let (value, view): (Arc<dyn Any>, AnyView) = match paths {
+                            Some(paths) => (Arc::new(paths.clone()), cx.new(|_| paths).into()),
+                            None => {
+                                let drop = ExternalDrop(item);
+                                (Arc::new(drop.clone()), cx.new(|_| drop).into())
+                            }
+                        };
+                        cx.active_drag = Some(AnyDrag {
+                            value,
+                            view,
+                            cursor_offset: position,
+                            cursor_style: None,
+                        });
+                    }
+                    PlatformInput::MouseMove(MouseMoveEvent {
+                        position,
+                        pressed_button: Some(MouseButton::Left),
+                        modifiers: Modifiers::default(),
+                    })
+                }
+                ExternalDropEvent::Over { position } => {
+                    self.mouse_position = position;
+                    PlatformInput::MouseMove(MouseMoveEvent {
+                        position,
+                        pressed_button: Some(MouseButton::Left),
+                        modifiers: Modifiers::default(),
+                    })
+                }
+                ExternalDropEvent::Dropped { position } => {
+                    cx.activate(true);
+                    self.mouse_position = position;
+                    PlatformInput::MouseUp(MouseUpEvent {
+                        button: MouseButton::Left,
+                        position,
+                        modifiers: Modifiers::default(),
+                        click_count: 1,
+                    })
+                }
+                ExternalDropEvent::Exited => {
+                    cx.active_drag.take();
+                    PlatformInput::ExternalDrop(ExternalDropEvent::Exited)
+                }
+            },
             PlatformInput::KeyDown(_) | PlatformInput::KeyUp(_) => event,
         };
 
@@ -6844,6 +7491,7 @@ impl Window {
         DispatchEventResult {
             propagate: cx.propagate_event,
             default_prevented: self.default_prevented,
+            drop_accepted: self.drop_accepted,
         }
     }
 
@@ -7113,9 +7761,14 @@ impl Window {
             currently_pending.needs_timeout |=
                 match_result.pending_has_binding || text_input_requires_timeout;
 
-            if currently_pending.needs_timeout {
+            let timeout = cx
+                .pending_input_timeouts
+                .timeout_for(&match_result.context_stack);
+            if currently_pending.needs_timeout
+                && let PendingInputTimeout::After(timeout) = timeout
+            {
                 currently_pending.timer = Some(self.spawn(cx, async move |cx| {
-                    cx.background_executor.timer(Duration::from_secs(1)).await;
+                    cx.background_executor.timer(timeout).await;
                     cx.update(move |window, cx| {
                         let Some(currently_pending) = window
                             .pending_input
@@ -7277,6 +7930,24 @@ impl Window {
             .map(|pending_input| pending_input.keystrokes.as_slice())
     }
 
+    /// Returns the pending input keystrokes, along with the bindings that could complete them in
+    /// the current context, highest precedence first.
+    pub fn pending_keystrokes(&self) -> PendingKeystrokes {
+        let keystrokes = self
+            .pending_input_keystrokes()
+            .map(<[Keystroke]>::to_vec)
+            .unwrap_or_default();
+        let possible_bindings = if keystrokes.is_empty() {
+            Vec::new()
+        } else {
+            self.possible_bindings_for_input(&keystrokes)
+        };
+        PendingKeystrokes {
+            keystrokes,
+            possible_bindings,
+        }
+    }
+
     fn replay_pending_input(&mut self, replays: SmallVec<[Replay; 1]>, cx: &mut App) {
         let node_id = self.focus_node_id_in_rendered_frame(self.focus);
         let dispatch_path = self.rendered_frame.dispatch_tree.dispatch_path(node_id);
@@ -7330,6 +8001,28 @@ impl Window {
         node_id: DispatchNodeId,
         action: &dyn Action,
         cx: &mut App,
+    ) {
+        let outermost = cx.macro_recorder.begin_dispatch();
+        self.dispatch_action_on_node_inner(node_id, action, cx);
+        cx.macro_recorder.end_dispatch();
+
+        let handled = !cx.propagate_event;
+        if outermost && handled && cx.macro_recorder.is_recording() {
+            let dispatch_tree = &self.rendered_frame.dispatch_tree;
+            let context_stack = dispatch_tree
+                .dispatch_path(node_id)
+                .into_iter()
+                .filter_map(|node_id| dispatch_tree.node(node_id).context.clone())
+                .collect::<Vec<_>>();
+            cx.macro_recorder.record_action(action, &context_stack);
+        }
+    }
+
+    fn dispatch_action_on_node_inner(
+        &mut self,
+        node_id: DispatchNodeId,
+        action: &dyn Action,
+        cx: &mut App,
     ) {
         let dispatch_path = self.rendered_frame.dispatch_tree.dispatch_path(node_id);
 
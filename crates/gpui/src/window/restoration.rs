@@ -0,0 +1,267 @@
+use std::{collections::BTreeMap, fs, io, path::PathBuf, rc::Rc, time::Duration};
+
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    App, Bounds, Global, Pixels, PlatformDisplay, WindowBounds, WindowOptions, point, size,
+    util::write_atomically,
+};
+
+/// How long the bounds of a window have to stay the same before its state is saved, so that it
+/// isn't saved at every step of a resize.
+pub(crate) const SAVE_WINDOW_STATE_DELAY: Duration = Duration::from_millis(500);
+
+/// The state of a window that's restored when it's opened again, see
+/// [`App::open_restorable_window`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WindowState {
+    /// The bounds of the window, and whether it was maximized or fullscreen.
+    pub bounds: WindowBounds,
+    /// The UUID of the display the window was on, see [`PlatformDisplay::uuid`].
+    pub display: Option<Uuid>,
+    /// The scale factor of the window. Bounds are in logical pixels, so they're restored as they
+    /// are on displays with a different scale factor.
+    pub scale_factor: f32,
+}
+
+/// Where the states of windows are kept, keyed by the stable identifiers the windows are opened
+/// with. Set with [`App::set_window_state_store`].
+pub trait WindowStateStore: 'static {
+    /// Load the saved state of the window with the given identifier, if there's one.
+    fn load(&self, id: &str) -> Result<Option<WindowState>>;
+
+    /// Save the state of the window with the given identifier.
+    fn save(&self, id: &str, state: &WindowState) -> Result<()>;
+}
+
+/// Keeps the states of windows in a JSON file, as an object mapping window identifiers to their
+/// states.
+pub struct JsonWindowStateStore {
+    path: PathBuf,
+}
+
+impl JsonWindowStateStore {
+    /// Create a store keeping window states in the file at the given path, which is created when a
+    /// state is first saved.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    fn read(&self) -> Result<BTreeMap<String, WindowState>> {
+        let json = match fs::read_to_string(&self.path) {
+            Ok(json) => json,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("failed to read window states {:?}", self.path));
+            }
+        };
+        serde_json::from_str(&json)
+            .with_context(|| format!("invalid window states {:?}", self.path))
+    }
+}
+
+impl WindowStateStore for JsonWindowStateStore {
+    fn load(&self, id: &str) -> Result<Option<WindowState>> {
+        Ok(self.read()?.remove(id))
+    }
+
+    fn save(&self, id: &str, state: &WindowState) -> Result<()> {
+        // Start over rather than never saving again when the file can't be parsed.
+        let mut states = self.read().unwrap_or_default();
+        states.insert(id.to_string(), state.clone());
+        write_atomically(&self.path, serde_json::to_string_pretty(&states)?)
+            .with_context(|| format!("failed to write window states {:?}", self.path))
+    }
+}
+
+pub(crate) struct GlobalWindowStateStore(pub(crate) Rc<dyn WindowStateStore>);
+
+impl Global for GlobalWindowStateStore {}
+
+pub(crate) fn window_state_store(cx: &App) -> Option<Rc<dyn WindowStateStore>> {
+    cx.try_global::<GlobalWindowStateStore>()
+        .map(|store| store.0.clone())
+}
+
+/// Open a window with the bounds of the given state, on the display it was on if it's still
+/// connected, and within the visible bounds of the display it's opened on.
+pub(crate) fn apply_window_state(state: &WindowState, options: &mut WindowOptions, cx: &App) {
+    let display = restored_display(state, cx);
+    options.window_bounds = Some(match &display {
+        Some(display) => clamp_window_bounds(state.bounds, display.visible_bounds()),
+        None => state.bounds,
+    });
+    options.display_id = display.map(|display| display.id());
+}
+
+/// The display to restore a window on: the one it was on if it's still connected, or the primary
+/// display otherwise.
+fn restored_display(state: &WindowState, cx: &App) -> Option<Rc<dyn PlatformDisplay>> {
+    state
+        .display
+        .and_then(|uuid| {
+            cx.displays()
+                .into_iter()
+                .find(|display| display.uuid().ok() == Some(uuid))
+        })
+        .or_else(|| cx.primary_display())
+}
+
+/// Move and shrink the bounds of a window as little as possible for it to be within the given area
+/// of a display, leaving them as they are if they already are.
+fn clamp_window_bounds(bounds: WindowBounds, area: Bounds<Pixels>) -> WindowBounds {
+    let clamp = |bounds: Bounds<Pixels>| {
+        let size = size(
+            bounds.size.width.min(area.size.width),
+            bounds.size.height.min(area.size.height),
+        );
+        let origin = point(
+            bounds
+                .origin
+                .x
+                .max(area.origin.x)
+                .min(area.right() - size.width),
+            bounds
+                .origin
+                .y
+                .max(area.origin.y)
+                .min(area.bottom() - size.height),
+        );
+        Bounds { origin, size }
+    };
+    match bounds {
+        WindowBounds::Windowed(bounds) => WindowBounds::Windowed(clamp(bounds)),
+        WindowBounds::Maximized(bounds) => WindowBounds::Maximized(clamp(bounds)),
+        WindowBounds::Fullscreen(bounds) => WindowBounds::Fullscreen(clamp(bounds)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppContext as _, Empty, TestAppContext, WindowOptions, px};
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct MemoryStore(Rc<RefCell<BTreeMap<String, WindowState>>>);
+
+    impl WindowStateStore for MemoryStore {
+        fn load(&self, id: &str) -> Result<Option<WindowState>> {
+            Ok(self.0.borrow().get(id).cloned())
+        }
+
+        fn save(&self, id: &str, state: &WindowState) -> Result<()> {
+            self.0.borrow_mut().insert(id.to_string(), state.clone());
+            Ok(())
+        }
+    }
+
+    fn bounds(x: f32, y: f32, width: f32, height: f32) -> Bounds<Pixels> {
+        Bounds::new(point(px(x), px(y)), size(px(width), px(height)))
+    }
+
+    #[test]
+    fn test_clamp_window_bounds() {
+        let area = bounds(0., 25., 1920., 1055.);
+        let windowed = WindowBounds::Windowed(bounds(100., 100., 800., 600.));
+        assert_eq!(clamp_window_bounds(windowed, area), windowed);
+        assert_eq!(
+            clamp_window_bounds(
+                WindowBounds::Windowed(bounds(2500., -40., 800., 600.)),
+                area
+            ),
+            WindowBounds::Windowed(bounds(1120., 25., 800., 600.))
+        );
+        assert_eq!(
+            clamp_window_bounds(
+                WindowBounds::Maximized(bounds(-300., 0., 2560., 1440.)),
+                area
+            ),
+            WindowBounds::Maximized(area)
+        );
+    }
+
+    #[test]
+    fn test_json_window_state_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("window-states/states.json");
+        let store = JsonWindowStateStore::new(&path);
+        assert_eq!(store.load("main").unwrap(), None);
+
+        let state = WindowState {
+            bounds: WindowBounds::Fullscreen(bounds(10., 20., 800., 600.)),
+            display: Some(Uuid::new_v4()),
+            scale_factor: 2.,
+        };
+        store.save("main", &state).unwrap();
+        store
+            .save(
+                "settings",
+                &WindowState {
+                    bounds: WindowBounds::Windowed(bounds(0., 0., 400., 300.)),
+                    display: None,
+                    scale_factor: 1.,
+                },
+            )
+            .unwrap();
+        assert_eq!(store.load("main").unwrap(), Some(state));
+
+        fs::write(&path, "not json").unwrap();
+        assert!(store.load("main").is_err());
+    }
+
+    #[crate::test]
+    fn test_restore_window(cx: &mut TestAppContext) {
+        let states = Rc::new(RefCell::new(BTreeMap::new()));
+        cx.update(|cx| cx.set_window_state_store(MemoryStore(states.clone())));
+        let open_window = |cx: &mut TestAppContext| {
+            cx.update(|cx| {
+                let options = WindowOptions {
+                    window_bounds: Some(WindowBounds::Windowed(bounds(0., 0., 640., 480.))),
+                    ..Default::default()
+                };
+                cx.open_restorable_window("main", options, |_, cx| cx.new(|_| Empty))
+                    .unwrap()
+            })
+        };
+
+        let window = open_window(cx);
+        let display = cx.update(|cx| cx.primary_display().unwrap().uuid().unwrap());
+        cx.simulate_window_resize(window.into(), size(px(800.), px(600.)));
+        cx.simulate_window_resize(window.into(), size(px(1024.), px(768.)));
+        assert!(states.borrow().is_empty());
+        cx.executor().advance_clock(SAVE_WINDOW_STATE_DELAY);
+        assert_eq!(
+            states.borrow()["main"],
+            WindowState {
+                bounds: WindowBounds::Windowed(bounds(0., 0., 1024., 768.)),
+                display: Some(display),
+                scale_factor: 2.,
+            }
+        );
+
+        // The display the window was on is gone, and the window doesn't fit on the primary one.
+        states.borrow_mut().insert(
+            "main".into(),
+            WindowState {
+                bounds: WindowBounds::Windowed(bounds(2200., 300., 1024., 2000.)),
+                display: Some(Uuid::new_v4()),
+                scale_factor: 1.,
+            },
+        );
+        window
+            .update(cx, |_, window, _| window.remove_window())
+            .unwrap();
+        let window = open_window(cx);
+        let restored = window
+            .update(cx, |_, window, _| window.window_bounds())
+            .unwrap();
+        assert_eq!(
+            restored,
+            WindowBounds::Windowed(bounds(896., 0., 1024., 1080.))
+        );
+    }
+}
@@ -648,6 +648,7 @@ fn dispatch_input(
         crate::DispatchEventResult {
             propagate: true,
             default_prevented: false,
+            drop_accepted: false,
         }
     }
 }
@@ -1,9 +1,24 @@
+#[cfg(any(feature = "wayland", feature = "x11"))]
+mod accessibility;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+mod clipboard;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+mod dbus_menu;
 mod dispatcher;
 mod headless;
 mod keyboard;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+mod notifications;
 mod platform;
+mod recent_documents;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+mod status_notifier;
 #[cfg(any(feature = "wayland", feature = "x11"))]
 mod text_system;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+mod thermal;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+mod url_scheme;
 #[cfg(feature = "wayland")]
 mod wayland;
 #[cfg(feature = "x11")]
@@ -12,18 +27,33 @@ mod x11;
 #[cfg(any(feature = "wayland", feature = "x11"))]
 mod xdg_desktop_portal;
 
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(crate) use accessibility::*;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(crate) use clipboard::*;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(crate) use dbus_menu::*;
 pub use dispatcher::*;
 pub(crate) use headless::*;
 pub(crate) use keyboard::*;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(crate) use notifications::*;
 pub(crate) use platform::*;
+pub(crate) use recent_documents::*;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(crate) use status_notifier::*;
 #[cfg(any(feature = "wayland", feature = "x11"))]
 pub(crate) use text_system::*;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(crate) use thermal::*;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(crate) use url_scheme::*;
 #[cfg(feature = "wayland")]
 pub(crate) use wayland::*;
 #[cfg(feature = "x11")]
 pub(crate) use x11::*;
 
-use std::rc::Rc;
+use std::{env, path::PathBuf, rc::Rc};
 
 /// Returns the default platform implementation for the current OS.
 pub fn current_platform(headless: bool) -> Rc<dyn gpui::Platform> {
@@ -55,3 +85,41 @@ pub fn current_platform(headless: bool) -> Rc<dyn gpui::Platform> {
         _ => unreachable!(),
     }
 }
+
+/// The name the application is known by on the desktop, the name of its executable.
+pub(crate) fn app_name() -> String {
+    env::current_exe()
+        .ok()
+        .and_then(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+        .unwrap_or_default()
+}
+
+/// The user's data directory, `~/.local/share` unless `XDG_DATA_HOME` says otherwise.
+pub(crate) fn xdg_data_home() -> PathBuf {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+
+/// The user's configuration directory, `~/.config` unless `XDG_CONFIG_HOME` says otherwise.
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(crate) fn xdg_config_home() -> PathBuf {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+/// The system data directories, in order of precedence.
+pub(crate) fn xdg_data_dirs() -> Vec<PathBuf> {
+    let dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    dirs.split(':')
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+fn xdg_dir(variable: &str, default: &str) -> PathBuf {
+    env::var_os(variable)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| util::paths::home_dir().join(default))
+}
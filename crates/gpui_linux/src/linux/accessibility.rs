@@ -0,0 +1,377 @@
+//! Exposes GPUI's accessibility tree to AT-SPI through AccessKit.
+//!
+//! AccessKit calls back into the adapter from its own thread, so activation and action requests
+//! are sent to the window over a channel, which wakes the event loop to handle them right away,
+//! even if the window is idle or hidden and isn't drawing frames.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use accesskit::{
+    ActionData, ActionHandler, ActionRequest, ActivationHandler, DeactivationHandler, NodeId, Rect,
+    Tree, TreeUpdate,
+};
+use futures::channel::mpsc;
+use gpui::{
+    AccessibilityAction, AccessibilityActionRequest, AccessibilityNode, AccessibilityNodeId,
+    AccessibilityTree, Bounds, Pixels, Role, Toggled,
+};
+
+/// A request from an assistive technology, to be handled by the window on the main thread.
+pub(crate) enum AccessibilityRequest {
+    /// An assistive technology started listening, so the window should draw a full frame to
+    /// deliver the initial tree.
+    Activate,
+    /// An assistive technology asked a node to perform an action.
+    Action(AccessibilityActionRequest),
+}
+
+struct Handler {
+    active: Arc<AtomicBool>,
+    requests: mpsc::UnboundedSender<AccessibilityRequest>,
+}
+
+impl ActivationHandler for Handler {
+    fn request_initial_tree(&mut self) -> Option<TreeUpdate> {
+        // The tree can only be built on the main thread, so it's delivered with the next frame.
+        self.active.store(true, Ordering::SeqCst);
+        self.requests
+            .unbounded_send(AccessibilityRequest::Activate)
+            .ok();
+        None
+    }
+}
+
+impl DeactivationHandler for Handler {
+    fn deactivate_accessibility(&mut self) {
+        self.active.store(false, Ordering::SeqCst);
+    }
+}
+
+impl ActionHandler for Handler {
+    fn do_action(&mut self, request: ActionRequest) {
+        if let Some(request) = action_request(request) {
+            self.requests
+                .unbounded_send(AccessibilityRequest::Action(request))
+                .ok();
+        }
+    }
+}
+
+pub(crate) struct LinuxAccessibility {
+    adapter: accesskit_unix::Adapter,
+    active: Arc<AtomicBool>,
+    title: Option<String>,
+    /// The last tree delivered and its scale factor, to deliver again when the title changes.
+    tree: Option<(AccessibilityTree, f32)>,
+}
+
+impl LinuxAccessibility {
+    /// Creates the adapter, along with the requests the window has to handle as they come.
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<AccessibilityRequest>) {
+        let active = Arc::new(AtomicBool::new(false));
+        let (requests, request_rx) = mpsc::unbounded();
+        let handler = || Handler {
+            active: active.clone(),
+            requests: requests.clone(),
+        };
+        let adapter = accesskit_unix::Adapter::new(handler(), handler(), handler());
+        let this = Self {
+            adapter,
+            active,
+            title: None,
+            tree: None,
+        };
+        (this, request_rx)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    /// The title is the root node's name, so the tree is delivered again with it.
+    pub fn set_title(&mut self, title: &str) {
+        self.title = Some(title.to_string());
+        if let Some((tree, scale_factor)) = &self.tree {
+            self.adapter
+                .update_if_active(|| tree_update(tree, Some(title), *scale_factor));
+        }
+    }
+
+    pub fn set_window_focused(&mut self, focused: bool) {
+        self.adapter.update_window_focus_state(focused);
+    }
+
+    /// Only meaningful on X11, as Wayland clients can't know where their windows are.
+    pub fn set_window_bounds(&mut self, bounds: Bounds<Pixels>, scale_factor: f32) {
+        let rect = rect(bounds, scale_factor);
+        self.adapter.set_root_window_bounds(rect, rect);
+    }
+
+    pub fn update(&mut self, tree: &AccessibilityTree, scale_factor: f32) {
+        let title = self.title.as_deref();
+        self.adapter
+            .update_if_active(|| tree_update(tree, title, scale_factor));
+        self.tree = Some((tree.clone(), scale_factor));
+    }
+}
+
+fn tree_update(tree: &AccessibilityTree, title: Option<&str>, scale_factor: f32) -> TreeUpdate {
+    let nodes = tree
+        .nodes()
+        .map(|node| {
+            let mut accesskit_node = accesskit_node(node);
+            if node.id == AccessibilityNodeId::ROOT {
+                // Node bounds are in logical pixels, while AccessKit expects physical ones.
+                accesskit_node.set_transform(accesskit::Affine::scale(scale_factor as f64));
+                if let Some(title) = title {
+                    accesskit_node.set_label(title);
+                }
+            }
+            (NodeId(node.id.0), accesskit_node)
+        })
+        .collect();
+
+    TreeUpdate {
+        nodes,
+        tree: Some(Tree {
+            root: NodeId(AccessibilityNodeId::ROOT.0),
+            toolkit_name: Some("GPUI".to_string()),
+            toolkit_version: None,
+        }),
+        focus: NodeId(tree.focus().id.0),
+    }
+}
+
+fn accesskit_node(node: &AccessibilityNode) -> accesskit::Node {
+    let properties = &node.properties;
+    let mut accesskit_node = accesskit::Node::new(role(properties.role));
+    if node.id != AccessibilityNodeId::ROOT {
+        accesskit_node.set_bounds(rect(node.bounds, 1.));
+    }
+    accesskit_node.set_children(
+        node.children
+            .iter()
+            .map(|child| NodeId(child.0))
+            .collect::<Vec<_>>(),
+    );
+    if let Some(label) = properties.label.as_ref() {
+        accesskit_node.set_label(label.as_ref());
+    }
+    if let Some(description) = properties.description.as_ref() {
+        accesskit_node.set_description(description.as_ref());
+    }
+    if let Some(value) = properties.value.as_ref() {
+        accesskit_node.set_value(value.as_ref());
+    }
+    if let Some(toggled) = properties.toggled {
+        accesskit_node.set_toggled(match toggled {
+            Toggled::False => accesskit::Toggled::False,
+            Toggled::True => accesskit::Toggled::True,
+            Toggled::Mixed => accesskit::Toggled::Mixed,
+        });
+    }
+    if let Some(selected) = properties.selected {
+        accesskit_node.set_selected(selected);
+    }
+    if let Some(expanded) = properties.expanded {
+        accesskit_node.set_expanded(expanded);
+    }
+    if properties.disabled {
+        accesskit_node.set_disabled();
+    }
+    for action in &properties.actions {
+        accesskit_node.add_action(match action {
+            AccessibilityAction::Click => accesskit::Action::Click,
+            AccessibilityAction::Focus => accesskit::Action::Focus,
+            AccessibilityAction::Expand => accesskit::Action::Expand,
+            AccessibilityAction::Collapse => accesskit::Action::Collapse,
+            AccessibilityAction::Increment => accesskit::Action::Increment,
+            AccessibilityAction::Decrement => accesskit::Action::Decrement,
+            AccessibilityAction::ScrollIntoView => accesskit::Action::ScrollIntoView,
+            AccessibilityAction::SetValue => accesskit::Action::SetValue,
+            AccessibilityAction::ShowContextMenu => accesskit::Action::ShowContextMenu,
+        });
+    }
+    accesskit_node
+}
+
+fn action_request(request: ActionRequest) -> Option<AccessibilityActionRequest> {
+    let action = match request.action {
+        accesskit::Action::Click => AccessibilityAction::Click,
+        accesskit::Action::Focus => AccessibilityAction::Focus,
+        accesskit::Action::Expand => AccessibilityAction::Expand,
+        accesskit::Action::Collapse => AccessibilityAction::Collapse,
+        accesskit::Action::Increment => AccessibilityAction::Increment,
+        accesskit::Action::Decrement => AccessibilityAction::Decrement,
+        accesskit::Action::ScrollIntoView => AccessibilityAction::ScrollIntoView,
+        accesskit::Action::SetValue => AccessibilityAction::SetValue,
+        accesskit::Action::ShowContextMenu => AccessibilityAction::ShowContextMenu,
+        _ => return None,
+    };
+    let value = match request.data {
+        Some(ActionData::Value(value)) => Some(value.to_string().into()),
+        Some(ActionData::NumericValue(value)) => Some(value.to_string().into()),
+        _ => None,
+    };
+    Some(AccessibilityActionRequest {
+        target: AccessibilityNodeId(request.target.0),
+        action,
+        value,
+    })
+}
+
+fn rect(bounds: Bounds<Pixels>, scale_factor: f32) -> Rect {
+    let scale = |value: Pixels| (f32::from(value) * scale_factor) as f64;
+    Rect {
+        x0: scale(bounds.left()),
+        y0: scale(bounds.top()),
+        x1: scale(bounds.right()),
+        y1: scale(bounds.bottom()),
+    }
+}
+
+fn role(role: Role) -> accesskit::Role {
+    match role {
+        Role::Group => accesskit::Role::Group,
+        Role::Window => accesskit::Role::Window,
+        Role::Unknown => accesskit::Role::Unknown,
+        Role::StaticText | Role::Label => accesskit::Role::Label,
+        Role::Heading => accesskit::Role::Heading,
+        Role::Paragraph => accesskit::Role::Paragraph,
+        Role::Image => accesskit::Role::Image,
+        Role::Button => accesskit::Role::Button,
+        Role::Link => accesskit::Role::Link,
+        Role::CheckBox => accesskit::Role::CheckBox,
+        Role::RadioButton => accesskit::Role::RadioButton,
+        Role::Switch => accesskit::Role::Switch,
+        Role::TextInput => accesskit::Role::TextInput,
+        Role::MultilineTextInput => accesskit::Role::MultilineTextInput,
+        Role::ComboBox => accesskit::Role::ComboBox,
+        Role::Slider => accesskit::Role::Slider,
+        Role::SpinButton => accesskit::Role::SpinButton,
+        Role::ProgressIndicator => accesskit::Role::ProgressIndicator,
+        Role::ScrollView => accesskit::Role::ScrollView,
+        Role::List => accesskit::Role::List,
+        Role::ListItem => accesskit::Role::ListItem,
+        Role::Tree => accesskit::Role::Tree,
+        Role::TreeItem => accesskit::Role::TreeItem,
+        Role::Table => accesskit::Role::Table,
+        Role::Row => accesskit::Role::Row,
+        Role::Cell => accesskit::Role::Cell,
+        Role::TabList => accesskit::Role::TabList,
+        Role::Tab => accesskit::Role::Tab,
+        Role::TabPanel => accesskit::Role::TabPanel,
+        Role::Menu => accesskit::Role::Menu,
+        Role::MenuBar => accesskit::Role::MenuBar,
+        Role::MenuItem => accesskit::Role::MenuItem,
+        Role::Toolbar => accesskit::Role::Toolbar,
+        Role::Dialog => accesskit::Role::Dialog,
+        Role::AlertDialog => accesskit::Role::AlertDialog,
+        Role::Alert => accesskit::Role::Alert,
+        Role::Tooltip => accesskit::Role::Tooltip,
+        Role::Separator => accesskit::Role::Splitter,
+        Role::Document => accesskit::Role::Document,
+        Role::Pane => accesskit::Role::Pane,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{AccessibilityProperties, point, px, size};
+
+    #[test]
+    fn test_accesskit_node() {
+        let node = AccessibilityNode {
+            id: AccessibilityNodeId(7),
+            parent: Some(AccessibilityNodeId::ROOT),
+            children: vec![AccessibilityNodeId(8), AccessibilityNodeId(9)],
+            bounds: Bounds::new(point(px(10.), px(20.)), size(px(30.), px(40.))),
+            focusable: true,
+            focused: false,
+            properties: AccessibilityProperties::new(Role::CheckBox)
+                .label("Wrap lines")
+                .toggled(Toggled::Mixed)
+                .disabled(true)
+                .action(AccessibilityAction::Click),
+        };
+        let accesskit_node = accesskit_node(&node);
+        assert_eq!(accesskit_node.role(), accesskit::Role::CheckBox);
+        assert_eq!(accesskit_node.label(), Some("Wrap lines"));
+        assert_eq!(accesskit_node.toggled(), Some(accesskit::Toggled::Mixed));
+        assert!(accesskit_node.is_disabled());
+        assert!(accesskit_node.supports_action(accesskit::Action::Click));
+        assert!(!accesskit_node.supports_action(accesskit::Action::Focus));
+        assert_eq!(accesskit_node.children(), &[NodeId(8), NodeId(9)]);
+        assert_eq!(
+            accesskit_node.bounds(),
+            Some(Rect {
+                x0: 10.,
+                y0: 20.,
+                x1: 40.,
+                y1: 60.,
+            })
+        );
+    }
+
+    #[test]
+    fn test_action_request() {
+        let request = action_request(ActionRequest {
+            action: accesskit::Action::SetValue,
+            target: NodeId(3),
+            data: Some(ActionData::Value("hello".into())),
+        })
+        .unwrap();
+        assert_eq!(
+            request,
+            AccessibilityActionRequest {
+                target: AccessibilityNodeId(3),
+                action: AccessibilityAction::SetValue,
+                value: Some("hello".into()),
+            }
+        );
+
+        let request = action_request(ActionRequest {
+            action: accesskit::Action::SetValue,
+            target: NodeId(3),
+            data: Some(ActionData::NumericValue(0.5)),
+        })
+        .unwrap();
+        assert_eq!(request.value, Some("0.5".into()));
+
+        let request = action_request(ActionRequest {
+            action: accesskit::Action::Click,
+            target: NodeId(4),
+            data: None,
+        })
+        .unwrap();
+        assert_eq!(request.action, AccessibilityAction::Click);
+        assert_eq!(request.value, None);
+
+        assert!(
+            action_request(ActionRequest {
+                action: accesskit::Action::Blur,
+                target: NodeId(4),
+                data: None,
+            })
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn test_rect_scales_to_physical_pixels() {
+        let bounds = Bounds::new(point(px(1.), px(2.)), size(px(3.), px(4.)));
+        assert_eq!(
+            rect(bounds, 2.),
+            Rect {
+                x0: 2.,
+                y0: 4.,
+                x1: 8.,
+                y1: 12.,
+            }
+        );
+    }
+}
@@ -0,0 +1,307 @@
+//! The mapping between clipboard entries and MIME types, shared by the X11 and Wayland
+//! clipboards.
+
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use gpui::{ClipboardEntry, ClipboardItem, ExternalPaths, Image, ImageFormat, hash};
+use strum::IntoEnumIterator;
+use url::Url;
+use util::ResultExt as _;
+
+/// Text MIME types, in order of preference. All of them are offered as UTF-8, and on X11,
+/// `UTF8_STRING` is the target for UTF-8 text.
+pub(crate) const TEXT_MIME_TYPES: [&str; 4] = [
+    "text/plain;charset=utf-8",
+    "UTF8_STRING",
+    "text/plain;charset=UTF-8",
+    "text/plain",
+];
+/// Latin-1 text, which is only accepted from older X11 programs.
+pub(crate) const LATIN_1_MIME_TYPE: &str = "STRING";
+pub(crate) const HTML_MIME_TYPE: &str = "text/html";
+/// RTF MIME types, in order of preference. Only the first two are offered.
+pub(crate) const RTF_MIME_TYPES: [&str; 3] = ["text/rtf", "application/rtf", "text/richtext"];
+pub(crate) const FILE_LIST_MIME_TYPE: &str = "text/uri-list";
+/// Prefix of the MIME type a GPUI app offers along with its clipboard contents, to recognize
+/// when they're its own.
+pub(crate) const SELF_MIME_TYPE_PREFIX: &str = "pid/";
+
+/// The MIME types to offer for an item, for every representation it holds.
+pub(crate) fn offered_mime_types(item: &ClipboardItem) -> Vec<String> {
+    let mut mime_types = Vec::new();
+    let mut offer = |mime_type: &str| {
+        if !mime_types.iter().any(|offered| offered == mime_type) {
+            mime_types.push(mime_type.to_string());
+        }
+    };
+    for entry in item.entries() {
+        match entry {
+            ClipboardEntry::Custom { mime, .. } => offer(mime),
+            ClipboardEntry::Html(_) => offer(HTML_MIME_TYPE),
+            ClipboardEntry::Rtf(_) => RTF_MIME_TYPES[..2].iter().for_each(|&rtf| offer(rtf)),
+            ClipboardEntry::Image(image) => offer(image.format.mime_type()),
+            ClipboardEntry::ExternalPaths(_) => offer(FILE_LIST_MIME_TYPE),
+            ClipboardEntry::String(_) => {}
+        }
+    }
+    // Paths are offered as text too, see `ClipboardItem::text`.
+    if item.text().is_some() {
+        TEXT_MIME_TYPES.iter().for_each(|&text| offer(text));
+    }
+    mime_types
+}
+
+/// The data to send when another program asks for the item in the given MIME type.
+pub(crate) fn data_for_mime_type(item: &ClipboardItem, mime_type: &str) -> Option<Vec<u8>> {
+    if let Some(bytes) = item.custom(mime_type) {
+        return Some(bytes.to_vec());
+    }
+    if TEXT_MIME_TYPES.contains(&mime_type) {
+        return item.text().map(String::into_bytes);
+    }
+    if mime_type == HTML_MIME_TYPE {
+        return item.html().map(|html| html.as_bytes().to_vec());
+    }
+    if RTF_MIME_TYPES.contains(&mime_type) {
+        return item.rtf().map(|rtf| rtf.as_bytes().to_vec());
+    }
+    item.entries().iter().find_map(|entry| match entry {
+        ClipboardEntry::Image(image) if image.format.mime_type() == mime_type => {
+            Some(image.bytes.clone())
+        }
+        ClipboardEntry::ExternalPaths(paths) if mime_type == FILE_LIST_MIME_TYPE => {
+            Some(encode_uri_list(&paths.0).into_bytes())
+        }
+        _ => None,
+    })
+}
+
+/// Build an item from every representation in the offered MIME types that can be read, reading
+/// each one with `read`. Images are only read when there's no text, as programs often offer a
+/// rendering of the text they copy. Custom MIME types aren't read, as they can be large and are
+/// rarely wanted; apps read them on request with `read_custom_from_clipboard`.
+pub(crate) fn read_offered_item(
+    offered: &[String],
+    mut read: impl FnMut(&str) -> Option<Vec<u8>>,
+) -> Option<ClipboardItem> {
+    let is_offered = |mime_type: &str| offered.iter().any(|offered| offered == mime_type);
+    let mut entries = Vec::new();
+
+    let text_mime_type = TEXT_MIME_TYPES
+        .into_iter()
+        .chain([LATIN_1_MIME_TYPE])
+        .find(|&mime_type| is_offered(mime_type));
+    if let Some(mime_type) = text_mime_type
+        && let Some(bytes) = read(mime_type)
+    {
+        let text = if mime_type == LATIN_1_MIME_TYPE {
+            Some(bytes.into_iter().map(char::from).collect())
+        } else {
+            String::from_utf8(bytes)
+                .context("clipboard text isn't valid UTF-8")
+                .log_err()
+        };
+        // Normalize the text to unix line endings, otherwise
+        // copying from eg: firefox inserts a lot of blank
+        // lines, and that is super annoying.
+        if let Some(text) = text {
+            entries.push(ClipboardEntry::from(text.replace("\r\n", "\n")));
+        }
+    }
+
+    if is_offered(HTML_MIME_TYPE)
+        && let Some(html) = read(HTML_MIME_TYPE).and_then(decode_html)
+    {
+        entries.push(ClipboardEntry::Html(html));
+    }
+
+    if let Some(mime_type) = RTF_MIME_TYPES
+        .into_iter()
+        .find(|&mime_type| is_offered(mime_type))
+        && let Some(bytes) = read(mime_type)
+    {
+        entries.push(ClipboardEntry::Rtf(
+            String::from_utf8_lossy(&bytes).into_owned(),
+        ));
+    }
+
+    if is_offered(FILE_LIST_MIME_TYPE)
+        && let Some(bytes) = read(FILE_LIST_MIME_TYPE)
+    {
+        let paths = decode_uri_list(&String::from_utf8_lossy(&bytes));
+        if !paths.is_empty() {
+            entries.push(ClipboardEntry::ExternalPaths(ExternalPaths(paths.into())));
+        }
+    }
+
+    if text_mime_type.is_none()
+        && let Some(format) = ImageFormat::iter().find(|format| is_offered(format.mime_type()))
+        && let Some(bytes) = read(format.mime_type())
+    {
+        let id = hash(&bytes);
+        entries.push(ClipboardEntry::Image(Image { format, bytes, id }));
+    }
+
+    (!entries.is_empty()).then_some(ClipboardItem { entries })
+}
+
+/// Build an item from data dragged onto a window, like [`read_offered_item`], but reading custom
+/// MIME types too as [`ClipboardEntry::Custom`] entries, as the drag's offer can't be read on
+/// request once it's over.
+pub(crate) fn read_dropped_item(
+    offered: &[String],
+    mut read: impl FnMut(&str) -> Option<Vec<u8>>,
+) -> Option<ClipboardItem> {
+    let mut entries = read_offered_item(offered, &mut read)
+        .map(|item| item.entries)
+        .unwrap_or_default();
+    for mime_type in offered {
+        if is_custom_mime_type(mime_type)
+            && let Some(bytes) = read(mime_type)
+        {
+            entries.push(ClipboardEntry::Custom {
+                mime: mime_type.clone(),
+                bytes,
+            });
+        }
+    }
+    (!entries.is_empty()).then_some(ClipboardItem { entries })
+}
+
+/// Whether a MIME type is read as a [`ClipboardEntry::Custom`]: it isn't text or an image, which
+/// are read as other entries or in other encodings, isn't an X11 target like `TARGETS`, and
+/// doesn't identify the program that offered it.
+fn is_custom_mime_type(mime_type: &str) -> bool {
+    mime_type.contains('/')
+        && !mime_type.starts_with("text/")
+        && !mime_type.starts_with("image/")
+        && !mime_type.starts_with(SELF_MIME_TYPE_PREFIX)
+        && !RTF_MIME_TYPES.contains(&mime_type)
+}
+
+fn decode_html(bytes: Vec<u8>) -> Option<String> {
+    // Firefox offers HTML as UTF-16 on X11.
+    let utf16 = |bytes: &[u8], from_bytes: fn([u8; 2]) -> u16| {
+        let units = bytes
+            .chunks_exact(2)
+            .map(|pair| from_bytes([pair[0], pair[1]]))
+            .collect::<Vec<_>>();
+        String::from_utf16(&units).ok()
+    };
+    if let Some(bytes) = bytes.strip_prefix(&[0xff, 0xfe]) {
+        utf16(bytes, u16::from_le_bytes)
+    } else if let Some(bytes) = bytes.strip_prefix(&[0xfe, 0xff]) {
+        utf16(bytes, u16::from_be_bytes)
+    } else {
+        String::from_utf8(bytes)
+            .context("clipboard HTML isn't valid UTF-8")
+            .log_err()
+    }
+}
+
+fn encode_uri_list(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .filter_map(|path| Url::from_file_path(path).ok())
+        .map(|url| format!("{url}\r\n"))
+        .collect()
+}
+
+fn decode_uri_list(uri_list: &str) -> Vec<PathBuf> {
+    uri_list
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| Url::parse(line.trim()).ok())
+        .filter_map(|url| url.to_file_path().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use collections::HashMap;
+
+    #[test]
+    fn test_round_trip_every_representation() {
+        let item = ClipboardItem::new_string("bold".into())
+            .with_entry(ClipboardEntry::Html("<b>bold</b>".into()))
+            .with_entry(ClipboardEntry::Rtf(r"{\rtf1 {\b bold}}".into()))
+            .with_entry(ClipboardEntry::Custom {
+                mime: "application/x-gpui-test".into(),
+                bytes: vec![1, 2, 3],
+            });
+
+        let offered = offered_mime_types(&item);
+        assert_eq!(
+            offered,
+            [
+                "text/html",
+                "text/rtf",
+                "application/rtf",
+                "application/x-gpui-test",
+                "text/plain;charset=utf-8",
+                "UTF8_STRING",
+                "text/plain;charset=UTF-8",
+                "text/plain",
+            ]
+        );
+        let data = offered
+            .iter()
+            .map(|mime_type| (mime_type.clone(), data_for_mime_type(&item, mime_type)))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(data["UTF8_STRING"].as_deref(), Some(&b"bold"[..]));
+
+        let mut reads = Vec::new();
+        let read = read_offered_item(&offered, |mime_type| {
+            reads.push(mime_type.to_string());
+            data[mime_type].clone()
+        })
+        .unwrap();
+        assert_eq!(read.entries(), &item.entries()[..3]);
+        // Only the preferred text and RTF types are read, and custom types are left for
+        // `read_custom_from_clipboard`.
+        assert_eq!(reads, ["text/plain;charset=utf-8", "text/html", "text/rtf"]);
+        assert_eq!(
+            data["application/x-gpui-test"].as_deref(),
+            Some(&[1, 2, 3][..])
+        );
+    }
+
+    #[test]
+    fn test_read_dropped_custom_types() {
+        let item = ClipboardItem::new_string("row".into()).with_entry(ClipboardEntry::Custom {
+            mime: "application/x-gpui-row".into(),
+            bytes: vec![4, 5, 6],
+        });
+        let mut offered = offered_mime_types(&item);
+        offered.push(format!("{SELF_MIME_TYPE_PREFIX}1"));
+        offered.push("TARGETS".into());
+        let data = |mime_type: &str| data_for_mime_type(&item, mime_type);
+
+        assert_eq!(read_dropped_item(&offered, data), Some(item.clone()));
+        assert_eq!(
+            read_offered_item(&offered, data),
+            Some(ClipboardItem::new_string("row".into()))
+        );
+    }
+
+    #[test]
+    fn test_read_other_programs_formats() {
+        let offered = ["TARGETS", "STRING", "text/html", "image/png"].map(String::from);
+        let item = read_offered_item(&offered, |mime_type| match mime_type {
+            "STRING" => Some(b"caf\xe9\r\n".to_vec()),
+            "text/html" => Some(
+                [0xff, 0xfe]
+                    .into_iter()
+                    .chain("<p>café</p>".encode_utf16().flat_map(u16::to_le_bytes))
+                    .collect(),
+            ),
+            _ => panic!("unexpected read of {mime_type}"),
+        })
+        .unwrap();
+        assert_eq!(item.text().as_deref(), Some("café\n"));
+        assert_eq!(item.html(), Some("<p>café</p>"));
+        assert_eq!(item.entries().len(), 2);
+    }
+}
@@ -0,0 +1,828 @@
+//! Exports the application menus over DBus with the `com.canonical.dbusmenu` protocol, for
+//! desktops that show them in a global menu bar.
+//!
+//! Windows are registered with the menu by the X11 and Wayland clients once it's exported, see
+//! [`MenuEvent::Exported`].
+
+use std::{cell::RefMut, collections::HashMap, sync::Arc};
+
+use calloop::channel::{Channel, Sender};
+use futures::{
+    StreamExt as _,
+    channel::{mpsc, oneshot},
+};
+use gpui::{Action, BackgroundExecutor, Keymap, OwnedMenu, OwnedMenuItem};
+use parking_lot::Mutex;
+use util::ResultExt as _;
+use zbus::{
+    fdo,
+    object_server::SignalEmitter,
+    zvariant::{ObjectPath, OwnedValue, Value},
+};
+
+use crate::linux::LinuxCommon;
+
+/// The object path the menus are exported at.
+pub(crate) const MENU_OBJECT_PATH: &str = "/MenuBar";
+
+const REGISTRAR_NAME: &str = "com.canonical.AppMenu.Registrar";
+const REGISTRAR_PATH: &str = "/com/canonical/AppMenu/Registrar";
+
+/// Sent from the DBus connection to the main thread.
+pub(crate) enum MenuEvent {
+    /// The menus were exported by the connection with this unique name.
+    Exported(String),
+    /// The menu item with this id was clicked.
+    Activated(i32),
+    /// The menu with this id is about to be shown, reply whether its items changed.
+    AboutToShow {
+        id: i32,
+        needs_update: oneshot::Sender<bool>,
+    },
+}
+
+/// Sent from the main thread to the DBus connection.
+pub(crate) enum MenuUpdate {
+    LayoutUpdated(u32),
+    #[cfg_attr(not(feature = "x11"), allow(dead_code))]
+    RegisterWindow(u32),
+}
+
+#[derive(Debug, PartialEq)]
+enum MenuNodeKind {
+    Submenu,
+    Separator,
+    Action,
+}
+
+#[derive(Debug)]
+struct MenuNode {
+    kind: MenuNodeKind,
+    label: String,
+    enabled: bool,
+    checkable: bool,
+    checked: bool,
+    shortcut: Vec<Vec<String>>,
+    children: Vec<i32>,
+}
+
+/// The menu items, indexed by their id, with the root menu at id 0.
+#[derive(Debug)]
+struct MenuLayout {
+    revision: u32,
+    nodes: Vec<MenuNode>,
+}
+
+impl Default for MenuLayout {
+    fn default() -> Self {
+        Self {
+            revision: 0,
+            nodes: vec![MenuNode {
+                kind: MenuNodeKind::Submenu,
+                label: String::new(),
+                enabled: true,
+                checkable: false,
+                checked: false,
+                shortcut: Vec::new(),
+                children: Vec::new(),
+            }],
+        }
+    }
+}
+
+impl MenuLayout {
+    fn node(&self, id: i32) -> Option<&MenuNode> {
+        self.nodes.get(usize::try_from(id).ok()?)
+    }
+
+    fn properties(&self, id: i32, names: &[String]) -> Option<HashMap<String, OwnedValue>> {
+        let node = self.node(id)?;
+        let mut properties = HashMap::new();
+        let mut insert = |name: &str, value: OwnedValue| {
+            if names.is_empty() || names.iter().any(|requested| requested == name) {
+                properties.insert(name.to_string(), value);
+            }
+        };
+
+        match node.kind {
+            MenuNodeKind::Separator => insert("type", owned("separator")),
+            MenuNodeKind::Submenu => insert("children-display", owned("submenu")),
+            MenuNodeKind::Action => {}
+        }
+        if node.kind != MenuNodeKind::Separator && id != 0 {
+            // Underscores mark mnemonics, so literal ones are doubled.
+            insert("label", owned(node.label.replace('_', "__")));
+        }
+        if !node.enabled {
+            insert("enabled", owned(false));
+        }
+        // Checkable items keep their checkbox while they're unchecked.
+        if node.checkable {
+            insert("toggle-type", owned("checkmark"));
+            insert("toggle-state", owned(i32::from(node.checked)));
+        }
+        if !node.shortcut.is_empty() {
+            insert("shortcut", owned(node.shortcut.clone()));
+        }
+        Some(properties)
+    }
+
+    /// The `(ia{sv}av)` layout of an item, with its children down to the given depth, or all of
+    /// them when it's negative.
+    fn layout(
+        &self,
+        id: i32,
+        depth: i32,
+        names: &[String],
+    ) -> Option<(i32, HashMap<String, OwnedValue>, Vec<OwnedValue>)> {
+        let properties = self.properties(id, names)?;
+        let children = if depth == 0 {
+            Vec::new()
+        } else {
+            self.node(id)?
+                .children
+                .iter()
+                .filter_map(|&child| self.layout(child, depth - 1, names))
+                .map(owned)
+                .collect()
+        };
+        Some((id, properties, children))
+    }
+
+    /// The ids of the items in the menu with the given id, and in its submenus.
+    fn descendants(&self, id: i32) -> Vec<i32> {
+        let mut descendants = Vec::new();
+        let mut stack = self
+            .node(id)
+            .map_or(Vec::new(), |node| node.children.clone());
+        while let Some(id) = stack.pop() {
+            descendants.push(id);
+            stack.extend(self.node(id).into_iter().flat_map(|node| &node.children));
+        }
+        descendants
+    }
+}
+
+fn owned(value: impl Into<Value<'static>>) -> OwnedValue {
+    value
+        .into()
+        .try_into_owned()
+        .expect("menu properties don't contain file descriptors")
+}
+
+/// The application menus, as exported over DBus.
+pub(crate) struct DbusMenu {
+    layout: Arc<Mutex<MenuLayout>>,
+    actions: HashMap<i32, Box<dyn Action>>,
+    updates: mpsc::UnboundedSender<MenuUpdate>,
+    service_name: Option<String>,
+}
+
+impl DbusMenu {
+    /// Create the menus, returning the channel on which their events are delivered. Nothing is
+    /// exported until the menus are first set.
+    pub fn new(executor: &BackgroundExecutor) -> (Self, Channel<MenuEvent>) {
+        let (events, channel) = calloop::channel::channel();
+        let (menu, interface, mut update_rx) = Self::with_interface(events.clone());
+
+        executor
+            .spawn(async move {
+                // Avoid connecting to the session bus for applications without menus.
+                loop {
+                    match update_rx.next().await {
+                        Some(MenuUpdate::LayoutUpdated(_)) => break,
+                        Some(MenuUpdate::RegisterWindow(_)) => {}
+                        None => return anyhow::Ok(()),
+                    }
+                }
+
+                let connection = zbus::connection::Builder::session()?
+                    .serve_at(MENU_OBJECT_PATH, interface)?
+                    .build()
+                    .await?;
+                if let Some(name) = connection.unique_name() {
+                    events.send(MenuEvent::Exported(name.to_string()))?;
+                }
+                send_updates(&connection, update_rx).await
+            })
+            .detach();
+
+        (menu, channel)
+    }
+
+    /// Create menus served by the caller with the returned interface, sending their events to
+    /// `events`. The updates to send over the connection are delivered on the returned receiver.
+    pub fn with_interface(
+        events: Sender<MenuEvent>,
+    ) -> (Self, DbusMenuInterface, mpsc::UnboundedReceiver<MenuUpdate>) {
+        let (updates, update_rx) = mpsc::unbounded();
+        let layout = Arc::new(Mutex::new(MenuLayout::default()));
+        let interface = DbusMenuInterface {
+            layout: layout.clone(),
+            events,
+        };
+        let menu = Self {
+            layout,
+            actions: HashMap::new(),
+            updates,
+            service_name: None,
+        };
+        (menu, interface, update_rx)
+    }
+
+    /// The unique name of the DBus connection the menus are exported by, once they are.
+    pub fn service_name(&self) -> Option<&str> {
+        self.service_name.as_deref()
+    }
+
+    /// Register an X11 window with the AppMenu registrar, so its menu bar shows these menus.
+    #[cfg_attr(not(feature = "x11"), allow(dead_code))]
+    pub fn register_window(&self, window: u32) {
+        self.updates
+            .unbounded_send(MenuUpdate::RegisterWindow(window))
+            .log_err();
+    }
+
+    /// Set the menus, shown as submenus of the root menu like in a menu bar.
+    pub fn set_menus(&mut self, menus: &[OwnedMenu], keymap: &Keymap) {
+        self.set_layout(|layout, actions| {
+            for menu in menus {
+                add_menu(layout, actions, 0, menu, keymap);
+            }
+        });
+    }
+
+    /// Set the items of the root menu, like in a context menu.
+    pub fn set_items(&mut self, items: &[OwnedMenuItem], keymap: &Keymap) {
+        self.set_layout(|layout, actions| add_items(layout, actions, 0, items, keymap));
+    }
+
+    fn set_layout(
+        &mut self,
+        build: impl FnOnce(&mut MenuLayout, &mut HashMap<i32, Box<dyn Action>>),
+    ) {
+        let mut layout = self.layout.lock();
+        let revision = layout.revision + 1;
+        *layout = MenuLayout {
+            revision,
+            ..MenuLayout::default()
+        };
+        self.actions.clear();
+        build(&mut layout, &mut self.actions);
+        drop(layout);
+
+        self.updates
+            .unbounded_send(MenuUpdate::LayoutUpdated(revision))
+            .log_err();
+    }
+}
+
+/// Send the updates to the menus over the connection they're exported on, until the menus are
+/// dropped.
+async fn send_updates(
+    connection: &zbus::Connection,
+    mut updates: mpsc::UnboundedReceiver<MenuUpdate>,
+) -> anyhow::Result<()> {
+    let emitter = SignalEmitter::new(connection, MENU_OBJECT_PATH)?;
+    while let Some(update) = updates.next().await {
+        match update {
+            MenuUpdate::LayoutUpdated(revision) => {
+                DbusMenuInterface::layout_updated(&emitter, revision, 0).await?;
+            }
+            MenuUpdate::RegisterWindow(window) => {
+                connection
+                    .call_method(
+                        Some(REGISTRAR_NAME),
+                        REGISTRAR_PATH,
+                        Some(REGISTRAR_NAME),
+                        "RegisterWindow",
+                        &(
+                            window,
+                            ObjectPath::from_static_str_unchecked(MENU_OBJECT_PATH),
+                        ),
+                    )
+                    .await
+                    .log_err();
+            }
+        }
+    }
+    Ok(())
+}
+
+fn add_menu(
+    layout: &mut MenuLayout,
+    actions: &mut HashMap<i32, Box<dyn Action>>,
+    parent: i32,
+    menu: &OwnedMenu,
+    keymap: &Keymap,
+) {
+    let id = add_node(layout, parent, MenuNodeKind::Submenu, menu.name.to_string());
+    add_items(layout, actions, id, &menu.items, keymap);
+}
+
+fn add_items(
+    layout: &mut MenuLayout,
+    actions: &mut HashMap<i32, Box<dyn Action>>,
+    parent: i32,
+    items: &[OwnedMenuItem],
+    keymap: &Keymap,
+) {
+    for item in items {
+        match item {
+            OwnedMenuItem::Separator => {
+                add_node(layout, parent, MenuNodeKind::Separator, String::new());
+            }
+            OwnedMenuItem::Submenu(submenu) => add_menu(layout, actions, parent, submenu, keymap),
+            OwnedMenuItem::Action {
+                name,
+                action,
+                checked,
+                checkable,
+                ..
+            } => {
+                let item = add_node(layout, parent, MenuNodeKind::Action, name.clone());
+                let node = &mut layout.nodes[item as usize];
+                node.checkable = *checkable || *checked;
+                node.checked = *checked;
+                node.shortcut = keymap
+                    .bindings_for_action(action.as_ref())
+                    .next_back()
+                    .map_or(Vec::new(), |binding| {
+                        binding
+                            .keystrokes()
+                            .iter()
+                            .map(|keystroke| {
+                                let modifiers = keystroke.modifiers();
+                                [
+                                    (modifiers.control, "Control"),
+                                    (modifiers.alt, "Alt"),
+                                    (modifiers.shift, "Shift"),
+                                    (modifiers.platform, "Super"),
+                                ]
+                                .into_iter()
+                                .filter_map(|(pressed, name)| pressed.then(|| name.to_string()))
+                                .chain([key_name(keystroke.key())])
+                                .collect()
+                            })
+                            .collect()
+                    });
+                actions.insert(item, action.boxed_clone());
+            }
+            // Menus managed by the system only exist on macOS.
+            OwnedMenuItem::SystemMenu(_) => {}
+        }
+    }
+}
+
+fn add_node(layout: &mut MenuLayout, parent: i32, kind: MenuNodeKind, label: String) -> i32 {
+    let id = layout.nodes.len() as i32;
+    layout.nodes.push(MenuNode {
+        kind,
+        label,
+        enabled: true,
+        checkable: false,
+        checked: false,
+        shortcut: Vec::new(),
+        children: Vec::new(),
+    });
+    layout.nodes[parent as usize].children.push(id);
+    id
+}
+
+/// The name of a key as shortcuts are described in the dbusmenu protocol, which uses the names
+/// of X keysyms.
+fn key_name(key: &str) -> String {
+    let name = match key {
+        "enter" => "Return",
+        "escape" => "Escape",
+        "backspace" => "BackSpace",
+        "delete" => "Delete",
+        "tab" => "Tab",
+        "space" => "space",
+        "up" => "Up",
+        "down" => "Down",
+        "left" => "Left",
+        "right" => "Right",
+        "home" => "Home",
+        "end" => "End",
+        "pageup" => "Page_Up",
+        "pagedown" => "Page_Down",
+        "insert" => "Insert",
+        key if key.len() > 1 && key.starts_with('f') && key[1..].parse::<u8>().is_ok() => {
+            return key.to_uppercase();
+        }
+        key => key,
+    };
+    name.to_string()
+}
+
+/// Which of the menus exported over DBus an event is for.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MenuKind {
+    /// The application menus.
+    AppMenu,
+    /// The menu of the status item.
+    StatusItem,
+}
+
+impl MenuKind {
+    fn menu(self, common: &mut LinuxCommon) -> Option<&mut DbusMenu> {
+        match self {
+            MenuKind::AppMenu => common.app_menu.as_mut(),
+            MenuKind::StatusItem => common.status_item.as_mut().map(|item| item.menu_mut()),
+        }
+    }
+}
+
+/// Handle an event from the DBus connection on the main thread, borrowing the platform's common
+/// state with `common` whenever it's needed.
+pub(crate) fn handle_menu_event<'a>(
+    event: MenuEvent,
+    kind: MenuKind,
+    common: impl Fn() -> RefMut<'a, LinuxCommon>,
+) {
+    match event {
+        MenuEvent::Exported(service_name) => {
+            if let Some(menu) = kind.menu(&mut common()) {
+                menu.service_name = Some(service_name);
+            }
+        }
+        MenuEvent::Activated(id) => {
+            let mut state = common();
+            let Some(action) = kind
+                .menu(&mut state)
+                .and_then(|menu| menu.actions.get(&id))
+                .map(|action| action.boxed_clone())
+            else {
+                return;
+            };
+            if let Some(mut callback) = state.callbacks.app_menu_action.take() {
+                drop(state);
+                callback(action.as_ref());
+                common().callbacks.app_menu_action = Some(callback);
+            }
+        }
+        MenuEvent::AboutToShow { id, needs_update } => {
+            let mut state = common();
+            if id == 0
+                && kind == MenuKind::AppMenu
+                && let Some(mut callback) = state.callbacks.will_open_app_menu.take()
+            {
+                drop(state);
+                callback();
+                state = common();
+                state.callbacks.will_open_app_menu = Some(callback);
+            }
+
+            let Some(menu) = kind.menu(&mut state) else {
+                needs_update.send(false).ok();
+                return;
+            };
+            let layout = menu.layout.lock();
+            let revision = layout.revision;
+            let actions = layout
+                .descendants(id)
+                .into_iter()
+                .filter_map(|id| Some((id, menu.actions.get(&id)?.boxed_clone())))
+                .collect::<Vec<_>>();
+            drop(layout);
+            let Some(mut validate) = state.callbacks.validate_app_menu_command.take() else {
+                needs_update.send(false).ok();
+                return;
+            };
+            drop(state);
+            let enabled = actions
+                .into_iter()
+                .map(|(id, action)| (id, validate(action.as_ref())))
+                .collect::<Vec<_>>();
+            let mut state = common();
+            state.callbacks.validate_app_menu_command = Some(validate);
+
+            let mut changed = false;
+            if let Some(menu) = kind.menu(&mut state) {
+                let mut layout = menu.layout.lock();
+                // The validate callback may have set new menus, whose items the IDs don't refer
+                // to. Their layout has been announced already, so the client fetches it anyway.
+                if layout.revision != revision {
+                    needs_update.send(true).ok();
+                    return;
+                }
+                for (id, enabled) in enabled {
+                    let node = &mut layout.nodes[id as usize];
+                    changed |= node.enabled != enabled;
+                    node.enabled = enabled;
+                }
+                if changed {
+                    layout.revision += 1;
+                }
+            }
+            needs_update.send(changed).ok();
+        }
+    }
+}
+
+/// The `com.canonical.dbusmenu` interface, as served by the DBus connection.
+#[derive(Clone)]
+pub(crate) struct DbusMenuInterface {
+    layout: Arc<Mutex<MenuLayout>>,
+    events: Sender<MenuEvent>,
+}
+
+impl DbusMenuInterface {
+    async fn about_to_show_item(&self, id: i32) -> fdo::Result<bool> {
+        if self.layout.lock().node(id).is_none() {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "No menu item with id {id}"
+            )));
+        }
+        let (needs_update, reply) = oneshot::channel();
+        self.events
+            .send(MenuEvent::AboutToShow { id, needs_update })
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+        Ok(reply.await.unwrap_or(false))
+    }
+}
+
+#[zbus::interface(name = "com.canonical.dbusmenu")]
+impl DbusMenuInterface {
+    fn get_layout(
+        &self,
+        parent_id: i32,
+        recursion_depth: i32,
+        property_names: Vec<String>,
+    ) -> fdo::Result<(u32, (i32, HashMap<String, OwnedValue>, Vec<OwnedValue>))> {
+        let layout = self.layout.lock();
+        let item = layout
+            .layout(parent_id, recursion_depth, &property_names)
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("No menu item with id {parent_id}")))?;
+        Ok((layout.revision, item))
+    }
+
+    fn get_group_properties(
+        &self,
+        ids: Vec<i32>,
+        property_names: Vec<String>,
+    ) -> Vec<(i32, HashMap<String, OwnedValue>)> {
+        let layout = self.layout.lock();
+        ids.into_iter()
+            .filter_map(|id| Some((id, layout.properties(id, &property_names)?)))
+            .collect()
+    }
+
+    fn get_property(&self, id: i32, name: String) -> fdo::Result<OwnedValue> {
+        self.layout
+            .lock()
+            .properties(id, std::slice::from_ref(&name))
+            .and_then(|mut properties| properties.remove(&name))
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("No property {name} on item {id}")))
+    }
+
+    fn event(&self, id: i32, event_id: String, _data: OwnedValue, _timestamp: u32) {
+        if event_id == "clicked" {
+            self.events.send(MenuEvent::Activated(id)).log_err();
+        }
+    }
+
+    /// Returns the ids of the items that don't exist.
+    fn event_group(&self, events: Vec<(i32, String, OwnedValue, u32)>) -> Vec<i32> {
+        let mut id_errors = Vec::new();
+        for (id, event_id, data, timestamp) in events {
+            if self.layout.lock().node(id).is_none() {
+                id_errors.push(id);
+            } else {
+                self.event(id, event_id, data, timestamp);
+            }
+        }
+        id_errors
+    }
+
+    async fn about_to_show(&self, id: i32) -> fdo::Result<bool> {
+        self.about_to_show_item(id).await
+    }
+
+    /// Returns the ids of the menus that changed, and of those that don't exist.
+    async fn about_to_show_group(&self, ids: Vec<i32>) -> (Vec<i32>, Vec<i32>) {
+        let mut updates_needed = Vec::new();
+        let mut id_errors = Vec::new();
+        for id in ids {
+            match self.about_to_show_item(id).await {
+                Ok(true) => updates_needed.push(id),
+                Ok(false) => {}
+                Err(_) => id_errors.push(id),
+            }
+        }
+        (updates_needed, id_errors)
+    }
+
+    #[zbus(property)]
+    fn version(&self) -> u32 {
+        3
+    }
+
+    #[zbus(property)]
+    fn text_direction(&self) -> &str {
+        "ltr"
+    }
+
+    #[zbus(property)]
+    fn status(&self) -> &str {
+        "normal"
+    }
+
+    #[zbus(property)]
+    fn icon_theme_path(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    #[zbus(signal)]
+    pub(crate) async fn layout_updated(
+        emitter: &SignalEmitter<'_>,
+        revision: u32,
+        parent: i32,
+    ) -> zbus::Result<()>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calloop::EventLoop;
+    use gpui::{KeyBinding, Menu, MenuItem, actions};
+    use std::{os::unix::net::UnixStream, time::Duration};
+    use zbus::{Guid, connection::Builder};
+
+    actions!(test, [SaveAs, Autosave, SoftWrap]);
+
+    type Layout = (i32, HashMap<String, OwnedValue>, Vec<OwnedValue>);
+
+    macro_rules! call {
+        ($connection:expr, $method:expr, $body:expr) => {
+            $connection
+                .call_method(
+                    None::<&str>,
+                    MENU_OBJECT_PATH,
+                    Some("com.canonical.dbusmenu"),
+                    $method,
+                    &$body,
+                )
+                .await
+                .unwrap()
+                .body()
+                .deserialize()
+                .unwrap()
+        };
+    }
+
+    #[test]
+    fn test_export_menus() {
+        let keymap = Keymap::new(vec![KeyBinding::new("ctrl-shift-s", SaveAs, None)]);
+        let menu = Menu {
+            name: "File".into(),
+            items: vec![
+                MenuItem::action("Save_As", SaveAs),
+                MenuItem::separator(),
+                MenuItem::action("Autosave", Autosave).checked(true),
+                MenuItem::action("Soft Wrap", SoftWrap).checked(false),
+            ],
+        }
+        .owned();
+        let mut layout = MenuLayout::default();
+        let mut actions = HashMap::new();
+        add_menu(&mut layout, &mut actions, 0, &menu, &keymap);
+        assert!(actions[&2].partial_eq(&SaveAs));
+
+        let mut event_loop = EventLoop::<Vec<MenuEvent>>::try_new().unwrap();
+        let (events, channel) = calloop::channel::channel();
+        event_loop
+            .handle()
+            .insert_source(channel, |event, _, received| {
+                if let calloop::channel::Event::Msg(event) = event {
+                    received.push(event);
+                }
+            })
+            .unwrap();
+        let interface = DbusMenuInterface {
+            layout: Arc::new(Mutex::new(layout)),
+            events,
+        };
+
+        smol::block_on(async {
+            let (server, client) = UnixStream::pair().unwrap();
+            let (_server, client) = futures::try_join!(
+                Builder::async_io_unix_stream(server)
+                    .server(Guid::generate())
+                    .unwrap()
+                    .p2p()
+                    .serve_at(MENU_OBJECT_PATH, interface)
+                    .unwrap()
+                    .build(),
+                Builder::async_io_unix_stream(client).p2p().build(),
+            )
+            .unwrap();
+
+            let (revision, (id, _, menus)): (u32, Layout) =
+                call!(client, "GetLayout", (0, -1, Vec::<String>::new()));
+            assert_eq!((revision, id, menus.len()), (0, 0, 1));
+            let (_, properties, items) = Layout::try_from(menus[0].try_clone().unwrap()).unwrap();
+            assert_eq!(properties["label"], owned("File"));
+            assert_eq!(properties["children-display"], owned("submenu"));
+
+            let items = items
+                .into_iter()
+                .map(|item| Layout::try_from(item).unwrap())
+                .collect::<Vec<_>>();
+            let ids = items.iter().map(|(id, _, _)| *id).collect::<Vec<_>>();
+            assert_eq!(ids, [2, 3, 4, 5]);
+            assert_eq!(items[0].1["label"], owned("Save__As"));
+            assert_eq!(
+                items[0].1["shortcut"],
+                owned(vec![vec!["Control", "Shift", "s"]])
+            );
+            assert_eq!(items[1].1["type"], owned("separator"));
+            assert_eq!(items[2].1["toggle-type"], owned("checkmark"));
+            assert_eq!(items[2].1["toggle-state"], owned(1));
+            assert_eq!(items[3].1["toggle-type"], owned("checkmark"));
+            assert_eq!(items[3].1["toggle-state"], owned(0));
+            assert!(!items[0].1.contains_key("toggle-type"));
+
+            let properties: Vec<(i32, HashMap<String, OwnedValue>)> = call!(
+                client,
+                "GetGroupProperties",
+                (vec![4, 99], vec!["label".to_string()])
+            );
+            assert_eq!(properties.len(), 1);
+            assert_eq!(properties[0].1.keys().collect::<Vec<_>>(), ["label"]);
+
+            let () = call!(client, "Event", (2, "clicked", owned(0), 0u32));
+
+            // The main thread decides whether the menu changed when it's about to be shown.
+            let about_to_show = async {
+                let needs_update: bool = call!(client, "AboutToShow", (1,));
+                needs_update
+            };
+            let reply = async {
+                let mut received = Vec::new();
+                loop {
+                    event_loop
+                        .dispatch(Duration::from_millis(10), &mut received)
+                        .unwrap();
+                    for event in received.drain(..) {
+                        match event {
+                            MenuEvent::Activated(id) => assert_eq!(id, 2),
+                            MenuEvent::AboutToShow { id, needs_update } => {
+                                assert_eq!(id, 1);
+                                needs_update.send(true).unwrap();
+                                return;
+                            }
+                            MenuEvent::Exported(_) => unreachable!(),
+                        }
+                    }
+                    smol::future::yield_now().await;
+                }
+            };
+            let (needs_update, ()) = futures::join!(about_to_show, reply);
+            assert!(needs_update);
+        });
+    }
+
+    #[derive(Default, Clone)]
+    struct Registrar {
+        windows: Arc<Mutex<Vec<(u32, String)>>>,
+    }
+
+    #[zbus::interface(name = "com.canonical.AppMenu.Registrar")]
+    impl Registrar {
+        fn register_window(&self, window: u32, menu: ObjectPath<'_>) {
+            self.windows.lock().push((window, menu.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_register_window() {
+        let registrar = Registrar::default();
+        smol::block_on(async {
+            let (server, client) = UnixStream::pair().unwrap();
+            let (_server, client) = futures::try_join!(
+                Builder::async_io_unix_stream(server)
+                    .server(Guid::generate())
+                    .unwrap()
+                    .p2p()
+                    .serve_at(REGISTRAR_PATH, registrar.clone())
+                    .unwrap()
+                    .build(),
+                Builder::async_io_unix_stream(client).p2p().build(),
+            )
+            .unwrap();
+
+            let (updates, update_rx) = mpsc::unbounded();
+            updates
+                .unbounded_send(MenuUpdate::RegisterWindow(42))
+                .unwrap();
+            drop(updates);
+            send_updates(&client, update_rx).await.unwrap();
+        });
+        assert_eq!(
+            *registrar.windows.lock(),
+            [(42, MENU_OBJECT_PATH.to_string())]
+        );
+    }
+}
@@ -115,6 +115,10 @@ impl LinuxClient for HeadlessClient {
         None
     }
 
+    fn read_custom_from_clipboard(&self, _mime: &str) -> Option<Vec<u8>> {
+        None
+    }
+
     fn run(&self) {
         let mut event_loop = self
             .0
@@ -1,4 +1,13 @@
+#[cfg(any(feature = "wayland", feature = "x11"))]
+use collections::HashMap;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+use gpui::{KeybindingKeystroke, Keystroke, Modifiers, PlatformKeyboardMapper};
 use gpui::{PlatformKeyboardLayout, SharedString};
+#[cfg(any(feature = "wayland", feature = "x11"))]
+use xkbcommon::xkb::{self, Keycode};
+
+#[cfg(any(feature = "wayland", feature = "x11"))]
+use crate::linux::{keystroke_from_xkb, keystroke_underlying_dead_key};
 
 #[derive(Clone)]
 pub(crate) struct LinuxKeyboardLayout {
@@ -20,3 +29,203 @@ impl LinuxKeyboardLayout {
         Self { name }
     }
 }
+
+/// Maps the keys of key bindings to the keystrokes typed with the current keyboard layout.
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(crate) struct LinuxKeyboardMapper {
+    /// The keystroke typed with the key that types each character. Characters typed with dead keys
+    /// are included, as the keystrokes typed with them are named after the key at their position
+    /// on a US layout, or after the dead key.
+    typed_keys: HashMap<String, TypedKey>,
+    /// The keystrokes typed with the keys at the positions of the digits and symbols of a US
+    /// layout, by the character they type on a US layout.
+    key_equivalents: HashMap<String, TypedKey>,
+}
+
+#[cfg(any(feature = "wayland", feature = "x11"))]
+#[derive(Clone, Debug, PartialEq)]
+struct TypedKey {
+    key: String,
+    shift: bool,
+}
+
+/// The keycodes of the digits and symbols on a US layout, with the characters they type without
+/// and with shift.
+#[cfg(any(feature = "wayland", feature = "x11"))]
+const US_SYMBOL_KEYS: &[(u32, char, char)] = &[
+    (49, '`', '~'),
+    (10, '1', '!'),
+    (11, '2', '@'),
+    (12, '3', '#'),
+    (13, '4', '$'),
+    (14, '5', '%'),
+    (15, '6', '^'),
+    (16, '7', '&'),
+    (17, '8', '*'),
+    (18, '9', '('),
+    (19, '0', ')'),
+    (20, '-', '_'),
+    (21, '=', '+'),
+    (34, '[', '{'),
+    (35, ']', '}'),
+    (51, '\\', '|'),
+    (47, ';', ':'),
+    (48, '\'', '"'),
+    (59, ',', '<'),
+    (60, '.', '>'),
+    (61, '/', '?'),
+];
+
+#[cfg(any(feature = "wayland", feature = "x11"))]
+impl LinuxKeyboardMapper {
+    /// Create the mapper for a layout of the keymap, typing each key of the keymap without and with
+    /// shift.
+    pub(crate) fn new(keymap: &xkb::Keymap, layout: xkb::LayoutIndex) -> Self {
+        let mut state = xkb::State::new(keymap);
+        let shift_mask = 1 << keymap.mod_get_index(xkb::MOD_NAME_SHIFT);
+        let mut typed_keys = HashMap::default();
+        let mut key_equivalents = HashMap::default();
+
+        // Prefer the keys that type a character without shift.
+        for shift in [false, true] {
+            state.update_mask(if shift { shift_mask } else { 0 }, 0, 0, 0, 0, layout);
+            let modifiers = Modifiers {
+                shift,
+                ..Modifiers::default()
+            };
+            let type_key = |keycode: Keycode| {
+                let keystroke = keystroke_from_xkb(&state, modifiers, keycode);
+                TypedKey {
+                    key: keystroke.key,
+                    shift: keystroke.modifiers.shift,
+                }
+            };
+
+            for raw_keycode in keymap.min_keycode().raw()..=keymap.max_keycode().raw() {
+                let keycode = Keycode::new(raw_keycode);
+                let keysym = state.key_get_one_sym(keycode);
+                if keysym.is_keypad_key() {
+                    continue;
+                }
+                let character = keystroke_underlying_dead_key(keysym)
+                    .unwrap_or_else(|| state.key_get_utf8(keycode));
+                let mut chars = character.chars();
+                if chars
+                    .next()
+                    .is_some_and(|char| !char.is_control() && chars.next().is_none())
+                {
+                    typed_keys
+                        .entry(character)
+                        .or_insert_with(|| type_key(keycode));
+                }
+            }
+
+            for &(raw_keycode, unshifted, shifted) in US_SYMBOL_KEYS {
+                let us_key = if shift { shifted } else { unshifted }.to_string();
+                let typed = type_key(Keycode::new(raw_keycode));
+                if !typed.key.is_empty() && (typed.key != us_key || typed.shift) {
+                    key_equivalents.insert(us_key, typed);
+                }
+            }
+        }
+        typed_keys.retain(|character, typed| typed.key != *character || typed.shift);
+
+        Self {
+            typed_keys,
+            key_equivalents,
+        }
+    }
+}
+
+#[cfg(any(feature = "wayland", feature = "x11"))]
+impl PlatformKeyboardMapper for LinuxKeyboardMapper {
+    fn map_key_equivalent(
+        &self,
+        mut keystroke: Keystroke,
+        use_key_equivalents: bool,
+    ) -> KeybindingKeystroke {
+        // Key equivalents are bound by the position of the keys, anything else by what they type.
+        let keys = if use_key_equivalents {
+            &self.key_equivalents
+        } else {
+            &self.typed_keys
+        };
+        if let Some(typed) = keys.get(&keystroke.key) {
+            keystroke.key = typed.key.clone();
+            keystroke.modifiers.shift |= typed.shift;
+        }
+        KeybindingKeystroke::from_keystroke(keystroke)
+    }
+
+    fn get_key_equivalents(&self) -> Option<&HashMap<char, char>> {
+        None
+    }
+}
+
+#[cfg(all(test, any(feature = "wayland", feature = "x11")))]
+mod tests {
+    use super::*;
+
+    fn mapper(layout: &str, variant: &str) -> LinuxKeyboardMapper {
+        let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+        let keymap = xkb::Keymap::new_from_names(
+            &context,
+            "",
+            "pc105",
+            layout,
+            variant,
+            None,
+            xkb::KEYMAP_COMPILE_NO_FLAGS,
+        )
+        .unwrap();
+        LinuxKeyboardMapper::new(&keymap, 0)
+    }
+
+    fn map(mapper: &LinuxKeyboardMapper, source: &str, use_key_equivalents: bool) -> String {
+        let keystroke = Keystroke::parse(source).unwrap();
+        mapper
+            .map_key_equivalent(keystroke, use_key_equivalents)
+            .inner()
+            .unparse()
+    }
+
+    #[test]
+    fn test_us_layout() {
+        let mapper = mapper("us", "");
+        for source in ["ctrl-a", "ctrl-[", "ctrl-!", "ctrl-shift-a", "ctrl-`"] {
+            assert_eq!(map(&mapper, source, false), source);
+            assert_eq!(map(&mapper, source, true), source);
+        }
+    }
+
+    #[test]
+    fn test_key_equivalents() {
+        let azerty = mapper("fr", "");
+        assert_eq!(map(&azerty, "ctrl-1", true), "ctrl-&");
+        assert_eq!(map(&azerty, "ctrl-!", true), "ctrl-1");
+        assert_eq!(map(&azerty, "ctrl-a", true), "ctrl-a");
+        assert_eq!(map(&azerty, "ctrl-;", true), "ctrl-m");
+        assert_eq!(map(&azerty, "ctrl-:", true), "ctrl-shift-m");
+
+        // The keys typing characters outside of ASCII are already named after their position on
+        // a US layout.
+        let qwertz = mapper("de", "");
+        assert_eq!(map(&qwertz, "ctrl-[", true), "ctrl-[");
+        assert_eq!(map(&qwertz, "ctrl-=", true), "ctrl-dead_acute");
+        assert_eq!(map(&qwertz, "ctrl-/", true), "ctrl--");
+    }
+
+    #[test]
+    fn test_typed_keys() {
+        let qwertz = mapper("de", "");
+        // Shifted symbols are typed as they are, on whichever key they're on.
+        assert_eq!(map(&qwertz, "ctrl-/", false), "ctrl-/");
+        assert_eq!(map(&qwertz, "ctrl-z", false), "ctrl-z");
+        // Dead keys are typed as the key at their position on a US layout, or by their name.
+        assert_eq!(map(&qwertz, "ctrl-^", false), "ctrl-`");
+
+        let dvorak = mapper("us", "dvorak");
+        assert_eq!(map(&dvorak, "ctrl-[", false), "ctrl-[");
+        assert_eq!(map(&dvorak, "ctrl-[", true), "ctrl-/");
+    }
+}
@@ -0,0 +1,282 @@
+//! Shows system notifications with the `org.freedesktop.Notifications` DBus interface, or with
+//! the notification portal when sandboxed.
+//!
+//! Responses are delivered to the main thread by the X11 and Wayland clients, see
+//! [`handle_notification_event`].
+
+use std::{cell::RefMut, collections::HashMap, path::Path};
+
+use ashpd::desktop::{Icon, notification as portal};
+use calloop::channel::{Channel, Sender};
+use futures::{StreamExt as _, channel::mpsc, future, stream};
+use gpui::{
+    BackgroundExecutor, Notification, NotificationId, NotificationResponse, NotificationUrgency,
+};
+use util::ResultExt as _;
+use zbus::zvariant::Value;
+
+use crate::linux::{LinuxCommon, app_name};
+
+const NOTIFICATIONS_NAME: &str = "org.freedesktop.Notifications";
+const NOTIFICATIONS_PATH: &str = "/org/freedesktop/Notifications";
+
+/// The action key for clicking on the notification itself.
+const DEFAULT_ACTION: &str = "default";
+
+/// Sent from the DBus connection to the main thread.
+pub(crate) struct NotificationEvent {
+    id: NotificationId,
+    response: NotificationResponse,
+}
+
+/// Sent from the main thread to the DBus connection.
+enum NotificationRequest {
+    Show(NotificationId, Notification),
+    Close(NotificationId),
+}
+
+/// The notifications shown by the application.
+pub(crate) struct DbusNotifications {
+    requests: mpsc::UnboundedSender<NotificationRequest>,
+}
+
+impl DbusNotifications {
+    /// Create the notifications, returning the channel on which responses to them are delivered.
+    /// Nothing connects to the session bus until a notification is first shown.
+    pub fn new(executor: &BackgroundExecutor) -> (Self, Channel<NotificationEvent>) {
+        let (events, channel) = calloop::channel::channel();
+        let (requests, mut request_rx) = mpsc::unbounded();
+
+        executor
+            .spawn(async move {
+                let Some(first) = request_rx.next().await else {
+                    return anyhow::Ok(());
+                };
+                let requests = stream::iter([first]).chain(request_rx);
+                if ashpd::is_sandboxed().await {
+                    show_with_portal(requests, events).await
+                } else {
+                    show_with_server(requests, events).await
+                }
+            })
+            .detach();
+
+        (Self { requests }, channel)
+    }
+
+    pub fn show(&self, id: NotificationId, notification: Notification) {
+        self.requests
+            .unbounded_send(NotificationRequest::Show(id, notification))
+            .log_err();
+    }
+
+    pub fn close(&self, id: NotificationId) {
+        self.requests
+            .unbounded_send(NotificationRequest::Close(id))
+            .log_err();
+    }
+}
+
+enum ServerMessage {
+    Request(NotificationRequest),
+    ActionInvoked(u32, String),
+    Closed(u32),
+}
+
+async fn show_with_server(
+    requests: impl futures::Stream<Item = NotificationRequest> + Unpin,
+    events: Sender<NotificationEvent>,
+) -> anyhow::Result<()> {
+    let connection = zbus::Connection::session().await?;
+    let proxy = zbus::Proxy::new(
+        &connection,
+        NOTIFICATIONS_NAME,
+        NOTIFICATIONS_PATH,
+        NOTIFICATIONS_NAME,
+    )
+    .await?;
+    let actions_invoked = proxy
+        .receive_signal("ActionInvoked")
+        .await?
+        .filter_map(|message| {
+            let body = message.body().deserialize::<(u32, String)>().log_err();
+            future::ready(body.map(|(server_id, key)| ServerMessage::ActionInvoked(server_id, key)))
+        });
+    let closed = proxy
+        .receive_signal("NotificationClosed")
+        .await?
+        .filter_map(|message| {
+            let body = message.body().deserialize::<(u32, u32)>().log_err();
+            future::ready(body.map(|(server_id, _reason)| ServerMessage::Closed(server_id)))
+        });
+    let mut messages = stream::select(
+        requests.map(ServerMessage::Request),
+        stream::select(actions_invoked, closed),
+    );
+
+    let app_name = app_name();
+    let mut server_ids = HashMap::<NotificationId, u32>::new();
+    let mut ids = HashMap::<u32, NotificationId>::new();
+    while let Some(message) = messages.next().await {
+        match message {
+            ServerMessage::Request(NotificationRequest::Show(id, notification)) => {
+                let replaces_id = server_ids.get(&id).copied().unwrap_or(0);
+                let mut actions = vec![DEFAULT_ACTION, ""];
+                for action in &notification.actions {
+                    actions.push(action.id.as_str());
+                    actions.push(action.label.as_str());
+                }
+                let urgency: u8 = match notification.urgency {
+                    NotificationUrgency::Low => 0,
+                    NotificationUrgency::Normal => 1,
+                    NotificationUrgency::Critical => 2,
+                };
+                let hints = HashMap::from([("urgency", Value::from(urgency))]);
+                let Some(server_id) = proxy
+                    .call::<_, _, u32>(
+                        "Notify",
+                        &(
+                            app_name.as_str(),
+                            replaces_id,
+                            notification.icon.as_ref().map_or("", |icon| icon.as_str()),
+                            notification.title.as_str(),
+                            notification.body.as_ref().map_or("", |body| body.as_str()),
+                            actions,
+                            hints,
+                            -1i32,
+                        ),
+                    )
+                    .await
+                    .log_err()
+                else {
+                    continue;
+                };
+                if let Some(previous) = server_ids.insert(id, server_id) {
+                    ids.remove(&previous);
+                }
+                ids.insert(server_id, id);
+            }
+            ServerMessage::Request(NotificationRequest::Close(id)) => {
+                if let Some(&server_id) = server_ids.get(&id) {
+                    proxy
+                        .call::<_, _, ()>("CloseNotification", &server_id)
+                        .await
+                        .log_err();
+                }
+            }
+            ServerMessage::ActionInvoked(server_id, key) => {
+                if let Some(&id) = ids.get(&server_id) {
+                    let response = if key == DEFAULT_ACTION {
+                        NotificationResponse::Activated
+                    } else {
+                        NotificationResponse::Action(key.into())
+                    };
+                    events.send(NotificationEvent { id, response })?;
+                }
+            }
+            ServerMessage::Closed(server_id) => {
+                if let Some(id) = ids.remove(&server_id) {
+                    server_ids.remove(&id);
+                    events.send(NotificationEvent {
+                        id,
+                        response: NotificationResponse::Closed,
+                    })?;
+                }
+            }
+        }
+    }
+    anyhow::Ok(())
+}
+
+enum PortalMessage {
+    Request(NotificationRequest),
+    ActionInvoked(portal::Action),
+}
+
+/// The notification portal doesn't report when notifications are dismissed, so only closing one
+/// with [`DbusNotifications::close`] responds with [`NotificationResponse::Closed`].
+async fn show_with_portal(
+    requests: impl futures::Stream<Item = NotificationRequest> + Unpin,
+    events: Sender<NotificationEvent>,
+) -> anyhow::Result<()> {
+    let proxy = portal::NotificationProxy::new().await?;
+    let actions_invoked = proxy
+        .receive_action_invoked()
+        .await?
+        .map(PortalMessage::ActionInvoked);
+    let mut messages = stream::select(requests.map(PortalMessage::Request), actions_invoked);
+
+    while let Some(message) = messages.next().await {
+        match message {
+            PortalMessage::Request(NotificationRequest::Show(id, notification)) => {
+                let priority = match notification.urgency {
+                    NotificationUrgency::Low => portal::Priority::Low,
+                    NotificationUrgency::Normal => portal::Priority::Normal,
+                    NotificationUrgency::Critical => portal::Priority::Urgent,
+                };
+                let icon = notification.icon.as_ref().map(|icon| {
+                    match std::fs::read(Path::new(icon.as_str())) {
+                        Ok(bytes) => Icon::Bytes(bytes),
+                        Err(_) => Icon::with_names([icon]),
+                    }
+                });
+                let mut request = portal::Notification::new(&notification.title)
+                    .body(notification.body.as_ref().map(|body| body.as_str()))
+                    .icon(icon)
+                    .priority(priority)
+                    .default_action(DEFAULT_ACTION);
+                for action in &notification.actions {
+                    request = request.button(portal::Button::new(
+                        action.label.as_str(),
+                        action.id.as_str(),
+                    ));
+                }
+                proxy
+                    .add_notification(&id.0.to_string(), request)
+                    .await
+                    .log_err();
+            }
+            PortalMessage::Request(NotificationRequest::Close(id)) => {
+                if proxy
+                    .remove_notification(&id.0.to_string())
+                    .await
+                    .log_err()
+                    .is_some()
+                {
+                    events.send(NotificationEvent {
+                        id,
+                        response: NotificationResponse::Closed,
+                    })?;
+                }
+            }
+            PortalMessage::ActionInvoked(action) => {
+                let Some(id) = action.id().parse().ok().map(NotificationId) else {
+                    continue;
+                };
+                let response = if action.name() == DEFAULT_ACTION {
+                    NotificationResponse::Activated
+                } else {
+                    NotificationResponse::Action(action.name().to_string().into())
+                };
+                events.send(NotificationEvent { id, response })?;
+            }
+        }
+    }
+    anyhow::Ok(())
+}
+
+/// Handle a response to a notification on the main thread, borrowing the platform's common
+/// state with `common` whenever it's needed.
+pub(crate) fn handle_notification_event<'a>(
+    event: NotificationEvent,
+    common: impl Fn() -> RefMut<'a, LinuxCommon>,
+) {
+    let callback = common().callbacks.notification_response.take();
+    if let Some(mut callback) = callback {
+        callback(event.id, event.response);
+        common()
+            .callbacks
+            .notification_response
+            .get_or_insert(callback);
+    }
+}
@@ -24,9 +24,10 @@ use xkbcommon::xkb::{self, Keycode, Keysym, State};
 use crate::linux::{LinuxDispatcher, PriorityQueueCalloopReceiver};
 use gpui::{
     Action, AnyWindowHandle, BackgroundExecutor, ClipboardItem, CursorStyle, DisplayId,
-    ForegroundExecutor, Keymap, Menu, MenuItem, OwnedMenu, PathPromptOptions, Platform,
-    PlatformDisplay, PlatformKeyboardLayout, PlatformKeyboardMapper, PlatformTextSystem,
-    PlatformWindow, Result, RunnableVariant, Task, ThermalState, WindowAppearance, WindowParams,
+    ForegroundExecutor, Keymap, Menu, MenuItem, Notification, NotificationId, NotificationResponse,
+    OwnedMenu, PathPromptOptions, Platform, PlatformDisplay, PlatformKeyboardLayout,
+    PlatformKeyboardMapper, PlatformTextSystem, PlatformWindow, Result, RunnableVariant,
+    StatusItem, StatusItemClick, Task, ThermalState, WindowAppearance, WindowParams,
 };
 #[cfg(any(feature = "wayland", feature = "x11"))]
 use gpui::{Pixels, Point, px};
@@ -50,6 +51,9 @@ pub(crate) trait LinuxClient {
     fn compositor_name(&self) -> &'static str;
     fn with_common<R>(&self, f: impl FnOnce(&mut LinuxCommon) -> R) -> R;
     fn keyboard_layout(&self) -> Box<dyn PlatformKeyboardLayout>;
+    fn keyboard_mapper(&self) -> Rc<dyn PlatformKeyboardMapper> {
+        Rc::new(gpui::DummyKeyboardMapper)
+    }
     fn displays(&self) -> Vec<Rc<dyn PlatformDisplay>>;
     #[allow(unused)]
     fn display(&self, id: DisplayId) -> Option<Rc<dyn PlatformDisplay>>;
@@ -85,6 +89,7 @@ pub(crate) trait LinuxClient {
     fn write_to_clipboard(&self, item: ClipboardItem);
     fn read_from_primary(&self) -> Option<ClipboardItem>;
     fn read_from_clipboard(&self) -> Option<ClipboardItem>;
+    fn read_custom_from_clipboard(&self, mime: &str) -> Option<Vec<u8>>;
     fn active_window(&self) -> Option<AnyWindowHandle>;
     fn window_stack(&self) -> Option<Vec<AnyWindowHandle>>;
     fn run(&self);
@@ -106,6 +111,9 @@ pub(crate) struct PlatformHandlers {
     pub(crate) will_open_app_menu: Option<Box<dyn FnMut()>>,
     pub(crate) validate_app_menu_command: Option<Box<dyn FnMut(&dyn Action) -> bool>>,
     pub(crate) keyboard_layout_change: Option<Box<dyn FnMut()>>,
+    pub(crate) notification_response: Option<Box<dyn FnMut(NotificationId, NotificationResponse)>>,
+    pub(crate) status_item_click: Option<Box<dyn FnMut(StatusItemClick)>>,
+    pub(crate) thermal_state_change: Option<Box<dyn FnMut()>>,
 }
 
 pub(crate) struct LinuxCommon {
@@ -114,9 +122,28 @@ pub(crate) struct LinuxCommon {
     pub(crate) text_system: Arc<dyn PlatformTextSystem>,
     pub(crate) appearance: WindowAppearance,
     pub(crate) auto_hide_scrollbars: bool,
+    pub(crate) thermal_state: ThermalState,
     pub(crate) callbacks: PlatformHandlers,
     pub(crate) signal: LoopSignal,
+    pub(crate) recent_documents: crate::linux::RecentDocuments,
     pub(crate) menus: Vec<OwnedMenu>,
+    /// The menus as exported over DBus, set by clients with a menu bar.
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    pub(crate) app_menu: Option<crate::linux::DbusMenu>,
+    /// The notifications shown by the application, set by clients that can show them.
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    pub(crate) notifications: Option<crate::linux::DbusNotifications>,
+    /// The icon in the system tray, set by clients that can show one.
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    pub(crate) status_item: Option<crate::linux::StatusNotifier>,
+    /// Sends the URLs the application is opened with to the main thread, set by clients that can
+    /// receive them.
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    pub(crate) open_urls: Option<calloop::channel::Sender<Vec<String>>>,
+    /// Sends the thermal state to the main thread, set by clients that can receive it. Taken once
+    /// the application first observes the state, see [`LinuxCommon::watch_thermal_state`].
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    pub(crate) thermal_states: Option<calloop::channel::Sender<ThermalState>>,
 }
 
 impl LinuxCommon {
@@ -133,6 +160,7 @@ impl LinuxCommon {
         let dispatcher = Arc::new(LinuxDispatcher::new(main_sender));
 
         let background_executor = BackgroundExecutor::new(dispatcher.clone());
+        let recent_documents = crate::linux::RecentDocuments::new(&background_executor);
 
         let common = LinuxCommon {
             background_executor,
@@ -140,13 +168,34 @@ impl LinuxCommon {
             text_system,
             appearance: WindowAppearance::Light,
             auto_hide_scrollbars: false,
+            thermal_state: ThermalState::Nominal,
             callbacks,
             signal,
+            recent_documents,
             menus: Vec::new(),
+            #[cfg(any(feature = "wayland", feature = "x11"))]
+            app_menu: None,
+            #[cfg(any(feature = "wayland", feature = "x11"))]
+            notifications: None,
+            #[cfg(any(feature = "wayland", feature = "x11"))]
+            status_item: None,
+            #[cfg(any(feature = "wayland", feature = "x11"))]
+            open_urls: None,
+            #[cfg(any(feature = "wayland", feature = "x11"))]
+            thermal_states: None,
         };
 
         (common, main_receiver)
     }
+
+    /// Start watching the thermal state the first time the application observes it. Until then,
+    /// the state stays nominal and nothing reads the thermal zones.
+    fn watch_thermal_state(&mut self) {
+        #[cfg(any(feature = "wayland", feature = "x11"))]
+        if let Some(thermal_states) = self.thermal_states.take() {
+            crate::linux::watch_thermal_state(&self.background_executor, thermal_states);
+        }
+    }
 }
 
 pub(crate) struct LinuxPlatform<P> {
@@ -173,7 +222,7 @@ impl<P: LinuxClient + 'static> Platform for LinuxPlatform<P> {
     }
 
     fn keyboard_mapper(&self) -> Rc<dyn PlatformKeyboardMapper> {
-        Rc::new(gpui::DummyKeyboardMapper)
+        self.inner.keyboard_mapper()
     }
 
     fn on_keyboard_layout_change(&self, callback: Box<dyn FnMut()>) {
@@ -181,13 +230,34 @@ impl<P: LinuxClient + 'static> Platform for LinuxPlatform<P> {
             .with_common(|common| common.callbacks.keyboard_layout_change = Some(callback));
     }
 
-    fn on_thermal_state_change(&self, _callback: Box<dyn FnMut()>) {}
+    fn on_thermal_state_change(&self, callback: Box<dyn FnMut()>) {
+        self.inner.with_common(|common| {
+            common.watch_thermal_state();
+            common.callbacks.thermal_state_change = Some(callback);
+        });
+    }
 
     fn thermal_state(&self) -> ThermalState {
-        ThermalState::Nominal
+        self.inner.with_common(|common| common.thermal_state)
     }
 
     fn run(&self, on_finish_launching: Box<dyn FnOnce()>) {
+        #[cfg(any(feature = "wayland", feature = "x11"))]
+        if let Some(open_urls) = self.inner.with_common(|common| common.open_urls.clone()) {
+            // Only URLs of the schemes the application handles are its to open, it launches as
+            // usual with anything else on its command line.
+            let schemes = crate::linux::registered_url_schemes();
+            let urls = crate::linux::urls_from_args(env::args().skip(1), &schemes);
+            if !urls.is_empty() {
+                // A running instance listens once it has registered a URL scheme.
+                if crate::linux::forward_to_running_instance(&urls) {
+                    log::info!("opened URLs in the running instance");
+                    return;
+                }
+                open_urls.send(urls).log_err();
+            }
+        }
+
         on_finish_launching();
 
         LinuxClient::run(&self.inner);
@@ -497,15 +567,52 @@ impl<P: LinuxClient + 'static> Platform for LinuxPlatform<P> {
         });
     }
 
+    fn show_notification(&self, id: NotificationId, notification: Notification) {
+        self.inner.with_common(|common| {
+            #[cfg(any(feature = "wayland", feature = "x11"))]
+            if let Some(notifications) = common.notifications.as_ref() {
+                notifications.show(id, notification);
+            }
+            #[cfg(not(any(feature = "wayland", feature = "x11")))]
+            let _ = (common, id, notification);
+        })
+    }
+
+    fn close_notification(&self, id: NotificationId) {
+        self.inner.with_common(|common| {
+            #[cfg(any(feature = "wayland", feature = "x11"))]
+            if let Some(notifications) = common.notifications.as_ref() {
+                notifications.close(id);
+            }
+            #[cfg(not(any(feature = "wayland", feature = "x11")))]
+            let _ = (common, id);
+        })
+    }
+
+    fn on_notification_response(
+        &self,
+        callback: Box<dyn FnMut(NotificationId, NotificationResponse)>,
+    ) {
+        self.inner.with_common(|common| {
+            common.callbacks.notification_response = Some(callback);
+        });
+    }
+
     fn app_path(&self) -> Result<PathBuf> {
         // get the path of the executable of the current process
         let app_path = env::current_exe()?;
         Ok(app_path)
     }
 
-    fn set_menus(&self, menus: Vec<Menu>, _keymap: &Keymap) {
+    fn set_menus(&self, menus: Vec<Menu>, keymap: &Keymap) {
         self.inner.with_common(|common| {
             common.menus = menus.into_iter().map(|menu| menu.owned()).collect();
+            #[cfg(any(feature = "wayland", feature = "x11"))]
+            if let Some(app_menu) = common.app_menu.as_mut() {
+                app_menu.set_menus(&common.menus, keymap);
+            }
+            #[cfg(not(any(feature = "wayland", feature = "x11")))]
+            let _ = keymap;
         })
     }
 
@@ -517,6 +624,23 @@ impl<P: LinuxClient + 'static> Platform for LinuxPlatform<P> {
         // todo(linux)
     }
 
+    fn set_status_item(&self, item: Option<StatusItem>, keymap: &Keymap) {
+        self.inner.with_common(|common| {
+            #[cfg(any(feature = "wayland", feature = "x11"))]
+            if let Some(status_item) = common.status_item.as_mut() {
+                status_item.set(item, keymap);
+            }
+            #[cfg(not(any(feature = "wayland", feature = "x11")))]
+            let _ = (common, item, keymap);
+        })
+    }
+
+    fn on_status_item_click(&self, callback: Box<dyn FnMut(StatusItemClick)>) {
+        self.inner.with_common(|common| {
+            common.callbacks.status_item_click = Some(callback);
+        });
+    }
+
     fn path_for_auxiliary_executable(&self, _name: &str) -> Result<PathBuf> {
         Err(anyhow::Error::msg(
             "Platform<LinuxPlatform>::path_for_auxiliary_executable is not implemented yet",
@@ -601,6 +725,22 @@ impl<P: LinuxClient + 'static> Platform for LinuxPlatform<P> {
         self.inner.with_common(|common| common.appearance)
     }
 
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    fn register_url_scheme(&self, scheme: &str) -> Task<anyhow::Result<()>> {
+        let scheme = scheme.to_string();
+        let open_urls = self.inner.with_common(|common| common.open_urls.clone());
+        let executor = self.background_executor();
+        self.background_executor().spawn(async move {
+            crate::linux::register_url_scheme(&scheme)?;
+            // Only applications handling URLs receive those forwarded by later instances.
+            if let Some(open_urls) = open_urls {
+                crate::linux::listen_for_urls(&executor, open_urls);
+            }
+            Ok(())
+        })
+    }
+
+    #[cfg(not(any(feature = "wayland", feature = "x11")))]
     fn register_url_scheme(&self, _: &str) -> Task<anyhow::Result<()>> {
         Task::ready(Err(anyhow!("register_url_scheme unimplemented")))
     }
@@ -621,7 +761,27 @@ impl<P: LinuxClient + 'static> Platform for LinuxPlatform<P> {
         self.inner.read_from_clipboard()
     }
 
-    fn add_recent_document(&self, _path: &Path) {}
+    fn read_custom_from_clipboard(&self, mime: &str) -> Option<Vec<u8>> {
+        self.inner.read_custom_from_clipboard(mime)
+    }
+
+    fn add_recent_document(&self, path: &Path) {
+        self.inner
+            .with_common(|common| common.recent_documents.add(path.to_path_buf()));
+    }
+
+    fn recent_documents(&self) -> Task<Result<Vec<PathBuf>>> {
+        let documents = self
+            .inner
+            .with_common(|common| common.recent_documents.read());
+        self.background_executor()
+            .spawn(async move { documents.await? })
+    }
+
+    fn clear_recent_documents(&self) {
+        self.inner
+            .with_common(|common| common.recent_documents.clear());
+    }
 }
 
 #[cfg(any(feature = "wayland", feature = "x11"))]
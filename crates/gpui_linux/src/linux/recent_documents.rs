@@ -0,0 +1,732 @@
+//! Remembers the documents opened by the application in `recently-used.xbel`, the list of recent
+//! files shared with file managers and GTK file choosers.
+//!
+//! Bookmarks left in the list by other applications are kept as they are written, only the parts
+//! describing this application are parsed and rewritten.
+
+use std::{
+    fs::{self, File},
+    io::{self, Read as _},
+    ops::Range,
+    os::unix::fs::MetadataExt as _,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::{
+    StreamExt as _,
+    channel::{mpsc, oneshot},
+};
+use gpui::BackgroundExecutor;
+use util::ResultExt as _;
+
+use crate::linux::{app_name, xdg_data_dirs, xdg_data_home};
+
+/// The most bookmarks kept in the list, the least recently used ones are removed beyond it.
+const MAX_BOOKMARKS: usize = 1000;
+
+const XBEL_HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>"#;
+const XBEL_FOOTER: &str = "</xbel>";
+
+enum RecentDocumentsRequest {
+    Add(PathBuf),
+    Read(oneshot::Sender<Result<Vec<PathBuf>>>),
+    Clear,
+}
+
+/// The recent documents of the application. The list is read and written on a background thread,
+/// one request at a time.
+pub(crate) struct RecentDocuments {
+    requests: mpsc::UnboundedSender<RecentDocumentsRequest>,
+}
+
+impl RecentDocuments {
+    pub fn new(executor: &BackgroundExecutor) -> Self {
+        let (requests, mut request_rx) = mpsc::unbounded();
+
+        executor
+            .spawn(async move {
+                while let Some(request) = request_rx.next().await {
+                    let path = xdg_data_home().join("recently-used.xbel");
+                    let app_name = app_name();
+                    match request {
+                        RecentDocumentsRequest::Add(document) => {
+                            let mime_type = mime_type(&document);
+                            update_xbel(&path, |xbel| {
+                                xbel.add(&document, &mime_type, &app_name, Utc::now())
+                            })
+                            .log_err();
+                        }
+                        RecentDocumentsRequest::Read(tx) => {
+                            let documents = read_xbel(&path).map(|xbel| xbel.documents(&app_name));
+                            tx.send(documents).ok();
+                        }
+                        RecentDocumentsRequest::Clear => {
+                            update_xbel(&path, |xbel| xbel.clear(&app_name)).log_err();
+                        }
+                    }
+                }
+            })
+            .detach();
+
+        Self { requests }
+    }
+
+    pub fn add(&self, path: PathBuf) {
+        self.requests
+            .unbounded_send(RecentDocumentsRequest::Add(path))
+            .log_err();
+    }
+
+    /// The documents the application used, most recently used first.
+    pub fn read(&self) -> oneshot::Receiver<Result<Vec<PathBuf>>> {
+        let (tx, rx) = oneshot::channel();
+        self.requests
+            .unbounded_send(RecentDocumentsRequest::Read(tx))
+            .log_err();
+        rx
+    }
+
+    /// Remove the application from the bookmarks of the documents it used, removing the bookmarks
+    /// no other application uses.
+    pub fn clear(&self) {
+        self.requests
+            .unbounded_send(RecentDocumentsRequest::Clear)
+            .log_err();
+    }
+}
+
+fn read_xbel(path: &Path) -> Result<Xbel> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Xbel::parse(&contents),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Xbel::new()),
+        Err(error) => Err(error).context("reading recent documents"),
+    }
+}
+
+/// Update the list while holding a lock on it, replacing it with the updated one at once so it's
+/// never read half written.
+fn update_xbel(path: &Path, update: impl FnOnce(&mut Xbel)) -> Result<()> {
+    let dir = path.parent().context("no parent directory")?;
+    fs::create_dir_all(dir)?;
+    let mut file = loop {
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        file.lock()?;
+        // Another process may have replaced the list while we were waiting for the lock.
+        let (locked, current) = (file.metadata()?, fs::metadata(path)?);
+        if locked.dev() == current.dev() && locked.ino() == current.ino() {
+            break file;
+        }
+    };
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+    // Rewriting a list we can't make sense of would lose the other applications' bookmarks.
+    let mut xbel = Xbel::parse(&contents).context("not updating recent documents")?;
+    update(&mut xbel);
+
+    let temp_path = dir.join(format!(".recently-used.xbel.{}", std::process::id()));
+    fs::write(&temp_path, xbel.to_string())?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+/// The contents of `recently-used.xbel`.
+struct Xbel {
+    /// Everything before the first bookmark.
+    header: String,
+    bookmarks: Vec<Bookmark>,
+}
+
+/// A `<bookmark>` element, as written.
+struct Bookmark {
+    text: String,
+}
+
+impl Xbel {
+    fn new() -> Self {
+        Self {
+            header: XBEL_HEADER.to_string(),
+            bookmarks: Vec::new(),
+        }
+    }
+
+    /// Parse the list, which is empty when the file was just created. Fails when the list is cut
+    /// short, as when another program is writing it in place.
+    fn parse(contents: &str) -> Result<Self> {
+        if contents.trim().is_empty() {
+            return Ok(Self::new());
+        }
+        let end = contents
+            .rfind(XBEL_FOOTER)
+            .context("recently-used.xbel has no closing </xbel>")?;
+        let body = &contents[..end];
+        let mut bookmarks = Vec::new();
+        let mut header_end = None;
+        let mut offset = 0;
+        while let Some(start) = find_start_tag(&body[offset..], "bookmark") {
+            let start = offset + start;
+            let len = element_end(&body[start..], "bookmark")
+                .context("recently-used.xbel has an unclosed <bookmark>")?;
+            header_end.get_or_insert(start);
+            bookmarks.push(Bookmark {
+                text: body[start..start + len].to_string(),
+            });
+            offset = start + len;
+        }
+        Ok(Self {
+            header: body[..header_end.unwrap_or(body.len())]
+                .trim_end()
+                .to_string(),
+            bookmarks,
+        })
+    }
+
+    fn add(&mut self, path: &Path, mime_type: &str, app_name: &str, now: DateTime<Utc>) {
+        let Some(href) = url::Url::from_file_path(path).ok().map(String::from) else {
+            log::error!("not adding {path:?} to recent documents, it's not an absolute path");
+            return;
+        };
+        let now = now.to_rfc3339_opts(SecondsFormat::Micros, true);
+        match self
+            .bookmarks
+            .iter_mut()
+            .find(|bookmark| bookmark.href().as_deref() == Some(href.as_str()))
+        {
+            Some(bookmark) => bookmark.visit(app_name, &now),
+            None => self
+                .bookmarks
+                .push(Bookmark::new(&href, mime_type, app_name, &now)),
+        }
+
+        if self.bookmarks.len() > MAX_BOOKMARKS {
+            self.bookmarks
+                .sort_by_cached_key(|bookmark| std::cmp::Reverse(bookmark.modified()));
+            self.bookmarks.truncate(MAX_BOOKMARKS);
+        }
+    }
+
+    fn documents(&self, app_name: &str) -> Vec<PathBuf> {
+        let mut documents = self
+            .bookmarks
+            .iter()
+            .filter_map(|bookmark| {
+                let application = bookmark.application(app_name)?;
+                let modified = attribute(application, "modified")
+                    .and_then(|modified| DateTime::parse_from_rfc3339(&modified).ok());
+                let path = url::Url::parse(&bookmark.href()?)
+                    .ok()?
+                    .to_file_path()
+                    .ok()?;
+                Some((modified, path))
+            })
+            .collect::<Vec<_>>();
+        documents.sort_by(|(a, _), (b, _)| b.cmp(a));
+        documents.into_iter().map(|(_, path)| path).collect()
+    }
+
+    fn clear(&mut self, app_name: &str) {
+        self.bookmarks.retain_mut(|bookmark| {
+            let Some(range) = bookmark.application_range(app_name) else {
+                return true;
+            };
+            // Remove the line the element is on.
+            let mut start = bookmark.text[..range.start].trim_end_matches(' ').len();
+            if bookmark.text[..start].ends_with('\n') {
+                start -= 1;
+            }
+            bookmark.text.replace_range(start..range.end, "");
+            find_start_tag(&bookmark.text, "bookmark:application").is_some()
+        });
+    }
+}
+
+impl std::fmt::Display for Xbel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.header)?;
+        for bookmark in &self.bookmarks {
+            writeln!(f, "  {}", bookmark.text)?;
+        }
+        writeln!(f, "{XBEL_FOOTER}")
+    }
+}
+
+impl Bookmark {
+    fn new(href: &str, mime_type: &str, app_name: &str, now: &str) -> Self {
+        let href = escape(href);
+        let mime_type = escape(mime_type);
+        Self {
+            text: format!(
+                "<bookmark href=\"{href}\" added=\"{now}\" modified=\"{now}\" visited=\"{now}\">
+    <info>
+      <metadata owner=\"http://freedesktop.org\">
+        <mime:mime-type type=\"{mime_type}\"/>
+        <bookmark:applications>
+          {}
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>",
+                application_element(app_name, now, 1)
+            ),
+        }
+    }
+
+    fn start_tag(&self) -> &str {
+        self.text
+            .find('>')
+            .map_or(self.text.as_str(), |end| &self.text[..=end])
+    }
+
+    fn href(&self) -> Option<String> {
+        attribute(self.start_tag(), "href")
+    }
+
+    fn modified(&self) -> Option<DateTime<chrono::FixedOffset>> {
+        let start_tag = self.start_tag();
+        ["modified", "visited", "added"]
+            .into_iter()
+            .filter_map(|name| attribute(start_tag, name))
+            .find_map(|time| DateTime::parse_from_rfc3339(&time).ok())
+    }
+
+    /// The `<bookmark:application>` element of the application.
+    fn application(&self, app_name: &str) -> Option<&str> {
+        self.application_range(app_name)
+            .map(|range| &self.text[range])
+    }
+
+    fn application_range(&self, app_name: &str) -> Option<Range<usize>> {
+        let mut offset = 0;
+        while let Some(start) = find_start_tag(&self.text[offset..], "bookmark:application") {
+            let start = offset + start;
+            let end = start + element_end(&self.text[start..], "bookmark:application")?;
+            if attribute(&self.text[start..end], "name").as_deref() == Some(app_name) {
+                return Some(start..end);
+            }
+            offset = end;
+        }
+        None
+    }
+
+    /// Record that the application used the document again.
+    fn visit(&mut self, app_name: &str, now: &str) {
+        match self.application_range(app_name) {
+            Some(range) => {
+                let count = attribute(&self.text[range.clone()], "count")
+                    .and_then(|count| count.parse::<u32>().ok())
+                    .unwrap_or(0);
+                self.text
+                    .replace_range(range, &application_element(app_name, now, count + 1));
+            }
+            None => {
+                let Some(end) = self.text.find("</bookmark:applications>") else {
+                    // Without a list of applications, the bookmark can't describe this one.
+                    let mime_type = self
+                        .text
+                        .find("<mime:mime-type")
+                        .and_then(|start| attribute(&self.text[start..], "type"))
+                        .unwrap_or_else(|| "application/octet-stream".to_string());
+                    if let Some(href) = self.href() {
+                        *self = Self::new(&href, &mime_type, app_name, now);
+                    }
+                    return;
+                };
+                let indent = self.text[..end].len() - self.text[..end].trim_end_matches(' ').len();
+                self.text.insert_str(
+                    end,
+                    &format!(
+                        "  {}\n{}",
+                        application_element(app_name, now, 1),
+                        " ".repeat(indent)
+                    ),
+                );
+            }
+        }
+
+        let mut start_tag = self.start_tag().to_string();
+        let start_tag_len = start_tag.len();
+        for name in ["modified", "visited"] {
+            start_tag = set_attribute(&start_tag, name, now);
+        }
+        self.text.replace_range(..start_tag_len, &start_tag);
+    }
+}
+
+fn application_element(app_name: &str, now: &str, count: u32) -> String {
+    let name = escape(app_name);
+    let exec = escape(&format!("'{app_name} %u'"));
+    format!(
+        "<bookmark:application name=\"{name}\" exec=\"{exec}\" modified=\"{now}\" count=\"{count}\"/>"
+    )
+}
+
+/// The offset of the first start tag of an element with the given name.
+fn find_start_tag(text: &str, name: &str) -> Option<usize> {
+    let pattern = format!("<{name}");
+    let mut offset = 0;
+    while let Some(start) = text[offset..].find(&pattern) {
+        let start = offset + start;
+        let after = text[start + pattern.len()..].chars().next();
+        if after.is_some_and(|char| char.is_whitespace() || char == '>' || char == '/') {
+            return Some(start);
+        }
+        offset = start + pattern.len();
+    }
+    None
+}
+
+/// The length of the element starting the text, up to the end of its end tag.
+fn element_end(element: &str, name: &str) -> Option<usize> {
+    let start_tag_end = element.find('>')?;
+    if element[..start_tag_end].ends_with('/') {
+        return Some(start_tag_end + 1);
+    }
+    let end_tag = format!("</{name}>");
+    let end = element.find(&end_tag)?;
+    Some(end + end_tag.len())
+}
+
+/// The unescaped value of an attribute in the start tag at the beginning of the text.
+fn attribute(text: &str, name: &str) -> Option<String> {
+    let start_tag = &text[..text.find('>').map_or(text.len(), |end| end + 1)];
+    let (value_start, quote) = attribute_value_start(start_tag, name)?;
+    let len = start_tag[value_start..].find(quote)?;
+    Some(unescape(&start_tag[value_start..value_start + len]))
+}
+
+fn attribute_value_start(start_tag: &str, name: &str) -> Option<(usize, char)> {
+    let mut offset = 0;
+    while let Some(start) = start_tag[offset..].find(name) {
+        let start = offset + start;
+        offset = start + name.len();
+        let preceded_by_space = start_tag[..start]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_whitespace);
+        let rest = start_tag[offset..].trim_start();
+        if !preceded_by_space || !rest.starts_with('=') {
+            continue;
+        }
+        let value = rest[1..].trim_start();
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        return Some((start_tag.len() - value.len() + 1, quote));
+    }
+    None
+}
+
+/// Set the value of an attribute in a start tag, adding it when it's missing.
+fn set_attribute(start_tag: &str, name: &str, value: &str) -> String {
+    let value = escape(value);
+    if let Some((value_start, quote)) = attribute_value_start(start_tag, name)
+        && let Some(len) = start_tag[value_start..].find(quote)
+    {
+        return format!(
+            "{}{value}{}",
+            &start_tag[..value_start],
+            &start_tag[value_start + len..]
+        );
+    }
+    let end = start_tag.trim_end_matches('>').trim_end_matches('/').len();
+    format!(
+        "{} {name}=\"{value}\"{}",
+        &start_tag[..end].trim_end(),
+        &start_tag[end..]
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// The MIME type of a file, matching its name against the patterns of the shared MIME info
+/// database, then telling text apart from other contents.
+fn mime_type(path: &Path) -> String {
+    if path.is_dir() {
+        return "inode/directory".to_string();
+    }
+    if let Some(file_name) = path.file_name() {
+        let file_name = file_name.to_string_lossy();
+        // Directories earlier in the search path take precedence over later ones.
+        let globs = std::iter::once(xdg_data_home())
+            .chain(xdg_data_dirs())
+            .filter_map(|dir| fs::read_to_string(dir.join("mime/globs2")).ok());
+        for globs in globs {
+            if let Some(mime_type) = mime_type_from_globs(&globs, &file_name) {
+                return mime_type.to_string();
+            }
+        }
+    }
+
+    let mut contents = Vec::new();
+    let is_text = File::open(path)
+        .and_then(|file| file.take(1024).read_to_end(&mut contents))
+        .is_ok_and(|len| {
+            len > 0 && !contents.contains(&0) && std::str::from_utf8(&contents).is_ok()
+        });
+    if is_text {
+        "text/plain".to_string()
+    } else {
+        "application/octet-stream".to_string()
+    }
+}
+
+/// Match a file name against the patterns in a `globs2` file, where each line has the weight, MIME
+/// type and pattern of a glob, optionally followed by its flags. The heaviest matching glob wins,
+/// then the longest.
+fn mime_type_from_globs<'a>(globs: &'a str, file_name: &str) -> Option<&'a str> {
+    let mut best: Option<(u32, usize, &str)> = None;
+    for line in globs.lines() {
+        if line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split(':');
+        let (Some(weight), Some(mime_type), Some(pattern)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let Ok(weight) = weight.parse::<u32>() else {
+            continue;
+        };
+        let case_sensitive = fields
+            .next()
+            .is_some_and(|flags| flags.split(',').any(|flag| flag == "cs"));
+        let matches = if case_sensitive {
+            glob_matches(pattern, file_name)
+        } else {
+            glob_matches(&pattern.to_lowercase(), &file_name.to_lowercase())
+        };
+        if matches
+            && best.is_none_or(|(best_weight, best_len, _)| {
+                (weight, pattern.len()) > (best_weight, best_len)
+            })
+        {
+            best = Some((weight, pattern.len(), mime_type));
+        }
+    }
+    best.map(|(_, _, mime_type)| mime_type)
+}
+
+/// Whether the text matches a glob with `*` and `?` wildcards. Globs with character classes are
+/// rare in the database, and never match.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    if pattern.contains('[') {
+        return false;
+    }
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, star_t)) => {
+                    p = star + 1;
+                    t = star_t + 1;
+                    backtrack = Some((star, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XBEL: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/user/notes.txt" added="2024-01-01T10:00:00.000000Z" modified="2024-01-01T10:00:00.000000Z" visited="2024-01-01T10:00:00.000000Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2024-01-01T10:00:00.000000Z" count="1"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+
+    fn time(time: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(time).unwrap().to_utc()
+    }
+
+    #[test]
+    fn test_add_documents() {
+        let mut xbel = Xbel::parse(XBEL).unwrap();
+        assert_eq!(xbel.to_string(), XBEL);
+
+        xbel.add(
+            Path::new("/home/user/main.rs"),
+            "text/rust",
+            "app",
+            time("2024-02-01T10:00:00Z"),
+        );
+        xbel.add(
+            Path::new("/home/user/notes.txt"),
+            "text/plain",
+            "app",
+            time("2024-03-01T10:00:00Z"),
+        );
+        let mut xbel = Xbel::parse(&xbel.to_string()).unwrap();
+        assert_eq!(xbel.bookmarks.len(), 2);
+        let notes = &xbel.bookmarks[0];
+        assert_eq!(
+            notes.start_tag(),
+            "<bookmark href=\"file:///home/user/notes.txt\" \
+             added=\"2024-01-01T10:00:00.000000Z\" modified=\"2024-03-01T10:00:00.000000Z\" \
+             visited=\"2024-03-01T10:00:00.000000Z\">"
+        );
+        assert!(notes.application("gedit").is_some());
+        assert_eq!(
+            attribute(notes.application("app").unwrap(), "count").as_deref(),
+            Some("1")
+        );
+        assert!(
+            xbel.bookmarks[1]
+                .text
+                .contains("<mime:mime-type type=\"text/rust\"/>")
+        );
+        assert_eq!(
+            xbel.documents("app"),
+            [
+                PathBuf::from("/home/user/notes.txt"),
+                PathBuf::from("/home/user/main.rs")
+            ]
+        );
+        assert_eq!(
+            xbel.documents("gedit"),
+            [PathBuf::from("/home/user/notes.txt")]
+        );
+
+        xbel.add(
+            Path::new("/home/user/main.rs"),
+            "text/rust",
+            "app",
+            time("2024-04-01T10:00:00Z"),
+        );
+        assert_eq!(
+            attribute(xbel.bookmarks[1].application("app").unwrap(), "count").as_deref(),
+            Some("2")
+        );
+        assert_eq!(
+            xbel.documents("app"),
+            [
+                PathBuf::from("/home/user/main.rs"),
+                PathBuf::from("/home/user/notes.txt")
+            ]
+        );
+
+        xbel.clear("app");
+        let xbel = Xbel::parse(&xbel.to_string()).unwrap();
+        assert!(xbel.documents("app").is_empty());
+        assert_eq!(xbel.bookmarks.len(), 1);
+        assert_eq!(
+            xbel.bookmarks[0].text,
+            Xbel::parse(XBEL).unwrap().bookmarks[0].text.replace(
+                "modified=\"2024-01-01T10:00:00.000000Z\" visited=\"2024-01-01T10:00:00.000000Z\"",
+                "modified=\"2024-03-01T10:00:00.000000Z\" visited=\"2024-03-01T10:00:00.000000Z\""
+            )
+        );
+    }
+
+    #[test]
+    fn test_unparseable_list() {
+        let cut_short = &XBEL[..XBEL.find("</bookmark>").unwrap()];
+        assert!(Xbel::parse(cut_short).is_err());
+        assert!(Xbel::parse(&XBEL.replace("</bookmark>", "")).is_err());
+
+        // The list is left as it is, rather than replaced with an empty one.
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("recently-used.xbel");
+        fs::write(&path, cut_short).unwrap();
+        assert!(update_xbel(&path, |xbel| xbel.clear("app")).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), cut_short);
+        assert!(read_xbel(&path).is_err());
+    }
+
+    #[test]
+    fn test_bookmark_limit() {
+        let mut xbel = Xbel::parse("").unwrap();
+        for ix in 0..MAX_BOOKMARKS + 1 {
+            let time = time("2024-01-01T00:00:00Z") + chrono::Duration::minutes(ix as i64);
+            xbel.add(
+                &PathBuf::from(format!("/{ix}.txt")),
+                "text/plain",
+                "app",
+                time,
+            );
+        }
+        let documents = xbel.documents("app");
+        assert_eq!(documents.len(), MAX_BOOKMARKS);
+        assert_eq!(
+            documents.first(),
+            Some(&PathBuf::from(format!("/{MAX_BOOKMARKS}.txt")))
+        );
+        assert_eq!(documents.last(), Some(&PathBuf::from("/1.txt")));
+    }
+
+    #[test]
+    fn test_mime_type_from_globs() {
+        let globs = "# comment\n\
+                     50:text/x-c++src:*.cc\n\
+                     50:text/x-csrc:*.c:cs\n\
+                     50:text/x-c++src:*.C:cs\n\
+                     50:application/x-compressed-tar:*.tar.gz\n\
+                     50:application/gzip:*.gz\n\
+                     60:text/x-makefile:makefile\n";
+        assert_eq!(mime_type_from_globs(globs, "a.cc"), Some("text/x-c++src"));
+        assert_eq!(mime_type_from_globs(globs, "a.c"), Some("text/x-csrc"));
+        assert_eq!(mime_type_from_globs(globs, "a.C"), Some("text/x-c++src"));
+        assert_eq!(
+            mime_type_from_globs(globs, "a.tar.gz"),
+            Some("application/x-compressed-tar")
+        );
+        assert_eq!(
+            mime_type_from_globs(globs, "a.GZ"),
+            Some("application/gzip")
+        );
+        assert_eq!(
+            mime_type_from_globs(globs, "Makefile"),
+            Some("text/x-makefile")
+        );
+        assert_eq!(mime_type_from_globs(globs, "a.rs"), None);
+    }
+}
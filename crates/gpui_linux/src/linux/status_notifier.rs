@@ -0,0 +1,323 @@
+//! Shows the status item in the system tray with the `org.kde.StatusNotifierItem` DBus
+//! interface, exporting its menu with `com.canonical.dbusmenu` on the same connection.
+
+use std::{cell::RefMut, path::Path};
+
+use calloop::channel::{Channel, Sender};
+use futures::{StreamExt as _, channel::mpsc, stream};
+use gpui::{BackgroundExecutor, Keymap, MenuItem, OwnedMenuItem, StatusItem, StatusItemClick};
+use util::ResultExt as _;
+use zbus::{
+    object_server::SignalEmitter,
+    zvariant::{ObjectPath, OwnedObjectPath},
+};
+
+use crate::linux::{DbusMenu, DbusMenuInterface, LinuxCommon, MenuEvent, MenuUpdate, app_name};
+
+const ITEM_PATH: &str = "/StatusNotifierItem";
+const MENU_PATH: &str = "/StatusNotifierItem/Menu";
+
+const WATCHER_NAME: &str = "org.kde.StatusNotifierWatcher";
+const WATCHER_PATH: &str = "/StatusNotifierWatcher";
+
+/// The icon and tooltip of the status item, as served over DBus.
+struct ItemProperties {
+    icon_name: String,
+    icon_theme_path: String,
+    tooltip: String,
+}
+
+impl ItemProperties {
+    fn new(item: &StatusItem) -> Self {
+        // Images are shown by their name, from the directory they're in.
+        let path = Path::new(item.icon.as_str());
+        let (icon_name, icon_theme_path) = match (path.is_absolute(), path.file_stem()) {
+            (true, Some(name)) => (
+                name.to_string_lossy().into_owned(),
+                path.parent()
+                    .map_or(String::new(), |dir| dir.to_string_lossy().into_owned()),
+            ),
+            _ => (item.icon.to_string(), String::new()),
+        };
+        Self {
+            icon_name,
+            icon_theme_path,
+            tooltip: item
+                .tooltip
+                .as_ref()
+                .map_or(String::new(), |tooltip| tooltip.to_string()),
+        }
+    }
+}
+
+/// Sent from the main thread to the DBus connection.
+enum ItemUpdate {
+    Show(ItemProperties),
+    Remove,
+}
+
+enum Message {
+    Item(ItemUpdate),
+    Menu(MenuUpdate),
+}
+
+/// The status item, shown while it's set.
+pub(crate) struct StatusNotifier {
+    menu: DbusMenu,
+    updates: mpsc::UnboundedSender<ItemUpdate>,
+}
+
+impl StatusNotifier {
+    /// Create the status item, returning the channels on which its clicks and the events of its
+    /// menu are delivered. Nothing connects to the session bus until it's first set.
+    pub fn new(
+        executor: &BackgroundExecutor,
+    ) -> (Self, Channel<StatusItemClick>, Channel<MenuEvent>) {
+        let (clicks, click_channel) = calloop::channel::channel();
+        let (menu_events, menu_channel) = calloop::channel::channel();
+        let (menu, menu_interface, menu_updates) = DbusMenu::with_interface(menu_events);
+        let (updates, update_rx) = mpsc::unbounded();
+
+        executor
+            .spawn(async move {
+                let mut messages = stream::select(
+                    update_rx.map(Message::Item),
+                    menu_updates.map(Message::Menu),
+                );
+                let mut connection = None;
+                let mut connection_count = 0;
+                while let Some(message) = messages.next().await {
+                    match message {
+                        Message::Item(ItemUpdate::Show(properties)) => {
+                            if let Some(connection) = &connection {
+                                update_item(connection, properties).await.log_err();
+                            } else {
+                                connection_count += 1;
+                                connection = show_item(
+                                    connection_count,
+                                    properties,
+                                    clicks.clone(),
+                                    menu_interface.clone(),
+                                )
+                                .await
+                                .log_err();
+                            }
+                        }
+                        // Closing the connection removes the item from the tray.
+                        Message::Item(ItemUpdate::Remove) => connection = None,
+                        Message::Menu(MenuUpdate::LayoutUpdated(revision)) => {
+                            if let Some(connection) = &connection {
+                                let emitter = SignalEmitter::new(connection, MENU_PATH)?;
+                                DbusMenuInterface::layout_updated(&emitter, revision, 0)
+                                    .await
+                                    .log_err();
+                            }
+                        }
+                        Message::Menu(MenuUpdate::RegisterWindow(_)) => {}
+                    }
+                }
+                anyhow::Ok(())
+            })
+            .detach();
+
+        (Self { menu, updates }, click_channel, menu_channel)
+    }
+
+    pub fn menu_mut(&mut self) -> &mut DbusMenu {
+        &mut self.menu
+    }
+
+    /// Show the given status item, or remove it when `None`.
+    pub fn set(&mut self, item: Option<StatusItem>, keymap: &Keymap) {
+        let update = match item {
+            Some(item) => {
+                let properties = ItemProperties::new(&item);
+                let items = item
+                    .menu
+                    .into_iter()
+                    .map(MenuItem::owned)
+                    .collect::<Vec<OwnedMenuItem>>();
+                self.menu.set_items(&items, keymap);
+                ItemUpdate::Show(properties)
+            }
+            None => ItemUpdate::Remove,
+        };
+        self.updates.unbounded_send(update).log_err();
+    }
+}
+
+async fn show_item(
+    connection_count: u32,
+    properties: ItemProperties,
+    clicks: Sender<StatusItemClick>,
+    menu_interface: DbusMenuInterface,
+) -> anyhow::Result<zbus::Connection> {
+    let service_name = format!(
+        "org.kde.StatusNotifierItem-{}-{connection_count}",
+        std::process::id()
+    );
+    let id = app_name();
+    let item = StatusNotifierItemInterface {
+        id,
+        properties,
+        clicks,
+    };
+    let connection = zbus::connection::Builder::session()?
+        .name(service_name.as_str())?
+        .serve_at(ITEM_PATH, item)?
+        .serve_at(MENU_PATH, menu_interface)?
+        .build()
+        .await?;
+    connection
+        .call_method(
+            Some(WATCHER_NAME),
+            WATCHER_PATH,
+            Some(WATCHER_NAME),
+            "RegisterStatusNotifierItem",
+            &service_name,
+        )
+        .await?;
+    Ok(connection)
+}
+
+async fn update_item(
+    connection: &zbus::Connection,
+    properties: ItemProperties,
+) -> zbus::Result<()> {
+    let item = connection
+        .object_server()
+        .interface::<_, StatusNotifierItemInterface>(ITEM_PATH)
+        .await?;
+    item.get_mut().await.properties = properties;
+    let emitter = item.signal_emitter();
+    StatusNotifierItemInterface::new_icon(emitter).await?;
+    StatusNotifierItemInterface::new_tool_tip(emitter).await?;
+    StatusNotifierItemInterface::new_title(emitter).await
+}
+
+/// Handle a click on the status item on the main thread, borrowing the platform's common state
+/// with `common` whenever it's needed.
+pub(crate) fn handle_status_item_click<'a>(
+    click: StatusItemClick,
+    common: impl Fn() -> RefMut<'a, LinuxCommon>,
+) {
+    let callback = common().callbacks.status_item_click.take();
+    if let Some(mut callback) = callback {
+        callback(click);
+        common().callbacks.status_item_click.get_or_insert(callback);
+    }
+}
+
+/// The `org.kde.StatusNotifierItem` interface, as served by the DBus connection.
+struct StatusNotifierItemInterface {
+    id: String,
+    properties: ItemProperties,
+    clicks: Sender<StatusItemClick>,
+}
+
+#[zbus::interface(name = "org.kde.StatusNotifierItem")]
+impl StatusNotifierItemInterface {
+    fn activate(&self, _x: i32, _y: i32) {
+        self.clicks.send(StatusItemClick::Primary).log_err();
+    }
+
+    fn secondary_activate(&self, _x: i32, _y: i32) {
+        self.clicks.send(StatusItemClick::Secondary).log_err();
+    }
+
+    /// Hosts show the exported menu themselves.
+    fn context_menu(&self, _x: i32, _y: i32) {}
+
+    fn scroll(&self, _delta: i32, _orientation: String) {}
+
+    #[zbus(property)]
+    fn category(&self) -> &str {
+        "ApplicationStatus"
+    }
+
+    #[zbus(property)]
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    #[zbus(property)]
+    fn title(&self) -> &str {
+        if self.properties.tooltip.is_empty() {
+            &self.id
+        } else {
+            &self.properties.tooltip
+        }
+    }
+
+    #[zbus(property)]
+    fn status(&self) -> &str {
+        "Active"
+    }
+
+    #[zbus(property)]
+    fn window_id(&self) -> i32 {
+        0
+    }
+
+    #[zbus(property)]
+    fn icon_name(&self) -> &str {
+        &self.properties.icon_name
+    }
+
+    #[zbus(property)]
+    fn icon_theme_path(&self) -> &str {
+        &self.properties.icon_theme_path
+    }
+
+    #[zbus(property)]
+    fn icon_pixmap(&self) -> Vec<(i32, i32, Vec<u8>)> {
+        Vec::new()
+    }
+
+    #[zbus(property)]
+    fn tool_tip(&self) -> (String, Vec<(i32, i32, Vec<u8>)>, String, String) {
+        (
+            String::new(),
+            Vec::new(),
+            self.properties.tooltip.clone(),
+            String::new(),
+        )
+    }
+
+    #[zbus(property)]
+    fn item_is_menu(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn menu(&self) -> OwnedObjectPath {
+        ObjectPath::from_static_str_unchecked(MENU_PATH).into()
+    }
+
+    #[zbus(signal)]
+    async fn new_icon(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn new_tool_tip(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn new_title(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_item_properties() {
+        let properties = ItemProperties::new(&StatusItem::new("mail-unread").tooltip("3 unread"));
+        assert_eq!(properties.icon_name, "mail-unread");
+        assert_eq!(properties.icon_theme_path, "");
+        assert_eq!(properties.tooltip, "3 unread");
+
+        let properties = ItemProperties::new(&StatusItem::new("/opt/app/icons/tray.png"));
+        assert_eq!(properties.icon_name, "tray");
+        assert_eq!(properties.icon_theme_path, "/opt/app/icons");
+        assert_eq!(properties.tooltip, "");
+    }
+}
@@ -0,0 +1,420 @@
+//! Tracks the thermal state of the system from the temperatures of its thermal zones, the battery
+//! state reported by UPower and the profile selected in power-profiles-daemon.
+//!
+//! Thermal zones are polled, as sysfs doesn't notify of temperature changes, while the power state
+//! is watched over the system bus. Changes are delivered to the main thread by the X11 and Wayland
+//! clients, see [`handle_thermal_state`].
+
+use std::{
+    cell::RefMut,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use calloop::channel::Sender;
+use futures::{Stream, StreamExt as _, future, stream};
+use gpui::{BackgroundExecutor, ThermalState};
+use util::ResultExt as _;
+use zbus::zvariant::OwnedValue;
+
+use crate::linux::LinuxCommon;
+
+const THERMAL_ROOT: &str = "/sys/class/thermal";
+/// How often the temperatures of the thermal zones are read.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+const UPOWER_NAME: &str = "org.freedesktop.UPower";
+const UPOWER_PATH: &str = "/org/freedesktop/UPower";
+/// The device combining all of the system's batteries.
+const DISPLAY_DEVICE_PATH: &str = "/org/freedesktop/UPower/devices/DisplayDevice";
+const DEVICE_INTERFACE: &str = "org.freedesktop.UPower.Device";
+const POWER_PROFILES_NAME: &str = "net.hadess.PowerProfiles";
+const POWER_PROFILES_PATH: &str = "/net/hadess/PowerProfiles";
+
+/// The `WarningLevel` of a UPower device.
+const WARNING_LEVEL_LOW: u32 = 3;
+const WARNING_LEVEL_CRITICAL: u32 = 4;
+const WARNING_LEVEL_ACTION: u32 = 5;
+
+/// Sent from the watched sources to the thermal state.
+enum PowerUpdate {
+    Poll,
+    OnBattery(bool),
+    WarningLevel(u32),
+    ActiveProfile(String),
+}
+
+#[derive(Default)]
+struct PowerState {
+    on_battery: bool,
+    warning_level: u32,
+    power_saver: bool,
+}
+
+impl PowerState {
+    fn thermal_state(&self) -> ThermalState {
+        match self.warning_level {
+            WARNING_LEVEL_CRITICAL | WARNING_LEVEL_ACTION => ThermalState::Critical,
+            WARNING_LEVEL_LOW => ThermalState::Serious,
+            _ if self.on_battery || self.power_saver => ThermalState::Fair,
+            _ => ThermalState::Nominal,
+        }
+    }
+}
+
+/// Start watching the thermal state of the system, sending it whenever it changes, starting with
+/// the current state.
+pub(crate) fn watch_thermal_state(executor: &BackgroundExecutor, events: Sender<ThermalState>) {
+    executor
+        .spawn(async move {
+            // Containers often have no system bus, leaving only the thermal zones to watch.
+            let connection = zbus::Connection::system().await.ok();
+            let ticks = smol::Timer::interval(POLL_INTERVAL).map(|_| ());
+            watch(Path::new(THERMAL_ROOT), connection.as_ref(), ticks, events).await
+        })
+        .detach();
+}
+
+/// Handle a change of the thermal state on the main thread, borrowing the platform's common state
+/// with `common` whenever it's needed.
+pub(crate) fn handle_thermal_state<'a>(
+    state: ThermalState,
+    common: impl Fn() -> RefMut<'a, LinuxCommon>,
+) {
+    {
+        let mut common = common();
+        if common.thermal_state == state {
+            return;
+        }
+        common.thermal_state = state;
+    }
+    let callback = common().callbacks.thermal_state_change.take();
+    if let Some(mut callback) = callback {
+        callback();
+        common()
+            .callbacks
+            .thermal_state_change
+            .get_or_insert(callback);
+    }
+}
+
+async fn watch(
+    thermal_root: &Path,
+    connection: Option<&zbus::Connection>,
+    ticks: impl Stream<Item = ()> + Send + 'static,
+    events: Sender<ThermalState>,
+) -> anyhow::Result<()> {
+    let mut sources = vec![ticks.map(|_| PowerUpdate::Poll).boxed()];
+    if let Some(connection) = connection {
+        let properties = [
+            property_updates(
+                connection,
+                (UPOWER_NAME, UPOWER_PATH, UPOWER_NAME),
+                "OnBattery",
+                PowerUpdate::OnBattery,
+            )
+            .await,
+            property_updates(
+                connection,
+                (UPOWER_NAME, DISPLAY_DEVICE_PATH, DEVICE_INTERFACE),
+                "WarningLevel",
+                PowerUpdate::WarningLevel,
+            )
+            .await,
+            property_updates(
+                connection,
+                (
+                    POWER_PROFILES_NAME,
+                    POWER_PROFILES_PATH,
+                    POWER_PROFILES_NAME,
+                ),
+                "ActiveProfile",
+                PowerUpdate::ActiveProfile,
+            )
+            .await,
+        ];
+        // The services are missing on plenty of systems, which then don't constrain the state.
+        sources.extend(properties.into_iter().flatten());
+    }
+
+    let mut updates = stream::select_all(sources);
+    let mut power = PowerState::default();
+    let mut last_state = None;
+    loop {
+        let state = sysfs_thermal_state(thermal_root).max(power.thermal_state());
+        if last_state != Some(state) {
+            last_state = Some(state);
+            events.send(state)?;
+        }
+        match updates.next().await {
+            Some(PowerUpdate::Poll) => {}
+            Some(PowerUpdate::OnBattery(on_battery)) => power.on_battery = on_battery,
+            Some(PowerUpdate::WarningLevel(level)) => power.warning_level = level,
+            Some(PowerUpdate::ActiveProfile(profile)) => {
+                power.power_saver = profile == "power-saver"
+            }
+            None => return Ok(()),
+        }
+    }
+}
+
+/// The current value of a property followed by each of its changes.
+async fn property_updates<T>(
+    connection: &zbus::Connection,
+    (destination, path, interface): (&'static str, &'static str, &'static str),
+    property: &'static str,
+    update: fn(T) -> PowerUpdate,
+) -> Option<stream::BoxStream<'static, PowerUpdate>>
+where
+    T: TryFrom<OwnedValue> + Send + Sync + Unpin + 'static,
+    T::Error: Into<zbus::Error>,
+{
+    let proxy = zbus::Proxy::new(connection, destination, path, interface)
+        .await
+        .log_err()?;
+    let changes = proxy.receive_property_changed::<T>(property).await;
+    let initial = proxy.get_property::<T>(property).await.ok()?;
+    let changes = changes.filter_map(|changed| async move { changed.get().await.ok() });
+    Some(
+        stream::once(future::ready(initial))
+            .chain(changes)
+            .map(update)
+            .boxed(),
+    )
+}
+
+/// The most severe state of the thermal zones under the given root, from the trip points their
+/// temperature has reached.
+fn sysfs_thermal_state(root: &Path) -> ThermalState {
+    let Ok(entries) = fs::read_dir(root) else {
+        return ThermalState::Nominal;
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with("thermal_zone")
+        })
+        .map(|entry| zone_thermal_state(&entry.path()))
+        .max()
+        .unwrap_or(ThermalState::Nominal)
+}
+
+fn zone_thermal_state(zone: &Path) -> ThermalState {
+    let read_millidegrees = |path: PathBuf| {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|text| text.trim().parse::<i64>().ok())
+    };
+    let Some(temperature) = read_millidegrees(zone.join("temp")) else {
+        return ThermalState::Nominal;
+    };
+    let mut state = ThermalState::Nominal;
+    for ix in 0.. {
+        let Some(trip_temperature) = read_millidegrees(zone.join(format!("trip_point_{ix}_temp")))
+        else {
+            break;
+        };
+        // Disabled trip points have no temperature.
+        if trip_temperature <= 0 || temperature < trip_temperature {
+            continue;
+        }
+        let trip_type =
+            fs::read_to_string(zone.join(format!("trip_point_{ix}_type"))).unwrap_or_default();
+        let trip_state = match trip_type.trim() {
+            "critical" | "hot" => ThermalState::Critical,
+            "passive" => ThermalState::Serious,
+            "active" => ThermalState::Fair,
+            _ => ThermalState::Nominal,
+        };
+        state = state.max(trip_state);
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calloop::EventLoop;
+    use futures::channel::mpsc;
+    use std::os::unix::net::UnixStream;
+    use zbus::{Guid, connection::Builder};
+
+    struct FakeUPower {
+        on_battery: bool,
+    }
+
+    #[zbus::interface(name = "org.freedesktop.UPower")]
+    impl FakeUPower {
+        #[zbus(property)]
+        fn on_battery(&self) -> bool {
+            self.on_battery
+        }
+    }
+
+    struct FakeDisplayDevice {
+        warning_level: u32,
+    }
+
+    #[zbus::interface(name = "org.freedesktop.UPower.Device")]
+    impl FakeDisplayDevice {
+        #[zbus(property)]
+        fn warning_level(&self) -> u32 {
+            self.warning_level
+        }
+    }
+
+    struct FakePowerProfiles {
+        active_profile: String,
+    }
+
+    #[zbus::interface(name = "net.hadess.PowerProfiles")]
+    impl FakePowerProfiles {
+        #[zbus(property)]
+        fn active_profile(&self) -> String {
+            self.active_profile.clone()
+        }
+    }
+
+    fn add_zone(root: &Path, name: &str, temperature: i64, trip_points: &[(&str, i64)]) {
+        let zone = root.join(name);
+        fs::create_dir_all(&zone).unwrap();
+        fs::write(zone.join("temp"), format!("{temperature}\n")).unwrap();
+        for (ix, (trip_type, trip_temperature)) in trip_points.iter().enumerate() {
+            fs::write(zone.join(format!("trip_point_{ix}_type")), trip_type).unwrap();
+            fs::write(
+                zone.join(format!("trip_point_{ix}_temp")),
+                trip_temperature.to_string(),
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_sysfs_thermal_state() {
+        let root = tempfile::tempdir().unwrap();
+        assert_eq!(sysfs_thermal_state(root.path()), ThermalState::Nominal);
+
+        let trip_points = [
+            ("active", 50000),
+            ("passive", 80000),
+            ("hot", 0),
+            ("critical", 100000),
+        ];
+        add_zone(root.path(), "thermal_zone0", 45000, &trip_points);
+        fs::create_dir(root.path().join("cooling_device0")).unwrap();
+        assert_eq!(sysfs_thermal_state(root.path()), ThermalState::Nominal);
+
+        add_zone(root.path(), "thermal_zone1", 55000, &trip_points);
+        assert_eq!(sysfs_thermal_state(root.path()), ThermalState::Fair);
+        add_zone(root.path(), "thermal_zone1", 85000, &trip_points);
+        assert_eq!(sysfs_thermal_state(root.path()), ThermalState::Serious);
+        add_zone(root.path(), "thermal_zone0", 105000, &trip_points);
+        assert_eq!(sysfs_thermal_state(root.path()), ThermalState::Critical);
+    }
+
+    #[test]
+    fn test_watch_thermal_state() {
+        let root = tempfile::tempdir().unwrap();
+        add_zone(root.path(), "thermal_zone0", 40000, &[("passive", 80000)]);
+
+        let mut event_loop = EventLoop::<Vec<ThermalState>>::try_new().unwrap();
+        let (events, channel) = calloop::channel::channel();
+        event_loop
+            .handle()
+            .insert_source(channel, |event, _, received| {
+                if let calloop::channel::Event::Msg(state) = event {
+                    received.push(state);
+                }
+            })
+            .unwrap();
+        let mut next_states = || {
+            let mut received = Vec::new();
+            for _ in 0..100 {
+                event_loop
+                    .dispatch(Some(Duration::from_millis(10)), &mut received)
+                    .unwrap();
+                if !received.is_empty() {
+                    break;
+                }
+            }
+            received
+        };
+
+        smol::block_on(async {
+            let (server, client) = UnixStream::pair().unwrap();
+            let (server, client) = futures::try_join!(
+                Builder::async_io_unix_stream(server)
+                    .server(Guid::generate())
+                    .unwrap()
+                    .p2p()
+                    .serve_at(UPOWER_PATH, FakeUPower { on_battery: false })
+                    .unwrap()
+                    .serve_at(DISPLAY_DEVICE_PATH, FakeDisplayDevice { warning_level: 1 })
+                    .unwrap()
+                    .serve_at(
+                        POWER_PROFILES_PATH,
+                        FakePowerProfiles {
+                            active_profile: "balanced".into(),
+                        },
+                    )
+                    .unwrap()
+                    .build(),
+                Builder::async_io_unix_stream(client).p2p().build(),
+            )
+            .unwrap();
+
+            let (ticks, tick_rx) = mpsc::unbounded();
+            let thermal_root = root.path().to_path_buf();
+            let _watch =
+                smol::spawn(
+                    async move { watch(&thermal_root, Some(&client), tick_rx, events).await },
+                );
+            assert_eq!(next_states(), [ThermalState::Nominal]);
+
+            let object_server = server.object_server();
+            let profiles = object_server
+                .interface::<_, FakePowerProfiles>(POWER_PROFILES_PATH)
+                .await
+                .unwrap();
+            profiles.get_mut().await.active_profile = "power-saver".into();
+            profiles
+                .get()
+                .await
+                .active_profile_changed(profiles.signal_emitter())
+                .await
+                .unwrap();
+            assert_eq!(next_states(), [ThermalState::Fair]);
+
+            let device = object_server
+                .interface::<_, FakeDisplayDevice>(DISPLAY_DEVICE_PATH)
+                .await
+                .unwrap();
+            device.get_mut().await.warning_level = WARNING_LEVEL_CRITICAL;
+            device
+                .get()
+                .await
+                .warning_level_changed(device.signal_emitter())
+                .await
+                .unwrap();
+            assert_eq!(next_states(), [ThermalState::Critical]);
+
+            device.get_mut().await.warning_level = 1;
+            device
+                .get()
+                .await
+                .warning_level_changed(device.signal_emitter())
+                .await
+                .unwrap();
+            assert_eq!(next_states(), [ThermalState::Fair]);
+
+            // Temperatures are only read again on the next poll.
+            add_zone(root.path(), "thermal_zone0", 90000, &[("passive", 80000)]);
+            ticks.unbounded_send(()).unwrap();
+            assert_eq!(next_states(), [ThermalState::Serious]);
+        });
+    }
+}
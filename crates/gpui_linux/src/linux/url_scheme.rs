@@ -0,0 +1,407 @@
+//! Registers the application as the handler of URL schemes with a desktop entry, and receives the
+//! URLs it's opened with.
+//!
+//! Desktop environments open a URL by launching the application with it on the command line. Once
+//! an instance has registered a scheme, it listens on a Unix socket, and instances launched after
+//! it forward their URLs to it instead. The URLs are delivered to the main thread by the X11 and
+//! Wayland clients, see [`handle_open_urls`].
+
+use std::{
+    cell::RefMut,
+    env, fs,
+    io::{self, Write as _},
+    iter,
+    os::unix::{fs::FileTypeExt as _, net::UnixStream},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use calloop::channel::Sender;
+use futures::AsyncReadExt as _;
+use gpui::BackgroundExecutor;
+use util::{ResultExt as _, command::new_std_command};
+
+use crate::linux::{LinuxCommon, app_name, xdg_config_home, xdg_data_dirs, xdg_data_home};
+
+/// The group of a desktop entry holding its keys.
+const DESKTOP_ENTRY_GROUP: &str = "[Desktop Entry]";
+/// The group of `mimeapps.list` holding the default application of each MIME type.
+const DEFAULT_APPLICATIONS_GROUP: &str = "[Default Applications]";
+/// The most URL text accepted from one forwarding instance.
+const MAX_FORWARDED_LEN: u64 = 64 * 1024;
+/// How long a forwarding instance has to send its URLs.
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Make the application the default handler of the given URL scheme, adding the scheme's MIME type
+/// to its desktop entry in the user's data directory, and setting it as the default application in
+/// the user's `mimeapps.list`.
+pub(crate) fn register_url_scheme(scheme: &str) -> anyhow::Result<()> {
+    let app_name = app_name();
+    let desktop_id = format!("{app_name}.desktop");
+    let mime_type = format!("x-scheme-handler/{scheme}");
+
+    // Start from the entry the application is installed with, so it keeps its name and icon.
+    let applications_dir = xdg_data_home().join("applications");
+    let entry = match installed_desktop_entry(&desktop_id) {
+        Some(entry) => entry,
+        None => new_desktop_entry(&app_name, &env::current_exe()?),
+    };
+    fs::create_dir_all(&applications_dir)?;
+    fs::write(
+        applications_dir.join(&desktop_id),
+        add_mime_type(&entry, &mime_type),
+    )?;
+    // Not every system has the MIME type cache, the default application is set either way.
+    new_std_command("update-desktop-database")
+        .arg(&applications_dir)
+        .output()
+        .ok();
+
+    let config_home = xdg_config_home();
+    let mimeapps_path = config_home.join("mimeapps.list");
+    let mimeapps = match fs::read_to_string(&mimeapps_path) {
+        Ok(mimeapps) => mimeapps,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(error) => return Err(error.into()),
+    };
+    fs::create_dir_all(&config_home)?;
+    fs::write(
+        mimeapps_path,
+        set_default_application(&mimeapps, &mime_type, &desktop_id),
+    )?;
+    Ok(())
+}
+
+/// The URL schemes the application is the handler of, from the desktop entry it's installed or
+/// registered with.
+pub(crate) fn registered_url_schemes() -> Vec<String> {
+    installed_desktop_entry(&format!("{}.desktop", app_name()))
+        .map(|entry| url_schemes(&entry))
+        .unwrap_or_default()
+}
+
+/// The URLs in the given command line arguments with one of the given schemes. Anything else, like
+/// file URLs and paths, is left for the application to open itself.
+pub(crate) fn urls_from_args(
+    args: impl IntoIterator<Item = String>,
+    schemes: &[String],
+) -> Vec<String> {
+    args.into_iter()
+        .filter(|arg| {
+            url::Url::parse(arg)
+                .is_ok_and(|url| schemes.iter().any(|scheme| scheme == url.scheme()))
+        })
+        .collect()
+}
+
+/// Send the given URLs to the instance of the application that's already running, returning
+/// whether there was one to receive them.
+pub(crate) fn forward_to_running_instance(urls: &[String]) -> bool {
+    let Some(path) = socket_path() else {
+        return false;
+    };
+    let Ok(mut stream) = UnixStream::connect(path) else {
+        return false;
+    };
+    stream
+        .write_all(urls.join("\n").as_bytes())
+        .log_err()
+        .is_some()
+}
+
+/// Receive the URLs forwarded by instances of the application launched after this one, sending
+/// them to the main thread. Nothing is received while another instance, or this one for another
+/// scheme, is already listening.
+pub(crate) fn listen_for_urls(executor: &BackgroundExecutor, urls: Sender<Vec<String>>) {
+    let Some(path) = socket_path() else {
+        return;
+    };
+    let executor = executor.clone();
+    executor
+        .clone()
+        .spawn(async move {
+            // The instance holding the lock, for as long as it listens, owns the socket. One
+            // that's there while nobody holds it was left behind by an instance that didn't exit
+            // cleanly.
+            let lock = fs::File::options()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(path.with_extension("lock"))?;
+            if lock.try_lock().is_err() {
+                return anyhow::Ok(());
+            }
+            match fs::symlink_metadata(&path) {
+                Ok(metadata) if metadata.file_type().is_socket() => fs::remove_file(&path)?,
+                Ok(_) => anyhow::bail!("{path:?} isn't a socket, not listening for URLs"),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                Err(error) => return Err(error.into()),
+            }
+            let listener = smol::net::unix::UnixListener::bind(&path)?;
+            loop {
+                let (stream, _) = listener.accept().await?;
+                let timeout = executor.timer(FORWARD_TIMEOUT);
+                let urls = urls.clone();
+                executor
+                    .spawn(async move {
+                        receive_urls(stream, timeout, urls).await.log_err();
+                    })
+                    .detach();
+            }
+        })
+        .detach();
+}
+
+/// Receive the URLs one instance forwards, giving up on instances that send too much or take too
+/// long.
+async fn receive_urls(
+    stream: smol::net::unix::UnixStream,
+    timeout: gpui::Task<()>,
+    urls: Sender<Vec<String>>,
+) -> anyhow::Result<()> {
+    let read = async {
+        let mut text = String::new();
+        stream
+            .take(MAX_FORWARDED_LEN)
+            .read_to_string(&mut text)
+            .await?;
+        anyhow::Ok(text)
+    };
+    let timeout = async {
+        timeout.await;
+        Err(anyhow!("timed out receiving forwarded URLs"))
+    };
+    let text = smol::future::or(read, timeout).await?;
+    let received = text
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    if !received.is_empty() {
+        urls.send(received)?;
+    }
+    Ok(())
+}
+
+/// Handle URLs the application was opened with on the main thread, borrowing the platform's
+/// common state with `common` whenever it's needed.
+pub(crate) fn handle_open_urls<'a>(
+    urls: Vec<String>,
+    common: impl Fn() -> RefMut<'a, LinuxCommon>,
+) {
+    let callback = common().callbacks.open_urls.take();
+    if let Some(mut callback) = callback {
+        callback(urls);
+        common().callbacks.open_urls.get_or_insert(callback);
+    }
+}
+
+fn socket_path() -> Option<PathBuf> {
+    let runtime_dir = env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty())?;
+    Some(Path::new(&runtime_dir).join(format!("{}.sock", app_name())))
+}
+
+/// The contents of the desktop entry with the given ID, preferring the user's data directory.
+fn installed_desktop_entry(desktop_id: &str) -> Option<String> {
+    iter::once(xdg_data_home())
+        .chain(xdg_data_dirs().iter().cloned())
+        .find_map(|dir| fs::read_to_string(dir.join("applications").join(desktop_id)).ok())
+}
+
+/// The URL schemes whose MIME types are handled by the desktop entry.
+fn url_schemes(entry: &str) -> Vec<String> {
+    let mut in_group = false;
+    let mut schemes = Vec::new();
+    for line in entry.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_group = line == DESKTOP_ENTRY_GROUP;
+        } else if in_group && let Some(types) = line.strip_prefix("MimeType=") {
+            schemes.extend(
+                types
+                    .split(';')
+                    .filter_map(|ty| ty.strip_prefix("x-scheme-handler/"))
+                    .map(|scheme| scheme.to_ascii_lowercase()),
+            );
+        }
+    }
+    schemes
+}
+
+/// A desktop entry launching the given executable with a URL, hidden from application menus.
+fn new_desktop_entry(app_name: &str, exe: &Path) -> String {
+    let mut quoted_exe = String::new();
+    for char in exe.to_string_lossy().chars() {
+        if matches!(char, '"' | '`' | '$' | '\\') {
+            quoted_exe.push('\\');
+        }
+        quoted_exe.push(char);
+    }
+    // Backslashes are escaped again in the values of desktop entries.
+    let quoted_exe = quoted_exe.replace('\\', "\\\\");
+    format!(
+        "{DESKTOP_ENTRY_GROUP}\nType=Application\nName={app_name}\nExec=\"{quoted_exe}\" %u\nNoDisplay=true\n"
+    )
+}
+
+/// Add the MIME type to those handled by the desktop entry, making sure it's launched with the
+/// URL it's opened with.
+fn add_mime_type(entry: &str, mime_type: &str) -> String {
+    let mut lines = entry.lines().map(ToString::to_string).collect::<Vec<_>>();
+    let group_start = lines
+        .iter()
+        .position(|line| line.trim() == DESKTOP_ENTRY_GROUP);
+    let group_start = match group_start {
+        Some(ix) => ix,
+        None => {
+            lines.insert(0, DESKTOP_ENTRY_GROUP.to_string());
+            0
+        }
+    };
+    let group_end = lines[group_start + 1..]
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .map_or(lines.len(), |ix| group_start + 1 + ix);
+
+    let mut has_mime_type = false;
+    for line in &mut lines[group_start + 1..group_end] {
+        if let Some(types) = line.strip_prefix("MimeType=") {
+            has_mime_type = true;
+            if !types.split(';').any(|ty| ty == mime_type) {
+                let separator = if types.is_empty() || types.ends_with(';') {
+                    ""
+                } else {
+                    ";"
+                };
+                *line = format!("{line}{separator}{mime_type};");
+            }
+        } else if let Some(exec) = line.strip_prefix("Exec=") {
+            let takes_url = ["%u", "%U", "%f", "%F"]
+                .iter()
+                .any(|code| exec.split_whitespace().any(|arg| arg == *code));
+            if !takes_url {
+                *line = format!("{line} %u");
+            }
+        }
+    }
+    if !has_mime_type {
+        lines.insert(group_end, format!("MimeType={mime_type};"));
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Set the default application of the MIME type in the contents of a `mimeapps.list`.
+fn set_default_application(mimeapps: &str, mime_type: &str, desktop_id: &str) -> String {
+    let mut lines = mimeapps
+        .lines()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    let default_line = format!("{mime_type}={desktop_id};");
+    let Some(group_start) = lines
+        .iter()
+        .position(|line| line.trim() == DEFAULT_APPLICATIONS_GROUP)
+    else {
+        if lines.last().is_some_and(|line| !line.is_empty()) {
+            lines.push(String::new());
+        }
+        lines.push(DEFAULT_APPLICATIONS_GROUP.to_string());
+        lines.push(default_line);
+        return lines.join("\n") + "\n";
+    };
+    let group_end = lines[group_start + 1..]
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .map_or(lines.len(), |ix| group_start + 1 + ix);
+
+    let existing = lines[group_start + 1..group_end].iter().position(|line| {
+        line.split_once('=')
+            .is_some_and(|(key, _)| key.trim() == mime_type)
+    });
+    match existing {
+        Some(ix) => lines[group_start + 1 + ix] = default_line,
+        None => {
+            // Keep the blank lines separating the group from the next one after it.
+            let mut insert_at = group_end;
+            while insert_at > group_start + 1 && lines[insert_at - 1].trim().is_empty() {
+                insert_at -= 1;
+            }
+            lines.insert(insert_at, default_line);
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_desktop_entry() {
+        let entry = new_desktop_entry("app", Path::new("/opt/my app/bin/app"));
+        assert_eq!(
+            add_mime_type(&entry, "x-scheme-handler/app"),
+            "[Desktop Entry]\nType=Application\nName=app\nExec=\"/opt/my app/bin/app\" %u\n\
+             NoDisplay=true\nMimeType=x-scheme-handler/app;\n"
+        );
+
+        let installed = "[Desktop Entry]\nName=App\nExec=app\nMimeType=text/plain\n\n\
+                         [Desktop Action new-window]\nExec=app --new\n";
+        let entry = add_mime_type(installed, "x-scheme-handler/app");
+        assert_eq!(
+            entry,
+            "[Desktop Entry]\nName=App\nExec=app %u\nMimeType=text/plain;x-scheme-handler/app;\n\n\
+             [Desktop Action new-window]\nExec=app --new\n"
+        );
+        assert_eq!(add_mime_type(&entry, "x-scheme-handler/app"), entry);
+    }
+
+    #[test]
+    fn test_default_application() {
+        assert_eq!(
+            set_default_application("", "x-scheme-handler/app", "app.desktop"),
+            "[Default Applications]\nx-scheme-handler/app=app.desktop;\n"
+        );
+        assert_eq!(
+            set_default_application(
+                "[Default Applications]\ntext/html=firefox.desktop\n\n[Added Associations]\n",
+                "x-scheme-handler/app",
+                "app.desktop",
+            ),
+            "[Default Applications]\ntext/html=firefox.desktop\nx-scheme-handler/app=app.desktop;\n\n\
+             [Added Associations]\n"
+        );
+        assert_eq!(
+            set_default_application(
+                "[Added Associations]\ntext/html=firefox.desktop;\n\n\
+                 [Default Applications]\nx-scheme-handler/app=other.desktop;\n",
+                "x-scheme-handler/app",
+                "app.desktop",
+            ),
+            "[Added Associations]\ntext/html=firefox.desktop;\n\n\
+             [Default Applications]\nx-scheme-handler/app=app.desktop;\n"
+        );
+    }
+
+    #[test]
+    fn test_urls_from_args() {
+        let entry = "[Desktop Entry]\nName=App\nMimeType=text/plain;x-scheme-handler/App;\n\n\
+                     [Desktop Action new-window]\nMimeType=x-scheme-handler/other;\n";
+        let schemes = url_schemes(entry);
+        assert_eq!(schemes, ["app"]);
+
+        let args = [
+            "--foreground",
+            "app://open?id=1",
+            "https://example.com",
+            "other://open",
+            "file:///tmp/a.txt",
+            "src/main.rs",
+        ];
+        assert_eq!(
+            urls_from_args(args.map(ToString::to_string), &schemes),
+            ["app://open?id=1"]
+        );
+        assert!(urls_from_args(args.map(ToString::to_string), &[]).is_empty());
+    }
+}
@@ -1,7 +1,6 @@
 use std::{
     cell::{RefCell, RefMut},
     hash::Hash,
-    os::fd::{AsRawFd, BorrowedFd},
     path::PathBuf,
     rc::{Rc, Weak},
     time::{Duration, Instant},
@@ -14,9 +13,7 @@ use calloop::{
 };
 use calloop_wayland_source::WaylandSource;
 use collections::HashMap;
-use filedescriptor::Pipe;
-use http_client::Url;
-use smallvec::SmallVec;
+use futures::channel::oneshot;
 use util::ResultExt as _;
 use wayland_backend::client::ObjectId;
 use wayland_backend::protocol::WEnum;
@@ -66,6 +63,9 @@ use wayland_protocols::{
     wp::fractional_scale::v1::client::{wp_fractional_scale_manager_v1, wp_fractional_scale_v1},
     xdg::dialog::v1::client::xdg_dialog_v1::XdgDialogV1,
 };
+use wayland_protocols_plasma::appmenu::client::{
+    org_kde_kwin_appmenu, org_kde_kwin_appmenu_manager,
+};
 use wayland_protocols_plasma::blur::client::{org_kde_kwin_blur, org_kde_kwin_blur_manager};
 use wayland_protocols_wlr::layer_shell::v1::client::{zwlr_layer_shell_v1, zwlr_layer_surface_v1};
 use xkbcommon::xkb::ffi::XKB_KEYMAP_FORMAT_TEXT_V1;
@@ -77,12 +77,15 @@ use super::{
 };
 
 use crate::linux::{
-    DOUBLE_CLICK_INTERVAL, LinuxClient, LinuxCommon, LinuxKeyboardLayout, SCROLL_LINES,
-    capslock_from_xkb, cursor_style_to_icon_names, get_xkb_compose_state, is_within_click_distance,
-    keystroke_from_xkb, keystroke_underlying_dead_key, modifiers_from_xkb, open_uri_internal,
-    read_fd, reveal_path_internal,
+    DOUBLE_CLICK_INTERVAL, DbusMenu, DbusNotifications, LinuxClient, LinuxCommon,
+    LinuxKeyboardLayout, LinuxKeyboardMapper, MenuEvent, MenuKind, NotificationEvent, SCROLL_LINES,
+    StatusNotifier, capslock_from_xkb, cursor_style_to_icon_names, data_for_mime_type,
+    get_xkb_compose_state, handle_menu_event, handle_notification_event, handle_open_urls,
+    handle_status_item_click, handle_thermal_state, is_within_click_distance, keystroke_from_xkb,
+    keystroke_underlying_dead_key, modifiers_from_xkb, offered_mime_types, open_uri_internal,
+    reveal_path_internal,
     wayland::{
-        clipboard::{Clipboard, DataOffer, FILE_LIST_MIME_TYPE, TEXT_MIME_TYPES},
+        clipboard::{Clipboard, DataOffer},
         cursor::Cursor,
         serial::{SerialKind, SerialTracker},
         to_shape,
@@ -91,12 +94,13 @@ use crate::linux::{
     xdg_desktop_portal::{Event as XDPEvent, XDPEventSource},
 };
 use gpui::{
-    AnyWindowHandle, Bounds, Capslock, CursorStyle, DevicePixels, DisplayId, FileDropEvent,
-    ForegroundExecutor, KeyDownEvent, KeyUpEvent, Keystroke, Modifiers, ModifiersChangedEvent,
-    MouseButton, MouseDownEvent, MouseExitEvent, MouseMoveEvent, MouseUpEvent, NavigationDirection,
-    Pixels, PlatformDisplay, PlatformInput, PlatformKeyboardLayout, PlatformWindow, Point,
-    ScrollDelta, ScrollWheelEvent, SharedString, Size, TaskTiming, TouchPhase, WindowParams, point,
-    profiler, px, size,
+    AnyWindowHandle, Bounds, Capslock, ClipboardItem, CursorStyle, DevicePixels, DisplayId,
+    ExternalDrag, ExternalDragOutcome, ExternalDropEvent, FileDropEvent, ForegroundExecutor,
+    KeyDownEvent, KeyUpEvent, Keystroke, Modifiers, ModifiersChangedEvent, MouseButton,
+    MouseDownEvent, MouseExitEvent, MouseMoveEvent, MouseUpEvent, NavigationDirection, Pixels,
+    PlatformDisplay, PlatformInput, PlatformKeyboardLayout, PlatformKeyboardMapper, PlatformWindow,
+    Point, ScrollDelta, ScrollWheelEvent, SharedString, Size, StatusItemClick, TaskTiming,
+    ThermalState, TouchPhase, WindowParams, point, profiler, px, size,
 };
 use gpui_wgpu::{CompositorGpuHint, GpuContext};
 use wayland_protocols::wp::linux_dmabuf::zv1::client::{
@@ -126,6 +130,7 @@ pub struct Globals {
     pub decoration_manager: Option<zxdg_decoration_manager_v1::ZxdgDecorationManagerV1>,
     pub layer_shell: Option<zwlr_layer_shell_v1::ZwlrLayerShellV1>,
     pub blur_manager: Option<org_kde_kwin_blur_manager::OrgKdeKwinBlurManager>,
+    pub appmenu_manager: Option<org_kde_kwin_appmenu_manager::OrgKdeKwinAppmenuManager>,
     pub text_input_manager: Option<zwp_text_input_manager_v3::ZwpTextInputManagerV3>,
     pub gesture_manager: Option<zwp_pointer_gestures_v1::ZwpPointerGesturesV1>,
     pub dialog: Option<xdg_wm_dialog_v1::XdgWmDialogV1>,
@@ -167,6 +172,7 @@ impl Globals {
             decoration_manager: globals.bind(&qh, 1..=1, ()).ok(),
             layer_shell: globals.bind(&qh, 1..=5, ()).ok(),
             blur_manager: globals.bind(&qh, 1..=1, ()).ok(),
+            appmenu_manager: globals.bind(&qh, 1..=2, ()).ok(),
             text_input_manager: globals.bind(&qh, 1..=1, ()).ok(),
             gesture_manager: globals.bind(&qh, 1..=3, ()).ok(),
             dialog: globals.bind(&qh, dialog_v..=dialog_v, ()).ok(),
@@ -233,6 +239,7 @@ pub(crate) struct WaylandClientState {
     keymap_state: Option<xkb::State>,
     compose_state: Option<xkb::compose::State>,
     drag: DragState,
+    drag_source: Option<DragSource>,
     click: ClickState,
     repeat: KeyRepeat,
     pub modifiers: Modifiers,
@@ -263,6 +270,31 @@ pub struct DragState {
     data_offer: Option<wl_data_offer::WlDataOffer>,
     window: Option<WaylandWindowStatePtr>,
     position: Point<Pixels>,
+    /// The MIME type to accept the offer as, when the hovered element can drop it.
+    mime_type: Option<String>,
+    accepted: bool,
+}
+
+impl DragState {
+    /// Tell the source whether the data can be dropped where it's hovering.
+    fn accept(&mut self, serial: u32, accepted: bool) {
+        if accepted == self.accepted {
+            return;
+        }
+        self.accepted = accepted;
+        if let Some(data_offer) = &self.data_offer {
+            data_offer.accept(serial, self.mime_type.clone().filter(|_| accepted));
+        }
+    }
+}
+
+/// A drag out of one of our surfaces, see [`gpui::Window::start_external_drag`].
+struct DragSource {
+    data_source: wl_data_source::WlDataSource,
+    item: ClipboardItem,
+    /// The action the compositor chose, from those the drop target accepts.
+    action: DndAction,
+    outcome: oneshot::Sender<ExternalDragOutcome>,
 }
 
 pub struct ClickState {
@@ -309,6 +341,45 @@ impl WaylandClientStatePtr {
             Some(PendingActivation::Window(window));
     }
 
+    pub fn start_drag(
+        &self,
+        surface: &wl_surface::WlSurface,
+        drag: ExternalDrag,
+    ) -> Option<oneshot::Receiver<ExternalDragOutcome>> {
+        let client = self.get_client();
+        let mut state = client.borrow_mut();
+        let (Some(data_device_manager), Some(data_device)) = (
+            state.globals.data_device_manager.clone(),
+            state.data_device.clone(),
+        ) else {
+            return None;
+        };
+        let data_source = data_device_manager.create_data_source(&state.globals.qh, ());
+        for mime_type in offered_mime_types(&drag.item) {
+            data_source.offer(mime_type);
+        }
+        data_source.set_actions(if drag.allow_move {
+            DndAction::Copy | DndAction::Move
+        } else {
+            DndAction::Copy
+        });
+        let serial = state.serial_tracker.get(SerialKind::MousePress);
+        data_device.start_drag(Some(&data_source), surface, None, serial);
+
+        let (outcome, outcome_rx) = oneshot::channel();
+        let previous = state.drag_source.replace(DragSource {
+            data_source,
+            item: drag.item,
+            action: DndAction::empty(),
+            outcome,
+        });
+        if let Some(previous) = previous {
+            previous.outcome.send(ExternalDragOutcome::Cancelled).ok();
+            previous.data_source.destroy();
+        }
+        Some(outcome_rx)
+    }
+
     pub fn enable_ime(&self) {
         let client = self.get_client();
         let mut state = client.borrow_mut();
@@ -390,6 +461,65 @@ impl WaylandClientStatePtr {
         }
     }
 
+    fn handle_menu_event(&self, event: MenuEvent, kind: MenuKind) {
+        let Some(client) = self.0.upgrade() else {
+            return;
+        };
+        let exported = matches!(event, MenuEvent::Exported(_));
+        handle_menu_event(event, kind, || {
+            RefMut::map(client.borrow_mut(), |state| &mut state.common)
+        });
+        if exported {
+            let state = client.borrow();
+            if let Some(service_name) = state
+                .common
+                .app_menu
+                .as_ref()
+                .and_then(|app_menu| app_menu.service_name())
+            {
+                for window in state.windows.values() {
+                    window.set_app_menu_address(service_name);
+                }
+            }
+        }
+    }
+
+    fn handle_status_item_click(&self, click: StatusItemClick) {
+        let Some(client) = self.0.upgrade() else {
+            return;
+        };
+        handle_status_item_click(click, || {
+            RefMut::map(client.borrow_mut(), |state| &mut state.common)
+        });
+    }
+
+    fn handle_notification_event(&self, event: NotificationEvent) {
+        let Some(client) = self.0.upgrade() else {
+            return;
+        };
+        handle_notification_event(event, || {
+            RefMut::map(client.borrow_mut(), |state| &mut state.common)
+        });
+    }
+
+    fn handle_open_urls(&self, urls: Vec<String>) {
+        let Some(client) = self.0.upgrade() else {
+            return;
+        };
+        handle_open_urls(urls, || {
+            RefMut::map(client.borrow_mut(), |state| &mut state.common)
+        });
+    }
+
+    fn handle_thermal_state(&self, thermal_state: ThermalState) {
+        let Some(client) = self.0.upgrade() else {
+            return;
+        };
+        handle_thermal_state(thermal_state, || {
+            RefMut::map(client.borrow_mut(), |state| &mut state.common)
+        });
+    }
+
     pub fn drop_window(&self, surface_id: &ObjectId) {
         let client = self.get_client();
         let mut state = client.borrow_mut();
@@ -501,7 +631,7 @@ impl WaylandClient {
 
         let event_loop = EventLoop::<WaylandClientStatePtr>::try_new().unwrap();
 
-        let (common, main_receiver) = LinuxCommon::new(event_loop.get_signal());
+        let (mut common, main_receiver) = LinuxCommon::new(event_loop.get_signal());
 
         let handle = event_loop.handle();
         handle
@@ -583,6 +713,65 @@ impl WaylandClient {
             })
             .unwrap();
 
+        let (app_menu, app_menu_events) = DbusMenu::new(&common.background_executor);
+        common.app_menu = Some(app_menu);
+        handle
+            .insert_source(app_menu_events, |event, _, client| {
+                if let calloop::channel::Event::Msg(event) = event {
+                    client.handle_menu_event(event, MenuKind::AppMenu);
+                }
+            })
+            .unwrap();
+
+        let (status_item, status_item_clicks, status_item_menu_events) =
+            StatusNotifier::new(&common.background_executor);
+        common.status_item = Some(status_item);
+        handle
+            .insert_source(status_item_clicks, |event, _, client| {
+                if let calloop::channel::Event::Msg(click) = event {
+                    client.handle_status_item_click(click);
+                }
+            })
+            .unwrap();
+        handle
+            .insert_source(status_item_menu_events, |event, _, client| {
+                if let calloop::channel::Event::Msg(event) = event {
+                    client.handle_menu_event(event, MenuKind::StatusItem);
+                }
+            })
+            .unwrap();
+
+        let (notifications, notification_events) =
+            DbusNotifications::new(&common.background_executor);
+        common.notifications = Some(notifications);
+        handle
+            .insert_source(notification_events, |event, _, client| {
+                if let calloop::channel::Event::Msg(event) = event {
+                    client.handle_notification_event(event);
+                }
+            })
+            .unwrap();
+
+        let (open_urls, open_url_events) = calloop::channel::channel();
+        common.open_urls = Some(open_urls);
+        handle
+            .insert_source(open_url_events, |event, _, client| {
+                if let calloop::channel::Event::Msg(urls) = event {
+                    client.handle_open_urls(urls);
+                }
+            })
+            .unwrap();
+
+        let (thermal_states, thermal_state_events) = calloop::channel::channel();
+        common.thermal_states = Some(thermal_states);
+        handle
+            .insert_source(thermal_state_events, |event, _, client| {
+                if let calloop::channel::Event::Msg(state) = event {
+                    client.handle_thermal_state(state);
+                }
+            })
+            .unwrap();
+
         let state = Rc::new(RefCell::new(WaylandClientState {
             serial_tracker: SerialTracker::new(),
             globals,
@@ -612,7 +801,10 @@ impl WaylandClient {
                 data_offer: None,
                 window: None,
                 position: Point::default(),
+                mime_type: None,
+                accepted: false,
             },
+            drag_source: None,
             click: ClickState {
                 last_click: Instant::now(),
                 last_mouse_button: None,
@@ -667,6 +859,18 @@ impl LinuxClient for WaylandClient {
         Box::new(self.0.borrow().keyboard_layout.clone())
     }
 
+    fn keyboard_mapper(&self) -> Rc<dyn PlatformKeyboardMapper> {
+        let state = self.0.borrow();
+        let Some(keymap_state) = &state.keymap_state else {
+            return Rc::new(gpui::DummyKeyboardMapper);
+        };
+        let layout_idx = keymap_state.serialize_layout(xkb::STATE_LAYOUT_EFFECTIVE);
+        Rc::new(LinuxKeyboardMapper::new(
+            &keymap_state.get_keymap(),
+            layout_idx,
+        ))
+    }
+
     fn displays(&self) -> Vec<Rc<dyn PlatformDisplay>> {
         self.0
             .borrow()
@@ -756,6 +960,14 @@ impl LinuxClient for WaylandClient {
             parent,
             target_output,
         )?;
+        if let Some(service_name) = state
+            .common
+            .app_menu
+            .as_ref()
+            .and_then(|app_menu| app_menu.service_name())
+        {
+            window.0.set_app_menu_address(service_name);
+        }
         state.windows.insert(surface_id, window.0.clone());
 
         Ok(Box::new(window))
@@ -866,13 +1078,12 @@ impl LinuxClient for WaylandClient {
             return;
         };
         if state.mouse_focused_window.is_some() || state.keyboard_focused_window.is_some() {
-            state.clipboard.set_primary(item);
             let serial = state.serial_tracker.get(SerialKind::KeyPress);
             let data_source = primary_selection_manager.create_source(&state.globals.qh, ());
-            for mime_type in TEXT_MIME_TYPES {
-                data_source.offer(mime_type.to_string());
+            for mime_type in state.clipboard.mime_types_to_offer(&item) {
+                data_source.offer(mime_type);
             }
-            data_source.offer(state.clipboard.self_mime());
+            state.clipboard.set_primary(item);
             primary_selection.set_selection(Some(&data_source), serial);
         }
     }
@@ -886,13 +1097,12 @@ impl LinuxClient for WaylandClient {
             return;
         };
         if state.mouse_focused_window.is_some() || state.keyboard_focused_window.is_some() {
-            state.clipboard.set(item);
             let serial = state.serial_tracker.get(SerialKind::KeyPress);
             let data_source = data_device_manager.create_data_source(&state.globals.qh, ());
-            for mime_type in TEXT_MIME_TYPES {
-                data_source.offer(mime_type.to_string());
+            for mime_type in state.clipboard.mime_types_to_offer(&item) {
+                data_source.offer(mime_type);
             }
-            data_source.offer(state.clipboard.self_mime());
+            state.clipboard.set(item);
             data_device.set_selection(Some(&data_source), serial);
         }
     }
@@ -905,6 +1115,10 @@ impl LinuxClient for WaylandClient {
         self.0.borrow_mut().clipboard.read()
     }
 
+    fn read_custom_from_clipboard(&self, mime: &str) -> Option<Vec<u8>> {
+        self.0.borrow_mut().clipboard.read_custom(mime)
+    }
+
     fn active_window(&self) -> Option<AnyWindowHandle> {
         self.0
             .borrow_mut()
@@ -1072,6 +1286,8 @@ delegate_noop!(WaylandClientStatePtr: ignore zwlr_layer_shell_v1::ZwlrLayerShell
 delegate_noop!(WaylandClientStatePtr: ignore org_kde_kwin_blur_manager::OrgKdeKwinBlurManager);
 delegate_noop!(WaylandClientStatePtr: ignore zwp_text_input_manager_v3::ZwpTextInputManagerV3);
 delegate_noop!(WaylandClientStatePtr: ignore org_kde_kwin_blur::OrgKdeKwinBlur);
+delegate_noop!(WaylandClientStatePtr: ignore org_kde_kwin_appmenu_manager::OrgKdeKwinAppmenuManager);
+delegate_noop!(WaylandClientStatePtr: ignore org_kde_kwin_appmenu::OrgKdeKwinAppmenu);
 delegate_noop!(WaylandClientStatePtr: ignore wp_viewporter::WpViewporter);
 delegate_noop!(WaylandClientStatePtr: ignore wp_viewport::WpViewport);
 
@@ -2135,7 +2351,7 @@ impl Dispatch<wl_data_device::WlDataDevice, ()> for WaylandClientStatePtr {
         _: &wl_data_device::WlDataDevice,
         event: wl_data_device::Event,
         _: &(),
-        _: &Connection,
+        conn: &Connection,
         _: &QueueHandle<Self>,
     ) {
         let client = this.get_client();
@@ -2177,20 +2393,37 @@ impl Dispatch<wl_data_device::WlDataDevice, ()> for WaylandClientStatePtr {
                         return;
                     };
 
+                    // Dragging out of one of our surfaces and back into one continues the drag
+                    // that's already in progress, rather than dropping files.
+                    if state.drag_source.is_some() {
+                        let position = Point::new(x.into(), y.into());
+                        state.drag.data_offer = Some(data_offer);
+                        state.drag.window = Some(drag_window.clone());
+                        state.drag.position = position;
+
+                        drop(state);
+                        drag_window.handle_input(PlatformInput::FileDrop(FileDropEvent::Pending {
+                            position,
+                        }));
+                        return;
+                    }
+
                     const ACTIONS: DndAction = DndAction::Copy;
                     data_offer.set_actions(ACTIONS, ACTIONS);
 
-                    let pipe = Pipe::new().unwrap();
-                    data_offer.receive(FILE_LIST_MIME_TYPE.to_string(), unsafe {
-                        BorrowedFd::borrow_raw(pipe.write.as_raw_fd())
-                    });
-                    let fd = pipe.read;
-                    drop(pipe.write);
-
-                    let read_task = state.common.background_executor.spawn(async {
-                        let buffer = unsafe { read_fd(fd)? };
-                        let text = String::from_utf8(buffer)?;
-                        anyhow::Ok(text)
+                    let Some(offer) = state
+                        .data_offers
+                        .iter()
+                        .find(|wrapper| wrapper.inner.id() == data_offer.id())
+                        .cloned()
+                    else {
+                        return;
+                    };
+                    let connection = conn.clone();
+                    let read_task = state.common.background_executor.spawn(async move {
+                        offer
+                            .read_dropped_item(&connection)
+                            .map(|item| (offer, item))
                     });
 
                     let this = this.clone();
@@ -2198,40 +2431,28 @@ impl Dispatch<wl_data_device::WlDataDevice, ()> for WaylandClientStatePtr {
                         .common
                         .foreground_executor
                         .spawn(async move {
-                            let file_list = match read_task.await {
-                                Ok(list) => list,
-                                Err(err) => {
-                                    log::error!("error reading drag and drop pipe: {err:?}");
-                                    return;
-                                }
-                            };
-
-                            let paths: SmallVec<[_; 2]> = file_list
-                                .lines()
-                                .filter_map(|path| Url::parse(path).log_err())
-                                .filter_map(|url| url.to_file_path().log_err())
-                                .collect();
-                            let position = Point::new(x.into(), y.into());
-
-                            // Prevent dropping text from other programs.
-                            if paths.is_empty() {
+                            let Some((offer, item)) = read_task.await else {
                                 data_offer.destroy();
                                 return;
-                            }
-
-                            let input = PlatformInput::FileDrop(FileDropEvent::Entered {
-                                position,
-                                paths: gpui::ExternalPaths(paths),
-                            });
+                            };
+                            let position = Point::new(x.into(), y.into());
 
                             let client = this.get_client();
                             let mut state = client.borrow_mut();
                             state.drag.data_offer = Some(data_offer);
                             state.drag.window = Some(drag_window.clone());
                             state.drag.position = position;
-
+                            state.drag.mime_type = offer.mime_types().first().cloned();
+                            state.drag.accepted = false;
                             drop(state);
-                            drag_window.handle_input(input);
+
+                            let accepted =
+                                drag_window.handle_external_drop(ExternalDropEvent::Entered {
+                                    position,
+                                    item,
+                                });
+                            let mut state = client.borrow_mut();
+                            state.drag.accept(serial, accepted);
                         })
                         .detach();
                 }
@@ -2243,9 +2464,18 @@ impl Dispatch<wl_data_device::WlDataDevice, ()> for WaylandClientStatePtr {
                 let position = Point::new(x.into(), y.into());
                 state.drag.position = position;
 
-                let input = PlatformInput::FileDrop(FileDropEvent::Pending { position });
+                if state.drag_source.is_some() {
+                    let input = PlatformInput::FileDrop(FileDropEvent::Pending { position });
+                    drop(state);
+                    drag_window.handle_input(input);
+                    return;
+                }
+
                 drop(state);
-                drag_window.handle_input(input);
+                let accepted =
+                    drag_window.handle_external_drop(ExternalDropEvent::Over { position });
+                let serial = client.borrow().serial_tracker.get(SerialKind::DataDevice);
+                client.borrow_mut().drag.accept(serial, accepted);
             }
             wl_data_device::Event::Leave => {
                 let Some(drag_window) = state.drag.window.clone() else {
@@ -2256,27 +2486,42 @@ impl Dispatch<wl_data_device::WlDataDevice, ()> for WaylandClientStatePtr {
 
                 state.drag.data_offer = None;
                 state.drag.window = None;
+                state.drag.accepted = false;
+                if state.drag_source.is_some() {
+                    return;
+                }
 
-                let input = PlatformInput::FileDrop(FileDropEvent::Exited {});
                 drop(state);
-                drag_window.handle_input(input);
+                drag_window.handle_external_drop(ExternalDropEvent::Exited);
             }
             wl_data_device::Event::Drop => {
                 let Some(drag_window) = state.drag.window.clone() else {
                     return;
                 };
                 let data_offer = state.drag.data_offer.clone().unwrap();
-                data_offer.finish();
-                data_offer.destroy();
+                let position = state.drag.position;
+                // Our own drags are dropped as the drag in progress, so they aren't accepted.
+                if state.drag_source.is_some() {
+                    data_offer.destroy();
+                    state.drag.data_offer = None;
+                    state.drag.window = None;
+
+                    let input = PlatformInput::FileDrop(FileDropEvent::Submit { position });
+                    drop(state);
+                    drag_window.handle_input(input);
+                    return;
+                }
 
+                if state.drag.accepted {
+                    data_offer.finish();
+                }
+                data_offer.destroy();
                 state.drag.data_offer = None;
                 state.drag.window = None;
+                state.drag.accepted = false;
 
-                let input = PlatformInput::FileDrop(FileDropEvent::Submit {
-                    position: state.drag.position,
-                });
                 drop(state);
-                drag_window.handle_input(input);
+                drag_window.handle_external_drop(ExternalDropEvent::Dropped { position });
             }
             _ => {}
         }
@@ -2300,14 +2545,6 @@ impl Dispatch<wl_data_offer::WlDataOffer, ()> for WaylandClientStatePtr {
         let mut state = client.borrow_mut();
 
         if let wl_data_offer::Event::Offer { mime_type } = event {
-            // Drag and drop
-            if mime_type == FILE_LIST_MIME_TYPE {
-                let serial = state.serial_tracker.get(SerialKind::DataDevice);
-                let mime_type = mime_type.clone();
-                data_offer.accept(serial, Some(mime_type));
-            }
-
-            // Clipboard
             if let Some(offer) = state
                 .data_offers
                 .iter_mut()
@@ -2329,13 +2566,48 @@ impl Dispatch<wl_data_source::WlDataSource, ()> for WaylandClientStatePtr {
         _: &QueueHandle<Self>,
     ) {
         let client = this.get_client();
-        let state = client.borrow_mut();
+        let mut state = client.borrow_mut();
+        let is_drag_source = |drag: &DragSource| drag.data_source == *data_source;
 
         match event {
             wl_data_source::Event::Send { mime_type, fd } => {
-                state.clipboard.send(mime_type, fd);
+                let drag_data = state
+                    .drag_source
+                    .as_ref()
+                    .filter(|drag| is_drag_source(drag))
+                    .map(|drag| data_for_mime_type(&drag.item, &mime_type));
+                match drag_data {
+                    Some(Some(bytes)) => state.clipboard.send_internal(fd, bytes),
+                    Some(None) => {}
+                    None => state.clipboard.send(mime_type, fd),
+                }
+            }
+            wl_data_source::Event::Action {
+                dnd_action: WEnum::Value(action),
+            } => {
+                if let Some(drag) = state
+                    .drag_source
+                    .as_mut()
+                    .filter(|drag| is_drag_source(drag))
+                {
+                    drag.action = action;
+                }
+            }
+            wl_data_source::Event::DndFinished => {
+                if let Some(drag) = state.drag_source.take_if(|drag| is_drag_source(drag)) {
+                    let outcome = if drag.action.contains(DndAction::Move) {
+                        ExternalDragOutcome::Moved
+                    } else {
+                        ExternalDragOutcome::Copied
+                    };
+                    drag.outcome.send(outcome).ok();
+                    data_source.destroy();
+                }
             }
             wl_data_source::Event::Cancelled => {
+                if let Some(drag) = state.drag_source.take_if(|drag| is_drag_source(drag)) {
+                    drag.outcome.send(ExternalDragOutcome::Cancelled).ok();
+                }
                 data_source.destroy();
             }
             _ => {}
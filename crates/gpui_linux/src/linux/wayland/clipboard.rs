@@ -6,20 +6,14 @@ use std::{
 
 use calloop::{LoopHandle, PostAction};
 use filedescriptor::Pipe;
-use strum::IntoEnumIterator;
 use wayland_client::{Connection, protocol::wl_data_offer::WlDataOffer};
 use wayland_protocols::wp::primary_selection::zv1::client::zwp_primary_selection_offer_v1::ZwpPrimarySelectionOfferV1;
 
-use crate::linux::{WaylandClientStatePtr, platform::read_fd};
-use gpui::{ClipboardEntry, ClipboardItem, Image, ImageFormat, hash};
-
-/// Text mime types that we'll offer to other programs.
-pub(crate) const TEXT_MIME_TYPES: [&str; 3] =
-    ["text/plain;charset=utf-8", "UTF8_STRING", "text/plain"];
-pub(crate) const FILE_LIST_MIME_TYPE: &str = "text/uri-list";
-
-/// Text mime types that we'll accept from other programs.
-pub(crate) const ALLOWED_TEXT_MIME_TYPES: [&str; 2] = ["text/plain;charset=utf-8", "UTF8_STRING"];
+use crate::linux::{
+    SELF_MIME_TYPE_PREFIX, WaylandClientStatePtr, data_for_mime_type, offered_mime_types,
+    platform::read_fd, read_dropped_item, read_offered_item,
+};
+use gpui::ClipboardItem;
 
 pub(crate) struct Clipboard {
     connection: Connection,
@@ -72,6 +66,10 @@ impl<T: ReceiveData> DataOffer<T> {
         self.mime_types.push(mime_type)
     }
 
+    pub fn mime_types(&self) -> &[String] {
+        &self.mime_types
+    }
+
     fn has_mime_type(&self, mime_type: &str) -> bool {
         self.mime_types.iter().any(|t| t == mime_type)
     }
@@ -95,43 +93,17 @@ impl<T: ReceiveData> DataOffer<T> {
         }
     }
 
-    fn read_text(&self, connection: &Connection) -> Option<ClipboardItem> {
-        let mime_type = self.mime_types.iter().find(|&mime_type| {
-            ALLOWED_TEXT_MIME_TYPES
-                .iter()
-                .any(|&allowed| allowed == mime_type)
-        })?;
-        let bytes = self.read_bytes(connection, mime_type)?;
-        let text_content = match String::from_utf8(bytes) {
-            Ok(content) => content,
-            Err(e) => {
-                log::error!("Failed to convert clipboard content to UTF-8: {}", e);
-                return None;
-            }
-        };
-
-        // Normalize the text to unix line endings, otherwise
-        // copying from eg: firefox inserts a lot of blank
-        // lines, and that is super annoying.
-        let result = text_content.replace("\r\n", "\n");
-        Some(ClipboardItem::new_string(result))
+    pub fn read_item(&self, connection: &Connection) -> Option<ClipboardItem> {
+        read_offered_item(&self.mime_types, |mime_type| {
+            self.read_bytes(connection, mime_type)
+        })
     }
 
-    fn read_image(&self, connection: &Connection) -> Option<ClipboardItem> {
-        for format in ImageFormat::iter() {
-            let mime_type = format.mime_type();
-            if !self.has_mime_type(mime_type) {
-                continue;
-            }
-
-            if let Some(bytes) = self.read_bytes(connection, mime_type) {
-                let id = hash(&bytes);
-                return Some(ClipboardItem {
-                    entries: vec![ClipboardEntry::Image(Image { format, bytes, id })],
-                });
-            }
-        }
-        None
+    /// Read the data dragged onto a window, including its custom MIME types.
+    pub fn read_dropped_item(&self, connection: &Connection) -> Option<ClipboardItem> {
+        read_dropped_item(&self.mime_types, |mime_type| {
+            self.read_bytes(connection, mime_type)
+        })
     }
 }
 
@@ -143,7 +115,7 @@ impl Clipboard {
         Self {
             connection,
             loop_handle,
-            self_mime: format!("pid/{}", std::process::id()),
+            self_mime: format!("{SELF_MIME_TYPE_PREFIX}{}", std::process::id()),
 
             contents: None,
             primary_contents: None,
@@ -177,19 +149,30 @@ impl Clipboard {
         self.self_mime.clone()
     }
 
-    pub fn send(&self, _mime_type: String, fd: OwnedFd) {
-        if let Some(text) = self.contents.as_ref().and_then(|contents| contents.text()) {
-            self.send_internal(fd, text.as_bytes().to_owned());
+    /// The MIME types to offer for an item, including the one identifying this program.
+    pub fn mime_types_to_offer(&self, item: &ClipboardItem) -> Vec<String> {
+        let mut mime_types = offered_mime_types(item);
+        mime_types.push(self.self_mime());
+        mime_types
+    }
+
+    pub fn send(&self, mime_type: String, fd: OwnedFd) {
+        if let Some(bytes) = self
+            .contents
+            .as_ref()
+            .and_then(|contents| data_for_mime_type(contents, &mime_type))
+        {
+            self.send_internal(fd, bytes);
         }
     }
 
-    pub fn send_primary(&self, _mime_type: String, fd: OwnedFd) {
-        if let Some(text) = self
+    pub fn send_primary(&self, mime_type: String, fd: OwnedFd) {
+        if let Some(bytes) = self
             .primary_contents
             .as_ref()
-            .and_then(|contents| contents.text())
+            .and_then(|contents| data_for_mime_type(contents, &mime_type))
         {
-            self.send_internal(fd, text.as_bytes().to_owned());
+            self.send_internal(fd, bytes);
         }
     }
 
@@ -203,14 +186,24 @@ impl Clipboard {
             return self.contents.clone();
         }
 
-        let item = offer
-            .read_text(&self.connection)
-            .or_else(|| offer.read_image(&self.connection))?;
+        let item = offer.read_item(&self.connection)?;
 
         self.cached_read = Some(item.clone());
         Some(item)
     }
 
+    /// Read the clipboard in one custom MIME type, which [`Self::read`] leaves out.
+    pub fn read_custom(&self, mime_type: &str) -> Option<Vec<u8>> {
+        let offer = self.current_offer.as_ref()?;
+        if offer.has_mime_type(&self.self_mime) {
+            return Some(self.contents.as_ref()?.custom(mime_type)?.to_vec());
+        }
+        if !offer.has_mime_type(mime_type) {
+            return None;
+        }
+        offer.read_bytes(&self.connection, mime_type)
+    }
+
     pub fn read_primary(&mut self) -> Option<ClipboardItem> {
         let offer = self.current_primary_offer.as_ref()?;
         if let Some(cached) = self.cached_primary_read.clone() {
@@ -221,15 +214,13 @@ impl Clipboard {
             return self.primary_contents.clone();
         }
 
-        let item = offer
-            .read_text(&self.connection)
-            .or_else(|| offer.read_image(&self.connection))?;
+        let item = offer.read_item(&self.connection)?;
 
         self.cached_primary_read = Some(item.clone());
         Some(item)
     }
 
-    fn send_internal(&self, fd: OwnedFd, bytes: Vec<u8>) {
+    pub fn send_internal(&self, fd: OwnedFd, bytes: Vec<u8>) {
         let mut written = 0;
         self.loop_handle
             .insert_source(
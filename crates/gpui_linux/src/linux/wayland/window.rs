@@ -7,7 +7,7 @@ use std::{
 };
 
 use collections::{FxHashSet, HashMap};
-use futures::channel::oneshot::Receiver;
+use futures::{StreamExt as _, channel::oneshot::Receiver};
 
 use raw_window_handle as rwh;
 use wayland_backend::client::ObjectId;
@@ -24,15 +24,20 @@ use wayland_protocols::{
     wp::fractional_scale::v1::client::wp_fractional_scale_v1,
     xdg::dialog::v1::client::xdg_dialog_v1::XdgDialogV1,
 };
+use wayland_protocols_plasma::appmenu::client::org_kde_kwin_appmenu;
 use wayland_protocols_plasma::blur::client::org_kde_kwin_blur;
 use wayland_protocols_wlr::layer_shell::v1::client::zwlr_layer_surface_v1;
 
 use crate::linux::wayland::{display::WaylandDisplay, serial::SerialKind};
-use crate::linux::{Globals, Output, WaylandClientStatePtr, get_window};
+use crate::linux::{
+    AccessibilityRequest, Globals, LinuxAccessibility, MENU_OBJECT_PATH, Output,
+    WaylandClientStatePtr, get_window,
+};
 use gpui::{
-    AnyWindowHandle, Bounds, Capslock, Decorations, DevicePixels, GpuSpecs, Modifiers, Pixels,
-    PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow, Point,
-    PromptButton, PromptLevel, RequestFrameOptions, ResizeEdge, Scene, Size, Tiling,
+    AccessibilityActionRequest, AccessibilityTree, AnyWindowHandle, Bounds, Capslock, Decorations,
+    DevicePixels, ExternalDrag, ExternalDragOutcome, ExternalDropEvent, GpuSpecs, Modifiers,
+    Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow,
+    Point, PromptButton, PromptLevel, RequestFrameOptions, ResizeEdge, Scene, Size, Tiling,
     WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControlArea, WindowControls,
     WindowDecorations, WindowKind, WindowParams, layer_shell::LayerShellNotSupportedError, px,
     size,
@@ -50,6 +55,7 @@ pub(crate) struct Callbacks {
     should_close: Option<Box<dyn FnMut() -> bool>>,
     close: Option<Box<dyn FnOnce()>>,
     appearance_changed: Option<Box<dyn FnMut()>>,
+    accessibility_action: Option<Box<dyn FnMut(AccessibilityActionRequest)>>,
 }
 
 struct RawWindow {
@@ -96,6 +102,7 @@ pub struct WaylandWindowState {
     app_id: Option<String>,
     appearance: WindowAppearance,
     blur: Option<org_kde_kwin_blur::OrgKdeKwinBlur>,
+    appmenu: Option<org_kde_kwin_appmenu::OrgKdeKwinAppmenu>,
     viewport: Option<wp_viewport::WpViewport>,
     outputs: HashMap<ObjectId, Output>,
     display: Option<(ObjectId, Output)>,
@@ -119,6 +126,7 @@ pub struct WaylandWindowState {
     in_progress_window_controls: Option<WindowControls>,
     window_controls: WindowControls,
     client_inset: Option<Pixels>,
+    accessibility: LinuxAccessibility,
 }
 
 pub enum WaylandSurfaceState {
@@ -325,6 +333,7 @@ impl WaylandWindowState {
         compositor_gpu: Option<CompositorGpuHint>,
         options: WindowParams,
         parent: Option<WaylandWindowStatePtr>,
+        accessibility: LinuxAccessibility,
     ) -> anyhow::Result<Self> {
         let renderer = {
             let raw_window = RawWindow {
@@ -366,6 +375,7 @@ impl WaylandWindowState {
             surface,
             app_id: None,
             blur: None,
+            appmenu: None,
             viewport,
             globals,
             outputs: HashMap::default(),
@@ -390,6 +400,7 @@ impl WaylandWindowState {
             in_progress_window_controls: None,
             window_controls: WindowControls::default(),
             client_inset: None,
+            accessibility,
         })
     }
 
@@ -443,10 +454,13 @@ impl Drop for WaylandWindow {
 
         state.renderer.destroy();
 
-        // Destroy blur first, this has no dependencies.
+        // Destroy blur and the app menu first, these have no dependencies.
         if let Some(blur) = &state.blur {
             blur.release();
         }
+        if let Some(appmenu) = &state.appmenu {
+            appmenu.release();
+        }
 
         // Decorations must be destroyed before the xdg state.
         // See https://wayland.app/protocols/xdg-decoration-unstable-v1#zxdg_toplevel_decoration_v1
@@ -513,6 +527,8 @@ impl WaylandWindow {
             .as_ref()
             .map(|viewporter| viewporter.get_viewport(&surface, &globals.qh, ()));
 
+        let executor = globals.executor.clone();
+        let (accessibility, mut accessibility_requests) = LinuxAccessibility::new();
         let this = Self(WaylandWindowStatePtr {
             state: Rc::new(RefCell::new(WaylandWindowState::new(
                 handle,
@@ -526,10 +542,26 @@ impl WaylandWindow {
                 compositor_gpu,
                 params,
                 parent,
+                accessibility,
             )?)),
             callbacks: Rc::new(RefCell::new(Callbacks::default())),
         });
 
+        let state = Rc::downgrade(&this.0.state);
+        let callbacks = Rc::downgrade(&this.0.callbacks);
+        executor
+            .spawn(async move {
+                while let Some(request) = accessibility_requests.next().await {
+                    let (Some(state), Some(callbacks)) = (state.upgrade(), callbacks.upgrade())
+                    else {
+                        break;
+                    };
+                    WaylandWindowStatePtr { state, callbacks }
+                        .handle_accessibility_request(request);
+                }
+            })
+            .detach();
+
         // Kick things off
         surface.commit();
 
@@ -576,6 +608,34 @@ impl WaylandWindowStatePtr {
         }
     }
 
+    /// Handles a request from an assistive technology as soon as it's made, as the window may
+    /// be idle or hidden and not drawing frames.
+    fn handle_accessibility_request(&self, request: AccessibilityRequest) {
+        match request {
+            AccessibilityRequest::Activate => {
+                // Nothing can be drawn before the first configure, whose frame delivers the
+                // initial tree.
+                if !self.state.borrow().acknowledged_first_configure {
+                    return;
+                }
+                let mut cb = self.callbacks.borrow_mut();
+                if let Some(fun) = cb.request_frame.as_mut() {
+                    fun(RequestFrameOptions {
+                        force_render: true,
+                        ..Default::default()
+                    });
+                }
+            }
+            AccessibilityRequest::Action(request) => {
+                let callback = self.callbacks.borrow_mut().accessibility_action.take();
+                if let Some(mut fun) = callback {
+                    fun(request);
+                    self.callbacks.borrow_mut().accessibility_action = Some(fun);
+                }
+            }
+        }
+    }
+
     pub fn handle_xdg_surface_event(&self, event: xdg_surface::Event) {
         if let xdg_surface::Event::Configure { serial } = event {
             {
@@ -1009,8 +1069,41 @@ impl WaylandWindowStatePtr {
         }
     }
 
+    /// Dispatch a drag from another application, returning whether the hovered element can
+    /// accept the data being dragged.
+    pub fn handle_external_drop(&self, event: ExternalDropEvent) -> bool {
+        if self.is_blocked() {
+            return false;
+        }
+        let callback = self.callbacks.borrow_mut().input.take();
+        let Some(mut fun) = callback else {
+            return false;
+        };
+        let result = fun(PlatformInput::ExternalDrop(event));
+        self.callbacks.borrow_mut().input = Some(fun);
+        result.drop_accepted
+    }
+
+    /// Link the window to the application menus exported over DBus by the given connection.
+    pub fn set_app_menu_address(&self, service_name: &str) {
+        let mut state = self.state.borrow_mut();
+        if state.appmenu.is_none() {
+            let Some(appmenu_manager) = state.globals.appmenu_manager.as_ref() else {
+                return;
+            };
+            let appmenu = appmenu_manager.create(&state.surface, &state.globals.qh, ());
+            state.appmenu = Some(appmenu);
+        }
+        if let Some(appmenu) = &state.appmenu {
+            appmenu.set_address(service_name.to_string(), MENU_OBJECT_PATH.to_string());
+        }
+    }
+
     pub fn set_focused(&self, focus: bool) {
-        self.state.borrow_mut().active = focus;
+        let mut state = self.state.borrow_mut();
+        state.active = focus;
+        state.accessibility.set_window_focused(focus);
+        drop(state);
         let callback = self.callbacks.borrow_mut().active_status_change.take();
         if let Some(mut fun) = callback {
             fun(focus);
@@ -1232,6 +1325,7 @@ impl PlatformWindow for WaylandWindow {
         if let Some(toplevel) = self.borrow().surface_state.toplevel() {
             toplevel.set_title(title.to_string());
         }
+        self.borrow_mut().accessibility.set_title(title);
     }
 
     fn set_app_id(&mut self, app_id: &str) {
@@ -1415,6 +1509,14 @@ impl PlatformWindow for WaylandWindow {
         }
     }
 
+    fn start_external_drag(&self, drag: ExternalDrag) -> Option<Receiver<ExternalDragOutcome>> {
+        let state = self.borrow();
+        let client = state.client.clone();
+        let surface = state.surface.clone();
+        drop(state);
+        client.start_drag(&surface, drag)
+    }
+
     fn window_decorations(&self) -> Decorations {
         let state = self.borrow();
         match state.decorations {
@@ -1465,6 +1567,20 @@ impl PlatformWindow for WaylandWindow {
     fn gpu_specs(&self) -> Option<GpuSpecs> {
         self.borrow().renderer.gpu_specs().into()
     }
+
+    fn is_accessibility_active(&self) -> bool {
+        self.borrow().accessibility.is_active()
+    }
+
+    fn update_accessibility_tree(&self, tree: &AccessibilityTree) {
+        let mut state = self.borrow_mut();
+        let scale_factor = state.scale;
+        state.accessibility.update(tree, scale_factor);
+    }
+
+    fn on_accessibility_action(&self, callback: Box<dyn FnMut(AccessibilityActionRequest)>) {
+        self.0.callbacks.borrow_mut().accessibility_action = Some(callback);
+    }
 }
 
 fn update_window(mut state: RefMut<WaylandWindowState>) {
@@ -6,12 +6,11 @@ use calloop::{
 };
 use collections::HashMap;
 use core::str;
+use futures::channel::oneshot;
 use gpui::{Capslock, TaskTiming, profiler};
-use http_client::Url;
 use log::Level;
-use smallvec::SmallVec;
 use std::{
-    cell::RefCell,
+    cell::{RefCell, RefMut},
     collections::{BTreeMap, HashSet},
     ops::Deref,
     path::PathBuf,
@@ -49,20 +48,26 @@ use super::{
 };
 
 use crate::linux::{
-    DEFAULT_CURSOR_ICON_NAME, LinuxClient, capslock_from_xkb, cursor_style_to_icon_names,
-    get_xkb_compose_state, is_within_click_distance, keystroke_from_xkb,
-    keystroke_underlying_dead_key, log_cursor_icon_warning, modifiers_from_xkb, open_uri_internal,
+    DEFAULT_CURSOR_ICON_NAME, DbusMenu, DbusNotifications, LinuxClient, MenuEvent, MenuKind,
+    NotificationEvent, StatusNotifier, capslock_from_xkb, cursor_style_to_icon_names,
+    get_xkb_compose_state, handle_menu_event, handle_notification_event, handle_open_urls,
+    handle_status_item_click, handle_thermal_state, is_within_click_distance, keystroke_from_xkb,
+    keystroke_underlying_dead_key, log_cursor_icon_warning, modifiers_from_xkb, offered_mime_types,
+    open_uri_internal,
     platform::{DOUBLE_CLICK_INTERVAL, SCROLL_LINES},
     reveal_path_internal,
     xdg_desktop_portal::{Event as XDPEvent, XDPEventSource},
 };
-use crate::linux::{LinuxCommon, LinuxKeyboardLayout, X11Window, modifiers_from_xinput_info};
+use crate::linux::{
+    LinuxCommon, LinuxKeyboardLayout, LinuxKeyboardMapper, X11Window, modifiers_from_xinput_info,
+};
 
 use gpui::{
-    AnyWindowHandle, Bounds, ClipboardItem, CursorStyle, DisplayId, FileDropEvent, Keystroke,
-    Modifiers, ModifiersChangedEvent, MouseButton, Pixels, PlatformDisplay, PlatformInput,
-    PlatformKeyboardLayout, PlatformWindow, Point, RequestFrameOptions, ScrollDelta, Size,
-    TouchPhase, WindowParams, point, px,
+    AnyWindowHandle, Bounds, ClipboardItem, CursorStyle, DisplayId, ExternalDrag,
+    ExternalDragOutcome, ExternalDropEvent, Keystroke, Modifiers, ModifiersChangedEvent,
+    MouseButton, Pixels, PlatformDisplay, PlatformInput, PlatformKeyboardLayout,
+    PlatformKeyboardMapper, PlatformWindow, Point, RequestFrameOptions, ScrollDelta, Size,
+    StatusItemClick, ThermalState, TouchPhase, WindowParams, point, px,
 };
 use gpui_wgpu::{CompositorGpuHint, GpuContext};
 
@@ -140,14 +145,50 @@ impl From<xim::ClientError> for EventHandlerError {
     }
 }
 
+/// A drag from another application over one of our windows.
 #[derive(Debug, Default)]
 pub struct Xdnd {
     other_window: xproto::Window,
-    drag_type: u32,
+    /// The formats the source offers the data in.
+    types: Vec<xproto::Atom>,
     retrieved: bool,
+    /// Whether the data was read and the drag delivered to the window.
+    entered: bool,
+    /// Whether the hovered element can drop the data.
+    accepted: bool,
     position: Point<Pixels>,
 }
 
+/// The XDND version we speak as a drag source.
+const XDND_VERSION: u32 = 5;
+/// How long to wait for the drop target to finish with dropped data before giving up on it.
+const XDND_FINISH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A drag out of one of our windows, see [`gpui::Window::start_external_drag`]. The data is
+/// served by the clipboard, as the `XdndSelection`.
+pub struct XdndSource {
+    /// The window the drag started in, which drop targets reply to.
+    window: xproto::Window,
+    types: Vec<xproto::Atom>,
+    action: xproto::Atom,
+    /// The top-level window under the pointer and the drop target within it, so the target is
+    /// only looked up again when the pointer moves to another window.
+    pointer_window: Option<(xproto::Window, Option<(xproto::Window, u32)>)>,
+    target: Option<XdndTarget>,
+    dropped: bool,
+    /// Cancels the drag if the target doesn't send `XdndFinished` after the drop.
+    finish_timeout: Option<RegistrationToken>,
+    outcome: oneshot::Sender<ExternalDragOutcome>,
+}
+
+#[derive(Clone, Copy)]
+struct XdndTarget {
+    window: xproto::Window,
+    version: u32,
+    /// The action the target last said it would perform, if it would accept the drop.
+    accepted_action: Option<xproto::Atom>,
+}
+
 #[derive(Debug)]
 struct PointerDeviceState {
     horizontal: ScrollAxisState,
@@ -217,6 +258,7 @@ pub struct X11ClientState {
     pub(crate) clipboard: Clipboard,
     pub(crate) clipboard_item: Option<ClipboardItem>,
     pub(crate) xdnd_state: Xdnd,
+    pub(crate) xdnd_source: Option<XdndSource>,
 }
 
 #[derive(Clone)]
@@ -301,7 +343,7 @@ impl X11Client {
     pub(crate) fn new() -> anyhow::Result<Self> {
         let event_loop = EventLoop::try_new()?;
 
-        let (common, main_receiver) = LinuxCommon::new(event_loop.get_signal());
+        let (mut common, main_receiver) = LinuxCommon::new(event_loop.get_signal());
 
         let handle = event_loop.handle();
 
@@ -479,6 +521,65 @@ impl X11Client {
             })
             .map_err(|err| anyhow!("Failed to initialize XDP event source: {err:?}"))?;
 
+        let (app_menu, app_menu_events) = DbusMenu::new(&common.background_executor);
+        common.app_menu = Some(app_menu);
+        handle
+            .insert_source(app_menu_events, |event, _, client| {
+                if let calloop::channel::Event::Msg(event) = event {
+                    client.handle_menu_event(event, MenuKind::AppMenu);
+                }
+            })
+            .map_err(|err| anyhow!("Failed to initialize app menu event source: {err:?}"))?;
+
+        let (status_item, status_item_clicks, status_item_menu_events) =
+            StatusNotifier::new(&common.background_executor);
+        common.status_item = Some(status_item);
+        handle
+            .insert_source(status_item_clicks, |event, _, client| {
+                if let calloop::channel::Event::Msg(click) = event {
+                    client.handle_status_item_click(click);
+                }
+            })
+            .map_err(|err| anyhow!("Failed to initialize status item event source: {err:?}"))?;
+        handle
+            .insert_source(status_item_menu_events, |event, _, client| {
+                if let calloop::channel::Event::Msg(event) = event {
+                    client.handle_menu_event(event, MenuKind::StatusItem);
+                }
+            })
+            .map_err(|err| anyhow!("Failed to initialize status item event source: {err:?}"))?;
+
+        let (notifications, notification_events) =
+            DbusNotifications::new(&common.background_executor);
+        common.notifications = Some(notifications);
+        handle
+            .insert_source(notification_events, |event, _, client| {
+                if let calloop::channel::Event::Msg(event) = event {
+                    client.handle_notification_event(event);
+                }
+            })
+            .map_err(|err| anyhow!("Failed to initialize notification event source: {err:?}"))?;
+
+        let (open_urls, open_url_events) = calloop::channel::channel();
+        common.open_urls = Some(open_urls);
+        handle
+            .insert_source(open_url_events, |event, _, client| {
+                if let calloop::channel::Event::Msg(urls) = event {
+                    client.handle_open_urls(urls);
+                }
+            })
+            .map_err(|err| anyhow!("Failed to initialize URL event source: {err:?}"))?;
+
+        let (thermal_states, thermal_state_events) = calloop::channel::channel();
+        common.thermal_states = Some(thermal_states);
+        handle
+            .insert_source(thermal_state_events, |event, _, client| {
+                if let calloop::channel::Event::Msg(state) = event {
+                    client.handle_thermal_state(state);
+                }
+            })
+            .map_err(|err| anyhow!("Failed to initialize thermal state event source: {err:?}"))?;
+
         xcb_flush(&xcb_connection);
 
         Ok(X11Client(Rc::new(RefCell::new(X11ClientState {
@@ -526,6 +627,7 @@ impl X11Client {
             clipboard,
             clipboard_item: None,
             xdnd_state: Xdnd::default(),
+            xdnd_source: None,
         }))))
     }
 
@@ -684,6 +786,69 @@ impl X11Client {
         Ok(())
     }
 
+    pub fn start_drag(
+        &self,
+        x_window: xproto::Window,
+        drag: ExternalDrag,
+    ) -> Option<oneshot::Receiver<ExternalDragOutcome>> {
+        let mut state = self.0.borrow_mut();
+        state
+            .clipboard
+            .set_item(
+                &drag.item,
+                clipboard::ClipboardKind::Drag,
+                clipboard::WaitConfig::None,
+            )
+            .context("X11: Failed to offer dragged data")
+            .log_err()?;
+
+        let types = offered_mime_types(&drag.item)
+            .into_iter()
+            .filter_map(|mime_type| {
+                get_reply(
+                    || "Failed to intern XDND type",
+                    state
+                        .xcb_connection
+                        .intern_atom(false, mime_type.as_bytes()),
+                )
+                .log_err()
+                .map(|reply| reply.atom)
+            })
+            .collect::<Vec<_>>();
+        // Only three types fit in `XdndEnter`, drop targets read the rest from this property.
+        check_reply(
+            || "Failed to set XdndTypeList property",
+            state.xcb_connection.change_property32(
+                xproto::PropMode::REPLACE,
+                x_window,
+                state.atoms.XdndTypeList,
+                state.atoms.XA_ATOM,
+                &types,
+            ),
+        )
+        .log_err();
+
+        let (outcome, outcome_rx) = oneshot::channel();
+        let source = XdndSource {
+            window: x_window,
+            types,
+            action: if drag.allow_move {
+                state.atoms.XdndActionMove
+            } else {
+                state.atoms.XdndActionCopy
+            },
+            pointer_window: None,
+            target: None,
+            dropped: false,
+            finish_timeout: None,
+            outcome,
+        };
+        if let Some(previous) = state.xdnd_source.replace(source) {
+            state.cancel_xdnd_source(previous);
+        }
+        Some(outcome_rx)
+    }
+
     pub fn enable_ime(&self) {
         let mut state = self.0.borrow_mut();
         if !state.has_xim() {
@@ -793,28 +958,25 @@ impl X11Client {
                 }
 
                 if event.type_ == state.atoms.XdndEnter {
-                    state.xdnd_state.other_window = atom;
-                    if (arg1 & 0x1) == 0x1 {
-                        state.xdnd_state.drag_type = xdnd_get_supported_atom(
-                            &state.xcb_connection,
-                            &state.atoms,
-                            state.xdnd_state.other_window,
-                        );
-                    } else {
-                        if let Some(atom) = [arg2, arg3, arg4]
-                            .into_iter()
-                            .find(|atom| xdnd_is_atom_supported(*atom, &state.atoms))
-                        {
-                            state.xdnd_state.drag_type = atom;
-                        }
-                    }
+                    state.xdnd_state = Xdnd {
+                        other_window: atom,
+                        types: if (arg1 & 0x1) == 0x1 {
+                            xdnd_get_type_list(&state.xcb_connection, &state.atoms, atom)
+                        } else {
+                            [arg2, arg3, arg4]
+                                .into_iter()
+                                .filter(|&atom| atom != x11rb::NONE)
+                                .collect()
+                        },
+                        ..Xdnd::default()
+                    };
                 } else if event.type_ == state.atoms.XdndLeave {
-                    let position = state.xdnd_state.position;
+                    let entered = state.xdnd_state.entered;
+                    state.xdnd_state = Xdnd::default();
                     drop(state);
-                    window
-                        .handle_input(PlatformInput::FileDrop(FileDropEvent::Pending { position }));
-                    window.handle_input(PlatformInput::FileDrop(FileDropEvent::Exited {}));
-                    self.0.borrow_mut().xdnd_state = Xdnd::default();
+                    if entered {
+                        window.handle_external_drop(ExternalDropEvent::Exited);
+                    }
                 } else if event.type_ == state.atoms.XdndPosition {
                     if let Ok(pos) = get_reply(
                         || "Failed to query pointer position",
@@ -823,75 +985,61 @@ impl X11Client {
                         state.xdnd_state.position =
                             Point::new(px(pos.win_x as f32), px(pos.win_y as f32));
                     }
-                    if !state.xdnd_state.retrieved {
-                        check_reply(
-                            || "Failed to convert selection for drag and drop",
-                            state.xcb_connection.convert_selection(
-                                event.window,
-                                state.atoms.XdndSelection,
-                                state.xdnd_state.drag_type,
-                                state.atoms.XDND_DATA,
-                                arg3,
-                            ),
-                        )
-                        .log_err();
-                    }
+                    let position = state.xdnd_state.position;
+                    let drop_event = if state.xdnd_state.retrieved {
+                        state
+                            .xdnd_state
+                            .entered
+                            .then_some(ExternalDropEvent::Over { position })
+                    } else {
+                        state.xdnd_state.retrieved = true;
+                        state
+                            .clipboard
+                            .get_offered(clipboard::ClipboardKind::Drag, &state.xdnd_state.types)
+                            .context("Failed to read drag and drop data")
+                            .log_err()
+                            .map(|item| ExternalDropEvent::Entered { position, item })
+                    };
+                    drop(state);
+
+                    let accepted = drop_event.is_some_and(|drop_event| {
+                        self.0.borrow_mut().xdnd_state.entered = true;
+                        window.handle_external_drop(drop_event)
+                    });
+                    let mut state = self.0.borrow_mut();
+                    state.xdnd_state.accepted = accepted;
                     xdnd_send_status(
                         &state.xcb_connection,
                         &state.atoms,
                         event.window,
                         state.xdnd_state.other_window,
-                        arg4,
+                        accepted.then_some(arg4),
                     );
-                    let position = state.xdnd_state.position;
-                    drop(state);
-                    window
-                        .handle_input(PlatformInput::FileDrop(FileDropEvent::Pending { position }));
                 } else if event.type_ == state.atoms.XdndDrop {
+                    let xdnd_state = std::mem::take(&mut state.xdnd_state);
                     xdnd_send_finished(
                         &state.xcb_connection,
                         &state.atoms,
                         event.window,
-                        state.xdnd_state.other_window,
+                        xdnd_state.other_window,
+                        xdnd_state.accepted,
                     );
-                    let position = state.xdnd_state.position;
                     drop(state);
-                    window
-                        .handle_input(PlatformInput::FileDrop(FileDropEvent::Submit { position }));
-                    self.0.borrow_mut().xdnd_state = Xdnd::default();
-                }
-            }
-            Event::SelectionNotify(event) => {
-                let window = self.get_window(event.requestor)?;
-                let state = self.0.borrow_mut();
-                let reply = get_reply(
-                    || "Failed to get XDND_DATA",
-                    state.xcb_connection.get_property(
-                        false,
-                        event.requestor,
-                        state.atoms.XDND_DATA,
-                        AtomEnum::ANY,
-                        0,
-                        1024,
-                    ),
-                )
-                .log_err();
-                let Some(reply) = reply else {
-                    return Some(());
-                };
-                if let Ok(file_list) = str::from_utf8(&reply.value) {
-                    let paths: SmallVec<[_; 2]> = file_list
-                        .lines()
-                        .filter_map(|path| Url::parse(path).log_err())
-                        .filter_map(|url| url.to_file_path().log_err())
-                        .collect();
-                    let input = PlatformInput::FileDrop(FileDropEvent::Entered {
-                        position: state.xdnd_state.position,
-                        paths: gpui::ExternalPaths(paths),
-                    });
-                    drop(state);
-                    window.handle_input(input);
-                    self.0.borrow_mut().xdnd_state.retrieved = true;
+                    if xdnd_state.entered {
+                        let position = xdnd_state.position;
+                        window.handle_external_drop(ExternalDropEvent::Dropped { position });
+                    }
+                } else if event.type_ == state.atoms.XdndStatus {
+                    if let Some(target) = state
+                        .xdnd_source
+                        .as_mut()
+                        .and_then(|source| source.target.as_mut())
+                        .filter(|target| target.window == atom)
+                    {
+                        target.accepted_action = (arg1 & 0x1 == 0x1).then_some(arg4);
+                    }
+                } else if event.type_ == state.atoms.XdndFinished {
+                    state.finish_xdnd_source(atom, arg1, arg2);
                 }
             }
             Event::ConfigureNotify(event) => {
@@ -1015,6 +1163,19 @@ impl X11Client {
                 // We therefore update the mask from the global state.
                 update_xkb_mask_from_event_state(&mut state.xkb, event.state);
 
+                // Escape cancels a drag out of our windows, rather than reaching the window.
+                if state
+                    .xdnd_source
+                    .as_ref()
+                    .is_some_and(|source| !source.dropped)
+                    && state.xkb.key_get_one_sym(event.detail.into()) == xkbc::Keysym::Escape
+                {
+                    if let Some(source) = state.xdnd_source.take() {
+                        state.cancel_xdnd_source(source);
+                    }
+                    return Some(());
+                }
+
                 let keystroke = {
                     let code = event.detail.into();
                     let mut keystroke = keystroke_from_xkb(&state.xkb, modifiers, code);
@@ -1199,6 +1360,9 @@ impl X11Client {
                             modifiers,
                             click_count,
                         }));
+                        if button == MouseButton::Left {
+                            self.0.borrow_mut().drop_xdnd_source(event.time);
+                        }
                     }
                     Some(ButtonOrScroll::Scroll(_)) => {}
                     None => {}
@@ -1241,6 +1405,15 @@ impl X11Client {
                 );
                 let modifiers = modifiers_from_xinput_info(event.mods);
                 state.modifiers = modifiers;
+                // The pointer is grabbed by the window while a button is held, so its motion
+                // outside of the window moves the drag.
+                if state.xdnd_source.is_some() {
+                    state.move_xdnd_source(
+                        (event.root_x >> 16) as i16,
+                        (event.root_y >> 16) as i16,
+                        event.time,
+                    );
+                }
                 drop(state);
 
                 if event.valuator_mask[0] & 3 != 0 {
@@ -1418,6 +1591,45 @@ impl X11Client {
         Some(())
     }
 
+    fn handle_menu_event(&self, event: MenuEvent, kind: MenuKind) {
+        let exported = matches!(event, MenuEvent::Exported(_));
+        handle_menu_event(event, kind, || {
+            RefMut::map(self.0.borrow_mut(), |state| &mut state.common)
+        });
+        if exported {
+            let state = self.0.borrow();
+            if let Some(app_menu) = &state.common.app_menu {
+                for &x_window in state.windows.keys() {
+                    app_menu.register_window(x_window);
+                }
+            }
+        }
+    }
+
+    fn handle_status_item_click(&self, click: StatusItemClick) {
+        handle_status_item_click(click, || {
+            RefMut::map(self.0.borrow_mut(), |state| &mut state.common)
+        });
+    }
+
+    fn handle_notification_event(&self, event: NotificationEvent) {
+        handle_notification_event(event, || {
+            RefMut::map(self.0.borrow_mut(), |state| &mut state.common)
+        });
+    }
+
+    fn handle_open_urls(&self, urls: Vec<String>) {
+        handle_open_urls(urls, || {
+            RefMut::map(self.0.borrow_mut(), |state| &mut state.common)
+        });
+    }
+
+    fn handle_thermal_state(&self, thermal_state: ThermalState) {
+        handle_thermal_state(thermal_state, || {
+            RefMut::map(self.0.borrow_mut(), |state| &mut state.common)
+        });
+    }
+
     fn handle_keyboard_layout_change(&self) {
         let mut state = self.0.borrow_mut();
         let layout_idx = state.xkb.serialize_layout(STATE_LAYOUT_EFFECTIVE);
@@ -1449,6 +1661,15 @@ impl LinuxClient for X11Client {
         Box::new(state.keyboard_layout.clone())
     }
 
+    fn keyboard_mapper(&self) -> Rc<dyn PlatformKeyboardMapper> {
+        let state = self.0.borrow();
+        let layout_idx = state.xkb.serialize_layout(STATE_LAYOUT_EFFECTIVE);
+        Rc::new(LinuxKeyboardMapper::new(
+            &state.xkb.get_keymap(),
+            layout_idx,
+        ))
+    }
+
     fn displays(&self) -> Vec<Rc<dyn PlatformDisplay>> {
         let state = self.0.borrow();
         let setup = state.xcb_connection.setup();
@@ -1561,6 +1782,14 @@ impl LinuxClient for X11Client {
         };
 
         state.windows.insert(x_window, window_ref);
+        if let Some(app_menu) = state
+            .common
+            .app_menu
+            .as_ref()
+            .filter(|app_menu| app_menu.service_name().is_some())
+        {
+            app_menu.register_window(x_window);
+        }
         Ok(Box::new(window))
     }
 
@@ -1626,8 +1855,8 @@ impl LinuxClient for X11Client {
         let state = self.0.borrow_mut();
         state
             .clipboard
-            .set_text(
-                std::borrow::Cow::Owned(item.text().unwrap_or_default()),
+            .set_item(
+                &item,
                 clipboard::ClipboardKind::Primary,
                 clipboard::WaitConfig::None,
             )
@@ -1639,8 +1868,8 @@ impl LinuxClient for X11Client {
         let mut state = self.0.borrow_mut();
         state
             .clipboard
-            .set_text(
-                std::borrow::Cow::Owned(item.text().unwrap_or_default()),
+            .set_item(
+                &item,
                 clipboard::ClipboardKind::Clipboard,
                 clipboard::WaitConfig::None,
             )
@@ -1675,6 +1904,21 @@ impl LinuxClient for X11Client {
             .log_with_level(log::Level::Debug)
     }
 
+    fn read_custom_from_clipboard(&self, mime: &str) -> Option<Vec<u8>> {
+        let state = self.0.borrow_mut();
+        if state
+            .clipboard
+            .is_owner(clipboard::ClipboardKind::Clipboard)
+        {
+            return Some(state.clipboard_item.as_ref()?.custom(mime)?.to_vec());
+        }
+        state
+            .clipboard
+            .get_custom(clipboard::ClipboardKind::Clipboard, mime)
+            .context("X11: Failed to read from clipboard (custom)")
+            .log_with_level(log::Level::Debug)
+    }
+
     fn run(&self) {
         let Some(mut event_loop) = self
             .0
@@ -1778,6 +2022,154 @@ impl X11ClientState {
         self.xim_handler = Some(xim_handler);
     }
 
+    /// Move the drag out of our windows to the given root coordinates, over the XDND-aware window
+    /// there. Our own windows are skipped, as they handle the drag in progress themselves.
+    fn move_xdnd_source(&mut self, root_x: i16, root_y: i16, time: u32) {
+        let Some(source) = self.xdnd_source.as_mut().filter(|source| !source.dropped) else {
+            return;
+        };
+        let root = self.xcb_connection.setup().roots[self.x_root_index].root;
+        let Some(pointer_window) = xdnd_child_at(&self.xcb_connection, root, root, root_x, root_y)
+        else {
+            return;
+        };
+        let target = match source.pointer_window {
+            Some((window, target)) if window == pointer_window => target,
+            _ => {
+                let target = xdnd_find_target(
+                    &self.xcb_connection,
+                    &self.atoms,
+                    root,
+                    pointer_window,
+                    root_x,
+                    root_y,
+                )
+                .filter(|(window, _)| !self.windows.contains_key(window));
+                source.pointer_window = Some((pointer_window, target));
+                target
+            }
+        };
+
+        if source.target.map(|target| target.window) != target.map(|(window, _)| window) {
+            if let Some(previous) = source.target.take() {
+                xdnd_send_message(
+                    &self.xcb_connection,
+                    previous.window,
+                    self.atoms.XdndLeave,
+                    [source.window, 0, 0, 0, 0],
+                );
+            }
+            if let Some((window, version)) = target {
+                let has_more_types = (source.types.len() > 3) as u32;
+                let mut data = [source.window, version << 24 | has_more_types, 0, 0, 0];
+                for (data, &type_) in data[2..].iter_mut().zip(&source.types) {
+                    *data = type_;
+                }
+                xdnd_send_message(&self.xcb_connection, window, self.atoms.XdndEnter, data);
+                source.target = Some(XdndTarget {
+                    window,
+                    version,
+                    accepted_action: None,
+                });
+            }
+        }
+
+        if let Some(target) = source.target {
+            let position = (root_x as u16 as u32) << 16 | root_y as u16 as u32;
+            xdnd_send_message(
+                &self.xcb_connection,
+                target.window,
+                self.atoms.XdndPosition,
+                [source.window, 0, position, time, source.action],
+            );
+        }
+    }
+
+    /// Drop the drag out of our windows on the target under the pointer, or cancel it when there's
+    /// no target that accepts it.
+    fn drop_xdnd_source(&mut self, time: u32) {
+        let Some(source) = self.xdnd_source.as_mut().filter(|source| !source.dropped) else {
+            return;
+        };
+        match source.target {
+            Some(target) if target.accepted_action.is_some() => {
+                xdnd_send_message(
+                    &self.xcb_connection,
+                    target.window,
+                    self.atoms.XdndDrop,
+                    [source.window, 0, time, 0, 0],
+                );
+                source.dropped = true;
+                source.finish_timeout = self
+                    .loop_handle
+                    .insert_source(
+                        calloop::timer::Timer::from_duration(XDND_FINISH_TIMEOUT),
+                        |_, _, client| {
+                            let mut state = client.0.borrow_mut();
+                            if let Some(source) = state.xdnd_source.take_if(|source| source.dropped)
+                            {
+                                log::warn!("XDND drop target didn't finish with the dropped data");
+                                source.outcome.send(ExternalDragOutcome::Cancelled).ok();
+                            }
+                            calloop::timer::TimeoutAction::Drop
+                        },
+                    )
+                    .log_err();
+            }
+            _ => {
+                if let Some(source) = self.xdnd_source.take() {
+                    self.cancel_xdnd_source(source);
+                }
+            }
+        }
+    }
+
+    fn cancel_xdnd_source(&self, source: XdndSource) {
+        if let Some(finish_timeout) = source.finish_timeout {
+            self.loop_handle.remove(finish_timeout);
+        }
+        if let Some(target) = source.target {
+            xdnd_send_message(
+                &self.xcb_connection,
+                target.window,
+                self.atoms.XdndLeave,
+                [source.window, 0, 0, 0, 0],
+            );
+        }
+        source.outcome.send(ExternalDragOutcome::Cancelled).ok();
+    }
+
+    /// Report the outcome of the drag out of our windows, once the target it was dropped on has
+    /// finished with the data.
+    fn finish_xdnd_source(&mut self, target: xproto::Window, flags: u32, action: xproto::Atom) {
+        let Some(source) = self
+            .xdnd_source
+            .take_if(|source| source.dropped && source.target.is_some_and(|t| t.window == target))
+        else {
+            return;
+        };
+        if let Some(finish_timeout) = source.finish_timeout {
+            self.loop_handle.remove(finish_timeout);
+        }
+        let Some(target) = source.target else {
+            return;
+        };
+        // Targets before version 5 don't say whether the drop succeeded, or what they did.
+        let (success, action) = if target.version >= 5 {
+            (flags & 0x1 == 0x1, action)
+        } else {
+            (true, target.accepted_action.unwrap_or(source.action))
+        };
+        let outcome = if !success {
+            ExternalDragOutcome::Cancelled
+        } else if action == self.atoms.XdndActionMove {
+            ExternalDragOutcome::Moved
+        } else {
+            ExternalDragOutcome::Copied
+        };
+        source.outcome.send(outcome).ok();
+    }
+
     fn update_refresh_loop(&mut self, x_window: xproto::Window) {
         let Some(window_ref) = self.windows.get_mut(&x_window) else {
             return;
@@ -2107,41 +2499,83 @@ fn check_gtk_frame_extents_supported(
     supported_atom_ids.contains(&atoms._GTK_FRAME_EXTENTS)
 }
 
-fn xdnd_is_atom_supported(atom: u32, atoms: &XcbAtoms) -> bool {
-    atom == atoms.TEXT
-        || atom == atoms.STRING
-        || atom == atoms.UTF8_STRING
-        || atom == atoms.TEXT_PLAIN
-        || atom == atoms.TEXT_PLAIN_UTF8
-        || atom == atoms.TextUriList
+/// The formats a drag source with more than three of them lists in its `XdndTypeList`.
+fn xdnd_get_type_list(
+    xcb_connection: &XCBConnection,
+    atoms: &XcbAtoms,
+    source: xproto::Window,
+) -> Vec<xproto::Atom> {
+    get_reply(
+        || "Failed to get XDnD type list",
+        xcb_connection.get_property(false, source, atoms.XdndTypeList, AtomEnum::ATOM, 0, 1024),
+    )
+    .log_with_level(Level::Warn)
+    .and_then(|reply| Some(reply.value32()?.collect()))
+    .unwrap_or_default()
 }
 
-fn xdnd_get_supported_atom(
+/// The child of `window` at the given root coordinates, or `NONE` if there isn't one.
+fn xdnd_child_at(
     xcb_connection: &XCBConnection,
-    supported_atoms: &XcbAtoms,
-    target: xproto::Window,
-) -> u32 {
-    if let Some(reply) = get_reply(
-        || "Failed to get XDnD supported atoms",
-        xcb_connection.get_property(
-            false,
-            target,
-            supported_atoms.XdndTypeList,
-            AtomEnum::ANY,
-            0,
-            1024,
-        ),
+    root: xproto::Window,
+    window: xproto::Window,
+    x: i16,
+    y: i16,
+) -> Option<xproto::Window> {
+    get_reply(
+        || "Failed to find the window under the drag",
+        xcb_connection.translate_coordinates(root, window, x, y),
     )
-    .log_with_level(Level::Warn)
-        && let Some(atoms) = reply.value32()
-    {
-        for atom in atoms {
-            if xdnd_is_atom_supported(atom, supported_atoms) {
-                return atom;
-            }
+    .log_err()
+    .map(|reply| reply.child)
+}
+
+/// Find the XDND-aware window at the given root coordinates, starting from the given top-level
+/// window, with the XDND version to speak to it.
+fn xdnd_find_target(
+    xcb_connection: &XCBConnection,
+    atoms: &XcbAtoms,
+    root: xproto::Window,
+    mut window: xproto::Window,
+    x: i16,
+    y: i16,
+) -> Option<(xproto::Window, u32)> {
+    while window != x11rb::NONE {
+        let version = get_reply(
+            || "Failed to get XdndAware property",
+            xcb_connection.get_property(false, window, atoms.XdndAware, AtomEnum::ATOM, 0, 1),
+        )
+        .log_err()?
+        .value32()
+        .and_then(|mut values| values.next());
+        if let Some(version) = version {
+            return (version >= 3).then_some((window, version.min(XDND_VERSION)));
         }
+        window = xdnd_child_at(xcb_connection, root, window, x, y)?;
     }
-    0
+    None
+}
+
+fn xdnd_send_message(
+    xcb_connection: &XCBConnection,
+    target: xproto::Window,
+    type_: xproto::Atom,
+    data: [u32; 5],
+) {
+    let message = ClientMessageEvent {
+        format: 32,
+        window: target,
+        type_,
+        data: ClientMessageData::from(data),
+        sequence: 0,
+        response_type: xproto::CLIENT_MESSAGE_EVENT,
+    };
+    check_reply(
+        || "Failed to send XDnD message",
+        xcb_connection.send_event(false, target, EventMask::default(), message),
+    )
+    .log_err();
+    xcb_connection.flush().log_err();
 }
 
 fn xdnd_send_finished(
@@ -2149,12 +2583,18 @@ fn xdnd_send_finished(
     atoms: &XcbAtoms,
     source: xproto::Window,
     target: xproto::Window,
+    accepted: bool,
 ) {
+    let action = if accepted {
+        atoms.XdndActionCopy
+    } else {
+        x11rb::NONE
+    };
     let message = ClientMessageEvent {
         format: 32,
         window: target,
         type_: atoms.XdndFinished,
-        data: ClientMessageData::from([source, 1, atoms.XdndActionCopy, 0, 0]),
+        data: ClientMessageData::from([source, accepted as u32, action, 0, 0]),
         sequence: 0,
         response_type: xproto::CLIENT_MESSAGE_EVENT,
     };
@@ -2171,13 +2611,19 @@ fn xdnd_send_status(
     atoms: &XcbAtoms,
     source: xproto::Window,
     target: xproto::Window,
-    action: u32,
+    action: Option<xproto::Atom>,
 ) {
     let message = ClientMessageEvent {
         format: 32,
         window: target,
         type_: atoms.XdndStatus,
-        data: ClientMessageData::from([source, 1, 0, 0, action]),
+        data: ClientMessageData::from([
+            source,
+            action.is_some() as u32,
+            0,
+            0,
+            action.unwrap_or(x11rb::NONE),
+        ]),
         sequence: 0,
         response_type: xproto::CLIENT_MESSAGE_EVENT,
     };
@@ -19,7 +19,6 @@
 // https://freedesktop.org/wiki/ClipboardManager/
 
 use std::{
-    borrow::Cow,
     cell::RefCell,
     collections::{HashMap, hash_map::Entry},
     sync::{
@@ -47,6 +46,7 @@ use x11rb::{
     wrapper::ConnectionExt as _,
 };
 
+use crate::linux::{data_for_mime_type, offered_mime_types, read_dropped_item, read_offered_item};
 use gpui::{ClipboardItem, Image, ImageFormat, hash};
 
 type Result<T, E = Error> = std::result::Result<T, E>;
@@ -58,6 +58,7 @@ x11rb::atom_manager! {
         CLIPBOARD,
         PRIMARY,
         SECONDARY,
+        XdndSelection,
 
         CLIPBOARD_MANAGER,
         SAVE_TARGETS,
@@ -131,6 +132,7 @@ struct Inner {
     clipboard: Selection,
     primary: Selection,
     secondary: Selection,
+    drag: Selection,
 
     handover_state: Mutex<ManagerHandoverState>,
     handover_cv: Condvar,
@@ -220,6 +222,7 @@ impl Inner {
             clipboard: Selection::default(),
             primary: Selection::default(),
             secondary: Selection::default(),
+            drag: Selection::default(),
             handover_state: Mutex::new(ManagerHandoverState::Idle),
             handover_cv: Condvar::new(),
             serve_stopped: AtomicBool::new(false),
@@ -362,6 +365,79 @@ impl Inner {
         Err(Error::ContentNotAvailable)
     }
 
+    /// Read the selection in every format it's offered in that [`read_offered_item`]
+    /// understands.
+    fn read_item(&self, selection: ClipboardKind) -> Result<ClipboardItem> {
+        if self.is_owner(selection)? {
+            let data = self.selection_of(selection).data.read();
+            let data_list = data.as_deref().unwrap_or_default();
+            let offered = data_list
+                .iter()
+                .map(|data| self.atom_name(data.format).to_string())
+                .collect::<Vec<_>>();
+            return read_offered_item(&offered, |mime_type| {
+                let ix = offered.iter().position(|offered| offered == mime_type)?;
+                Some(data_list[ix].bytes.clone())
+            })
+            .ok_or(Error::ContentNotAvailable);
+        }
+
+        let reader = XContext::new()?;
+        let targets = match self.read_single(&reader, selection, self.atoms.TARGETS)? {
+            ClipboardData { bytes, format } if format == self.atoms.ATOM => {
+                Self::parse_formats(&bytes)
+            }
+            ClipboardData { format, .. } => {
+                log::trace!(
+                    "Unexpected clipboard TARGETS format {}",
+                    self.atom_name(format)
+                );
+                return Err(Error::ConversionFailure);
+            }
+        };
+        self.read_targets(&reader, selection, &targets)
+    }
+
+    /// Read the selection in each of the given formats that [`read_offered_item`] understands,
+    /// or [`read_dropped_item`] for the data dragged onto a window.
+    fn read_targets(
+        &self,
+        reader: &XContext,
+        selection: ClipboardKind,
+        targets: &[Atom],
+    ) -> Result<ClipboardItem> {
+        let offered = targets
+            .iter()
+            .map(|&target| self.atom_name(target).to_string())
+            .collect::<Vec<_>>();
+        let read = |mime_type: &str| {
+            let ix = offered.iter().position(|offered| offered == mime_type)?;
+            match self.read_single(reader, selection, targets[ix]) {
+                Ok(data) => Some(data.bytes),
+                Err(err) => {
+                    log::trace!("Conversion to {mime_type} failed: {err}");
+                    None
+                }
+            }
+        };
+        match selection {
+            ClipboardKind::Drag => read_dropped_item(&offered, read),
+            _ => read_offered_item(&offered, read),
+        }
+        .ok_or(Error::ContentNotAvailable)
+    }
+
+    fn intern_atom(&self, name: &str) -> Result<Atom> {
+        Ok(self
+            .server
+            .conn
+            .intern_atom(false, name.as_bytes())
+            .map_err(into_unknown)?
+            .reply()
+            .map_err(into_unknown)?
+            .atom)
+    }
+
     fn parse_formats(bytes: &[u8]) -> Vec<Atom> {
         bytes
             .chunks_exact(4)
@@ -467,6 +543,7 @@ impl Inner {
             ClipboardKind::Clipboard => self.atoms.CLIPBOARD,
             ClipboardKind::Primary => self.atoms.PRIMARY,
             ClipboardKind::Secondary => self.atoms.SECONDARY,
+            ClipboardKind::Drag => self.atoms.XdndSelection,
         }
     }
 
@@ -475,6 +552,7 @@ impl Inner {
             ClipboardKind::Clipboard => &self.clipboard,
             ClipboardKind::Primary => &self.primary,
             ClipboardKind::Secondary => &self.secondary,
+            ClipboardKind::Drag => &self.drag,
         }
     }
 
@@ -483,6 +561,7 @@ impl Inner {
             a if a == self.atoms.CLIPBOARD => Some(ClipboardKind::Clipboard),
             a if a == self.atoms.PRIMARY => Some(ClipboardKind::Primary),
             a if a == self.atoms.SECONDARY => Some(ClipboardKind::Secondary),
+            a if a == self.atoms.XdndSelection => Some(ClipboardKind::Drag),
             _ => None,
         }
     }
@@ -696,6 +775,8 @@ impl Inner {
                         targets.push(self.atoms.UTF8_MIME_1);
                     }
                 }
+                targets.sort_unstable();
+                targets.dedup();
             }
             self.server
                 .conn
@@ -976,16 +1057,20 @@ impl Clipboard {
         Ok(Self { inner: ctx })
     }
 
-    pub(crate) fn set_text(
+    /// Offer every representation of the item, in each of the formats it can be converted to.
+    pub(crate) fn set_item(
         &self,
-        message: Cow<'_, str>,
+        item: &ClipboardItem,
         selection: ClipboardKind,
         wait: WaitConfig,
     ) -> Result<()> {
-        let data = vec![ClipboardData {
-            bytes: message.into_owned().into_bytes(),
-            format: self.inner.atoms.UTF8_STRING,
-        }];
+        let mut data = Vec::new();
+        for mime_type in offered_mime_types(item) {
+            if let Some(bytes) = data_for_mime_type(item, &mime_type) {
+                let format = self.inner.intern_atom(&mime_type)?;
+                data.push(ClipboardData { bytes, format });
+            }
+        }
         self.inner.write(data, selection, wait)
     }
 
@@ -1013,7 +1098,33 @@ impl Clipboard {
         self.inner.write(data, selection, wait)
     }
 
+    /// Read every representation of the selection's contents that's understood, falling back to
+    /// the first supported text or image format when the owner can't list its formats.
     pub(crate) fn get_any(&self, selection: ClipboardKind) -> Result<ClipboardItem> {
+        self.inner.read_item(selection).or_else(|err| {
+            log::trace!("Reading every clipboard format failed with {err:?}");
+            self.get_first_supported(selection)
+        })
+    }
+
+    /// Read the selection in the formats its owner says it's offered in, as drag sources list
+    /// them in `XdndEnter` rather than answering `TARGETS`.
+    pub(crate) fn get_offered(
+        &self,
+        selection: ClipboardKind,
+        targets: &[Atom],
+    ) -> Result<ClipboardItem> {
+        let reader = XContext::new()?;
+        self.inner.read_targets(&reader, selection, targets)
+    }
+
+    /// Read the selection in one custom MIME type, which [`Self::get_any`] leaves out.
+    pub(crate) fn get_custom(&self, selection: ClipboardKind, mime_type: &str) -> Result<Vec<u8>> {
+        let format = self.inner.intern_atom(mime_type)?;
+        Ok(self.inner.read(&[format], selection)?.bytes)
+    }
+
+    fn get_first_supported(&self, selection: ClipboardKind) -> Result<ClipboardItem> {
         const IMAGE_FORMAT_COUNT: usize = 7;
         let image_format_atoms: [Atom; IMAGE_FORMAT_COUNT] = [
             self.inner.atoms.PNG__MIME,
@@ -1174,6 +1285,9 @@ pub enum ClipboardKind {
 
     /// The secondary clipboard is rarely used but theoretically available on X11.
     Secondary,
+
+    /// The data dragged out of a window, which drop targets read over XDND.
+    Drag,
 }
 
 /// Configuration on how long to wait for a new X11 copy event is emitted.
@@ -1,17 +1,19 @@
 use anyhow::{Context as _, anyhow};
 use x11rb::connection::RequestConnection;
 
-use crate::linux::X11ClientStatePtr;
+use crate::linux::{AccessibilityRequest, LinuxAccessibility, X11ClientStatePtr};
 use gpui::{
-    AnyWindowHandle, Bounds, Decorations, DevicePixels, ForegroundExecutor, GpuSpecs, Modifiers,
-    Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow,
-    Point, PromptButton, PromptLevel, RequestFrameOptions, ResizeEdge, ScaledPixels, Scene, Size,
-    Tiling, WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControlArea,
-    WindowDecorations, WindowKind, WindowParams, px,
+    AccessibilityActionRequest, AccessibilityTree, AnyWindowHandle, Bounds, Decorations,
+    DevicePixels, ExternalDrag, ExternalDragOutcome, ExternalDropEvent, ForegroundExecutor,
+    GpuSpecs, Modifiers, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput,
+    PlatformInputHandler, PlatformWindow, Point, PromptButton, PromptLevel, RequestFrameOptions,
+    ResizeEdge, ScaledPixels, Scene, Size, Tiling, WindowAppearance, WindowBackgroundAppearance,
+    WindowBounds, WindowControlArea, WindowDecorations, WindowKind, WindowParams, px,
 };
 use gpui_wgpu::{CompositorGpuHint, WgpuRenderer, WgpuSurfaceConfig};
 
 use collections::FxHashSet;
+use futures::StreamExt as _;
 use raw_window_handle as rwh;
 use util::{ResultExt, maybe};
 use x11rb::{
@@ -47,6 +49,7 @@ x11rb::atom_manager! {
         XdndFinished,
         XdndTypeList,
         XdndActionCopy,
+        XdndActionMove,
         TextUriList: b"text/uri-list",
         UTF8_STRING,
         TEXT,
@@ -249,6 +252,7 @@ pub struct Callbacks {
     should_close: Option<Box<dyn FnMut() -> bool>>,
     close: Option<Box<dyn FnOnce()>>,
     appearance_changed: Option<Box<dyn FnMut()>>,
+    accessibility_action: Option<Box<dyn FnMut(AccessibilityActionRequest)>>,
 }
 
 pub struct X11WindowState {
@@ -281,6 +285,7 @@ pub struct X11WindowState {
     edge_constraints: Option<EdgeConstraints>,
     pub handle: AnyWindowHandle,
     last_insets: [u32; 4],
+    accessibility: LinuxAccessibility,
 }
 
 impl X11WindowState {
@@ -420,6 +425,7 @@ impl X11WindowState {
         scale_factor: f32,
         appearance: WindowAppearance,
         parent_window: Option<X11WindowStatePtr>,
+        accessibility: LinuxAccessibility,
     ) -> anyhow::Result<Self> {
         let x_screen_index = params
             .display_id
@@ -749,6 +755,7 @@ impl X11WindowState {
                 client_side_decorations_supported,
                 decorations: WindowDecorations::Server,
                 last_insets: [0, 0, 0, 0],
+                accessibility,
                 edge_constraints: None,
                 counter_id: sync_request_counter,
                 last_sync_counter: None,
@@ -835,11 +842,12 @@ impl X11Window {
         appearance: WindowAppearance,
         parent_window: Option<X11WindowStatePtr>,
     ) -> anyhow::Result<Self> {
+        let (accessibility, mut accessibility_requests) = LinuxAccessibility::new();
         let ptr = X11WindowStatePtr {
             state: Rc::new(RefCell::new(X11WindowState::new(
                 handle,
                 client,
-                executor,
+                executor.clone(),
                 gpu_context,
                 compositor_gpu,
                 params,
@@ -851,12 +859,34 @@ impl X11Window {
                 scale_factor,
                 appearance,
                 parent_window,
+                accessibility,
             )?)),
             callbacks: Rc::new(RefCell::new(Callbacks::default())),
             xcb: xcb.clone(),
             x_window,
         };
 
+        let state = Rc::downgrade(&ptr.state);
+        let callbacks = Rc::downgrade(&ptr.callbacks);
+        let xcb = xcb.clone();
+        executor
+            .spawn(async move {
+                while let Some(request) = accessibility_requests.next().await {
+                    let (Some(state), Some(callbacks)) = (state.upgrade(), callbacks.upgrade())
+                    else {
+                        break;
+                    };
+                    let ptr = X11WindowStatePtr {
+                        state,
+                        callbacks,
+                        xcb: xcb.clone(),
+                        x_window,
+                    };
+                    ptr.handle_accessibility_request(request);
+                }
+            })
+            .detach();
+
         let state = ptr.state.borrow_mut();
         ptr.set_wm_properties(state)?;
 
@@ -1079,6 +1109,24 @@ impl X11WindowStatePtr {
         }
     }
 
+    /// Handles a request from an assistive technology as soon as it's made, as the window may
+    /// be idle or hidden and not drawing frames.
+    fn handle_accessibility_request(&self, request: AccessibilityRequest) {
+        match request {
+            AccessibilityRequest::Activate => self.refresh(RequestFrameOptions {
+                force_render: true,
+                ..Default::default()
+            }),
+            AccessibilityRequest::Action(request) => {
+                let callback = self.callbacks.borrow_mut().accessibility_action.take();
+                if let Some(mut fun) = callback {
+                    fun(request);
+                    self.callbacks.borrow_mut().accessibility_action = Some(fun);
+                }
+            }
+        }
+    }
+
     pub fn handle_input(&self, input: PlatformInput) {
         if self.is_blocked() {
             return;
@@ -1107,6 +1155,21 @@ impl X11WindowStatePtr {
         }
     }
 
+    /// Dispatch a drag from another application, returning whether the hovered element can
+    /// accept the data being dragged.
+    pub fn handle_external_drop(&self, event: ExternalDropEvent) -> bool {
+        if self.is_blocked() {
+            return false;
+        }
+        let callback = self.callbacks.borrow_mut().input.take();
+        let Some(mut fun) = callback else {
+            return false;
+        };
+        let result = fun(PlatformInput::ExternalDrop(event));
+        self.callbacks.borrow_mut().input = Some(fun);
+        result.drop_accepted
+    }
+
     pub fn handle_ime_commit(&self, text: String) {
         if self.is_blocked() {
             return;
@@ -1217,6 +1280,10 @@ impl X11WindowStatePtr {
     }
 
     pub fn set_active(&self, focus: bool) {
+        self.state
+            .borrow_mut()
+            .accessibility
+            .set_window_focused(focus);
         let callback = self.callbacks.borrow_mut().active_status_change.take();
         if let Some(mut fun) = callback {
             fun(focus);
@@ -1447,6 +1514,7 @@ impl PlatformWindow for X11Window {
         )
         .log_err();
         xcb_flush(&self.0.xcb);
+        self.0.state.borrow_mut().accessibility.set_title(title);
     }
 
     fn set_app_id(&mut self, app_id: &str) {
@@ -1680,6 +1748,14 @@ impl PlatformWindow for X11Window {
             .log_err();
     }
 
+    fn start_external_drag(
+        &self,
+        drag: ExternalDrag,
+    ) -> Option<futures::channel::oneshot::Receiver<ExternalDragOutcome>> {
+        let client = self.0.state.borrow().client.get_client()?;
+        client.start_drag(self.0.x_window, drag)
+    }
+
     fn window_decorations(&self) -> gpui::Decorations {
         let state = self.0.state.borrow();
 
@@ -1822,4 +1898,19 @@ impl PlatformWindow for X11Window {
     fn gpu_specs(&self) -> Option<GpuSpecs> {
         self.0.state.borrow().renderer.gpu_specs().into()
     }
+
+    fn is_accessibility_active(&self) -> bool {
+        self.0.state.borrow().accessibility.is_active()
+    }
+
+    fn update_accessibility_tree(&self, tree: &AccessibilityTree) {
+        let mut state = self.0.state.borrow_mut();
+        let (bounds, scale_factor) = (state.bounds, state.scale_factor);
+        state.accessibility.set_window_bounds(bounds, scale_factor);
+        state.accessibility.update(tree, scale_factor);
+    }
+
+    fn on_accessibility_action(&self, callback: Box<dyn FnMut(AccessibilityActionRequest)>) {
+        self.0.callbacks.borrow_mut().accessibility_action = Some(callback);
+    }
 }
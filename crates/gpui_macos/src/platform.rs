@@ -297,6 +297,7 @@ impl MacPlatform {
                     action,
                     os_action,
                     checked,
+                    ..
                 } => {
                     // Note that this is intentionally using earlier bindings, whereas typically
                     // later ones take display precedence. See the discussion on
@@ -956,6 +957,26 @@ impl Platform for MacPlatform {
         }
     }
 
+    fn recent_documents(&self) -> Task<Result<Vec<PathBuf>>> {
+        let paths = unsafe {
+            let document_controller: id =
+                msg_send![class!(NSDocumentController), sharedDocumentController];
+            let urls: id = msg_send![document_controller, recentDocumentURLs];
+            (0..urls.count())
+                .filter_map(|i| ns_url_to_path(urls.objectAtIndex(i)).log_err())
+                .collect()
+        };
+        Task::ready(Ok(paths))
+    }
+
+    fn clear_recent_documents(&self) {
+        unsafe {
+            let document_controller: id =
+                msg_send![class!(NSDocumentController), sharedDocumentController];
+            let _: () = msg_send![document_controller, clearRecentDocuments: nil];
+        }
+    }
+
     fn path_for_auxiliary_executable(&self, name: &str) -> Result<PathBuf> {
         unsafe {
             let bundle: id = NSBundle::mainBundle();
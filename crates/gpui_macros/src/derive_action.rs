@@ -133,6 +133,17 @@ pub(crate) fn derive_action(input: TokenStream) -> TokenStream {
         quote! { Some(<Self as gpui::private::schemars::JsonSchema>::json_schema(_generator)) }
     };
 
+    let to_json_fn_body = if no_json {
+        quote! { None }
+    } else if is_unit_struct {
+        quote! { Some(gpui::private::serde_json::Value::Object(Default::default())) }
+    } else {
+        quote! {
+            use gpui::{NoActionJson as _, SerializeActionJson as _};
+            (&&gpui::ActionJson(self)).to_json()
+        }
+    };
+
     let deprecated_aliases_fn_body = if deprecated_aliases.is_empty() {
         quote! { &[] }
     } else {
@@ -189,6 +200,10 @@ pub(crate) fn derive_action(input: TokenStream) -> TokenStream {
                 #build_fn_body
             }
 
+            fn to_json(&self) -> Option<gpui::private::serde_json::Value> {
+                #to_json_fn_body
+            }
+
             fn action_json_schema(
                 _generator: &mut gpui::private::schemars::SchemaGenerator,
             ) -> Option<gpui::private::schemars::Schema> {
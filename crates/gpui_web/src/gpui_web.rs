@@ -6,6 +6,7 @@ mod events;
 mod http_client;
 mod keyboard;
 mod logging;
+mod notifications;
 mod platform;
 mod window;
 
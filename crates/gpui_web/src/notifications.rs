@@ -0,0 +1,158 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use gpui::{Notification, NotificationId, NotificationResponse, NotificationUrgency};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::NotificationPermission;
+
+/// Shows notifications with the browser's Notification API. Action buttons are only supported
+/// for notifications shown by a service worker, so they're left out.
+#[derive(Default)]
+pub(crate) struct WebNotifications(Rc<RefCell<WebNotificationsState>>);
+
+#[derive(Default)]
+struct WebNotificationsState {
+    response_callback: Option<Box<dyn FnMut(NotificationId, NotificationResponse)>>,
+    /// Notifications waiting for the user to grant permission to show them.
+    pending: HashSet<NotificationId>,
+    shown: HashMap<NotificationId, ShownNotification>,
+}
+
+struct ShownNotification {
+    notification: web_sys::Notification,
+    _onclick: Closure<dyn FnMut(JsValue)>,
+    _onclose: Closure<dyn FnMut(JsValue)>,
+}
+
+impl Drop for ShownNotification {
+    fn drop(&mut self) {
+        self.notification.set_onclick(None);
+        self.notification.set_onclose(None);
+    }
+}
+
+impl WebNotifications {
+    pub fn show(&self, id: NotificationId, notification: Notification) {
+        match web_sys::Notification::permission() {
+            NotificationPermission::Granted => self.show_now(id, notification),
+            NotificationPermission::Default => {
+                let Ok(request) = web_sys::Notification::request_permission() else {
+                    self.respond_later(id, NotificationResponse::Closed);
+                    return;
+                };
+                self.0.borrow_mut().pending.insert(id);
+                let this = Self(self.0.clone());
+                wasm_bindgen_futures::spawn_local(async move {
+                    let permission = JsFuture::from(request).await;
+                    let still_pending = this.0.borrow_mut().pending.remove(&id);
+                    let permission = permission
+                        .ok()
+                        .and_then(|permission| permission.as_string());
+                    if !still_pending {
+                        return;
+                    }
+                    if permission.as_deref() == Some("granted") {
+                        this.show_now(id, notification);
+                    } else {
+                        this.respond(id, NotificationResponse::Closed);
+                    }
+                });
+            }
+            _ => {
+                log::warn!("not showing notification, permission was denied");
+                self.respond_later(id, NotificationResponse::Closed);
+            }
+        }
+    }
+
+    fn show_now(&self, id: NotificationId, notification: Notification) {
+        let options = web_sys::NotificationOptions::new();
+        options.set_tag(&id.0.to_string());
+        if let Some(body) = &notification.body {
+            options.set_body(body.as_str());
+        }
+        if let Some(icon) = &notification.icon {
+            options.set_icon(icon.as_str());
+        }
+        options.set_require_interaction(notification.urgency == NotificationUrgency::Critical);
+        let shown =
+            match web_sys::Notification::new_with_options(notification.title.as_str(), &options) {
+                Ok(shown) => shown,
+                Err(error) => {
+                    log::error!("failed to show notification: {error:?}");
+                    self.respond_later(id, NotificationResponse::Closed);
+                    return;
+                }
+            };
+
+        let onclick = Closure::<dyn FnMut(JsValue)>::new({
+            let this = Self(self.0.clone());
+            move |_| {
+                if let Some(window) = web_sys::window() {
+                    window.focus().ok();
+                }
+                this.respond(id, NotificationResponse::Activated);
+            }
+        });
+        let onclose = Closure::<dyn FnMut(JsValue)>::new({
+            let this = Self(self.0.clone());
+            move |_| {
+                // Dropping the notification from inside its own handler would free the closure
+                // while it's running.
+                let closed = this.0.borrow_mut().shown.remove(&id);
+                this.respond(id, NotificationResponse::Closed);
+                if let Some(closed) = closed {
+                    wasm_bindgen_futures::spawn_local(async move { drop(closed) });
+                }
+            }
+        });
+        shown.set_onclick(Some(onclick.as_ref().unchecked_ref()));
+        shown.set_onclose(Some(onclose.as_ref().unchecked_ref()));
+
+        // The tag makes the browser replace a notification with the same id in place, so the
+        // replaced one is dropped without closing it.
+        self.0.borrow_mut().shown.insert(
+            id,
+            ShownNotification {
+                notification: shown,
+                _onclick: onclick,
+                _onclose: onclose,
+            },
+        );
+    }
+
+    pub fn close(&self, id: NotificationId) {
+        let mut state = self.0.borrow_mut();
+        if state.pending.remove(&id) {
+            drop(state);
+            self.respond_later(id, NotificationResponse::Closed);
+        } else if let Some(shown) = state.shown.get(&id) {
+            shown.notification.close();
+        }
+    }
+
+    pub fn on_response(&self, callback: Box<dyn FnMut(NotificationId, NotificationResponse)>) {
+        self.0.borrow_mut().response_callback = Some(callback);
+    }
+
+    /// Respond once the app is no longer borrowed by the call that led to the response.
+    fn respond_later(&self, id: NotificationId, response: NotificationResponse) {
+        let this = Self(self.0.clone());
+        wasm_bindgen_futures::spawn_local(async move { this.respond(id, response) });
+    }
+
+    fn respond(&self, id: NotificationId, response: NotificationResponse) {
+        let callback = self.0.borrow_mut().response_callback.take();
+        if let Some(mut callback) = callback {
+            callback(id, response);
+            self.0
+                .borrow_mut()
+                .response_callback
+                .get_or_insert(callback);
+        }
+    }
+}
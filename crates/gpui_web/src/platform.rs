@@ -1,14 +1,15 @@
 use crate::dispatcher::WebDispatcher;
 use crate::display::WebDisplay;
 use crate::keyboard::WebKeyboardLayout;
+use crate::notifications::WebNotifications;
 use crate::window::WebWindow;
 use anyhow::Result;
 use futures::channel::oneshot;
 use gpui::{
     Action, AnyWindowHandle, BackgroundExecutor, ClipboardItem, CursorStyle, DummyKeyboardMapper,
-    ForegroundExecutor, Keymap, Menu, MenuItem, PathPromptOptions, Platform, PlatformDisplay,
-    PlatformKeyboardLayout, PlatformKeyboardMapper, PlatformTextSystem, PlatformWindow, Task,
-    ThermalState, WindowAppearance, WindowParams,
+    ForegroundExecutor, Keymap, Menu, MenuItem, Notification, NotificationId, NotificationResponse,
+    PathPromptOptions, Platform, PlatformDisplay, PlatformKeyboardLayout, PlatformKeyboardMapper,
+    PlatformTextSystem, PlatformWindow, Task, ThermalState, WindowAppearance, WindowParams,
 };
 use gpui_wgpu::WgpuContext;
 use std::{
@@ -38,6 +39,7 @@ pub struct WebPlatform {
     active_window: RefCell<Option<AnyWindowHandle>>,
     active_display: Rc<dyn PlatformDisplay>,
     callbacks: RefCell<WebPlatformCallbacks>,
+    notifications: WebNotifications,
     wgpu_context: Rc<RefCell<Option<WgpuContext>>>,
 }
 
@@ -85,6 +87,7 @@ impl WebPlatform {
             active_window: RefCell::new(None),
             active_display,
             callbacks: RefCell::new(WebPlatformCallbacks::default()),
+            notifications: WebNotifications::default(),
             wgpu_context: Rc::new(RefCell::new(None)),
         }
     }
@@ -247,6 +250,21 @@ impl Platform for WebPlatform {
         self.callbacks.borrow_mut().validate_app_menu_command = Some(callback);
     }
 
+    fn show_notification(&self, id: NotificationId, notification: Notification) {
+        self.notifications.show(id, notification);
+    }
+
+    fn close_notification(&self, id: NotificationId) {
+        self.notifications.close(id);
+    }
+
+    fn on_notification_response(
+        &self,
+        callback: Box<dyn FnMut(NotificationId, NotificationResponse)>,
+    ) {
+        self.notifications.on_response(callback);
+    }
+
     fn thermal_state(&self) -> ThermalState {
         ThermalState::Nominal
     }